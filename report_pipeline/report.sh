@@ -1,4 +1,5 @@
 #!/bin/sh
 
-cargo run --release -- report election-metadata raw-data preprocessed reports "$@"
+cargo run --release -- report election-metadata raw-data preprocessed reports \
+    --operations-log operations.jsonl "$@"
 
@@ -0,0 +1,236 @@
+//! Aggregations across contest reports, such as candidate profiles,
+//! jurisdiction statistics and trends across elections.
+
+use crate::model::aggregate::{
+    CandidateAppearance, CandidateIndex, CandidateProfile, ElectionTrendPoint, JurisdictionSummary,
+    JurisdictionTrend, TrendIndex,
+};
+use crate::model::election::CandidateType;
+use crate::model::report::ContestReport;
+use std::collections::{BTreeMap, BTreeSet};
+
+/// Return the path of a contest's report relative to the report directory.
+pub fn contest_path(report: &ContestReport) -> String {
+    format!(
+        "{}/{}/{}",
+        report.info.jurisdiction_path, report.info.election_path, report.info.office
+    )
+}
+
+/// Key used to match a candidate across contests.
+fn candidate_key(name: &str) -> String {
+    name.split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .to_lowercase()
+}
+
+/// Collect each declared candidate's appearances across the given reports.
+/// Write-in candidates are not included.
+pub fn candidate_profiles(reports: &[ContestReport]) -> CandidateIndex {
+    let mut appearances: BTreeMap<String, Vec<(String, CandidateAppearance)>> = BTreeMap::new();
+
+    for report in reports {
+        let num_rounds = report.rounds.len() as u32;
+
+        for votes in &report.total_votes {
+            let candidate = &report.candidates[votes.candidate.0 as usize];
+            if candidate.candidate_type == CandidateType::WriteIn {
+                continue;
+            }
+
+            let appearance = CandidateAppearance {
+                contest_path: contest_path(report),
+                date: report.info.date.clone(),
+                jurisdiction_name: report.info.jurisdiction_name.clone(),
+                office_name: report.info.office_name.clone(),
+                first_round_votes: votes.first_round_votes,
+                final_votes: votes.first_round_votes + votes.transfer_votes,
+                rounds_survived: votes.round_eliminated.unwrap_or(num_rounds),
+                won: report.is_winner(votes.candidate),
+            };
+
+            appearances
+                .entry(candidate_key(&candidate.name))
+                .or_default()
+                .push((candidate.name.clone(), appearance));
+        }
+    }
+
+    let candidates = appearances
+        .into_values()
+        .map(|mut entries| {
+            entries.sort_by(|(_, a), (_, b)| {
+                (&a.date, &a.contest_path).cmp(&(&b.date, &b.contest_path))
+            });
+            let name = entries.last().unwrap().0.clone();
+            let appearances: Vec<CandidateAppearance> =
+                entries.into_iter().map(|(_, a)| a).collect();
+
+            CandidateProfile {
+                name,
+                races_entered: appearances.len() as u32,
+                wins: appearances.iter().filter(|a| a.won).count() as u32,
+                appearances,
+            }
+        })
+        .collect();
+
+    CandidateIndex { candidates }
+}
+
+/// Fraction of a contest's ballots that were exhausted by the final round.
+pub fn exhaustion_rate(report: &ContestReport) -> f64 {
+    let last = report.rounds.last().unwrap();
+    if report.ballot_count == 0 {
+        return 0.;
+    }
+    (last.undervote + last.overvote) as f64 / report.ballot_count as f64
+}
+
+/// Return true if the candidate leading the first round won the contest.
+pub fn first_round_leader_won(report: &ContestReport) -> bool {
+    report.rounds[0]
+        .allocations
+        .iter()
+        .filter_map(|a| a.allocatee.candidate_id().map(|c| (c, a.votes)))
+        .max_by_key(|(c, votes)| (*votes, std::cmp::Reverse(*c)))
+        .is_some_and(|(c, _)| report.is_winner(c))
+}
+
+fn mean(values: impl Iterator<Item = f64>) -> f64 {
+    let (sum, count) = values.fold((0., 0), |(s, n), v| (s + v, n + 1));
+    if count == 0 {
+        0.
+    } else {
+        sum / count as f64
+    }
+}
+
+/// Summarize the contests in each jurisdiction, ordered by jurisdiction path.
+pub fn jurisdiction_summaries(reports: &[ContestReport]) -> Vec<JurisdictionSummary> {
+    let mut by_jurisdiction: BTreeMap<&str, Vec<&ContestReport>> = BTreeMap::new();
+    for report in reports {
+        by_jurisdiction
+            .entry(&report.info.jurisdiction_path)
+            .or_default()
+            .push(report);
+    }
+
+    by_jurisdiction
+        .into_iter()
+        .map(|(path, reports)| {
+            let elections: BTreeSet<&str> = reports
+                .iter()
+                .map(|r| r.info.election_path.as_str())
+                .collect();
+
+            JurisdictionSummary {
+                path: path.to_string(),
+                name: reports[0].info.jurisdiction_name.clone(),
+                elections: elections.len() as u32,
+                contests: reports.len() as u32,
+                average_rounds: mean(reports.iter().map(|r| r.rounds.len() as f64)),
+                average_exhaustion_rate: mean(reports.iter().map(|r| exhaustion_rate(r))),
+                first_round_leader_win_rate: mean(reports.iter().map(|r| {
+                    if first_round_leader_won(r) {
+                        1.
+                    } else {
+                        0.
+                    }
+                })),
+            }
+        })
+        .collect()
+}
+
+/// Fraction of first-round votes cast for write-in candidates.
+pub fn write_in_share(report: &ContestReport) -> f64 {
+    let (write_in, total) = report
+        .total_votes
+        .iter()
+        .fold((0, 0), |(write_in, total), votes| {
+            let candidate = &report.candidates[votes.candidate.0 as usize];
+            if candidate.candidate_type == CandidateType::WriteIn {
+                (
+                    write_in + votes.first_round_votes,
+                    total + votes.first_round_votes,
+                )
+            } else {
+                (write_in, total + votes.first_round_votes)
+            }
+        });
+
+    if total == 0 {
+        0.
+    } else {
+        write_in as f64 / total as f64
+    }
+}
+
+/// Average contest statistics over each election in each jurisdiction.
+pub fn trends(reports: &[ContestReport]) -> TrendIndex {
+    let mut by_jurisdiction: BTreeMap<&str, BTreeMap<&str, Vec<&ContestReport>>> = BTreeMap::new();
+    for report in reports {
+        by_jurisdiction
+            .entry(&report.info.jurisdiction_path)
+            .or_default()
+            .entry(&report.info.election_path)
+            .or_default()
+            .push(report);
+    }
+
+    let jurisdictions = by_jurisdiction
+        .into_iter()
+        .map(|(path, elections)| {
+            let name = elections.values().next().unwrap()[0]
+                .info
+                .jurisdiction_name
+                .clone();
+
+            let mut points: Vec<ElectionTrendPoint> = elections
+                .into_iter()
+                .map(|(election_path, reports)| {
+                    let ranks: Option<Vec<f64>> = reports.iter().map(|r| r.average_ranks).collect();
+
+                    ElectionTrendPoint {
+                        election_path: election_path.to_string(),
+                        date: reports[0].info.date.clone(),
+                        contests: reports.len() as u32,
+                        average_ranks: ranks.map(|r| mean(r.into_iter())),
+                        exhaustion_rate: mean(reports.iter().map(|r| exhaustion_rate(r))),
+                        candidates_per_contest: mean(
+                            reports.iter().map(|r| r.num_candidates as f64),
+                        ),
+                        write_in_share: mean(reports.iter().map(|r| write_in_share(r))),
+                    }
+                })
+                .collect();
+            points.sort_by(|a, b| (&a.date, &a.election_path).cmp(&(&b.date, &b.election_path)));
+
+            JurisdictionTrend {
+                path: path.to_string(),
+                name,
+                elections: points,
+            }
+        })
+        .collect();
+
+    TrendIndex { jurisdictions }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_candidate_key() {
+        assert_eq!("london breed", candidate_key("London  BREED "));
+    }
+
+    #[test]
+    fn test_mean() {
+        assert_eq!(0., mean(std::iter::empty()));
+        assert_eq!(2., mean(vec![1., 2., 3.].into_iter()));
+    }
+}
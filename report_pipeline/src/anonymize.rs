@@ -0,0 +1,64 @@
+//! Replacement of cast vote record ids with salted hashes, for elections
+//! whose ballot-level data may not expose the original ids.
+
+use crate::formats::FormatError;
+use crate::model::election::Election;
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+use std::fs::read;
+use std::path::Path;
+
+/// File in an election's raw data directory holding the salt. It is hidden,
+/// so `sync` doesn't record it in metadata, and raw data isn't published.
+pub const BALLOT_ID_SALT_FILENAME: &str = ".ballot-id-salt";
+
+/// Number of hex digits of the HMAC kept as the new ballot id.
+const HASHED_ID_LENGTH: usize = 32;
+
+/// Read the salt for an election's ballot ids from its raw data directory.
+pub fn read_ballot_id_salt(election_dir: &Path) -> Result<Vec<u8>, FormatError> {
+    let file = election_dir.join(BALLOT_ID_SALT_FILENAME);
+    let salt = read(&file).map_err(|source| FormatError::Io {
+        file: file.clone(),
+        source,
+    })?;
+    if salt.iter().all(u8::is_ascii_whitespace) {
+        return Err(FormatError::Io {
+            file,
+            source: std::io::Error::new(std::io::ErrorKind::InvalidData, "salt is empty"),
+        });
+    }
+    Ok(salt)
+}
+
+/// Hash a ballot id with the salt (HMAC-SHA256, truncated). The same id and
+/// salt always give the same hash, so re-reading an election gives the same
+/// ids.
+pub fn hash_ballot_id(salt: &[u8], id: &str) -> String {
+    let mut mac = Hmac::<Sha256>::new_from_slice(salt).unwrap();
+    mac.update(id.as_bytes());
+    let mut hash = format!("{:x}", mac.finalize().into_bytes());
+    hash.truncate(HASHED_ID_LENGTH);
+    hash
+}
+
+/// Replace the id of every ballot in the election with its salted hash.
+pub fn anonymize_ballot_ids(election: &mut Election, salt: &[u8]) {
+    for ballot in &mut election.ballots {
+        ballot.id = hash_ballot_id(salt, &ballot.id);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hash_ballot_id() {
+        let hash = hash_ballot_id(b"salt", "CVR-0001");
+        assert_eq!(HASHED_ID_LENGTH, hash.len());
+        assert_eq!(hash, hash_ballot_id(b"salt", "CVR-0001"));
+        assert_ne!(hash, hash_ballot_id(b"salt", "CVR-0002"));
+        assert_ne!(hash, hash_ballot_id(b"pepper", "CVR-0001"));
+    }
+}
@@ -0,0 +1,111 @@
+use crate::commands::export_ballots::{preprocessed_contests, PREPROCESSED_FILENAME};
+use crate::commands::report::write_cache_key;
+use crate::export::{read_ballot_images, write_ballot_images};
+use crate::model::election::{ElectionPreprocessed, NormalizedElection};
+use crate::read_metadata::read_meta;
+use crate::report::{election_info, preprocess_cache_key};
+use crate::util::{
+    read_serialized, record_input, record_outcome, record_output, write_output, DirLock, Outcome,
+};
+use colored::*;
+use std::fs::{create_dir_all, File};
+use std::io::{BufReader, BufWriter};
+use std::path::Path;
+use tracing::{error, info};
+
+const BALLOT_IMAGES_FILENAME: &str = "ballots.jsonl";
+
+/// Export every preprocessed contest under `preprocessed_dir` as a
+/// newline-delimited JSON ballot image file, mirroring the directory layout
+/// under `output_dir`.
+pub fn export_ballot_images(preprocessed_dir: &Path, output_dir: &Path) {
+    for (contest_dir, file) in preprocessed_contests(preprocessed_dir) {
+        let preprocessed: ElectionPreprocessed = read_serialized(&file);
+
+        let output_path = output_dir.join(contest_dir).join(BALLOT_IMAGES_FILENAME);
+        create_dir_all(output_path.parent().unwrap()).unwrap();
+        info!(
+            "Exporting {} ballots to {}",
+            preprocessed.ballots.ballots.len(),
+            output_path.to_string_lossy().bright_blue()
+        );
+
+        let mut writer = BufWriter::new(File::create(&output_path).unwrap());
+        write_ballot_images(&preprocessed.ballots, &mut writer).unwrap();
+        record_output(&output_path);
+    }
+}
+
+/// Read a ballot image file as the preprocessed ballots of the contest at
+/// `contest_path` (e.g. `us/ca/sfo/2019/11/mayor`), which must be listed in
+/// the metadata. The ballots are written to the preprocessed directory, where
+/// the report command picks them up instead of reading raw data. The raw data
+/// directory `raw_dir` is read only for the salt of anonymized ballot ids,
+/// which is part of the preprocessed ballots' cache key.
+pub fn import_ballot_images(
+    meta_dir: &Path,
+    raw_dir: &Path,
+    input: &Path,
+    preprocessed_dir: &Path,
+    contest_path: &str,
+) {
+    let contest_path = contest_path.trim_end_matches('/');
+    let _lock = match DirLock::acquire(preprocessed_dir, "import-ballot-images") {
+        Ok(lock) => lock,
+        Err(err) => {
+            record_outcome(Outcome::InfraError);
+            error!("{}", err);
+            return;
+        }
+    };
+
+    for (_, jurisdiction) in read_meta(meta_dir) {
+        for (election_path, election) in &jurisdiction.elections {
+            for contest in &election.contests {
+                let path = format!("{}/{}/{}", jurisdiction.path, election_path, contest.office);
+                if path != contest_path {
+                    continue;
+                }
+
+                let reader = BufReader::new(File::open(input).unwrap());
+                record_input(input);
+                let ballots: NormalizedElection = match read_ballot_images(reader) {
+                    Ok(ballots) => ballots,
+                    Err(err) => {
+                        record_outcome(Outcome::DataError);
+                        error!("Could not read {}: {}", input.to_string_lossy().red(), err);
+                        return;
+                    }
+                };
+
+                let output_path = preprocessed_dir.join(&path).join(PREPROCESSED_FILENAME);
+                create_dir_all(output_path.parent().unwrap()).unwrap();
+                info!(
+                    "Importing {} ballots to {}",
+                    ballots.ballots.len(),
+                    output_path.to_string_lossy().bright_blue()
+                );
+
+                let preprocessed = ElectionPreprocessed {
+                    info: election_info(election, election_path, &jurisdiction, contest),
+                    ballots,
+                };
+                write_output(&output_path, &preprocessed);
+                // Key the imported ballots to the current metadata so the
+                // report command uses them until the raw data changes.
+                write_cache_key(
+                    output_path.parent().unwrap(),
+                    &preprocess_cache_key(
+                        election,
+                        contest,
+                        &raw_dir.join(&jurisdiction.path).join(election_path),
+                    ),
+                );
+                return;
+            }
+        }
+    }
+
+    record_outcome(Outcome::DataError);
+    error!("Contest {} not found in metadata.", contest_path.red());
+}
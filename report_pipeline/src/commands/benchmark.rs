@@ -0,0 +1,109 @@
+use crate::metrics::{
+    find_regressions, timestamp, ContestMetrics, MetricsCollector, MetricsHistory,
+};
+use crate::model::election::ElectionPreprocessed;
+use crate::normalizers::normalize_election;
+use crate::read_metadata::read_meta;
+use crate::report::{election_info, generate_report, read_contest};
+use crate::rules::tabulation_options;
+use crate::util::{record_outcome, Outcome};
+use colored::*;
+use std::path::Path;
+use tracing::{error, info, warn};
+
+/// Time each stage of processing one contest (reading raw data,
+/// normalizing, generating the report, and serializing it) entirely in
+/// memory, append the timings to the history file, and warn about stages
+/// that slowed down by more than `threshold_percent` since the last run.
+pub fn benchmark(
+    meta_dir: &Path,
+    raw_dir: &Path,
+    contest_path: &str,
+    history_path: &Path,
+    threshold_percent: f64,
+) {
+    let contest_path = contest_path.trim_end_matches('/');
+
+    for (_, jurisdiction) in read_meta(meta_dir) {
+        for (election_path, election) in &jurisdiction.elections {
+            for contest in &election.contests {
+                let path = format!("{}/{}/{}", jurisdiction.path, election_path, contest.office);
+                if path != contest_path {
+                    continue;
+                }
+
+                let raw_base = raw_dir.join(&jurisdiction.path);
+                let mut metrics = MetricsCollector::new();
+
+                let raw = match metrics.time("read", || {
+                    read_contest(&raw_base, election, election_path, contest)
+                }) {
+                    Ok(raw) => raw,
+                    Err(err) => {
+                        record_outcome(Outcome::DataError);
+                        error!("Could not read {}: {}", path.red(), err);
+                        return;
+                    }
+                };
+                let ballots = metrics.time("normalize", || {
+                    normalize_election(
+                        &election.normalization,
+                        &tabulation_options(election).ballot_rules(),
+                        raw,
+                    )
+                });
+                let preprocessed = ElectionPreprocessed {
+                    info: election_info(election, election_path, &jurisdiction, contest),
+                    ballots,
+                };
+                let report = match metrics.time("report", || generate_report(&preprocessed)) {
+                    Ok(report) => report,
+                    Err(err) => {
+                        record_outcome(Outcome::DataError);
+                        error!("Could not report {}: {}", path.red(), err);
+                        return;
+                    }
+                };
+                metrics.time("serialize", || serde_json::to_vec(&report).unwrap());
+
+                let run = ContestMetrics {
+                    contest: path,
+                    timestamp: timestamp(),
+                    ballots: report.ballot_count,
+                    stages: metrics.into_stages(),
+                };
+
+                for stage in &run.stages {
+                    info!("{:>10}: {:.1} ms", stage.stage, stage.millis);
+                }
+
+                let mut history = MetricsHistory::load(history_path);
+                match history.last_run(&run.contest) {
+                    Some(previous) => {
+                        let regressions = find_regressions(previous, &run, threshold_percent);
+                        for r in &regressions {
+                            warn!(
+                                "{} regressed by {:.1}% ({:.1} ms -> {:.1} ms)",
+                                r.stage.red(),
+                                r.percent_change,
+                                r.previous_millis,
+                                r.current_millis
+                            );
+                        }
+                        if regressions.is_empty() {
+                            info!("No stage regressed by more than {}%.", threshold_percent);
+                        }
+                    }
+                    None => info!("No previous run of {} to compare with.", run.contest),
+                }
+
+                history.runs.push(run);
+                history.save(history_path);
+                return;
+            }
+        }
+    }
+
+    record_outcome(Outcome::DataError);
+    error!("Contest {} not found in metadata.", contest_path.red());
+}
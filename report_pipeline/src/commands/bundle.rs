@@ -0,0 +1,213 @@
+use crate::util::{get_files_from_path, read_serialized, write_output};
+use colored::*;
+use ed25519_dalek::{Signature, Signer, SigningKey, Verifier, VerifyingKey};
+use rand_core::OsRng;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use std::convert::TryInto;
+use std::fs::{read_to_string, File};
+use std::io;
+use std::path::{Path, PathBuf};
+use tracing::{error, info};
+
+/// Name of the manifest written at the root of a signed report directory.
+const MANIFEST_FILENAME: &str = "manifest.json";
+
+const SIGNATURE_ALGORITHM: &str = "ed25519";
+
+/// The SHA-256 hash of every file in a report directory, and a signature
+/// over those hashes.
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BundleManifest {
+    pub algorithm: String,
+    /// Map from file path, relative to the report directory, to its SHA-256
+    /// hash.
+    pub files: BTreeMap<String, String>,
+    /// Ed25519 signature of the JSON serialization of `files`, in hex.
+    pub signature: String,
+}
+
+fn sha256_file(path: &Path) -> String {
+    let mut file = File::open(path).unwrap();
+    let mut hasher = Sha256::new();
+    io::copy(&mut file, &mut hasher).unwrap();
+    format!("{:x}", hasher.finalize())
+}
+
+/// Hash every file under `report_dir` except the manifest itself.
+fn hash_bundle(report_dir: &Path) -> BTreeMap<String, String> {
+    get_files_from_path(report_dir)
+        .unwrap()
+        .into_iter()
+        .filter_map(|file| {
+            let name = file
+                .strip_prefix(report_dir)
+                .unwrap()
+                .to_string_lossy()
+                .replace('\\', "/");
+            if name == MANIFEST_FILENAME {
+                None
+            } else {
+                Some((name, sha256_file(&file)))
+            }
+        })
+        .collect()
+}
+
+pub fn sign(key: &SigningKey, files: BTreeMap<String, String>) -> BundleManifest {
+    let signature = key.sign(&serde_json::to_vec(&files).unwrap());
+    BundleManifest {
+        algorithm: SIGNATURE_ALGORITHM.to_string(),
+        files,
+        signature: encode_hex(&signature.to_bytes()),
+    }
+}
+
+fn encode_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+fn decode_hex(hex: &str) -> Option<Vec<u8>> {
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(hex.get(i..i + 2)?, 16).ok())
+        .collect()
+}
+
+/// Return true if the manifest's signature over its file hashes was made
+/// with the private key of `key`.
+pub fn signature_is_valid(key: &VerifyingKey, manifest: &BundleManifest) -> bool {
+    manifest.algorithm == SIGNATURE_ALGORITHM
+        && decode_hex(&manifest.signature)
+            .and_then(|signature| Signature::from_slice(&signature).ok())
+            .is_some_and(|signature| {
+                key.verify(&serde_json::to_vec(&manifest.files).unwrap(), &signature)
+                    .is_ok()
+            })
+}
+
+/// Read a 32-byte key written in hex, ignoring surrounding whitespace such
+/// as a final newline.
+fn read_key(key_file: &Path) -> [u8; 32] {
+    let hex = read_to_string(key_file)
+        .unwrap_or_else(|err| panic!("Could not read key {}: {}", key_file.display(), err));
+    decode_hex(hex.trim())
+        .and_then(|key| key.try_into().ok())
+        .unwrap_or_else(|| panic!("Key {} is not 64 hex digits.", key_file.display()))
+}
+
+/// Generate an Ed25519 key pair, writing the private key to `key_file` and
+/// the public key to the same path with `.pub` appended, both in hex. The
+/// private key signs bundles; the public key is given to mirrors to verify
+/// them.
+pub fn generate_signing_key(key_file: &Path) {
+    let key = SigningKey::generate(&mut OsRng);
+    let public_file = public_key_path(key_file);
+    for (path, bytes) in [
+        (key_file, key.to_bytes()),
+        (public_file.as_path(), key.verifying_key().to_bytes()),
+    ] {
+        std::fs::write(path, format!("{}\n", encode_hex(&bytes)))
+            .unwrap_or_else(|err| panic!("Could not write key {}: {}", path.display(), err));
+    }
+    info!(
+        "Wrote private key {} and public key {}.",
+        key_file.display().to_string().green(),
+        public_file.display().to_string().green()
+    );
+}
+
+fn public_key_path(key_file: &Path) -> PathBuf {
+    let mut path = key_file.as_os_str().to_owned();
+    path.push(".pub");
+    PathBuf::from(path)
+}
+
+/// Hash every file in `report_dir` and write a signed manifest of the hashes
+/// to `manifest.json` at its root, signed with the private key in
+/// `key_file`.
+pub fn sign_bundle(report_dir: &Path, key_file: &Path) {
+    let key = SigningKey::from_bytes(&read_key(key_file));
+    let manifest = sign(&key, hash_bundle(report_dir));
+    info!(
+        "Signed {} report files.",
+        manifest.files.len().to_string().green()
+    );
+    write_output(&report_dir.join(MANIFEST_FILENAME), &manifest);
+}
+
+/// Check the files in `report_dir` against its signed manifest, logging any
+/// that were added, removed or changed since signing. Returns true if the
+/// signature is valid for the public key in `public_key_file` and every file
+/// matches.
+pub fn verify_bundle(report_dir: &Path, public_key_file: &Path) -> bool {
+    let key = VerifyingKey::from_bytes(&read_key(public_key_file)).unwrap_or_else(|err| {
+        panic!(
+            "{} is not an Ed25519 public key: {}",
+            public_key_file.display(),
+            err
+        )
+    });
+    let manifest: BundleManifest = read_serialized(&report_dir.join(MANIFEST_FILENAME));
+    if !signature_is_valid(&key, &manifest) {
+        error!("The manifest signature is not valid for this key.");
+        return false;
+    }
+
+    let actual = hash_bundle(report_dir);
+    let mut valid = true;
+    for (file, hash) in &manifest.files {
+        match actual.get(file) {
+            None => error!("{} is missing", file.red()),
+            Some(actual) if actual != hash => error!("{} has been modified", file.red()),
+            Some(_) => continue,
+        }
+        valid = false;
+    }
+    for file in actual.keys() {
+        if !manifest.files.contains_key(file) {
+            error!("{} is not in the manifest", file.red());
+            valid = false;
+        }
+    }
+
+    if valid {
+        info!(
+            "All {} files match the signed manifest.",
+            manifest.files.len().to_string().green()
+        );
+    }
+    valid
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_signature() {
+        let key = SigningKey::from_bytes(&[1; 32]);
+        let other = SigningKey::from_bytes(&[2; 32]);
+        let mut files = BTreeMap::new();
+        files.insert("index.json".to_string(), "abc".to_string());
+        let manifest = sign(&key, files);
+
+        assert_eq!(128, manifest.signature.len());
+        assert!(signature_is_valid(&key.verifying_key(), &manifest));
+        assert!(!signature_is_valid(&other.verifying_key(), &manifest));
+
+        let mut tampered = sign(&key, manifest.files.clone());
+        tampered.files.insert("index.json".into(), "abd".into());
+        assert!(!signature_is_valid(&key.verifying_key(), &tampered));
+    }
+
+    #[test]
+    fn test_decode_hex() {
+        assert_eq!(Some(vec![0x0f, 0xa0]), decode_hex("0fa0"));
+        assert_eq!("0fa0", encode_hex(&[0x0f, 0xa0]));
+        assert_eq!(None, decode_hex("0fa"));
+        assert_eq!(None, decode_hex("zz"));
+    }
+}
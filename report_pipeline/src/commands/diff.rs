@@ -0,0 +1,330 @@
+use crate::model::report::{ContestReport, DropResult};
+use crate::util::{get_files_from_path, read_serialized};
+use colored::*;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::path::{Path, PathBuf};
+use tracing::info;
+
+const REPORT_FILENAME: &str = "report.json";
+
+/// A difference in one contest's results between two reports.
+#[derive(Debug, PartialEq)]
+pub enum ContestChange {
+    Winner {
+        old: String,
+        new: String,
+    },
+    BallotCount {
+        old: u32,
+        new: u32,
+    },
+    Rounds {
+        old: usize,
+        new: usize,
+    },
+    FirstRoundVotes {
+        candidate: String,
+        old: u32,
+        new: u32,
+    },
+    FinalVotes {
+        candidate: String,
+        old: u32,
+        new: u32,
+    },
+    CandidateAdded(String),
+    CandidateRemoved(String),
+}
+
+impl fmt::Display for ContestChange {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ContestChange::Winner { old, new } => write!(f, "winner: {} -> {}", old, new),
+            ContestChange::BallotCount { old, new } => {
+                write!(f, "ballots: {} -> {}", old, new)
+            }
+            ContestChange::Rounds { old, new } => write!(f, "rounds: {} -> {}", old, new),
+            ContestChange::FirstRoundVotes {
+                candidate,
+                old,
+                new,
+            } => write!(f, "{} first-round votes: {} -> {}", candidate, old, new),
+            ContestChange::FinalVotes {
+                candidate,
+                old,
+                new,
+            } => write!(f, "{} final votes: {} -> {}", candidate, old, new),
+            ContestChange::CandidateAdded(candidate) => write!(f, "{} added", candidate),
+            ContestChange::CandidateRemoved(candidate) => write!(f, "{} removed", candidate),
+        }
+    }
+}
+
+/// The parts of a contest report compared by `diff`.
+#[derive(Debug, PartialEq)]
+pub struct ContestSummary {
+    pub winner: String,
+    pub ballot_count: u32,
+    pub rounds: usize,
+    /// First-round and final vote totals of each candidate, by name.
+    pub tallies: BTreeMap<String, (u32, u32)>,
+}
+
+impl ContestSummary {
+    pub fn new(report: &ContestReport) -> ContestSummary {
+        ContestSummary {
+            winner: report.winner_names(),
+            ballot_count: report.ballot_count,
+            rounds: report.rounds.len(),
+            tallies: report
+                .total_votes
+                .iter()
+                .map(|v| {
+                    let name = report.candidates[v.candidate.0 as usize].name.clone();
+                    let final_votes = v.first_round_votes + v.transfer_votes;
+                    (name, (v.first_round_votes, final_votes))
+                })
+                .collect(),
+        }
+    }
+}
+
+/// Record a contest's results as of `drop`, with the changes since the
+/// results of the `previous` drop, if any.
+pub fn drop_result(
+    drop: u32,
+    recorded_at: u64,
+    summary: &ContestSummary,
+    previous: Option<&DropResult>,
+) -> DropResult {
+    let first_round_votes: BTreeMap<String, u32> = summary
+        .tallies
+        .iter()
+        .map(|(name, (first, _))| (name.clone(), *first))
+        .collect();
+    let final_votes: BTreeMap<String, u32> = summary
+        .tallies
+        .iter()
+        .map(|(name, (_, last))| (name.clone(), *last))
+        .collect();
+
+    let changes = |votes: &BTreeMap<String, u32>, old: Option<&BTreeMap<String, u32>>| {
+        votes
+            .iter()
+            .map(|(name, v)| {
+                let old = old.and_then(|o| o.get(name)).copied().unwrap_or(0);
+                (name.clone(), *v as i64 - old as i64)
+            })
+            .collect()
+    };
+
+    DropResult {
+        drop,
+        recorded_at,
+        winner: summary.winner.clone(),
+        ballot_count: summary.ballot_count,
+        ballots_added: summary.ballot_count as i64 - previous.map_or(0, |p| p.ballot_count as i64),
+        first_round_changes: changes(&first_round_votes, previous.map(|p| &p.first_round_votes)),
+        final_changes: changes(&final_votes, previous.map(|p| &p.final_votes)),
+        first_round_votes,
+        final_votes,
+    }
+}
+
+/// Compare the results of two reports of the same contest. Candidates are
+/// matched by name, since their ids may change between pipeline versions.
+pub fn diff_contests(old: &ContestSummary, new: &ContestSummary) -> Vec<ContestChange> {
+    let mut changes = Vec::new();
+
+    if old.winner != new.winner {
+        changes.push(ContestChange::Winner {
+            old: old.winner.clone(),
+            new: new.winner.clone(),
+        });
+    }
+    if old.ballot_count != new.ballot_count {
+        changes.push(ContestChange::BallotCount {
+            old: old.ballot_count,
+            new: new.ballot_count,
+        });
+    }
+    if old.rounds != new.rounds {
+        changes.push(ContestChange::Rounds {
+            old: old.rounds,
+            new: new.rounds,
+        });
+    }
+
+    for (candidate, (old_first, old_final)) in &old.tallies {
+        match new.tallies.get(candidate) {
+            None => changes.push(ContestChange::CandidateRemoved(candidate.clone())),
+            Some((new_first, new_final)) => {
+                if old_first != new_first {
+                    changes.push(ContestChange::FirstRoundVotes {
+                        candidate: candidate.clone(),
+                        old: *old_first,
+                        new: *new_first,
+                    });
+                }
+                if old_final != new_final {
+                    changes.push(ContestChange::FinalVotes {
+                        candidate: candidate.clone(),
+                        old: *old_final,
+                        new: *new_final,
+                    });
+                }
+            }
+        }
+    }
+    for candidate in new.tallies.keys() {
+        if !old.tallies.contains_key(candidate) {
+            changes.push(ContestChange::CandidateAdded(candidate.clone()));
+        }
+    }
+
+    changes
+}
+
+/// Read and compare two report files of the same contest.
+fn diff_report_files(old: &Path, new: &Path) -> Vec<ContestChange> {
+    let old: ContestReport = read_serialized(old);
+    let new: ContestReport = read_serialized(new);
+    diff_contests(&ContestSummary::new(&old), &ContestSummary::new(&new))
+}
+
+/// Find the contest reports under `path`, keyed by contest path.
+pub(crate) fn contest_reports(path: &Path) -> BTreeMap<String, PathBuf> {
+    get_files_from_path(path)
+        .unwrap()
+        .into_iter()
+        .filter(|file| file.file_name().and_then(|f| f.to_str()) == Some(REPORT_FILENAME))
+        .map(|file| {
+            let contest = file.parent().unwrap().strip_prefix(path).unwrap();
+            (contest.to_string_lossy().into_owned(), file)
+        })
+        .collect()
+}
+
+/// Compare two report directories (or two report files of the same contest)
+/// and log the contests whose winner, ballot count, number of rounds or
+/// candidate tallies differ, and contests found in only one of them.
+pub fn diff(old_path: &Path, new_path: &Path) {
+    if old_path.is_file() && new_path.is_file() {
+        let changes = diff_report_files(old_path, new_path);
+        for change in &changes {
+            info!("  {}", change);
+        }
+        info!("{} difference(s).", changes.len());
+        return;
+    }
+
+    let old_reports = contest_reports(old_path);
+    let new_reports = contest_reports(new_path);
+    let mut num_changed = 0;
+
+    for (contest, old_file) in &old_reports {
+        let new_file = match new_reports.get(contest) {
+            Some(new_file) => new_file,
+            None => {
+                info!("{}: {}", contest.red(), "only in old reports");
+                num_changed += 1;
+                continue;
+            }
+        };
+
+        let changes = diff_report_files(old_file, new_file);
+        if !changes.is_empty() {
+            num_changed += 1;
+            info!("{}:", contest.yellow());
+            for change in &changes {
+                info!("  {}", change);
+            }
+        }
+    }
+    for contest in new_reports.keys() {
+        if !old_reports.contains_key(contest) {
+            info!("{}: {}", contest.green(), "only in new reports");
+            num_changed += 1;
+        }
+    }
+
+    info!(
+        "{} of {} contest(s) differ.",
+        num_changed,
+        old_reports.len().max(new_reports.len())
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn summary(winner: &str, ballot_count: u32, tallies: &[(&str, u32, u32)]) -> ContestSummary {
+        ContestSummary {
+            winner: winner.to_string(),
+            ballot_count,
+            rounds: 2,
+            tallies: tallies
+                .iter()
+                .map(|(name, first, last)| (name.to_string(), (*first, *last)))
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn test_diff_contests() {
+        let old = summary("Ann", 10, &[("Ann", 6, 6), ("Bea", 4, 4)]);
+        assert!(diff_contests(&old, &old).is_empty());
+
+        let new = summary("Bea", 11, &[("Ann", 6, 6), ("Bea", 5, 7), ("Cal", 0, 0)]);
+        let changes = diff_contests(&old, &new);
+        assert_eq!(
+            vec![
+                ContestChange::Winner {
+                    old: "Ann".into(),
+                    new: "Bea".into()
+                },
+                ContestChange::BallotCount { old: 10, new: 11 },
+                ContestChange::FirstRoundVotes {
+                    candidate: "Bea".into(),
+                    old: 4,
+                    new: 5
+                },
+                ContestChange::FinalVotes {
+                    candidate: "Bea".into(),
+                    old: 4,
+                    new: 7
+                },
+                ContestChange::CandidateAdded("Cal".into()),
+            ],
+            changes
+        );
+        assert_eq!("winner: Ann -> Bea", changes[0].to_string());
+    }
+
+    #[test]
+    fn test_drop_result() {
+        let first = drop_result(
+            1,
+            0,
+            &summary("Ann", 10, &[("Ann", 6, 6), ("Bea", 4, 4)]),
+            None,
+        );
+        assert_eq!(10, first.ballots_added);
+        assert_eq!(Some(&6), first.first_round_changes.get("Ann"));
+
+        let second = drop_result(
+            2,
+            0,
+            &summary("Bea", 15, &[("Ann", 7, 7), ("Bea", 6, 8), ("Cal", 2, 0)]),
+            Some(&first),
+        );
+        assert_eq!(5, second.ballots_added);
+        assert_eq!(Some(&1), second.first_round_changes.get("Ann"));
+        assert_eq!(Some(&2), second.first_round_changes.get("Bea"));
+        assert_eq!(Some(&2), second.first_round_changes.get("Cal"));
+        assert_eq!(Some(&4), second.final_changes.get("Bea"));
+        assert_eq!("Bea", second.winner);
+    }
+}
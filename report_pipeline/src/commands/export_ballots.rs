@@ -0,0 +1,96 @@
+use crate::export::{write_ballots_parquet, write_choices_parquet, write_rank_csv};
+use crate::model::election::ElectionPreprocessed;
+use crate::util::{get_files_from_path, read_serialized, record_output};
+use colored::*;
+use std::fs::{create_dir_all, File};
+use std::io::BufWriter;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use tracing::info;
+
+pub(crate) const PREPROCESSED_FILENAME: &str = "normalized.json.gz";
+const BALLOTS_CSV_FILENAME: &str = "ballots.csv";
+const BALLOTS_PARQUET_FILENAME: &str = "ballots.parquet";
+const CHOICES_PARQUET_FILENAME: &str = "choices.parquet";
+
+/// The file format ballots are exported in.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BallotExportFormat {
+    /// A rank-column CSV per contest, for re-tabulation in other tools.
+    Csv,
+    /// A table of ballots and a table of their choices per contest, as
+    /// Apache Parquet, for analysis in pandas, DuckDB and the like.
+    Parquet,
+}
+
+impl FromStr for BallotExportFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "csv" => Ok(BallotExportFormat::Csv),
+            "parquet" => Ok(BallotExportFormat::Parquet),
+            _ => Err(format!(
+                "unknown ballot export format {:?}; expected csv or parquet",
+                s
+            )),
+        }
+    }
+}
+
+/// Find every preprocessed contest under `preprocessed_dir`, returning the
+/// contest's directory relative to `preprocessed_dir` and the file path.
+pub(crate) fn preprocessed_contests(preprocessed_dir: &Path) -> Vec<(PathBuf, PathBuf)> {
+    get_files_from_path(preprocessed_dir)
+        .unwrap()
+        .into_iter()
+        .filter(|file| file.file_name().and_then(|f| f.to_str()) == Some(PREPROCESSED_FILENAME))
+        .map(|file| {
+            let contest_dir = file
+                .parent()
+                .unwrap()
+                .strip_prefix(preprocessed_dir)
+                .unwrap()
+                .to_path_buf();
+            (contest_dir, file)
+        })
+        .collect()
+}
+
+/// Export every preprocessed contest under `preprocessed_dir` in `format`,
+/// mirroring the directory layout under `output_dir`.
+pub fn export_ballots(preprocessed_dir: &Path, output_dir: &Path, format: BallotExportFormat) {
+    for (contest_dir, file) in preprocessed_contests(preprocessed_dir) {
+        let preprocessed: ElectionPreprocessed = read_serialized(&file);
+        let ballots = &preprocessed.ballots;
+
+        let contest_output_dir = output_dir.join(&contest_dir);
+        create_dir_all(&contest_output_dir).unwrap();
+        info!(
+            "Exporting {} ballots to {}",
+            ballots.ballots.len(),
+            contest_output_dir.to_string_lossy().bright_blue()
+        );
+
+        match format {
+            BallotExportFormat::Csv => {
+                let output_path = contest_output_dir.join(BALLOTS_CSV_FILENAME);
+                let mut writer = BufWriter::new(File::create(&output_path).unwrap());
+                write_rank_csv(ballots, &mut writer).unwrap();
+                record_output(&output_path);
+            }
+            BallotExportFormat::Parquet => {
+                let contest = contest_dir.to_string_lossy().replace('\\', "/");
+                let ballots_path = contest_output_dir.join(BALLOTS_PARQUET_FILENAME);
+                write_ballots_parquet(&contest, ballots, File::create(&ballots_path).unwrap())
+                    .unwrap();
+                record_output(&ballots_path);
+
+                let choices_path = contest_output_dir.join(CHOICES_PARQUET_FILENAME);
+                write_choices_parquet(&contest, ballots, File::create(&choices_path).unwrap())
+                    .unwrap();
+                record_output(&choices_path);
+            }
+        }
+    }
+}
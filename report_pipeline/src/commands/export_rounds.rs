@@ -0,0 +1,31 @@
+use crate::commands::diff::contest_reports;
+use crate::export::write_rounds_csv;
+use crate::model::report::ContestReport;
+use crate::util::{read_serialized, record_output};
+use colored::*;
+use std::fs::{create_dir_all, File};
+use std::io::BufWriter;
+use std::path::Path;
+use tracing::info;
+
+const ROUNDS_CSV_FILENAME: &str = "rounds.csv";
+
+/// Export the rounds of every report under `report_dir` as a round-by-round
+/// CSV, mirroring the directory layout under `output_dir`.
+pub fn export_rounds(report_dir: &Path, output_dir: &Path) {
+    for (contest, file) in contest_reports(report_dir) {
+        let report: ContestReport = read_serialized(&file);
+
+        let output_path = output_dir.join(&contest).join(ROUNDS_CSV_FILENAME);
+        create_dir_all(output_path.parent().unwrap()).unwrap();
+        info!(
+            "Exporting {} rounds to {}",
+            report.rounds.len(),
+            output_path.to_string_lossy().bright_blue()
+        );
+
+        let mut writer = BufWriter::new(File::create(&output_path).unwrap());
+        write_rounds_csv(&report.candidates, &report.rounds, &mut writer).unwrap();
+        record_output(&output_path);
+    }
+}
@@ -0,0 +1,155 @@
+use regex::Regex;
+
+/// Selects which contests a command should process.
+#[derive(Default)]
+pub struct ContestFilter {
+    /// Contests to include, each given either as an office id (`mayor`) or as
+    /// a full contest path (`us/ny/nyc/2025/07/mayor`). Empty means all.
+    pub contests: Vec<String>,
+    /// Regular expression matched against the office id and office name.
+    pub office_pattern: Option<Regex>,
+    /// Jurisdiction paths (`us/ny/nyc`) to include. Empty means all.
+    pub jurisdictions: Vec<String>,
+    /// Elections to include, each given either as an election path within
+    /// its jurisdiction (`2025/07`) or in full (`us/ny/nyc/2025/07`). Empty
+    /// means all.
+    pub elections: Vec<String>,
+}
+
+impl ContestFilter {
+    pub fn new(contests: Vec<String>, office_pattern: Option<&str>) -> ContestFilter {
+        let office_pattern = office_pattern.map(|p| {
+            Regex::new(p).unwrap_or_else(|e| panic!("Invalid office pattern {}: {}", p, e))
+        });
+
+        ContestFilter {
+            contests,
+            office_pattern,
+            ..Default::default()
+        }
+    }
+
+    pub fn with_jurisdictions(self, jurisdictions: Vec<String>) -> ContestFilter {
+        ContestFilter {
+            jurisdictions,
+            ..self
+        }
+    }
+
+    pub fn with_elections(self, elections: Vec<String>) -> ContestFilter {
+        ContestFilter { elections, ..self }
+    }
+
+    /// Return true if no filters are set, i.e. every contest matches.
+    pub fn is_empty(&self) -> bool {
+        self.contests.is_empty()
+            && self.office_pattern.is_none()
+            && self.jurisdictions.is_empty()
+            && self.elections.is_empty()
+    }
+
+    /// Return true if the contest at `contest_path`, for the office with the
+    /// given id and display name, passes the filter.
+    pub fn matches(&self, contest_path: &str, office: &str, office_name: &str) -> bool {
+        if !self.contests.is_empty()
+            && !self
+                .contests
+                .iter()
+                .any(|c| c == office || c.trim_end_matches('/') == contest_path)
+        {
+            return false;
+        }
+
+        // Contest paths are `<jurisdiction>/<election>/<office>`.
+        let election_path = contest_path
+            .strip_suffix(office)
+            .unwrap_or(contest_path)
+            .trim_end_matches('/');
+
+        if !self.jurisdictions.is_empty()
+            && !self.jurisdictions.iter().any(|j| {
+                election_path
+                    .strip_prefix(j.trim_end_matches('/'))
+                    .is_some_and(|rest| rest.starts_with('/'))
+            })
+        {
+            return false;
+        }
+
+        if !self.elections.is_empty()
+            && !self.elections.iter().any(|e| {
+                let e = e.trim_end_matches('/');
+                election_path == e
+                    || election_path
+                        .strip_suffix(e)
+                        .is_some_and(|rest| rest.ends_with('/'))
+            })
+        {
+            return false;
+        }
+
+        if let Some(rx) = &self.office_pattern {
+            if !rx.is_match(office) && !rx.is_match(office_name) {
+                return false;
+            }
+        }
+
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_empty_filter_matches_everything() {
+        let filter = ContestFilter::default();
+        assert!(filter.is_empty());
+        assert!(filter.matches("us/ny/nyc/2025/07/mayor", "mayor", "DEM Mayor"));
+    }
+
+    #[test]
+    fn test_contest_filter() {
+        let filter = ContestFilter::new(
+            vec!["comptroller".into(), "us/ny/nyc/2025/07/mayor".into()],
+            None,
+        );
+        assert!(filter.matches("us/ny/nyc/2025/07/mayor", "mayor", "DEM Mayor"));
+        assert!(filter.matches(
+            "us/ny/nyc/2025/07/comptroller",
+            "comptroller",
+            "DEM Comptroller"
+        ));
+        assert!(!filter.matches("us/ny/nyc/2021/06/mayor", "mayor", "DEM Mayor"));
+    }
+
+    #[test]
+    fn test_jurisdiction_and_election_filters() {
+        let filter = ContestFilter::default().with_jurisdictions(vec!["us/ny/nyc".into()]);
+        assert!(!filter.is_empty());
+        assert!(filter.matches("us/ny/nyc/2025/07/mayor", "mayor", "DEM Mayor"));
+        assert!(!filter.matches("us/ny/nycx/2025/07/mayor", "mayor", "DEM Mayor"));
+        assert!(!filter.matches("us/ca/sfo/2019/11/mayor", "mayor", "Mayor"));
+
+        let filter = ContestFilter::default().with_elections(vec!["2025/07".into()]);
+        assert!(filter.matches("us/ny/nyc/2025/07/mayor", "mayor", "DEM Mayor"));
+        assert!(filter.matches("us/ca/sfo/2025/07/mayor", "mayor", "Mayor"));
+        assert!(!filter.matches("us/ny/nyc/2021/06/mayor", "mayor", "DEM Mayor"));
+
+        let filter = ContestFilter::default().with_elections(vec!["us/ny/nyc/2025/07".into()]);
+        assert!(filter.matches("us/ny/nyc/2025/07/mayor", "mayor", "DEM Mayor"));
+        assert!(!filter.matches("us/ca/sfo/2025/07/mayor", "mayor", "Mayor"));
+    }
+
+    #[test]
+    fn test_office_pattern() {
+        let filter = ContestFilter::new(vec![], Some("^council-member"));
+        assert!(filter.matches(
+            "us/ny/nyc/2025/07/council-member-1st-council-district",
+            "council-member-1st-council-district",
+            "DEM Council Member - 1st Council District"
+        ));
+        assert!(!filter.matches("us/ny/nyc/2025/07/mayor", "mayor", "DEM Mayor"));
+    }
+}
@@ -0,0 +1,230 @@
+use crate::commands::diff::contest_reports;
+use crate::model::election::Candidate;
+use crate::model::report::{ContestReport, SubJurisdictionResult};
+use crate::tabulator::{Allocatee, TabulatorAllocation};
+use crate::util::{read_serialized, write_output};
+use colored::*;
+use serde_json::{Map, Value};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs::create_dir_all;
+use std::path::Path;
+use tracing::{info, warn};
+
+const GEOJSON_FILENAME: &str = "precincts.geojson";
+
+/// Names of the feature properties matched and written by `join_geojson`.
+pub struct GeoJsonOptions {
+    /// Property holding the precinct id, matched against the names of a
+    /// report's sub-jurisdiction results.
+    pub id_property: String,
+    /// Property set to the number of ballots cast in the precinct.
+    pub ballots_property: String,
+    /// Property set to first-choice votes, keyed by candidate name.
+    pub first_choice_property: String,
+    /// Property set to final-round votes, keyed by candidate name.
+    pub final_round_property: String,
+    /// Property set to the name of the final-round leader in the precinct.
+    pub leader_property: String,
+}
+
+impl Default for GeoJsonOptions {
+    fn default() -> Self {
+        GeoJsonOptions {
+            id_property: "precinct_id".to_string(),
+            ballots_property: "ballots".to_string(),
+            first_choice_property: "first_choice".to_string(),
+            final_round_property: "final_round".to_string(),
+            leader_property: "leader".to_string(),
+        }
+    }
+}
+
+/// Votes by candidate name, with exhausted ballots under `Exhausted`.
+fn votes_by_name(candidates: &[Candidate], allocations: &[TabulatorAllocation]) -> Value {
+    allocations
+        .iter()
+        .map(|a| {
+            let name = match a.allocatee {
+                Allocatee::Candidate(c) => candidates[c.0 as usize].name.clone(),
+                Allocatee::Exhausted => "Exhausted".to_string(),
+            };
+            (name, Value::from(a.votes))
+        })
+        .collect::<Map<_, _>>()
+        .into()
+}
+
+/// A property value as a precinct id. Ids may be given as strings or
+/// numbers.
+fn property_id(value: &Value) -> Option<String> {
+    match value {
+        Value::String(s) => Some(s.clone()),
+        Value::Number(n) => Some(n.to_string()),
+        _ => None,
+    }
+}
+
+/// Copy `geojson`, a feature collection, adding the results in each precinct
+/// to the properties of the features with the matching id. Features without
+/// results are copied unchanged. Returns the merged collection and the
+/// number of precincts matched.
+pub fn join_results(
+    geojson: &Value,
+    candidates: &[Candidate],
+    results: &[SubJurisdictionResult],
+    options: &GeoJsonOptions,
+) -> (Value, usize) {
+    let results: BTreeMap<&str, &SubJurisdictionResult> =
+        results.iter().map(|r| (r.name.as_str(), r)).collect();
+
+    let mut merged = geojson.clone();
+    let mut matched: BTreeSet<&str> = BTreeSet::new();
+    let features = merged
+        .get_mut("features")
+        .and_then(Value::as_array_mut)
+        .into_iter()
+        .flatten();
+    for feature in features {
+        let properties = match feature.get_mut("properties").and_then(Value::as_object_mut) {
+            Some(properties) => properties,
+            None => continue,
+        };
+        let result = properties
+            .get(&options.id_property)
+            .and_then(property_id)
+            .and_then(|id| results.get(id.as_str()));
+        let result = match result {
+            Some(result) => *result,
+            None => continue,
+        };
+
+        properties.insert(
+            options.ballots_property.clone(),
+            Value::from(result.ballot_count),
+        );
+        properties.insert(
+            options.first_choice_property.clone(),
+            votes_by_name(candidates, &result.first_choice),
+        );
+        properties.insert(
+            options.final_round_property.clone(),
+            votes_by_name(candidates, &result.final_round),
+        );
+        let leader = result
+            .final_round
+            .iter()
+            .filter_map(|a| a.allocatee.candidate_id().map(|c| (c, a.votes)))
+            .max_by_key(|(_, votes)| *votes)
+            .map(|(c, _)| candidates[c.0 as usize].name.clone());
+        if let Some(leader) = leader {
+            properties.insert(options.leader_property.clone(), Value::from(leader));
+        }
+        matched.insert(result.name.as_str());
+    }
+
+    (merged, matched.len())
+}
+
+/// Join the precinct results of every contest report under `report_dir` to
+/// the precinct features in `geojson_path`, writing a merged GeoJSON file
+/// per contest under `output_dir`. Contests without precinct results, or
+/// whose precincts match no feature, are skipped.
+pub fn join_geojson(
+    report_dir: &Path,
+    geojson_path: &Path,
+    output_dir: &Path,
+    options: &GeoJsonOptions,
+) {
+    let geojson: Value = read_serialized(geojson_path);
+
+    for (contest, report_path) in contest_reports(report_dir) {
+        let report: ContestReport = read_serialized(&report_path);
+        let results = match &report.sub_jurisdiction_results {
+            Some(results) => results,
+            None => continue,
+        };
+        let num_precincts = results.len();
+
+        let (merged, matched) = join_results(&geojson, &report.candidates, results, options);
+        if matched == 0 {
+            warn!(
+                "{}: none of its {} precincts match a feature",
+                contest.yellow(),
+                num_precincts
+            );
+            continue;
+        }
+        if matched < num_precincts {
+            warn!(
+                "{}: only {} of {} precincts match a feature",
+                contest.yellow(),
+                matched,
+                num_precincts
+            );
+        }
+
+        let output_path = output_dir.join(&contest).join(GEOJSON_FILENAME);
+        create_dir_all(output_path.parent().unwrap()).unwrap();
+        info!("{}: joined {} precincts", contest.green(), matched);
+        write_output(&output_path, &merged);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::election::{CandidateId, CandidateType};
+    use serde_json::json;
+
+    fn allocations(votes: &[(Allocatee, u32)]) -> Vec<TabulatorAllocation> {
+        votes
+            .iter()
+            .map(|(allocatee, votes)| TabulatorAllocation {
+                allocatee: *allocatee,
+                votes: *votes,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_join_results() {
+        let candidates = vec![
+            Candidate::new("Ann".into(), CandidateType::Regular),
+            Candidate::new("Bea".into(), CandidateType::Regular),
+        ];
+        let ann = Allocatee::Candidate(CandidateId(0));
+        let bea = Allocatee::Candidate(CandidateId(1));
+        let results = vec![SubJurisdictionResult {
+            name: "12".into(),
+            ballot_count: 10,
+            first_choice: allocations(&[(ann, 4), (bea, 6)]),
+            final_round: allocations(&[(bea, 6), (ann, 3), (Allocatee::Exhausted, 1)]),
+        }];
+        let geojson = json!({
+            "type": "FeatureCollection",
+            "features": [
+                {"type": "Feature", "properties": {"pct": 12}, "geometry": null},
+                {"type": "Feature", "properties": {"pct": "13"}, "geometry": null}
+            ]
+        });
+        let options = GeoJsonOptions {
+            id_property: "pct".into(),
+            ..GeoJsonOptions::default()
+        };
+
+        let (merged, matched) = join_results(&geojson, &candidates, &results, &options);
+
+        assert_eq!(1, matched);
+        assert_eq!(
+            json!({
+                "pct": 12,
+                "ballots": 10,
+                "first_choice": {"Ann": 4, "Bea": 6},
+                "final_round": {"Bea": 6, "Ann": 3, "Exhausted": 1},
+                "leader": "Bea"
+            }),
+            merged["features"][0]["properties"]
+        );
+        assert_eq!(json!({"pct": "13"}), merged["features"][1]["properties"]);
+    }
+}
@@ -0,0 +1,44 @@
+use crate::operations::read_operations;
+use crate::util::{record_outcome, Outcome};
+use colored::*;
+use std::path::Path;
+use tracing::{error, info};
+
+/// Print the operations recorded in the log at `log_path`, oldest first,
+/// optionally only those of one command or that read or wrote a file whose
+/// path contains `file`. With `file`, the hashes of the matching files are
+/// printed too, to trace a published file back to its inputs.
+pub fn history(log_path: &Path, command: Option<&str>, file: Option<&str>) {
+    let records = match read_operations(log_path) {
+        Ok(records) => records,
+        Err(err) => {
+            record_outcome(Outcome::DataError);
+            error!("Could not read operations log {}", err);
+            return;
+        }
+    };
+
+    for record in records {
+        if command.is_some_and(|c| c != record.command) || file.is_some_and(|f| !record.touches(f))
+        {
+            continue;
+        }
+
+        info!(
+            "{} {} (started {}, {}s): {} input(s), {} output(s)",
+            record.command.blue(),
+            record.args.join(" "),
+            record.started_at,
+            record.finished_at - record.started_at,
+            record.inputs.len(),
+            record.outputs.len()
+        );
+        if let Some(file) = file {
+            for (kind, files) in &[("read", &record.inputs), ("wrote", &record.outputs)] {
+                for (path, hash) in files.iter().filter(|(path, _)| path.contains(file)) {
+                    info!("  {} {} {}", kind, path.bright_cyan(), hash);
+                }
+            }
+        }
+    }
+}
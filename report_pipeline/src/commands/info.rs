@@ -1,22 +1,77 @@
 use std::path::Path;
 
 use crate::read_metadata::read_meta;
+use crate::util::{get_files_from_path, print_summary};
+use crate::validate::{validate_metadata, MetadataError};
 use colored::*;
+use serde::Serialize;
+use tracing::{error, info};
+
+/// What `info` found, printed to stdout with `--output json`.
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct InfoSummary<'a> {
+    command: &'static str,
+    jurisdictions: u32,
+    /// Election paths, e.g. `us/ny/nyc/2025/07`.
+    elections: Vec<String>,
+    files: u32,
+    problems: &'a [MetadataError],
+}
+
+/// Dump the jurisdictions and elections in `meta_dir`, then validate every
+/// metadata file, logging each problem found (or, with `json`, printing the
+/// list of problems to stdout as JSON). Returns true if there were none.
+pub fn info(meta_dir: &Path, json: bool) -> bool {
+    let mut jurisdictions = 0;
+    let mut elections = Vec::new();
+    let mut files = 0;
 
-pub fn info(meta_dir: &Path) {
     for (_, ec) in read_meta(meta_dir) {
-        eprintln!("Name: {}", ec.name.blue());
-        eprintln!("Path: {}", ec.path.blue());
-        eprintln!("Kind: {}", ec.kind.blue());
+        info!("Name: {}", ec.name.blue());
+        info!("Path: {}", ec.path.blue());
+        info!("Kind: {}", ec.kind.blue());
+        jurisdictions += 1;
 
         for (key, election) in &ec.elections {
-            eprintln!("Election: {}", key.blue());
-            eprintln!("  Name: {}", election.name.blue());
-            eprintln!("  Date: {}", election.date.blue());
+            info!("Election: {}", key.blue());
+            info!("  Name: {}", election.name.blue());
+            info!("  Date: {}", election.date.blue());
+            elections.push(format!("{}/{}", ec.path, key));
 
             for file in election.files.keys() {
-                eprintln!("    File: {}", file.blue());
+                info!("    File: {}", file.blue());
+                files += 1;
             }
         }
     }
+
+    let errors: Vec<_> = get_files_from_path(meta_dir)
+        .unwrap()
+        .iter()
+        .flat_map(|file| validate_metadata(file))
+        .collect();
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&errors).unwrap());
+    } else {
+        for err in &errors {
+            error!("{}", err);
+        }
+    }
+    if errors.is_empty() {
+        info!("{}", "Metadata is valid.".green());
+    } else {
+        error!("{} metadata problem(s) found.", errors.len());
+    }
+
+    print_summary(&InfoSummary {
+        command: "info",
+        jurisdictions,
+        elections,
+        files,
+        problems: &errors,
+    });
+
+    errors.is_empty()
 }
@@ -0,0 +1,328 @@
+use crate::anonymize::anonymize_ballot_ids;
+use crate::commands::export_ballots::PREPROCESSED_FILENAME;
+use crate::commands::report::{is_cache_fresh, write_cache_key};
+use crate::formats::read_elections;
+use crate::metrics::{MonitoringOptions, RunSummary};
+use crate::read_metadata::read_meta;
+use crate::report::{ballot_id_salt, normalize_contest, preprocess_cache_key};
+use crate::util::{
+    hash_serialized, read_serialized, record_outcome, try_read_serialized, verify_files,
+    write_output, DirLock, Outcome,
+};
+use colored::*;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs::create_dir_all;
+use std::path::{Path, PathBuf};
+use tracing::{error, info, warn};
+
+/// A list of elections to preprocess in one run.
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IngestManifest {
+    pub elections: Vec<IngestEntry>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IngestEntry {
+    /// Jurisdiction path, e.g. `us/ny/nyc`.
+    pub jurisdiction: String,
+    /// Election path within the jurisdiction, e.g. `2025/07`.
+    pub election: String,
+    /// Directory holding the election's raw data, relative to the manifest.
+    pub raw_path: PathBuf,
+    /// Expected `dataFormat`. If given, it must match the metadata.
+    #[serde(default)]
+    pub format: Option<String>,
+}
+
+/// Name of the file, in each election's preprocessed directory, recording
+/// what was last ingested for it.
+pub(crate) const INGEST_RECORD_FILENAME: &str = "ingest.json";
+
+/// What was last ingested for an election, so that a later drop of the same
+/// election's raw data only replaces the contests it changes.
+#[derive(Serialize, Deserialize, Default, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct IngestRecord {
+    /// Incremented each time an ingest changes the ballots of any contest.
+    pub revision: u32,
+    /// Hashes of the raw data files ingested, keyed by file name.
+    pub files: BTreeMap<String, String>,
+    /// Hashes of each contest's normalized ballots, keyed by office.
+    pub contests: BTreeMap<String, String>,
+}
+
+/// The outcome of ingesting one manifest entry.
+enum IngestResult {
+    Ingested {
+        contests: usize,
+        changed: usize,
+        ballots: usize,
+        revision: u32,
+    },
+    Unchanged {
+        revision: u32,
+    },
+    Failed(String),
+}
+
+fn ingest_election(
+    entry: &IngestEntry,
+    raw_path: &Path,
+    meta_dir: &Path,
+    preprocessed_dir: &Path,
+) -> IngestResult {
+    let jurisdiction = match read_meta(meta_dir).find(|(_, j)| j.path == entry.jurisdiction) {
+        Some((_, jurisdiction)) => jurisdiction,
+        None => return IngestResult::Failed("jurisdiction not in metadata".to_string()),
+    };
+    let election = match jurisdiction.elections.get(&entry.election) {
+        Some(election) => election,
+        None => return IngestResult::Failed("election not in metadata".to_string()),
+    };
+    if let Some(format) = &entry.format {
+        if format != &election.data_format {
+            return IngestResult::Failed(format!(
+                "manifest format {} doesn't match metadata format {}",
+                format, election.data_format
+            ));
+        }
+    }
+
+    let problems = verify_files(raw_path, &election.files);
+    if !problems.is_empty() {
+        for problem in &problems {
+            warn!("{}", problem);
+        }
+        return IngestResult::Failed("raw data doesn't match metadata".to_string());
+    }
+
+    let election_dir = preprocessed_dir
+        .join(&jurisdiction.path)
+        .join(&entry.election);
+    let record_path = election_dir.join(INGEST_RECORD_FILENAME);
+    let previous: IngestRecord = try_read_serialized(&record_path).unwrap_or_default();
+
+    // If the raw files are the ones last ingested and every contest is still
+    // preprocessed from them, there is nothing to re-read.
+    let all_fresh = election.contests.iter().all(|contest| {
+        is_cache_fresh(
+            &election_dir.join(&contest.office),
+            &preprocess_cache_key(election, contest, raw_path),
+        )
+    });
+    if previous.files == election.files && all_fresh {
+        return IngestResult::Unchanged {
+            revision: previous.revision,
+        };
+    }
+
+    let contests = election
+        .contests
+        .iter()
+        .map(|c| {
+            (
+                c.office.clone(),
+                c.loader_params.clone().unwrap_or_default(),
+            )
+        })
+        .collect();
+    let mut elections = match read_elections(&election.data_format, raw_path, contests) {
+        Ok(elections) => elections,
+        Err(err) => return IngestResult::Failed(err.to_string()),
+    };
+    match ballot_id_salt(election, raw_path) {
+        Ok(Some(salt)) => {
+            for read in elections.values_mut() {
+                anonymize_ballot_ids(read, &salt);
+            }
+        }
+        Ok(None) => {}
+        Err(err) => return IngestResult::Failed(err.to_string()),
+    }
+
+    // Every contest is read and normalized before any is replaced, so a drop
+    // that fails to read leaves the previously ingested ballots in place.
+    let mut record = IngestRecord {
+        revision: previous.revision,
+        files: election.files.clone(),
+        contests: BTreeMap::new(),
+    };
+    let mut ballots = 0;
+    let mut replace = Vec::new();
+    for contest in &election.contests {
+        let read = elections.remove(&contest.office).unwrap();
+        let preprocessed =
+            normalize_contest(election, &entry.election, &jurisdiction, contest, read);
+        ballots += preprocessed.ballots.ballots.len();
+
+        let hash = hash_serialized(&preprocessed.ballots);
+        let contest_dir = election_dir.join(&contest.office);
+        let unchanged = previous.contests.get(&contest.office) == Some(&hash)
+            && contest_dir.join(PREPROCESSED_FILENAME).exists();
+        if unchanged {
+            // Only the cache key needs to move on to the new raw files.
+            write_cache_key(
+                &contest_dir,
+                &preprocess_cache_key(election, contest, raw_path),
+            );
+        } else {
+            replace.push((contest, contest_dir, preprocessed));
+        }
+        record.contests.insert(contest.office.clone(), hash);
+    }
+
+    let changed = replace.len();
+    for (contest, contest_dir, preprocessed) in replace {
+        info!(
+            "Replacing ballots of {}",
+            contest_dir.to_str().unwrap().bright_cyan()
+        );
+        create_dir_all(&contest_dir).unwrap();
+        write_output(&contest_dir.join(PREPROCESSED_FILENAME), &preprocessed);
+        write_cache_key(
+            &contest_dir,
+            &preprocess_cache_key(election, contest, raw_path),
+        );
+    }
+
+    if changed > 0 {
+        record.revision += 1;
+    }
+    create_dir_all(&election_dir).unwrap();
+    write_output(&record_path, &record);
+
+    IngestResult::Ingested {
+        contests: election.contests.len(),
+        changed,
+        ballots,
+        revision: record.revision,
+    }
+}
+
+/// Preprocess every election listed in the manifest at `manifest_path`, in
+/// order, writing each contest's normalized ballots to `preprocessed_dir`
+/// where the report command picks them up. An election that was ingested
+/// before is only re-read if its raw files changed, and only contests whose
+/// ballots changed are replaced. An election that fails is logged and
+/// skipped; a summary of every election is logged at the end, and metrics and
+/// a summary of the run are sent as set in `monitoring`.
+pub fn ingest_all(
+    manifest_path: &Path,
+    meta_dir: &Path,
+    preprocessed_dir: &Path,
+    monitoring: &MonitoringOptions,
+) {
+    let _lock = match DirLock::acquire(preprocessed_dir, "ingest-all") {
+        Ok(lock) => lock,
+        Err(err) => {
+            record_outcome(Outcome::InfraError);
+            error!("{}", err);
+            return;
+        }
+    };
+    let manifest: IngestManifest = read_serialized(manifest_path);
+    let manifest_dir = manifest_path.parent().unwrap_or_else(|| Path::new(""));
+
+    let mut run_summary = RunSummary::new("ingest_all");
+    let mut results = Vec::new();
+    for entry in &manifest.elections {
+        let name = format!("{}/{}", entry.jurisdiction, entry.election);
+        info!("Ingesting {}", name.red());
+        let result = ingest_election(
+            entry,
+            &manifest_dir.join(&entry.raw_path),
+            meta_dir,
+            preprocessed_dir,
+        );
+        if let IngestResult::Failed(reason) = &result {
+            record_outcome(Outcome::DataError);
+            error!("Skipping {}: {}", name.red(), reason);
+        }
+        results.push((name, result));
+    }
+
+    let failed = results
+        .iter()
+        .filter(|(_, r)| matches!(r, IngestResult::Failed(_)))
+        .count();
+    info!(
+        "Ingested {} of {} elections:",
+        results.len() - failed,
+        results.len()
+    );
+    for (name, result) in &results {
+        match result {
+            IngestResult::Ingested {
+                contests,
+                changed,
+                ballots,
+                revision,
+            } => info!(
+                "  {}: {} contest(s), {} changed, {} ballots, revision {}",
+                name.green(),
+                contests,
+                changed,
+                ballots,
+                revision
+            ),
+            IngestResult::Unchanged { revision } => {
+                info!("  {}: unchanged, revision {}", name.green(), revision)
+            }
+            IngestResult::Failed(reason) => {
+                warn!("  {}: failed: {}", name.red(), reason);
+                run_summary.add_warning(format!("{}: failed: {}", name, reason));
+            }
+        }
+        if let IngestResult::Ingested {
+            contests, ballots, ..
+        } = result
+        {
+            run_summary.add_election(name);
+            run_summary.add_contests(*contests as u32, *ballots as u64);
+        }
+    }
+
+    run_summary.add_failures(failed as u32);
+    run_summary.add_dir_size("preprocessed", preprocessed_dir);
+    run_summary.export(monitoring);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_read_manifest() {
+        let manifest: IngestManifest = serde_json::from_str(
+            r#"{"elections": [
+                {"jurisdiction": "us/ny/nyc", "election": "2025/07",
+                 "rawPath": "raw-data/us/ny/nyc/2025/07", "format": "us_ny_nyc"},
+                {"jurisdiction": "us/ca/sfo", "election": "2019/11", "rawPath": "sfo"}
+            ]}"#,
+        )
+        .unwrap();
+
+        assert_eq!(2, manifest.elections.len());
+        assert_eq!(Some("us_ny_nyc"), manifest.elections[0].format.as_deref());
+        assert_eq!(PathBuf::from("sfo"), manifest.elections[1].raw_path);
+        assert_eq!(None, manifest.elections[1].format);
+    }
+
+    #[test]
+    fn test_read_ingest_record() {
+        let record: IngestRecord = serde_json::from_str(
+            r#"{"revision": 2, "files": {"cvr.zip": "abc"}, "contests": {"mayor": "def"}}"#,
+        )
+        .unwrap();
+
+        assert_eq!(2, record.revision);
+        assert_eq!(
+            Some("def"),
+            record.contests.get("mayor").map(String::as_str)
+        );
+        assert_eq!(0, IngestRecord::default().revision);
+    }
+}
@@ -0,0 +1,107 @@
+use crate::commands::export_ballots::PREPROCESSED_FILENAME;
+use crate::model::election::{Choice, ElectionPreprocessed};
+use crate::model::report::RawChoice;
+use crate::read_metadata::read_meta;
+use crate::report::{lookup_ballot as trace_ballot, read_contest, require_irv};
+use crate::tabulator::count_rounds;
+use crate::util::try_read_serialized;
+use colored::*;
+use std::path::Path;
+use tracing::{error, warn};
+
+/// Read the raw rankings of one ballot of a contest, or `None` (with a
+/// warning) if the raw data can't be read or doesn't have the ballot.
+fn raw_choices(
+    meta_dir: &Path,
+    raw_dir: &Path,
+    contest_path: &str,
+    ballot_id: &str,
+) -> Option<Vec<RawChoice>> {
+    for (_, jurisdiction) in read_meta(meta_dir) {
+        for (election_path, election) in &jurisdiction.elections {
+            for contest in &election.contests {
+                let path = format!("{}/{}/{}", jurisdiction.path, election_path, contest.office);
+                if path != contest_path {
+                    continue;
+                }
+
+                let raw_base = raw_dir.join(&jurisdiction.path);
+                let raw = match read_contest(&raw_base, election, election_path, contest) {
+                    Ok(raw) => raw,
+                    Err(err) => {
+                        warn!("Could not read raw data of {}: {}", path.red(), err);
+                        return None;
+                    }
+                };
+                let ballot = raw.ballots.into_iter().find(|b| b.id == ballot_id)?;
+                return Some(
+                    ballot
+                        .choices
+                        .into_iter()
+                        .map(|choice| match choice {
+                            Choice::Vote(c) => RawChoice::Candidate(c),
+                            Choice::Undervote => RawChoice::Undervote,
+                            Choice::Overvote => RawChoice::Overvote,
+                        })
+                        .collect(),
+                );
+            }
+        }
+    }
+
+    warn!("Contest {} not found in metadata.", contest_path.red());
+    None
+}
+
+/// Print how one ballot of a contest was counted to stdout as JSON: its
+/// normalized ranking, the candidate it counted for in each round, and the
+/// round in which it was exhausted, if it was. With `raw`, a metadata and raw
+/// data directory, its rankings as read from the raw data are included too.
+/// Ballots are looked up by the id in the preprocessed ballots, which is the
+/// salted hash of the original id for elections with `anonymizeBallotIds`.
+/// Returns false if the contest or ballot can't be found, or if the contest
+/// isn't counted by instant runoff.
+pub fn lookup_ballot(
+    preprocessed_dir: &Path,
+    contest_path: &str,
+    ballot_id: &str,
+    raw: Option<(&Path, &Path)>,
+) -> bool {
+    let contest_path = contest_path.trim_end_matches('/');
+    let file = preprocessed_dir
+        .join(contest_path)
+        .join(PREPROCESSED_FILENAME);
+    let preprocessed: ElectionPreprocessed = match try_read_serialized(&file) {
+        Ok(preprocessed) => preprocessed,
+        Err(err) => {
+            error!(
+                "No preprocessed ballots for {}: {}",
+                contest_path.red(),
+                err
+            );
+            return false;
+        }
+    };
+
+    if let Err(err) = require_irv(&preprocessed.info.tabulation_options) {
+        error!("Can't trace a ballot of {}: {}", contest_path.red(), err);
+        return false;
+    }
+
+    let ballots = &preprocessed.ballots.ballots;
+    let ballot = match ballots.iter().find(|b| b.id == ballot_id) {
+        Some(ballot) => ballot,
+        None => {
+            error!("No ballot {} in {}.", ballot_id.red(), contest_path);
+            return false;
+        }
+    };
+
+    let rounds = count_rounds(ballots, &preprocessed.info.tabulation_options);
+    let mut result = trace_ballot(ballot, &rounds);
+    if let Some((meta_dir, raw_dir)) = raw {
+        result.raw_choices = raw_choices(meta_dir, raw_dir, contest_path, ballot_id);
+    }
+    println!("{}", serde_json::to_string(&result).unwrap());
+    true
+}
@@ -0,0 +1,31 @@
+use crate::metrics::MetricsHistory;
+use crate::util::{record_outcome, Outcome};
+use colored::*;
+use std::path::Path;
+use tracing::{error, info};
+
+/// Print a per-election summary of the stage timings recorded in a metrics
+/// file, using the most recent run of each contest.
+pub fn metrics(metrics_path: &Path) {
+    if !metrics_path.exists() {
+        record_outcome(Outcome::DataError);
+        error!(
+            "No metrics file at {}.",
+            metrics_path.to_string_lossy().red()
+        );
+        return;
+    }
+
+    let history = MetricsHistory::load(metrics_path);
+    for summary in history.election_summaries() {
+        info!(
+            "Election: {} ({} contests, {} ballots)",
+            summary.election.blue(),
+            summary.contests,
+            summary.ballots
+        );
+        for (stage, millis) in &summary.stage_millis {
+            info!("{:>12}: {:.1} ms", stage, millis);
+        }
+    }
+}
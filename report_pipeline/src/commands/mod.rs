@@ -1,7 +1,45 @@
+//! Implementations of the command-line subcommands.
+
+mod ballot_images;
+mod benchmark;
+mod bundle;
+mod diff;
+mod export_ballots;
+mod export_rounds;
+mod filter;
+mod geojson;
+mod history;
 mod info;
+mod ingest;
+mod lookup_ballot;
+mod metrics;
+mod rename_office;
 mod report;
+mod sample_ballots;
+#[cfg(feature = "schema")]
+mod schema;
+mod status;
 mod sync;
+mod what_if;
 
+pub use ballot_images::{export_ballot_images, import_ballot_images};
+pub use benchmark::benchmark;
+pub use bundle::{generate_signing_key, sign_bundle, verify_bundle};
+pub use diff::diff;
+pub use export_ballots::{export_ballots, BallotExportFormat};
+pub use export_rounds::export_rounds;
+pub use filter::ContestFilter;
+pub use geojson::{join_geojson, GeoJsonOptions};
+pub use history::history;
 pub use info::info;
-pub use report::report;
+pub use ingest::ingest_all;
+pub use lookup_ballot::lookup_ballot;
+pub use metrics::metrics;
+pub use rename_office::rename_office;
+pub use report::{report, ReportOptions};
+pub use sample_ballots::sample_ballots;
+#[cfg(feature = "schema")]
+pub use schema::schema;
+pub use status::update_status;
 pub use sync::sync;
+pub use what_if::what_if;
@@ -0,0 +1,119 @@
+use crate::commands::ingest::{IngestRecord, INGEST_RECORD_FILENAME};
+use crate::read_metadata::read_meta;
+use crate::util::{
+    record_outcome, try_read_serialized, write_output, write_serialized, DirLock, Outcome,
+};
+use colored::*;
+use std::fs::{create_dir_all, rename};
+use std::path::Path;
+use tracing::{error, info};
+
+/// Move a contest's directory from `from` to `to` if it exists.
+fn move_contest_dir(from: &Path, to: &Path) {
+    if !from.exists() {
+        return;
+    }
+    info!(
+        "Moving {} to {}",
+        from.to_str().unwrap().bright_blue(),
+        to.to_str().unwrap().bright_blue()
+    );
+    create_dir_all(to.parent().unwrap()).unwrap();
+    rename(from, to).unwrap();
+}
+
+/// Rename office `old` of the jurisdiction at `jurisdiction_path` (e.g.
+/// `us/ny/nyc`) to `new` in its metadata file, recording `old` among the
+/// office's previous ids so links to it keep working. The contests'
+/// preprocessed ballots and reports are moved to match, so nothing has to be
+/// re-read; the next `report` run regenerates the moved reports from the
+/// cached ballots. Renaming to an id that is, or was, used by another office
+/// is refused.
+pub fn rename_office(
+    meta_dir: &Path,
+    jurisdiction_path: &str,
+    old: &str,
+    new: &str,
+    preprocessed_dir: &Path,
+    report_dir: &Path,
+) {
+    let jurisdiction_path = jurisdiction_path.trim_end_matches('/');
+    let _locks = match DirLock::acquire_all(&[preprocessed_dir, report_dir], "rename-office") {
+        Ok(locks) => locks,
+        Err(err) => {
+            record_outcome(Outcome::InfraError);
+            error!("{}", err);
+            return;
+        }
+    };
+    let (file, mut jurisdiction) =
+        match read_meta(meta_dir).find(|(_, j)| j.path == jurisdiction_path) {
+            Some(found) => found,
+            None => {
+                record_outcome(Outcome::DataError);
+                error!("No jurisdiction {} in metadata.", jurisdiction_path.red());
+                return;
+            }
+        };
+
+    let taken = jurisdiction.offices.iter().any(|(id, office)| {
+        id == new || (id != old && office.previous_ids.iter().any(|p| p == new))
+    });
+    if taken {
+        record_outcome(Outcome::DataError);
+        error!(
+            "Office id {} is already used in {}.",
+            new.red(),
+            jurisdiction_path
+        );
+        return;
+    }
+    let mut office = match jurisdiction.offices.remove(old) {
+        Some(office) => office,
+        None => {
+            record_outcome(Outcome::DataError);
+            error!("No office {} in {}.", old.red(), jurisdiction_path);
+            return;
+        }
+    };
+
+    office.previous_ids.retain(|p| p != new);
+    office.previous_ids.push(old.to_string());
+    jurisdiction.offices.insert(new.to_string(), office);
+
+    for (election_path, election) in &mut jurisdiction.elections {
+        let mut renamed = false;
+        for contest in election.contests.iter_mut().filter(|c| c.office == old) {
+            contest.office = new.to_string();
+            renamed = true;
+        }
+        if !renamed {
+            continue;
+        }
+
+        for dir in &[preprocessed_dir, report_dir] {
+            let election_dir = dir.join(&jurisdiction.path).join(election_path);
+            move_contest_dir(&election_dir.join(old), &election_dir.join(new));
+        }
+
+        let record_path = preprocessed_dir
+            .join(&jurisdiction.path)
+            .join(election_path)
+            .join(INGEST_RECORD_FILENAME);
+        if let Ok(mut record) = try_read_serialized::<IngestRecord>(&record_path) {
+            if let Some(hash) = record.contests.remove(old) {
+                record.contests.insert(new.to_string(), hash);
+                write_output(&record_path, &record);
+            }
+        }
+        info!(
+            "{}/{}: {} -> {}",
+            jurisdiction.path.blue(),
+            election_path.blue(),
+            old,
+            new.green()
+        );
+    }
+
+    write_serialized(&file, &jurisdiction);
+}
@@ -1,29 +1,315 @@
-use crate::model::election::ElectionPreprocessed;
-use crate::model::report::{ContestIndexEntry, ElectionIndexEntry, ReportIndex};
+use crate::aggregate::{candidate_profiles, jurisdiction_summaries, trends};
+use crate::commands::diff::{diff_contests, drop_result, ContestSummary};
+use crate::commands::export_ballots::PREPROCESSED_FILENAME;
+use crate::commands::{sign_bundle, ContestFilter};
+use crate::formats::{get_multi_contest_reader_for_format, read_precinct_attributes};
+use crate::metrics::{
+    timestamp, ContestMetrics, MetricsCollector, MetricsHistory, MonitoringOptions, RunSummary,
+};
+use crate::model::election::{CandidateType, Election, ElectionPreprocessed};
+use crate::model::metadata::{Contest, Office};
+use crate::model::report::{
+    BallotCountCheck, ContestIndexEntry, ContestReport, ElectionIndexEntry, ReportHistory,
+    ReportIndex, ReportRevision,
+};
 use crate::read_metadata::read_meta;
-use crate::report::{generate_report, preprocess_election};
-use crate::util::{read_serialized, write_serialized};
+use crate::report::{
+    ecological_estimates, election_info, generate_report, has_votes, localize_candidates,
+    normalize_contest, preprocess_cache_key, provenance, read_contest, read_contests,
+};
+#[cfg(feature = "research")]
+use crate::research::research_methods;
+use crate::tabulator::trace;
+use crate::transfers::transfer_graph;
+use crate::util::{
+    available_threads, map_parallel_with, progress_bar, read_serialized, record_outcome,
+    try_read_serialized, verify_files, write_output, DirLock, Outcome,
+};
 use colored::*;
-use std::fs::create_dir_all;
-use std::path::Path;
+use std::collections::BTreeMap;
+use std::fs::{create_dir_all, read_to_string, write};
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+use tracing::{error, info, warn};
+
+/// Check raw data files against the hashes recorded in metadata, logging
+/// any problems. Returns false if preprocessing should not proceed.
+fn verify_election_files(
+    dir: &Path,
+    files: &BTreeMap<String, String>,
+    allow_hash_mismatch: bool,
+) -> bool {
+    let problems = verify_files(dir, files);
+    for problem in &problems {
+        warn!("{}", problem);
+    }
+
+    problems.is_empty() || allow_hash_mismatch
+}
+
+/// Name of the file, next to each preprocessed file, recording the cache key
+/// it was generated under.
+const CACHE_KEY_FILENAME: &str = "normalized.key";
+
+/// Return true if the preprocessed file in `dir` exists and was generated
+/// under `key`.
+pub(crate) fn is_cache_fresh(dir: &Path, key: &str) -> bool {
+    dir.join(PREPROCESSED_FILENAME).exists()
+        && read_to_string(dir.join(CACHE_KEY_FILENAME)).is_ok_and(|k| k.trim() == key)
+}
+
+/// Record the cache key the preprocessed file in `dir` was generated under.
+pub(crate) fn write_cache_key(dir: &Path, key: &str) {
+    write(dir.join(CACHE_KEY_FILENAME), key).unwrap();
+}
+
+/// Name of the file, next to each report, holding the flow of votes between
+/// rounds for Sankey diagrams.
+const TRANSFERS_FILENAME: &str = "transfers.json";
+
+/// Name of the file, next to each report, explaining each round's
+/// eliminations, written if the `trace` option is set.
+const TRACE_FILENAME: &str = "trace.json";
+
+/// Name of the file, next to each report, holding the contest's winners
+/// under the experimental research methods.
+#[cfg(feature = "research")]
+const METHODS_FILENAME: &str = "methods.json";
+
+/// Name of the file, next to each report, holding the contest's earlier
+/// reports.
+const HISTORY_FILENAME: &str = "history.json";
+
+/// If `new` has different results from the `old` report it replaces, add
+/// `old` to the history in `dir`, so the site can show that results were
+/// updated and when.
+fn record_revision(dir: &Path, old: ContestReport, new: &ContestReport) {
+    let changes = diff_contests(&ContestSummary::new(&old), &ContestSummary::new(new));
+    if changes.is_empty() {
+        return;
+    }
+
+    let history_path = dir.join(HISTORY_FILENAME);
+    let mut history: ReportHistory = if history_path.exists() {
+        read_serialized(&history_path)
+    } else {
+        ReportHistory::default()
+    };
+    info!(
+        "Results changed; keeping previous report in {}",
+        history_path.to_str().unwrap().bright_cyan()
+    );
+    history.revisions.push(ReportRevision {
+        replaced_at: timestamp(),
+        changes: changes.iter().map(|c| c.to_string()).collect(),
+        report: old,
+    });
+    write_output(&history_path, &history);
+}
+
+/// If the report's results are as of a drop of cast vote records, record
+/// them in the history in `dir`. A drop that was already recorded (because
+/// the report was regenerated before the next drop) is replaced.
+fn record_drop(dir: &Path, report: &ContestReport) {
+    let drop = match report.info.drop {
+        Some(drop) => drop,
+        None => return,
+    };
+
+    let history_path = dir.join(HISTORY_FILENAME);
+    let mut history: ReportHistory = if history_path.exists() {
+        read_serialized(&history_path)
+    } else {
+        ReportHistory::default()
+    };
+    if history.drops.last().is_some_and(|d| d.drop == drop) {
+        history.drops.pop();
+    }
+    let result = drop_result(
+        drop,
+        timestamp(),
+        &ContestSummary::new(report),
+        history.drops.last(),
+    );
+    info!(
+        "Recording drop {}: {} ballot(s) added",
+        drop, result.ballots_added
+    );
+    history.drops.push(result);
+    write_output(&history_path, &history);
+}
+
+fn index_entry(report: &ContestReport, office: &Office) -> ContestIndexEntry {
+    ContestIndexEntry {
+        office: report.info.office.clone(),
+        previous_offices: office.previous_ids.clone(),
+        office_name: report.info.office_name.clone(),
+        office_names: report.info.office_names.clone(),
+        name: report.info.name.clone(),
+        winner: report.winner_names(),
+        candidates: report
+            .candidates
+            .iter()
+            .filter(|c| c.candidate_type != CandidateType::WriteIn)
+            .map(|c| c.name.clone())
+            .collect(),
+        num_candidates: report.num_candidates,
+        num_rounds: report.rounds.len() as u32,
+        close_contest: report.close_contest,
+        tie_break_used: report.tie_break_used,
+        uncontested: report.uncontested,
+        ballot_count_discrepancy: report
+            .ballot_count_check
+            .as_ref()
+            .is_some_and(|c| c.discrepancy),
+    }
+}
+
+/// A contest whose ballots are loaded and waiting to be tabulated.
+struct PendingReport<'a> {
+    contest: &'a Contest,
+    office: &'a Office,
+    contest_path: String,
+    report_path: PathBuf,
+    preprocessed: ElectionPreprocessed,
+    metrics: MetricsCollector,
+}
+
+/// What the `report` command does with a contest once its ballots are read.
+enum ContestWork<'a> {
+    /// Excluded by the filter, but reported before, so kept in the index.
+    Excluded(ContestReport, &'a Office),
+    /// Up to date, so the existing report is kept.
+    Reused {
+        report: ContestReport,
+        office: &'a Office,
+        contest_path: String,
+        report_path: PathBuf,
+    },
+    Pending(PendingReport<'a>),
+}
+
+/// Options for the `report` command beyond its input and output directories.
+#[derive(Default)]
+pub struct ReportOptions {
+    /// Re-read raw data even if an up-to-date preprocessed file exists.
+    pub force_preprocess: bool,
+    /// Regenerate reports even if a report file exists.
+    pub force_report: bool,
+    /// Preprocess even if raw files don't match their recorded hashes.
+    pub allow_hash_mismatch: bool,
+    /// Contests to process.
+    pub filter: ContestFilter,
+    /// File to which per-contest stage timings are appended, if any.
+    pub metrics_path: Option<PathBuf>,
+    /// List what would be preprocessed and reported without doing it.
+    pub dry_run: bool,
+    /// Key file with which to sign a manifest of the report files, if any.
+    pub sign_key: Option<PathBuf>,
+    /// Write a trace of each contest's eliminations next to its report.
+    pub trace: bool,
+    /// Where to send metrics and a summary of the run.
+    pub monitoring: MonitoringOptions,
+    /// Most contests tabulated at once; `None` means one per available CPU.
+    pub jobs: Option<usize>,
+}
 
 pub fn report(
     meta_dir: &Path,
     raw_dir: &Path,
     report_dir: &Path,
     preprocessed_dir: &Path,
-    force_preprocess: bool,
-    force_report: bool,
+    options: &ReportOptions,
 ) {
+    let ReportOptions {
+        force_preprocess,
+        force_report,
+        allow_hash_mismatch,
+        filter,
+        metrics_path,
+        dry_run,
+        sign_key,
+        trace: write_trace,
+        monitoring,
+        jobs,
+    } = options;
+    let jobs = jobs.unwrap_or_else(available_threads).max(1);
+    let _locks = if *dry_run {
+        Vec::new()
+    } else {
+        match DirLock::acquire_all(&[preprocessed_dir, report_dir], "report") {
+            Ok(locks) => locks,
+            Err(err) => {
+                record_outcome(Outcome::InfraError);
+                error!("{}", err);
+                return;
+            }
+        }
+    };
+    let mut metrics_history = metrics_path.as_deref().map(MetricsHistory::load);
+    let mut run_summary = RunSummary::new("report");
+
     let raw_path = Path::new(raw_dir);
     let mut election_index_entries: Vec<ElectionIndexEntry> = Vec::new();
+    let mut reports: Vec<ContestReport> = Vec::new();
+    let mut failed_contests: Vec<String> = Vec::new();
+    let mut skipped_rows: Vec<(String, usize)> = Vec::new();
+    let mut count_discrepancies: Vec<(String, BallotCountCheck)> = Vec::new();
 
-    for (_, jurisdiction) in read_meta(meta_dir) {
+    let jurisdictions: Vec<_> = read_meta(meta_dir).collect();
+    let num_contests: usize = jurisdictions
+        .iter()
+        .flat_map(|(_, j)| j.elections.values())
+        .map(|e| e.contests.len())
+        .sum();
+    let progress = progress_bar("Contests", num_contests as u64);
+
+    for (_, jurisdiction) in jurisdictions {
         let raw_base = raw_path.join(jurisdiction.path.clone());
 
         for (election_path, election) in &jurisdiction.elections {
             let mut contest_index_entries: Vec<ContestIndexEntry> = Vec::new();
-            eprintln!("Election: {}", election_path.red());
+            info!("Election: {}", election_path.red());
+
+            // Raw files are verified against metadata hashes once per election,
+            // the first time a contest in it needs to be preprocessed.
+            let mut files_verified: Option<bool> = None;
+
+            // Contests whose raw data needs to be read. Formats that can read
+            // several contests in one pass over the raw files read all of
+            // them the first time one is needed.
+            let to_read: Vec<&Contest> = election
+                .contests
+                .iter()
+                .filter(|contest| {
+                    let contest_path =
+                        format!("{}/{}/{}", jurisdiction.path, election_path, contest.office);
+                    let dir = Path::new(preprocessed_dir).join(&contest_path);
+                    jurisdiction
+                        .offices
+                        .get(&contest.office)
+                        .is_some_and(|office| {
+                            filter.matches(&contest_path, &contest.office, &office.name)
+                        })
+                        && (*force_preprocess
+                            || !is_cache_fresh(
+                                &dir,
+                                &preprocess_cache_key(
+                                    election,
+                                    contest,
+                                    &raw_base.join(election_path),
+                                ),
+                            ))
+                })
+                .collect();
+            let read_together = to_read.len() > 1
+                && get_multi_contest_reader_for_format(&election.data_format).is_some();
+            let mut read_elections: Option<Result<BTreeMap<String, Election>, String>> = None;
+            // Ballots are read and preprocessed one contest at a time, in
+            // order; then the election's contests are tabulated on up to `jobs`
+            // threads, each taking the next contest as soon as it is free; then
+            // files are written one contest at a time, in order.
+            let mut work: Vec<ContestWork> = Vec::new();
             for contest in &election.contests {
                 let office = jurisdiction
                     .offices
@@ -31,72 +317,312 @@ pub fn report(
                     .unwrap_or_else(|| {
                         panic!("Expected office {} to be in offices.", &contest.office)
                     });
-                eprintln!("Office: {}", office.name.red());
 
                 let report_path = Path::new(report_dir)
                     .join(&jurisdiction.path)
-                    .join(&election_path)
+                    .join(election_path)
                     .join(&contest.office)
                     .join("report.json");
-                let preprocessed_path = Path::new(preprocessed_dir)
+
+                let contest_path =
+                    format!("{}/{}/{}", jurisdiction.path, election_path, contest.office);
+                if !filter.matches(&contest_path, &contest.office, &office.name) {
+                    // Keep contests excluded by the filter in the index if they
+                    // have already been reported.
+                    if report_path.exists() {
+                        work.push(ContestWork::Excluded(read_serialized(&report_path), office));
+                    }
+                    progress.inc(1);
+                    continue;
+                }
+                info!("Office: {}", office.name.red());
+
+                let preprocessed_contest_dir = Path::new(preprocessed_dir)
                     .join(&jurisdiction.path)
-                    .join(&election_path)
-                    .join(&contest.office)
-                    .join("normalized.json.gz");
-
-                let report = if report_path.exists()
-                    && preprocessed_path.exists()
-                    && !force_report
-                    && !force_preprocess
-                {
-                    eprintln!(
+                    .join(election_path)
+                    .join(&contest.office);
+                let preprocessed_path = preprocessed_contest_dir.join(PREPROCESSED_FILENAME);
+
+                // A preprocessed file is reused only if the raw file hashes,
+                // loader parameters and normalization it was generated from
+                // are unchanged; otherwise both it and the report are rebuilt.
+                let cache_key =
+                    preprocess_cache_key(election, contest, &raw_base.join(election_path));
+                let cache_fresh =
+                    !force_preprocess && is_cache_fresh(&preprocessed_contest_dir, &cache_key);
+
+                // An existing report is reused only if its ballots are cached
+                // and the metadata it describes (such as the election's
+                // status or candidates' display names) hasn't changed since
+                // it was generated.
+                let info = election_info(election, election_path, &jurisdiction, contest);
+                let existing_report: Option<ContestReport> =
+                    if report_path.exists() && cache_fresh && !force_report {
+                        try_read_serialized(&report_path)
+                            .ok()
+                            .filter(|report: &ContestReport| {
+                                let mut candidates = report.candidates.clone();
+                                localize_candidates(&mut candidates, election);
+                                report.info == info && report.candidates == candidates
+                            })
+                    } else {
+                        None
+                    };
+
+                if *dry_run {
+                    let action = if existing_report.is_some() {
+                        "up to date"
+                    } else if cache_fresh {
+                        "report"
+                    } else {
+                        "preprocess and report"
+                    };
+                    info!("{}: {}", contest_path.bright_cyan(), action);
+                    progress.inc(1);
+                    continue;
+                }
+
+                if let Some(report) = existing_report {
+                    info!(
                         "Skipping because {} exists.",
                         report_path.to_str().unwrap().bright_cyan()
                     );
-                    read_serialized(&report_path)
+                    work.push(ContestWork::Reused {
+                        report,
+                        office,
+                        contest_path,
+                        report_path,
+                    });
+                    continue;
+                }
+
+                create_dir_all(report_path.parent().unwrap()).unwrap();
+
+                let mut metrics = MetricsCollector::new();
+                let load_start = Instant::now();
+                let preprocessed: ElectionPreprocessed = if cache_fresh {
+                    info!(
+                        "Loading preprocessed {}.",
+                        preprocessed_path.to_str().unwrap().bright_cyan()
+                    );
+                    let mut preprocessed: ElectionPreprocessed =
+                        read_serialized(&preprocessed_path);
+                    // Pick up metadata changes made since preprocessing.
+                    preprocessed.info = info;
+                    metrics.record_since("load", load_start);
+                    preprocessed
                 } else {
-                    create_dir_all(&report_path.parent().unwrap()).unwrap();
+                    let verified = *files_verified.get_or_insert_with(|| {
+                        verify_election_files(
+                            &raw_base.join(election_path),
+                            &election.files,
+                            *allow_hash_mismatch,
+                        )
+                    });
+                    if !verified {
+                        record_outcome(Outcome::DataError);
+                        error!(
+                            "Skipping {} because its raw data doesn't match metadata.",
+                            contest_path.red()
+                        );
+                        progress.inc(1);
+                        continue;
+                    }
 
-                    let preprocessed: ElectionPreprocessed =
-                        if preprocessed_path.exists() && !force_preprocess {
-                            eprintln!(
-                                "Loading preprocessed {}.",
-                                preprocessed_path.to_str().unwrap().bright_cyan()
-                            );
-                            read_serialized(&preprocessed_path)
-                        } else {
-                            create_dir_all(preprocessed_path.parent().unwrap()).unwrap();
+                    create_dir_all(preprocessed_path.parent().unwrap()).unwrap();
+
+                    info!(
+                        "Generating preprocessed {}.",
+                        preprocessed_path.to_str().unwrap().bright_cyan()
+                    );
+                    let read = if read_together {
+                        let elections = read_elections.get_or_insert_with(|| {
+                            read_contests(&raw_base, election, election_path, &to_read)
+                                .map_err(|err| err.to_string())
+                        });
+                        match elections {
+                            Ok(elections) => elections
+                                .remove(&contest.office)
+                                .ok_or_else(|| "contest was not read".to_string()),
+                            Err(err) => Err(err.clone()),
+                        }
+                    } else {
+                        read_contest(&raw_base, election, election_path, contest)
+                            .map_err(|err| err.to_string())
+                    };
+                    let preprocessed = match read {
+                        Ok(read) => {
+                            normalize_contest(election, election_path, &jurisdiction, contest, read)
+                        }
+                        Err(err) => {
+                            record_outcome(Outcome::DataError);
+                            error!("Skipping {}: {}", contest_path.red(), err);
+                            failed_contests.push(contest_path);
+                            progress.inc(1);
+                            continue;
+                        }
+                    };
+                    metrics.record_since("preprocess", load_start);
+                    write_output(&preprocessed_path, &preprocessed);
+                    write_cache_key(&preprocessed_contest_dir, &cache_key);
+                    info!("Processed {} ballots", preprocessed.ballots.ballots.len());
+                    preprocessed
+                };
+
+                if !has_votes(&preprocessed.ballots.ballots) {
+                    warn!(
+                        "Skipping report of {}: no ballot ranks a candidate.",
+                        contest_path.yellow()
+                    );
+                    progress.inc(1);
+                    continue;
+                }
+
+                work.push(ContestWork::Pending(PendingReport {
+                    contest,
+                    office,
+                    contest_path,
+                    report_path,
+                    preprocessed,
+                    metrics,
+                }));
+            }
+
+            let tabulated = map_parallel_with(&work, jobs, |item| match item {
+                ContestWork::Pending(pending) => {
+                    let start = Instant::now();
+                    let report = generate_report(&pending.preprocessed);
+                    Some((report, start.elapsed()))
+                }
+                _ => None,
+            });
 
-                            eprintln!(
-                                "Generating preprocessed {}.",
-                                preprocessed_path.to_str().unwrap().bright_cyan()
+            for (item, tabulated) in work.into_iter().zip(tabulated) {
+                let (report, report_reused, office, contest_path, report_path) = match item {
+                    ContestWork::Excluded(report, office) => {
+                        contest_index_entries.push(index_entry(&report, office));
+                        reports.push(report);
+                        continue;
+                    }
+                    ContestWork::Reused {
+                        report,
+                        office,
+                        contest_path,
+                        report_path,
+                    } => (report, true, office, contest_path, report_path),
+                    ContestWork::Pending(pending) => {
+                        let PendingReport {
+                            contest,
+                            office,
+                            contest_path,
+                            report_path,
+                            mut metrics,
+                            ..
+                        } = pending;
+                        let (tabulated, duration) = tabulated.unwrap();
+                        metrics.record_duration("tabulate", duration);
+                        let mut contest_report = match tabulated {
+                            Ok(report) => report,
+                            Err(err) => {
+                                record_outcome(Outcome::DataError);
+                                error!("Skipping {}: {}", contest_path.red(), err);
+                                failed_contests.push(contest_path);
+                                progress.inc(1);
+                                continue;
+                            }
+                        };
+                        contest_report.provenance = Some(provenance(election, contest));
+                        localize_candidates(&mut contest_report.candidates, election);
+                        if let (Some(file), Some(results)) = (
+                            &election.precinct_attributes,
+                            &contest_report.sub_jurisdiction_results,
+                        ) {
+                            let path = raw_base.join(election_path).join(file);
+                            match read_precinct_attributes(&path) {
+                                Ok(attributes) => {
+                                    let candidates: Vec<_> = contest_report
+                                        .total_votes
+                                        .iter()
+                                        .map(|v| v.candidate)
+                                        .collect();
+                                    contest_report.ecological_estimates =
+                                        ecological_estimates(results, &candidates, &attributes);
+                                }
+                                Err(err) => warn!("Skipping ecological estimates: {}", err),
+                            }
+                        }
+
+                        let previous: Option<ContestReport> = if report_path.exists() {
+                            try_read_serialized(&report_path).ok()
+                        } else {
+                            None
+                        };
+                        metrics.time("serialize", || write_output(&report_path, &contest_report));
+                        #[cfg(feature = "research")]
+                        {
+                            let candidates: Vec<_> = contest_report
+                                .total_votes
+                                .iter()
+                                .map(|v| v.candidate)
+                                .collect();
+                            write_output(
+                                &report_path.with_file_name(METHODS_FILENAME),
+                                &research_methods(
+                                    &candidates,
+                                    &pending.preprocessed.ballots.ballots,
+                                    contest_report.winner,
+                                ),
                             );
-                            let preprocessed = preprocess_election(
-                                &raw_base,
-                                election,
-                                election_path,
-                                &jurisdiction,
-                                contest,
+                        }
+                        if let Some(previous) = previous {
+                            record_revision(
+                                report_path.parent().unwrap(),
+                                previous,
+                                &contest_report,
                             );
-                            write_serialized(&preprocessed_path, &preprocessed);
-                            eprintln!("Processed {} ballots", preprocessed.ballots.ballots.len());
-                            preprocessed
+                        }
+                        record_drop(report_path.parent().unwrap(), &contest_report);
+
+                        let contest_metrics = ContestMetrics {
+                            contest: contest_path.clone(),
+                            timestamp: timestamp(),
+                            ballots: contest_report.ballot_count,
+                            stages: metrics.into_stages(),
                         };
+                        run_summary.add_contest(&contest_metrics);
+                        run_summary
+                            .add_election(&format!("{}/{}", jurisdiction.path, election_path));
+                        if let Some(history) = &mut metrics_history {
+                            history.runs.push(contest_metrics);
+                        }
+                        (contest_report, false, office, contest_path, report_path)
+                    }
+                };
 
-                    let contest_report = generate_report(&preprocessed);
+                let transfers_path = report_path.with_file_name(TRANSFERS_FILENAME);
+                if !transfers_path.exists() || !report_reused {
+                    write_output(
+                        &transfers_path,
+                        &transfer_graph(&report.candidates, &report.rounds),
+                    );
+                }
 
-                    write_serialized(&report_path, &contest_report);
-                    contest_report
-                };
+                let trace_path = report_path.with_file_name(TRACE_FILENAME);
+                if *write_trace && (!trace_path.exists() || !report_reused) {
+                    write_output(&trace_path, &trace(&report.rounds));
+                }
 
-                contest_index_entries.push(ContestIndexEntry {
-                    office: report.info.office.clone(),
-                    office_name: report.info.office_name.clone(),
-                    name: report.info.name.clone(),
-                    winner: report.winner().name.clone(),
-                    num_candidates: report.num_candidates,
-                    num_rounds: report.rounds.len() as u32,
-                })
+                if !report.parse_issues.is_empty() {
+                    skipped_rows.push((contest_path.clone(), report.parse_issues.len()));
+                }
+                if let Some(check) = &report.ballot_count_check {
+                    if check.discrepancy {
+                        count_discrepancies.push((contest_path, check.clone()));
+                    }
+                }
+                contest_index_entries.push(index_entry(&report, office));
+                reports.push(report);
+                progress.inc(1);
             }
 
             election_index_entries.push(ElectionIndexEntry {
@@ -104,15 +630,70 @@ pub fn report(
                 jurisdiction_name: jurisdiction.name.clone(),
                 election_name: election.name.clone(),
                 date: election.date.clone(),
+                status: election.status,
                 contests: contest_index_entries,
             })
         }
     }
 
+    progress.finish_and_clear();
+
+    if *dry_run {
+        return;
+    }
+
+    for (contest_path, rows) in &skipped_rows {
+        let message = format!(
+            "skipped {} malformed row(s); see parseIssues in its report",
+            rows
+        );
+        warn!("{}: {}", contest_path.yellow(), message);
+        run_summary.add_warning(format!("{}: {}", contest_path, message));
+    }
+    for (contest_path, check) in &count_discrepancies {
+        let message = format!(
+            "read {} ballots but {} were officially cast ({:+.2}%)",
+            check.counted, check.official, check.percent
+        );
+        warn!("{}: {}", contest_path.yellow(), message);
+        run_summary.add_warning(format!("{}: {}", contest_path, message));
+    }
+    if !failed_contests.is_empty() {
+        let message = format!(
+            "{} contest(s) could not be read or tabulated and were left out of the index: {}",
+            failed_contests.len(),
+            failed_contests.join(", ")
+        );
+        warn!("{}", message);
+        run_summary.add_warning(message);
+    }
+
     election_index_entries.sort_by(|a, b| (&b.date, &b.path).cmp(&(&a.date, &a.path)));
     let report_index = ReportIndex {
         elections: election_index_entries,
+        jurisdiction_summaries: jurisdiction_summaries(&reports),
     };
 
-    write_serialized(&Path::new(report_dir).join("index.json"), &report_index);
+    write_output(&Path::new(report_dir).join("index.json"), &report_index);
+    write_output(
+        &Path::new(report_dir).join("candidates.json"),
+        &candidate_profiles(&reports),
+    );
+    write_output(
+        &Path::new(report_dir).join("trends.json"),
+        &trends(&reports),
+    );
+
+    if let (Some(history), Some(path)) = (metrics_history, metrics_path) {
+        history.save(path);
+    }
+
+    if let Some(key_file) = sign_key {
+        sign_bundle(report_dir, key_file);
+    }
+
+    run_summary.add_failures(failed_contests.len() as u32);
+    run_summary.add_dir_size("preprocessed", preprocessed_dir);
+    run_summary.add_dir_size("reports", report_dir);
+    run_summary.export(monitoring);
 }
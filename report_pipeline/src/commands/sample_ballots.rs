@@ -0,0 +1,188 @@
+use crate::model::election::{Ballot, Candidate, Choice, NormalizedBallot};
+use crate::normalizers::{apply_overvote_policy, normalize_ballot, skipped_rank_policy};
+use crate::read_metadata::read_meta;
+use crate::report::read_contest;
+use crate::rules::tabulation_options;
+use crate::sensitivity::Rng;
+use crate::util::{record_outcome, Outcome};
+use colored::*;
+use std::path::Path;
+use tracing::{error, info};
+
+/// Choose `count` distinct indices in `0..len` (all of them if there are
+/// fewer), the same ones for the same seed, in ascending order.
+fn sample_indices(len: usize, count: usize, seed: u64) -> Vec<usize> {
+    let mut rng = Rng(seed);
+    let mut indices: Vec<usize> = (0..len).collect();
+    let count = count.min(len);
+    // Partial Fisher-Yates shuffle: the first `count` entries are the sample.
+    for i in 0..count {
+        let j = i + rng.below(len - i);
+        indices.swap(i, j);
+    }
+    indices.truncate(count);
+    indices.sort_unstable();
+    indices
+}
+
+fn choice_name(candidates: &[Candidate], choice: &Choice) -> String {
+    match choice {
+        Choice::Vote(c) => candidates[c.0 as usize].name.clone(),
+        Choice::Undervote => "(skipped)".to_string(),
+        Choice::Overvote => "(overvote)".to_string(),
+    }
+}
+
+/// Lay out a ballot's raw rankings and its normalized ranking side by side,
+/// a line per rank. A normalized ballot that was exhausted by an overvote
+/// ends with an `(overvote)` entry.
+fn ballot_lines(
+    candidates: &[Candidate],
+    raw: &Ballot,
+    normalized: &NormalizedBallot,
+) -> Vec<String> {
+    let raw: Vec<String> = raw
+        .choices
+        .iter()
+        .map(|choice| choice_name(candidates, choice))
+        .collect();
+    let overvoted = normalized.overvoted;
+    let mut normalized: Vec<String> = normalized
+        .choices()
+        .iter()
+        .map(|c| candidates[c.0 as usize].name.clone())
+        .collect();
+    if overvoted {
+        normalized.push("(overvote)".to_string());
+    }
+
+    let width = raw
+        .iter()
+        .map(|name| name.chars().count())
+        .max()
+        .unwrap_or(0)
+        .max("Raw".len());
+    let mut lines = vec![format!("  Rank  {:w$}  Normalized", "Raw", w = width)];
+    for rank in 0..raw.len().max(normalized.len()) {
+        let cell = |names: &[String]| names.get(rank).cloned().unwrap_or_default();
+        lines.push(
+            format!(
+                "  {:<4}  {:w$}  {}",
+                rank + 1,
+                cell(&raw),
+                cell(&normalized),
+                w = width
+            )
+            .trim_end()
+            .to_string(),
+        );
+    }
+    lines
+}
+
+/// Print `count` ballots of a contest, chosen at random with `seed`, with
+/// each one's raw rankings (as read from the raw data) next to its
+/// normalized ranking, to cross-check against the source files by hand.
+pub fn sample_ballots(
+    meta_dir: &Path,
+    raw_dir: &Path,
+    contest_path: &str,
+    count: usize,
+    seed: u64,
+) {
+    let contest_path = contest_path.trim_end_matches('/');
+
+    for (_, jurisdiction) in read_meta(meta_dir) {
+        for (election_path, election) in &jurisdiction.elections {
+            for contest in &election.contests {
+                let path = format!("{}/{}/{}", jurisdiction.path, election_path, contest.office);
+                if path != contest_path {
+                    continue;
+                }
+
+                let raw_base = raw_dir.join(&jurisdiction.path);
+                let raw = match read_contest(&raw_base, election, election_path, contest) {
+                    Ok(raw) => raw,
+                    Err(err) => {
+                        record_outcome(Outcome::DataError);
+                        error!("Could not read {}: {}", path.red(), err);
+                        return;
+                    }
+                };
+
+                let rules = tabulation_options(election).ballot_rules();
+                let policy = skipped_rank_policy(&election.normalization, &rules);
+                let indices = sample_indices(raw.ballots.len(), count, seed);
+                info!(
+                    "Sampled {} of {} ballots (seed {}).",
+                    indices.len(),
+                    raw.ballots.len(),
+                    seed
+                );
+
+                for i in indices {
+                    let ballot = &raw.ballots[i];
+                    let normalized = normalize_ballot(
+                        apply_overvote_policy(ballot.clone(), rules.overvote_policy),
+                        policy,
+                    );
+                    println!("Ballot {}", ballot.id);
+                    for line in ballot_lines(&raw.candidates, ballot, &normalized) {
+                        println!("{}", line);
+                    }
+                    println!();
+                }
+                return;
+            }
+        }
+    }
+
+    record_outcome(Outcome::DataError);
+    error!("Contest {} not found in metadata.", contest_path.red());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::election::{CandidateId, CandidateType};
+
+    #[test]
+    fn test_sample_indices() {
+        let sample = sample_indices(100, 5, 3);
+        assert_eq!(5, sample.len());
+        assert!(sample.windows(2).all(|w| w[0] < w[1]));
+        assert!(sample.iter().all(|i| *i < 100));
+        assert_eq!(sample, sample_indices(100, 5, 3));
+        assert_eq!(vec![0, 1, 2], sample_indices(3, 10, 3));
+    }
+
+    #[test]
+    fn test_ballot_lines() {
+        let candidates = vec![
+            Candidate::new("Alice".into(), CandidateType::Regular),
+            Candidate::new("Bob".into(), CandidateType::Regular),
+        ];
+        let (a, b) = (CandidateId(0), CandidateId(1));
+        let raw = Ballot::new(
+            "7".into(),
+            vec![
+                Choice::Undervote,
+                Choice::Vote(a),
+                Choice::Overvote,
+                Choice::Vote(b),
+            ],
+        );
+        let normalized = NormalizedBallot::new("7".into(), vec![a], true);
+
+        assert_eq!(
+            vec![
+                "  Rank  Raw         Normalized",
+                "  1     (skipped)   Alice",
+                "  2     Alice       (overvote)",
+                "  3     (overvote)",
+                "  4     Bob",
+            ],
+            ballot_lines(&candidates, &raw, &normalized)
+        );
+    }
+}
@@ -0,0 +1,14 @@
+use crate::schema::report_schemas;
+use crate::util::write_serialized;
+use std::fs::create_dir_all;
+use std::path::Path;
+
+/// Write the JSON Schema of each published report file to `output_dir`, as
+/// e.g. `report.schema.json` for `report.json`.
+pub fn schema(output_dir: &Path) {
+    create_dir_all(output_dir).unwrap();
+    for (file, schema) in report_schemas() {
+        let name = file.replace(".json", ".schema.json");
+        write_serialized(&output_dir.join(name), &schema);
+    }
+}
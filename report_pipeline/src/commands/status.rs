@@ -0,0 +1,56 @@
+use crate::model::metadata::ElectionStatus;
+use crate::read_metadata::read_meta;
+use crate::util::{record_outcome, write_serialized, Outcome};
+use colored::*;
+use std::path::Path;
+use tracing::{error, info};
+
+/// Set the status of the election at `election_path` (e.g.
+/// `us/ny/nyc/2025/07`) in its metadata file, and the number of the latest
+/// drop of cast vote records if given. The next `report` run regenerates the
+/// election's reports and index entry with the new status, recording the
+/// results of a new drop in each contest's history.
+pub fn update_status(
+    meta_dir: &Path,
+    election_path: &str,
+    status: ElectionStatus,
+    drop: Option<u32>,
+) {
+    let election_path = election_path.trim_end_matches('/');
+
+    for (file, mut jurisdiction) in read_meta(meta_dir) {
+        let election = match election_path
+            .strip_prefix(&jurisdiction.path)
+            .and_then(|rest| rest.strip_prefix('/'))
+        {
+            Some(election) => election.to_string(),
+            None => continue,
+        };
+
+        if let Some(metadata) = jurisdiction.elections.get_mut(&election) {
+            let old = metadata
+                .status
+                .map_or_else(|| "none".to_string(), |s| s.to_string());
+            metadata.status = Some(status);
+            info!(
+                "{}: status {} -> {}",
+                election_path.blue(),
+                old,
+                status.to_string().green()
+            );
+            if let Some(drop) = drop {
+                info!(
+                    "{}: drop {}",
+                    election_path.blue(),
+                    drop.to_string().green()
+                );
+                metadata.drop = Some(drop);
+            }
+            write_serialized(&file, &jurisdiction);
+            return;
+        }
+    }
+
+    record_outcome(Outcome::DataError);
+    error!("No election {} in metadata.", election_path.red());
+}
@@ -1,16 +1,230 @@
+use crate::model::metadata::ElectionMetadata;
 use crate::read_metadata::read_meta;
-use crate::util::{hash_file, write_serialized};
+use crate::util::{
+    extract_archive, fetch_url, file_matches, hash_file, hash_to_match, is_legacy_hash,
+    map_parallel, print_summary, record_outcome, write_serialized, Outcome,
+};
 use colored::*;
-use std::collections::HashSet;
+use serde::Serialize;
+use std::collections::BTreeSet;
 use std::fs;
 use std::fs::create_dir_all;
-use std::path::Path;
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+use tracing::{error, info, warn};
+
+/// Download raw data files listed in an election's `sources` that are missing
+/// from `dir` or (unless `skip_verify` is set) don't match their recorded hash.
+fn fetch_sources(dir: &Path, election: &ElectionMetadata, skip_verify: bool) {
+    let sources = match &election.sources {
+        Some(sources) => sources,
+        None => return,
+    };
+
+    for (filename, url) in sources {
+        let dest = dir.join(filename);
+        let expected = election.files.get(filename);
+
+        let needs_fetch =
+            !dest.exists() || (!skip_verify && expected.is_some_and(|h| !file_matches(&dest, h)));
+        if !needs_fetch {
+            continue;
+        }
+
+        info!("Downloading {} from {}", filename.red(), url.blue());
+        create_dir_all(dest.parent().unwrap()).unwrap();
+        if let Err(e) = fetch_url(url, &dest) {
+            record_outcome(Outcome::InfraError);
+            error!("{}", e);
+            continue;
+        }
+
+        if let Some(expected) = expected {
+            if let (hash_str, false) = hash_to_match(&dest, expected) {
+                warn!(
+                    "downloaded {} hashes to {} but metadata records {}",
+                    filename.red(),
+                    hash_str.green(),
+                    expected.blue()
+                );
+            }
+        }
+    }
+}
+
+/// What hashing a raw data file found.
+#[derive(Debug, PartialEq)]
+enum FileCheck {
+    /// The file isn't in metadata; it hashes to this.
+    Orphan(String),
+    /// The file matches its legacy hash; this is its new hash.
+    Upgrade(String),
+    /// The file doesn't match the hash in metadata; it hashes to this.
+    Changed(String),
+    /// The file matches its hash, or wasn't checked.
+    Unchanged,
+}
+
+/// Hash the raw data file at `path` and compare it with its recorded hash,
+/// `expected`, if it has one.
+fn check_file(path: PathBuf, expected: Option<&str>, skip_verify: bool) -> FileCheck {
+    match expected {
+        None => FileCheck::Orphan(hash_file(path)),
+        Some(_) if skip_verify => FileCheck::Unchanged,
+        Some(expected) => match hash_to_match(&path, expected) {
+            (hash_str, false) => FileCheck::Changed(hash_str),
+            (_, true) if is_legacy_hash(expected) => FileCheck::Upgrade(hash_file(path)),
+            (_, true) => FileCheck::Unchanged,
+        },
+    }
+}
+
+/// A difference between an election's raw data directory and the `files`
+/// map in its metadata.
+enum SyncProblem {
+    /// A raw file not listed in metadata.
+    Orphan { hash: String },
+    /// A file listed in metadata that isn't on disk.
+    Missing { has_source: bool },
+    /// A file whose contents don't match the hash in metadata.
+    Mismatch { actual: String },
+}
+
+impl SyncProblem {
+    fn description(&self) -> &'static str {
+        match self {
+            SyncProblem::Orphan { .. } => "not in metadata",
+            SyncProblem::Missing { .. } => "missing on disk",
+            SyncProblem::Mismatch { .. } => "hash mismatch",
+        }
+    }
+
+    fn suggested_fix(&self) -> &'static str {
+        match self {
+            SyncProblem::Orphan { .. } => "record its hash (--apply), or delete the file",
+            SyncProblem::Missing { has_source: true } => "download it (run without --offline)",
+            SyncProblem::Missing { has_source: false } => {
+                "restore the file, or remove it from metadata (--apply)"
+            }
+            SyncProblem::Mismatch { .. } => {
+                "restore the original file, or record the new hash (--apply)"
+            }
+        }
+    }
+
+    /// Update the election's `files` map to resolve the problem, if it can
+    /// be resolved in metadata.
+    fn apply(&self, file: &str, election: &mut ElectionMetadata) {
+        match self {
+            SyncProblem::Orphan { hash } | SyncProblem::Mismatch { actual: hash } => {
+                election.files.insert(file.to_string(), hash.clone());
+            }
+            SyncProblem::Missing { has_source: false } => {
+                election.files.remove(file);
+            }
+            SyncProblem::Missing { has_source: true } => {}
+        }
+    }
+}
+
+/// A row of the table of differences, in the JSON summary.
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SyncDifference<'a> {
+    election: &'a str,
+    file: &'a str,
+    problem: &'a str,
+    suggested_fix: &'a str,
+}
+
+/// What `sync` found, printed to stdout with `--output json`.
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SyncSummary<'a> {
+    command: &'static str,
+    elections: u32,
+    differences: Vec<SyncDifference<'a>>,
+    legacy_hashes: u32,
+    /// Whether metadata was updated to resolve the differences.
+    applied: bool,
+    duration_seconds: f64,
+}
+
+/// Log rows as a table with aligned columns.
+fn log_table(rows: &[[String; 4]]) {
+    let mut widths = [0; 4];
+    for row in rows {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.chars().count());
+        }
+    }
+    for row in rows {
+        info!(
+            "{:w0$}  {:w1$}  {:w2$}  {}",
+            row[0],
+            row[1],
+            row[2],
+            row[3],
+            w0 = widths[0],
+            w1 = widths[1],
+            w2 = widths[2]
+        );
+    }
+}
+
+/// Extract the ZIP archives in `dir` in place. Files that already exist are
+/// only re-extracted if their contents don't match the hash in metadata.
+fn extract_archives(dir: &Path, election: &ElectionMetadata) {
+    for entry in fs::read_dir(dir).unwrap() {
+        let path = entry.unwrap().path();
+        if path.extension().and_then(|e| e.to_str()) != Some("zip") {
+            continue;
+        }
+
+        let result = extract_archive(&path, dir, |name| {
+            let dest = dir.join(name);
+            match election.files.get(name) {
+                _ if !dest.exists() => true,
+                Some(expected) => !file_matches(&dest, expected),
+                None => false,
+            }
+        });
+
+        match result {
+            Ok(extracted) => {
+                for name in extracted {
+                    info!(
+                        "Extracted {} from {}",
+                        name.red(),
+                        path.file_name().unwrap().to_string_lossy().blue()
+                    );
+                }
+            }
+            Err(e) => {
+                record_outcome(Outcome::DataError);
+                error!("Could not extract {}: {}", path.to_string_lossy(), e)
+            }
+        }
+    }
+}
+
+/// Compare each election's raw data directory with the `files` map in its
+/// metadata, and log a table of raw files not in metadata (orphans), files
+/// in metadata missing on disk, and (unless `skip_verify` is set) files
+/// whose contents don't match their recorded hash, with suggested fixes.
+/// With `apply`, the metadata is updated to match the raw data, and legacy
+/// SHA-1 hashes of matching files are replaced. Unless `offline` is set,
+/// first download files with a recorded source that are missing or stale.
+pub fn sync(meta_dir: &Path, raw_dir: &Path, skip_verify: bool, offline: bool, apply: bool) {
+    let start = Instant::now();
+    let mut rows: Vec<[String; 4]> = Vec::new();
+    let mut num_upgradable = 0;
+    let mut num_elections = 0;
 
-pub fn sync(meta_dir: &Path, raw_dir: &Path) {
     for (path, mut ec) in read_meta(meta_dir) {
         let ec_path = raw_dir.join(ec.path.clone());
         if !ec_path.is_dir() {
-            eprintln!(
+            info!(
                 "Creating missing directory: {}",
                 ec_path.to_string_lossy().red()
             );
@@ -18,41 +232,150 @@ pub fn sync(meta_dir: &Path, raw_dir: &Path) {
         }
 
         for (election_key, election) in ec.elections.iter_mut() {
+            num_elections += 1;
             let election_path = ec_path.join(election_key);
             if !election_path.is_dir() {
-                eprintln!(
+                info!(
                     "Creating missing directory: {}",
                     election_path.to_string_lossy().red()
                 );
                 create_dir_all(election_path.clone()).unwrap();
             }
 
-            let mut expected_files: HashSet<String> = election.files.keys().cloned().collect();
+            if !offline {
+                fetch_sources(&election_path, election, skip_verify);
+            }
 
-            for entry in fs::read_dir(election_path).unwrap() {
-                let entry = entry.unwrap();
-                let filename = String::from(entry.file_name().to_str().unwrap());
-                if filename.starts_with('.') {
-                    continue;
-                };
-                if !expected_files.remove(&filename) {
-                    eprintln!(
-                        "Found data file: {}",
-                        entry.file_name().to_string_lossy().red()
-                    );
+            if election.extract_archives == Some(true) {
+                extract_archives(&election_path, election);
+            }
+
+            let mut expected_files: BTreeSet<String> = election.files.keys().cloned().collect();
 
-                    let hash_str = hash_file(entry.path());
-                    eprintln!("Hash: {}", hash_str.green());
+            let filenames: Vec<String> = fs::read_dir(&election_path)
+                .unwrap()
+                .map(|entry| entry.unwrap().file_name().to_str().unwrap().to_string())
+                .filter(|filename| !filename.starts_with('.'))
+                .collect();
+            let files = &election.files;
+            let checks = map_parallel(&filenames, |filename| {
+                check_file(
+                    election_path.join(filename),
+                    files.get(filename).map(String::as_str),
+                    skip_verify,
+                )
+            });
 
-                    election.files.insert(filename, hash_str);
+            let mut problems: Vec<(String, SyncProblem)> = Vec::new();
+            for (filename, check) in filenames.into_iter().zip(checks) {
+                expected_files.remove(&filename);
+                match check {
+                    FileCheck::Orphan(hash) => {
+                        problems.push((filename, SyncProblem::Orphan { hash }))
+                    }
+                    FileCheck::Changed(actual) => {
+                        problems.push((filename, SyncProblem::Mismatch { actual }))
+                    }
+                    FileCheck::Upgrade(hash_str) => {
+                        num_upgradable += 1;
+                        if apply {
+                            election.files.insert(filename, hash_str);
+                        }
+                    }
+                    FileCheck::Unchanged => {}
                 }
             }
+            for filename in expected_files {
+                let has_source = election
+                    .sources
+                    .as_ref()
+                    .is_some_and(|sources| sources.contains_key(&filename));
+                problems.push((filename, SyncProblem::Missing { has_source }));
+            }
 
-            for missing_file in expected_files {
-                eprintln!("{}: missing file {}", "Warning".red(), missing_file.blue());
+            for (filename, problem) in problems {
+                if apply {
+                    problem.apply(&filename, election);
+                }
+                rows.push([
+                    format!("{}/{}", ec.path, election_key),
+                    filename,
+                    problem.description().to_string(),
+                    problem.suggested_fix().to_string(),
+                ]);
             }
         }
 
-        write_serialized(&path, &ec);
+        if apply {
+            write_serialized(&path, &ec);
+        }
+    }
+
+    if rows.is_empty() {
+        info!("Raw data matches metadata.");
+    } else {
+        let mut table = vec![["Election", "File", "Problem", "Suggested fix"].map(String::from)];
+        table.extend(rows.iter().cloned());
+        log_table(&table);
+        if apply {
+            info!("Updated metadata to match raw data.");
+        } else {
+            warn!(
+                "{} differences between raw data and metadata; run with --apply to update metadata.",
+                rows.len()
+            );
+        }
+    }
+    if num_upgradable > 0 {
+        if apply {
+            info!("Replaced {} legacy SHA-1 hashes.", num_upgradable);
+        } else {
+            info!(
+                "{} files have legacy SHA-1 hashes; run with --apply to replace them.",
+                num_upgradable
+            );
+        }
+    }
+
+    print_summary(&SyncSummary {
+        command: "sync",
+        elections: num_elections,
+        differences: rows
+            .iter()
+            .map(|[election, file, problem, suggested_fix]| SyncDifference {
+                election,
+                file,
+                problem,
+                suggested_fix,
+            })
+            .collect(),
+        legacy_hashes: num_upgradable,
+        applied: apply,
+        duration_seconds: start.elapsed().as_secs_f64(),
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::{hash_file_with, HashAlgorithm};
+
+    #[test]
+    fn test_check_file_upgrades_only_legacy_hashes() {
+        let path = std::env::temp_dir().join(format!("sync_test_{}", std::process::id()));
+        std::fs::write(&path, "abc").unwrap();
+        let sha256 = hash_file(path.clone());
+        let blake3 = hash_file_with(&path, HashAlgorithm::Blake3);
+        let check = |expected: &str| check_file(path.clone(), Some(expected), false);
+
+        assert_eq!(
+            FileCheck::Upgrade(sha256.clone()),
+            check("a9993e364706816aba3e25717850c26c9cd0d89d")
+        );
+        assert_eq!(FileCheck::Unchanged, check(&sha256));
+        assert_eq!(FileCheck::Unchanged, check(&blake3));
+        assert_eq!(FileCheck::Changed(sha256), check("sha256:00"));
+
+        std::fs::remove_file(&path).unwrap();
     }
 }
@@ -0,0 +1,50 @@
+use crate::commands::export_ballots::PREPROCESSED_FILENAME;
+use crate::model::election::{CandidateId, ElectionPreprocessed};
+use crate::report::{require_irv, what_if as tabulate_what_if};
+use crate::util::try_read_serialized;
+use colored::*;
+use std::path::Path;
+use tracing::error;
+
+/// Re-tabulate a contest's preprocessed ballots as if the candidates named
+/// in `eliminate` had dropped out, printing the resulting rounds to stdout as
+/// JSON. Returns false if the contest or a candidate can't be found, or if
+/// the contest isn't counted by instant runoff.
+pub fn what_if(preprocessed_dir: &Path, contest_path: &str, eliminate: &[String]) -> bool {
+    let contest_path = contest_path.trim_end_matches('/');
+    let file = preprocessed_dir
+        .join(contest_path)
+        .join(PREPROCESSED_FILENAME);
+    let preprocessed: ElectionPreprocessed = match try_read_serialized(&file) {
+        Ok(preprocessed) => preprocessed,
+        Err(err) => {
+            error!(
+                "No preprocessed ballots for {}: {}",
+                contest_path.red(),
+                err
+            );
+            return false;
+        }
+    };
+
+    if let Err(err) = require_irv(&preprocessed.info.tabulation_options) {
+        error!("Can't re-run the count of {}: {}", contest_path.red(), err);
+        return false;
+    }
+
+    let candidates = &preprocessed.ballots.candidates;
+    let mut eliminated: Vec<CandidateId> = Vec::new();
+    for name in eliminate {
+        match candidates.iter().position(|c| &c.name == name) {
+            Some(i) => eliminated.push(CandidateId(i as u32)),
+            None => {
+                error!("No candidate {} in {}.", name.red(), contest_path);
+                return false;
+            }
+        }
+    }
+
+    let result = tabulate_what_if(&preprocessed.ballots.ballots, &eliminated);
+    println!("{}", serde_json::to_string(&result).unwrap());
+    true
+}
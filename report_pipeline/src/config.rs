@@ -0,0 +1,100 @@
+//! Defaults for the command-line options, read from a TOML file so routine
+//! runs don't need to repeat the data directories and global flags. Options
+//! given on the command line override the file.
+
+use serde::Deserialize;
+use std::fs::read_to_string;
+use std::path::{Path, PathBuf};
+
+/// Name of the configuration file read from the working directory if no
+/// `--config` is given.
+pub const CONFIG_FILENAME: &str = "rcvreport.toml";
+
+/// Contents of a configuration file. Keys are spelled like the command-line
+/// flags (e.g. `gzip-level`), and relative paths are relative to the working
+/// directory.
+#[derive(Deserialize, Debug, PartialEq)]
+#[serde(default, rename_all = "kebab-case", deny_unknown_fields)]
+pub struct Config {
+    pub meta_dir: PathBuf,
+    pub raw_data_dir: PathBuf,
+    pub preprocessed_dir: PathBuf,
+    pub report_dir: PathBuf,
+    /// Jurisdictions the `report` command processes if none are given with
+    /// `--jurisdiction`.
+    pub jurisdictions: Vec<String>,
+    /// Most files to hash at once (default: one per CPU).
+    pub threads: Option<usize>,
+    /// Most contests the `report` command tabulates at once (default: one
+    /// per CPU).
+    pub jobs: Option<usize>,
+    pub quiet: bool,
+    pub json_logs: bool,
+    pub compact: bool,
+    pub gzip_level: Option<u32>,
+    pub max_output_size: Option<u64>,
+    pub operations_log: Option<PathBuf>,
+}
+
+impl Default for Config {
+    /// The directory layout the scripts in this repository use.
+    fn default() -> Self {
+        Config {
+            meta_dir: PathBuf::from("election-metadata"),
+            raw_data_dir: PathBuf::from("raw-data"),
+            preprocessed_dir: PathBuf::from("preprocessed"),
+            report_dir: PathBuf::from("reports"),
+            jurisdictions: Vec::new(),
+            threads: None,
+            jobs: None,
+            quiet: false,
+            json_logs: false,
+            compact: false,
+            gzip_level: None,
+            max_output_size: None,
+            operations_log: None,
+        }
+    }
+}
+
+impl Config {
+    /// Read the configuration file at `path`, or `rcvreport.toml` in the
+    /// working directory if `path` is `None` and that file exists. Without a
+    /// file, returns the defaults.
+    pub fn load(path: Option<&Path>) -> Result<Config, String> {
+        let path = match path {
+            Some(path) => path,
+            None if Path::new(CONFIG_FILENAME).exists() => Path::new(CONFIG_FILENAME),
+            None => return Ok(Config::default()),
+        };
+        let contents = read_to_string(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+        toml::from_str(&contents).map_err(|e| format!("{}: {}", path.display(), e))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_config() {
+        let config: Config = toml::from_str(
+            r#"
+            meta-dir = "../election-metadata"
+            jurisdictions = ["us/ny/nyc"]
+            threads = 4
+            gzip-level = 6
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(PathBuf::from("../election-metadata"), config.meta_dir);
+        assert_eq!(PathBuf::from("reports"), config.report_dir);
+        assert_eq!(vec!["us/ny/nyc".to_string()], config.jurisdictions);
+        assert_eq!(Some(4), config.threads);
+        assert_eq!(Some(6), config.gzip_level);
+        assert!(!config.compact);
+
+        assert!(toml::from_str::<Config>("report-directory = \"out\"").is_err());
+    }
+}
@@ -0,0 +1,152 @@
+use crate::model::election::{Candidate, CandidateId, NormalizedBallot, NormalizedElection};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::io::{self, BufRead, Write};
+
+/// First line of a ballot image file, listing every candidate.
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BallotImageHeader {
+    pub candidates: Vec<Candidate>,
+}
+
+/// One normalized ballot, with its rankings given as candidate names.
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct BallotImage {
+    pub id: String,
+    pub ranks: Vec<String>,
+    /// True if the ballot was exhausted by an overvote after its last rank.
+    #[serde(default)]
+    pub overvoted: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sub_jurisdiction: Option<String>,
+}
+
+fn invalid_data(line: usize, message: impl std::fmt::Display) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidData,
+        format!("line {}: {}", line, message),
+    )
+}
+
+/// Write normalized ballots as newline-delimited JSON: a
+/// `BallotImageHeader` line followed by one `BallotImage` per ballot.
+pub fn write_ballot_images<W: Write>(
+    election: &NormalizedElection,
+    writer: &mut W,
+) -> io::Result<()> {
+    let header = BallotImageHeader {
+        candidates: election.candidates.clone(),
+    };
+    serde_json::to_writer(&mut *writer, &header)?;
+    writeln!(writer)?;
+
+    for ballot in &election.ballots {
+        let image = BallotImage {
+            id: ballot.id.clone(),
+            ranks: ballot
+                .choices()
+                .iter()
+                .map(|c| election.candidates[c.0 as usize].name.clone())
+                .collect(),
+            overvoted: ballot.overvoted,
+            sub_jurisdiction: ballot.sub_jurisdiction.clone(),
+        };
+        serde_json::to_writer(&mut *writer, &image)?;
+        writeln!(writer)?;
+    }
+
+    Ok(())
+}
+
+/// Read ballots written by `write_ballot_images`.
+pub fn read_ballot_images<R: BufRead>(reader: R) -> io::Result<NormalizedElection> {
+    let mut lines = reader.lines();
+
+    let header: BallotImageHeader = match lines.next() {
+        Some(line) => serde_json::from_str(&line?).map_err(|e| invalid_data(1, e))?,
+        None => return Err(invalid_data(1, "missing header")),
+    };
+    let ids: HashMap<&str, CandidateId> = header
+        .candidates
+        .iter()
+        .enumerate()
+        .map(|(i, c)| (c.name.as_str(), CandidateId(i as u32)))
+        .collect();
+
+    let mut ballots = Vec::new();
+    for (i, line) in lines.enumerate() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let image: BallotImage = serde_json::from_str(&line).map_err(|e| invalid_data(i + 2, e))?;
+
+        let choices = image
+            .ranks
+            .iter()
+            .map(|name| {
+                ids.get(name.as_str())
+                    .copied()
+                    .ok_or_else(|| invalid_data(i + 2, format!("unknown candidate {:?}", name)))
+            })
+            .collect::<io::Result<Vec<CandidateId>>>()?;
+
+        ballots.push(
+            NormalizedBallot::new(image.id, choices, image.overvoted)
+                .with_sub_jurisdiction(image.sub_jurisdiction),
+        );
+    }
+
+    Ok(NormalizedElection {
+        candidates: header.candidates,
+        ballots,
+        parse_issues: Vec::new(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::election::CandidateType;
+
+    #[test]
+    fn test_round_trip() {
+        let election = NormalizedElection {
+            candidates: vec![
+                Candidate::new("Alice".into(), CandidateType::Regular),
+                Candidate::new("Write-in".into(), CandidateType::WriteIn),
+            ],
+            ballots: vec![
+                NormalizedBallot::new("1".into(), vec![CandidateId(1), CandidateId(0)], false),
+                NormalizedBallot::new("2".into(), vec![], true)
+                    .with_sub_jurisdiction(Some("Ward 1".into())),
+            ],
+            parse_issues: Vec::new(),
+        };
+
+        let mut buf = Vec::new();
+        write_ballot_images(&election, &mut buf).unwrap();
+        let text = String::from_utf8(buf.clone()).unwrap();
+        assert_eq!(3, text.lines().count());
+        assert!(text
+            .lines()
+            .nth(1)
+            .unwrap()
+            .contains(r#""ranks":["Write-in","Alice"]"#));
+
+        let read = read_ballot_images(&buf[..]).unwrap();
+        assert_eq!(election.candidates, read.candidates);
+        assert_eq!(election.ballots, read.ballots);
+    }
+
+    #[test]
+    fn test_unknown_candidate() {
+        let text = "{\"candidates\":[]}\n{\"id\":\"1\",\"ranks\":[\"Bob\"]}\n";
+        match read_ballot_images(text.as_bytes()) {
+            Err(err) => assert!(err.to_string().starts_with("line 2:")),
+            Ok(_) => panic!("Expected an error for an unknown candidate."),
+        }
+    }
+}
@@ -0,0 +1,200 @@
+use crate::model::election::NormalizedElection;
+use arrow_array::{ArrayRef, BooleanArray, RecordBatch, StringArray, UInt32Array};
+use arrow_schema::{DataType, Field, Schema};
+use parquet::arrow::ArrowWriter;
+use parquet::basic::Compression;
+use parquet::errors::Result;
+use parquet::file::properties::WriterProperties;
+use std::io::Write;
+use std::sync::Arc;
+
+fn write_batch<W: Write + Send>(
+    fields: Vec<Field>,
+    columns: Vec<ArrayRef>,
+    writer: W,
+) -> Result<()> {
+    let schema = Arc::new(Schema::new(fields));
+    let batch = RecordBatch::try_new(schema.clone(), columns)?;
+    let properties = WriterProperties::builder()
+        .set_compression(Compression::SNAPPY)
+        .build();
+    let mut writer = ArrowWriter::try_new(writer, schema, Some(properties))?;
+    writer.write(&batch)?;
+    writer.close()?;
+    Ok(())
+}
+
+/// Write one row per normalized ballot as Parquet, with the columns:
+///
+/// - `contest` (string): the contest's directory, e.g. `us/ca/alameda/2022/11/mayor`
+/// - `ballot_id` (string)
+/// - `choices` (uint32): number of valid choices after normalization
+/// - `overvoted` (bool): whether the ballot was exhausted by an overvote
+/// - `sub_jurisdiction` (string, nullable): precinct or district, if known
+///
+/// The columns are part of the export's interface; add new ones at the end.
+pub fn write_ballots_parquet<W: Write + Send>(
+    contest: &str,
+    election: &NormalizedElection,
+    writer: W,
+) -> Result<()> {
+    let ballots = &election.ballots;
+    write_batch(
+        vec![
+            Field::new("contest", DataType::Utf8, false),
+            Field::new("ballot_id", DataType::Utf8, false),
+            Field::new("choices", DataType::UInt32, false),
+            Field::new("overvoted", DataType::Boolean, false),
+            Field::new("sub_jurisdiction", DataType::Utf8, true),
+        ],
+        vec![
+            Arc::new(StringArray::from(vec![contest; ballots.len()])),
+            Arc::new(StringArray::from_iter_values(ballots.iter().map(|b| &b.id))),
+            Arc::new(UInt32Array::from_iter_values(
+                ballots.iter().map(|b| b.choices().len() as u32),
+            )),
+            Arc::new(
+                ballots
+                    .iter()
+                    .map(|b| Some(b.overvoted))
+                    .collect::<BooleanArray>(),
+            ),
+            Arc::new(
+                ballots
+                    .iter()
+                    .map(|b| b.sub_jurisdiction.as_deref())
+                    .collect::<StringArray>(),
+            ),
+        ],
+        writer,
+    )
+}
+
+/// Write one row per valid choice on each normalized ballot as Parquet, with
+/// the columns:
+///
+/// - `contest` (string): as in `write_ballots_parquet`
+/// - `ballot_id` (string)
+/// - `rank` (uint32): 1 for the ballot's first valid choice, and so on
+/// - `candidate_id` (uint32): index of the candidate in the contest
+/// - `candidate` (string): candidate name
+///
+/// Skipped and duplicate rankings were removed by normalization, so ranks
+/// are consecutive. Ballots with no valid choices have no rows.
+pub fn write_choices_parquet<W: Write + Send>(
+    contest: &str,
+    election: &NormalizedElection,
+    writer: W,
+) -> Result<()> {
+    let mut ballot_ids: Vec<&str> = Vec::new();
+    let mut ranks: Vec<u32> = Vec::new();
+    let mut candidate_ids: Vec<u32> = Vec::new();
+    for ballot in &election.ballots {
+        for (rank, choice) in ballot.choices().into_iter().enumerate() {
+            ballot_ids.push(&ballot.id);
+            ranks.push(rank as u32 + 1);
+            candidate_ids.push(choice.0);
+        }
+    }
+    let candidates = StringArray::from_iter_values(
+        candidate_ids
+            .iter()
+            .map(|c| &election.candidates[*c as usize].name),
+    );
+
+    write_batch(
+        vec![
+            Field::new("contest", DataType::Utf8, false),
+            Field::new("ballot_id", DataType::Utf8, false),
+            Field::new("rank", DataType::UInt32, false),
+            Field::new("candidate_id", DataType::UInt32, false),
+            Field::new("candidate", DataType::Utf8, false),
+        ],
+        vec![
+            Arc::new(StringArray::from(vec![contest; ranks.len()])),
+            Arc::new(StringArray::from(ballot_ids)),
+            Arc::new(UInt32Array::from(ranks)),
+            Arc::new(UInt32Array::from(candidate_ids)),
+            Arc::new(candidates),
+        ],
+        writer,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::election::{Candidate, CandidateId, CandidateType, NormalizedBallot};
+    use arrow_array::cast::AsArray;
+    use arrow_array::types::UInt32Type;
+    use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+    use std::fs::File;
+
+    fn read_batch(path: &std::path::Path) -> RecordBatch {
+        ParquetRecordBatchReaderBuilder::try_new(File::open(path).unwrap())
+            .unwrap()
+            .build()
+            .unwrap()
+            .next()
+            .unwrap()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_write_parquet() {
+        let election = NormalizedElection {
+            candidates: vec![
+                Candidate::new("Alice".into(), CandidateType::Regular),
+                Candidate::new("Bob".into(), CandidateType::Regular),
+            ],
+            ballots: vec![
+                NormalizedBallot::new("1".into(), vec![CandidateId(0), CandidateId(1)], false)
+                    .with_sub_jurisdiction(Some("Pct 1".into())),
+                NormalizedBallot::new("2".into(), vec![CandidateId(1)], true),
+                NormalizedBallot::new("3".into(), vec![], false),
+            ],
+            parse_issues: Vec::new(),
+        };
+        let dir = std::env::temp_dir().join(format!("parquet_test_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+
+        let path = dir.join("ballots.parquet");
+        write_ballots_parquet("x/mayor", &election, File::create(&path).unwrap()).unwrap();
+        let ballots = read_batch(&path);
+        assert_eq!(3, ballots.num_rows());
+        let choices = ballots.column_by_name("choices").unwrap();
+        assert_eq!(&[2, 1, 0], choices.as_primitive::<UInt32Type>().values());
+        let overvoted = ballots.column_by_name("overvoted").unwrap().as_boolean();
+        assert!(!overvoted.value(0) && overvoted.value(1));
+        let sub_jurisdiction = ballots.column_by_name("sub_jurisdiction").unwrap();
+        assert_eq!("Pct 1", sub_jurisdiction.as_string::<i32>().value(0));
+        assert!(sub_jurisdiction.is_null(1));
+
+        let path = dir.join("choices.parquet");
+        write_choices_parquet("x/mayor", &election, File::create(&path).unwrap()).unwrap();
+        let choices = read_batch(&path);
+        assert_eq!(3, choices.num_rows());
+        let ballot_id = choices
+            .column_by_name("ballot_id")
+            .unwrap()
+            .as_string::<i32>();
+        assert_eq!(
+            vec!["1", "1", "2"],
+            ballot_id.iter().flatten().collect::<Vec<_>>()
+        );
+        let rank = choices.column_by_name("rank").unwrap();
+        assert_eq!(&[1, 2, 1], rank.as_primitive::<UInt32Type>().values());
+        let candidate = choices
+            .column_by_name("candidate")
+            .unwrap()
+            .as_string::<i32>();
+        assert_eq!("Bob", candidate.value(2));
+        let contest = choices
+            .column_by_name("contest")
+            .unwrap()
+            .as_string::<i32>();
+        assert_eq!("x/mayor", contest.value(0));
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
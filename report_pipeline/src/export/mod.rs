@@ -0,0 +1,12 @@
+//! Exporters that write preprocessed ballots and tabulated rounds in formats
+//! other tools can consume, and the matching importers.
+
+mod ballot_images;
+mod ballots_parquet;
+mod rank_csv;
+mod rounds_csv;
+
+pub use ballot_images::{read_ballot_images, write_ballot_images, BallotImage, BallotImageHeader};
+pub use ballots_parquet::{write_ballots_parquet, write_choices_parquet};
+pub use rank_csv::{write_rank_csv, OVERVOTE_LABEL};
+pub use rounds_csv::write_rounds_csv;
@@ -0,0 +1,72 @@
+use crate::model::election::NormalizedElection;
+use crate::util::write_csv_row;
+use std::io::{self, Write};
+
+/// Label written in a rank column for an overvote. Configure RCTab's
+/// `overvoteLabel` (or the equivalent URCVT setting) to this value.
+pub const OVERVOTE_LABEL: &str = "overvote";
+
+/// Write normalized ballots in the generic rank-column CSV layout read by
+/// RCTab and the Universal RCV Tabulator: a `BallotID` column followed by
+/// one column per rank, holding candidate names.
+///
+/// Because the ballots are already normalized, skipped and duplicate
+/// rankings have been removed. A ballot that was exhausted by an overvote
+/// has `OVERVOTE_LABEL` in the rank after its last valid choice, so
+/// tabulating the file with "exhaust on overvote" reproduces our rounds.
+pub fn write_rank_csv<W: Write>(election: &NormalizedElection, writer: &mut W) -> io::Result<()> {
+    let num_ranks = election
+        .ballots
+        .iter()
+        .map(|b| b.choices().len() + if b.overvoted { 1 } else { 0 })
+        .max()
+        .unwrap_or(0);
+
+    let mut header = vec!["BallotID".to_string()];
+    header.extend((1..=num_ranks).map(|i| format!("Rank {}", i)));
+    write_csv_row(writer, &header)?;
+
+    for ballot in &election.ballots {
+        let mut row: Vec<&str> = Vec::with_capacity(num_ranks + 1);
+        row.push(&ballot.id);
+        for choice in ballot.choices() {
+            row.push(&election.candidates[choice.0 as usize].name);
+        }
+        if ballot.overvoted {
+            row.push(OVERVOTE_LABEL);
+        }
+        row.resize(num_ranks + 1, "");
+        write_csv_row(writer, &row)?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::election::{Candidate, CandidateId, CandidateType, NormalizedBallot};
+
+    #[test]
+    fn test_write_rank_csv() {
+        let election = NormalizedElection {
+            candidates: vec![
+                Candidate::new("Alice".into(), CandidateType::Regular),
+                Candidate::new("Bob".into(), CandidateType::Regular),
+            ],
+            ballots: vec![
+                NormalizedBallot::new("1".into(), vec![CandidateId(0), CandidateId(1)], false),
+                NormalizedBallot::new("2".into(), vec![CandidateId(1)], true),
+                NormalizedBallot::new("3".into(), vec![], false),
+            ],
+            parse_issues: Vec::new(),
+        };
+
+        let mut out: Vec<u8> = Vec::new();
+        write_rank_csv(&election, &mut out).unwrap();
+        assert_eq!(
+            "BallotID,Rank 1,Rank 2\n1,Alice,Bob\n2,Bob,overvote\n3,,\n",
+            String::from_utf8(out).unwrap()
+        );
+    }
+}
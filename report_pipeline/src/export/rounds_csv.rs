@@ -0,0 +1,106 @@
+use crate::model::election::{Candidate, CandidateId};
+use crate::tabulator::TabulatorRound;
+use crate::util::write_csv_row;
+use std::io::{self, Write};
+
+/// A summary row under the candidate rows: its label and its count in a round.
+type TotalRow = (&'static str, fn(&TabulatorRound) -> u32);
+
+/// Write a contest's rounds in the round-by-round layout of official RCV
+/// results reports (as produced by RCTab): a row per candidate and a column
+/// per round, followed by rows for active ballots, the threshold and
+/// inactive ballots.
+///
+/// Candidates are listed in order of finish: those in the final round by
+/// their final votes, then the others in reverse order of elimination. A
+/// candidate's cells are empty after the round in which they were
+/// eliminated. Inactive ballot counts are cumulative.
+pub fn write_rounds_csv<W: Write>(
+    candidates: &[Candidate],
+    rounds: &[TabulatorRound],
+    writer: &mut W,
+) -> io::Result<()> {
+    let mut order: Vec<CandidateId> = Vec::new();
+    for round in rounds.iter().rev() {
+        for allocation in &round.allocations {
+            if let Some(c) = allocation.allocatee.candidate_id() {
+                if !order.contains(&c) {
+                    order.push(c);
+                }
+            }
+        }
+    }
+
+    let mut header = vec!["Candidate".to_string()];
+    header.extend((1..=rounds.len()).map(|i| format!("Round {}", i)));
+    write_csv_row(writer, &header)?;
+
+    for candidate in order {
+        let mut row = vec![candidates[candidate.0 as usize].name.clone()];
+        row.extend(rounds.iter().map(|round| {
+            round
+                .allocations
+                .iter()
+                .find(|a| a.allocatee.candidate_id() == Some(candidate))
+                .map(|a| a.votes.to_string())
+                .unwrap_or_default()
+        }));
+        write_csv_row(writer, &row)?;
+    }
+
+    let totals: [TotalRow; 5] = [
+        ("Active Ballots", |r| r.continuing_ballots),
+        ("Current Round Threshold", |r| r.continuing_ballots / 2 + 1),
+        ("Inactive Ballots by Overvotes", |r| r.overvote),
+        ("Inactive Ballots by Exhausted Choices", |r| r.undervote),
+        ("Inactive Ballots Total", |r| r.overvote + r.undervote),
+    ];
+    for (label, count) in &totals {
+        let mut row = vec![label.to_string()];
+        row.extend(rounds.iter().map(|round| count(round).to_string()));
+        write_csv_row(writer, &row)?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::election::{CandidateType, NormalizedBallot};
+    use crate::tabulator::tabulate;
+
+    #[test]
+    fn test_write_rounds_csv() {
+        let candidates = vec![
+            Candidate::new("Alice".into(), CandidateType::Regular),
+            Candidate::new("Bob".into(), CandidateType::Regular),
+            Candidate::new("Smith, Carol".into(), CandidateType::Regular),
+        ];
+        let (a, b, c) = (CandidateId(0), CandidateId(1), CandidateId(2));
+        let ballots = vec![
+            NormalizedBallot::new("1".into(), vec![a], false),
+            NormalizedBallot::new("2".into(), vec![a], false),
+            NormalizedBallot::new("3".into(), vec![b], false),
+            NormalizedBallot::new("4".into(), vec![b, a], false),
+            NormalizedBallot::new("5".into(), vec![c, b], false),
+            NormalizedBallot::new("6".into(), vec![c], true),
+            NormalizedBallot::new("7".into(), vec![], false),
+        ];
+
+        let mut out: Vec<u8> = Vec::new();
+        write_rounds_csv(&candidates, &tabulate(&ballots), &mut out).unwrap();
+        assert_eq!(
+            "Candidate,Round 1,Round 2\n\
+             Bob,2,3\n\
+             Alice,2,2\n\
+             \"Smith, Carol\",2,\n\
+             Active Ballots,6,5\n\
+             Current Round Threshold,4,3\n\
+             Inactive Ballots by Overvotes,0,1\n\
+             Inactive Ballots by Exhausted Choices,1,1\n\
+             Inactive Ballots Total,1,2\n",
+            String::from_utf8(out).unwrap()
+        );
+    }
+}
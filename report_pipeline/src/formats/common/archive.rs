@@ -0,0 +1,35 @@
+use crate::formats::FormatError;
+use std::fs::File;
+use std::path::Path;
+use zip::read::ZipFile;
+use zip::ZipArchive;
+
+/// Return a `FormatError` for the ZIP archive at `file`, or for the given
+/// entry in it.
+pub fn archive_error(file: &Path, entry: Option<&str>, message: impl ToString) -> FormatError {
+    FormatError::Archive {
+        file: file.to_path_buf(),
+        entry: entry.map(|e| e.to_string()),
+        message: message.to_string(),
+    }
+}
+
+/// Open the ZIP archive at `file`.
+pub fn open_archive(file: &Path) -> Result<ZipArchive<File>, FormatError> {
+    let reader = File::open(file).map_err(|source| FormatError::Io {
+        file: file.to_path_buf(),
+        source,
+    })?;
+    ZipArchive::new(reader).map_err(|err| archive_error(file, None, err))
+}
+
+/// Return the entry `name` of `archive`, which was opened from `file`.
+pub fn archive_entry<'a>(
+    archive: &'a mut ZipArchive<File>,
+    file: &Path,
+    name: &str,
+) -> Result<ZipFile<'a>, FormatError> {
+    archive
+        .by_name(name)
+        .map_err(|err| archive_error(file, Some(name), err))
+}
@@ -2,6 +2,7 @@ use crate::model::election::{Candidate, CandidateId, Choice};
 use std::collections::HashMap;
 use std::fmt::Debug;
 use std::hash::Hash;
+use tracing::info;
 
 #[derive(Debug)]
 pub struct CandidateMap<ExternalCandidateId: Eq + Hash + Clone> {
@@ -32,20 +33,19 @@ impl<ExternalCandidateId: Eq + Hash + Clone + Debug> CandidateMap<ExternalCandid
         candidate: Candidate,
     ) -> Choice {
         if !self.id_to_index.contains_key(&external_candidate_id) {
-            eprintln!("New candidate: {:?}", external_candidate_id);
+            info!("New candidate: {:?}", external_candidate_id);
             self.add(external_candidate_id.clone(), candidate);
         }
 
-        self.id_to_choice(external_candidate_id)
+        Choice::Vote(self.id_to_index[&external_candidate_id])
     }
 
-    pub fn id_to_choice(&self, external_candidate_id: ExternalCandidateId) -> Choice {
-        let index = self
-            .id_to_index
+    /// Return the choice for a candidate added to the map, or `None` if the
+    /// ballot names a candidate that isn't in it.
+    pub fn id_to_choice(&self, external_candidate_id: ExternalCandidateId) -> Option<Choice> {
+        self.id_to_index
             .get(&external_candidate_id)
-            .expect("Candidate on ballot but not in master lookup.");
-
-        Choice::Vote(*index)
+            .map(|index| Choice::Vote(*index))
     }
 
     pub fn into_vec(self) -> Vec<Candidate> {
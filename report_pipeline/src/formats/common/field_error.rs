@@ -0,0 +1,30 @@
+use crate::formats::FormatError;
+use std::path::Path;
+
+/// A value in a row that couldn't be interpreted, found before the caller
+/// knows which file and row it came from. The column is 1-based.
+#[derive(Debug, PartialEq)]
+pub struct FieldError {
+    pub column: usize,
+    pub message: String,
+}
+
+impl FieldError {
+    pub fn new(column: usize, message: impl ToString) -> FieldError {
+        FieldError {
+            column,
+            message: message.to_string(),
+        }
+    }
+
+    /// Locate the error at the given 1-based row (or line) of `file`.
+    pub fn at(self, file: &Path, sheet: Option<&str>, row: usize) -> FormatError {
+        FormatError::Cell {
+            file: file.to_path_buf(),
+            sheet: sheet.map(|s| s.to_string()),
+            row,
+            column: self.column,
+            message: self.message,
+        }
+    }
+}
@@ -1,5 +1,17 @@
+mod archive;
 mod candidate_map;
+mod field_error;
 mod normalize_name;
+mod params;
+mod row_issues;
+mod rows;
 
+pub use archive::{archive_entry, archive_error, open_archive};
 pub use candidate_map::CandidateMap;
+pub use field_error::FieldError;
 pub use normalize_name::normalize_name;
+pub use params::{parsed_param, required_param};
+pub use row_issues::RowIssues;
+pub use rows::{
+    for_each_workbook, parse_csv, read_first_sheet, read_sheets, read_tables, SheetOptions,
+};
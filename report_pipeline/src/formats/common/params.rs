@@ -0,0 +1,56 @@
+use crate::formats::FormatError;
+use std::collections::BTreeMap;
+use std::fmt::Display;
+use std::str::FromStr;
+
+/// Return the value of a required loader parameter.
+pub fn required_param<'a>(
+    params: &'a BTreeMap<String, String>,
+    name: &str,
+) -> Result<&'a String, FormatError> {
+    params
+        .get(name)
+        .ok_or_else(|| FormatError::missing_param(name))
+}
+
+/// Parse the value of a loader parameter, if it is given.
+pub fn parsed_param<T>(
+    params: &BTreeMap<String, String>,
+    name: &str,
+) -> Result<Option<T>, FormatError>
+where
+    T: FromStr,
+    T::Err: Display,
+{
+    params
+        .get(name)
+        .map(|value| {
+            value.trim().parse().map_err(|err| FormatError::Param {
+                name: name.to_string(),
+                message: format!("is invalid ({:?}: {})", value, err),
+            })
+        })
+        .transpose()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parsed_param() {
+        let mut params = BTreeMap::new();
+        assert_eq!(None, parsed_param::<u32>(&params, "contest").unwrap());
+
+        params.insert("contest".to_string(), "12".to_string());
+        assert_eq!(Some(12), parsed_param::<u32>(&params, "contest").unwrap());
+
+        params.insert("contest".to_string(), "x".to_string());
+        assert_eq!(
+            "loader parameter contest is invalid (\"x\": invalid digit found in string)",
+            parsed_param::<u32>(&params, "contest")
+                .unwrap_err()
+                .to_string()
+        );
+    }
+}
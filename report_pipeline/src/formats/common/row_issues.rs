@@ -0,0 +1,94 @@
+use crate::formats::FormatError;
+use crate::model::election::ParseIssue;
+use std::collections::BTreeMap;
+
+/// Decides what happens to rows that can't be parsed. By default the error
+/// is returned and reading stops; if the `skipMalformedRows` loader parameter
+/// is `true`, the row is skipped and recorded as a [`ParseIssue`] instead.
+pub struct RowIssues {
+    skip_malformed: bool,
+    issues: Vec<ParseIssue>,
+}
+
+impl RowIssues {
+    pub fn from_params(params: &BTreeMap<String, String>) -> RowIssues {
+        RowIssues {
+            skip_malformed: params
+                .get("skipMalformedRows")
+                .is_some_and(|v| v.eq_ignore_ascii_case("true")),
+            issues: Vec::new(),
+        }
+    }
+
+    /// Handle an error parsing a row. Returns `Ok` if the caller should skip
+    /// the row and carry on, or the error if reading should stop. Only cell
+    /// errors are skipped; missing files and bad headers always stop.
+    pub fn skip_row(&mut self, err: FormatError) -> Result<(), FormatError> {
+        match err {
+            FormatError::Cell {
+                file,
+                sheet,
+                row,
+                column,
+                message,
+            } if self.skip_malformed => {
+                self.issues.push(ParseIssue {
+                    file: file
+                        .file_name()
+                        .map_or_else(|| file.to_string_lossy(), |f| f.to_string_lossy())
+                        .into_owned(),
+                    sheet,
+                    row,
+                    column,
+                    reason: message,
+                });
+                Ok(())
+            }
+            err => Err(err),
+        }
+    }
+
+    pub fn into_vec(self) -> Vec<ParseIssue> {
+        self.issues
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    fn cell_error() -> FormatError {
+        FormatError::Cell {
+            file: PathBuf::from("raw/cvr.xlsx"),
+            sheet: Some("Sheet1".into()),
+            row: 3,
+            column: 2,
+            message: "invalid ballot count".into(),
+        }
+    }
+
+    #[test]
+    fn test_skip_row() {
+        let mut params = BTreeMap::new();
+        params.insert("skipMalformedRows".to_string(), "true".to_string());
+        let mut issues = RowIssues::from_params(&params);
+
+        assert!(issues.skip_row(cell_error()).is_ok());
+        assert!(issues
+            .skip_row(FormatError::missing_param("files"))
+            .is_err());
+
+        let issues = issues.into_vec();
+        assert_eq!(1, issues.len());
+        assert_eq!("cvr.xlsx", issues[0].file);
+        assert_eq!(3, issues[0].row);
+    }
+
+    #[test]
+    fn test_strict_by_default() {
+        let mut issues = RowIssues::from_params(&BTreeMap::new());
+        assert!(issues.skip_row(cell_error()).is_err());
+        assert!(issues.into_vec().is_empty());
+    }
+}
@@ -0,0 +1,346 @@
+use crate::formats::FormatError;
+use calamine::{open_workbook_auto, DataType, Range, Reader};
+use std::collections::BTreeMap;
+use std::fs::read_to_string;
+use std::path::{Path, PathBuf};
+use std::thread;
+
+/// Split CSV text into records. Fields may be quoted with `"`, in which case
+/// they can contain the delimiter, newlines, and doubled quotes.
+pub fn parse_csv(text: &str, delimiter: char) -> Vec<Vec<String>> {
+    let mut records = Vec::new();
+    let mut record: Vec<String> = Vec::new();
+    let mut field = String::new();
+    let mut in_quotes = false;
+    let mut chars = text.chars().peekable();
+
+    while let Some(ch) = chars.next() {
+        if in_quotes {
+            if ch == '"' {
+                if chars.peek() == Some(&'"') {
+                    chars.next();
+                    field.push('"');
+                } else {
+                    in_quotes = false;
+                }
+            } else {
+                field.push(ch);
+            }
+        } else if ch == '"' {
+            in_quotes = true;
+        } else if ch == delimiter {
+            record.push(std::mem::take(&mut field));
+        } else if ch == '\n' || ch == '\r' {
+            if ch == '\r' && chars.peek() == Some(&'\n') {
+                chars.next();
+            }
+            record.push(std::mem::take(&mut field));
+            records.push(std::mem::take(&mut record));
+        } else {
+            field.push(ch);
+        }
+    }
+
+    if !field.is_empty() || !record.is_empty() {
+        record.push(field);
+        records.push(record);
+    }
+
+    records
+}
+
+fn cell_to_string(cell: &DataType) -> String {
+    match cell {
+        DataType::Float(f) if f.fract() == 0. => format!("{}", *f as i64),
+        DataType::Empty => String::new(),
+        _ => cell.to_string(),
+    }
+}
+
+/// Which worksheets of a workbook to read.
+#[derive(Clone, Debug, PartialEq)]
+pub enum SheetSelection {
+    /// The sheet at the given (0-based) position.
+    Index(usize),
+    /// The sheet with the given name.
+    Name(String),
+    /// Every sheet, in order.
+    All,
+}
+
+/// Where to find ballot rows in a spreadsheet or CSV file, set by the
+/// `sheet` loader parameter (a sheet name, a 1-based sheet number, or `all`;
+/// defaults to the first sheet) and the `headerRow` loader parameter (the
+/// 1-based row holding column headers, default 1; rows above it are
+/// ignored).
+pub struct SheetOptions {
+    pub sheets: SheetSelection,
+    pub header_row: usize,
+}
+
+impl Default for SheetOptions {
+    fn default() -> Self {
+        SheetOptions {
+            sheets: SheetSelection::Index(0),
+            header_row: 1,
+        }
+    }
+}
+
+impl SheetOptions {
+    pub fn from_params(params: &BTreeMap<String, String>) -> Result<SheetOptions, FormatError> {
+        let sheets = match params.get("sheet").map(|s| s.trim()) {
+            None => SheetSelection::Index(0),
+            Some(s) if s.eq_ignore_ascii_case("all") => SheetSelection::All,
+            Some(s) => match s.parse::<usize>() {
+                Ok(0) => {
+                    return Err(FormatError::Param {
+                        name: "sheet".to_string(),
+                        message: "numbers start at 1".to_string(),
+                    })
+                }
+                Ok(n) => SheetSelection::Index(n - 1),
+                Err(_) => SheetSelection::Name(s.to_string()),
+            },
+        };
+
+        let header_row = match params.get("headerRow") {
+            None => 1,
+            Some(row) => match row.trim().parse::<usize>() {
+                Ok(row) if row > 0 => row,
+                _ => {
+                    return Err(FormatError::Param {
+                        name: "headerRow".to_string(),
+                        message: format!("should be a row number from 1, got {:?}", row),
+                    })
+                }
+            },
+        };
+
+        Ok(SheetOptions { sheets, header_row })
+    }
+}
+
+/// Open a workbook and return the name and contents of each selected sheet.
+pub fn read_sheets(
+    path: &Path,
+    selection: &SheetSelection,
+) -> Result<Vec<(String, Range<DataType>)>, FormatError> {
+    let workbook_error = |sheet: Option<&String>, message: String| FormatError::Workbook {
+        file: path.to_path_buf(),
+        sheet: sheet.cloned(),
+        message,
+    };
+
+    let mut workbook =
+        open_workbook_auto(path).map_err(|err| workbook_error(None, err.to_string()))?;
+    let sheet_names = workbook.sheet_names().to_vec();
+    let selected: Vec<String> = match selection {
+        SheetSelection::All => sheet_names,
+        SheetSelection::Index(i) => vec![sheet_names
+            .get(*i)
+            .cloned()
+            .ok_or_else(|| workbook_error(None, format!("workbook has no sheet {}", i + 1)))?],
+        SheetSelection::Name(name) => vec![name.clone()],
+    };
+    if selected.is_empty() {
+        return Err(workbook_error(None, "workbook has no sheets".to_string()));
+    }
+
+    selected
+        .into_iter()
+        .map(|name| {
+            let sheet = workbook
+                .worksheet_range(&name)
+                .ok_or_else(|| workbook_error(Some(&name), "sheet not found".to_string()))?
+                .map_err(|err| workbook_error(Some(&name), err.to_string()))?;
+            Ok((name, sheet))
+        })
+        .collect()
+}
+
+/// Decode the selected sheets of each workbook in `files` and pass them to
+/// `process` in file order. Up to `threads` workbooks are decoded at once,
+/// which speeds up contests split over many large workbooks at the cost of
+/// holding that many decoded workbooks in memory.
+pub fn for_each_workbook<F>(
+    files: &[PathBuf],
+    selection: &SheetSelection,
+    threads: usize,
+    mut process: F,
+) -> Result<(), FormatError>
+where
+    F: FnMut(&Path, Vec<(String, Range<DataType>)>) -> Result<(), FormatError>,
+{
+    for chunk in files.chunks(threads.max(1)) {
+        let decoded: Vec<_> = thread::scope(|scope| {
+            let handles: Vec<_> = chunk
+                .iter()
+                .map(|file| scope.spawn(move || read_sheets(file, selection)))
+                .collect();
+            handles
+                .into_iter()
+                .map(|handle| handle.join().expect("Workbook decoding thread panicked."))
+                .collect()
+        });
+
+        for (file, sheets) in chunk.iter().zip(decoded) {
+            process(file, sheets?)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Open a workbook and return the name and contents of its first sheet.
+pub fn read_first_sheet(path: &Path) -> Result<(String, Range<DataType>), FormatError> {
+    Ok(read_sheets(path, &SheetSelection::Index(0))?.remove(0))
+}
+
+/// The rows of one worksheet or CSV file, split into the header row and the
+/// rows below it.
+pub struct Table {
+    /// Name of the worksheet, for spreadsheets.
+    pub sheet: Option<String>,
+    pub header: Vec<String>,
+    pub rows: Vec<Vec<String>>,
+    /// 1-based row number of `rows[0]` in the file, for error messages.
+    pub first_row: usize,
+}
+
+fn split_header(
+    path: &Path,
+    sheet: Option<String>,
+    mut rows: Vec<Vec<String>>,
+    header_row: usize,
+) -> Result<Table, FormatError> {
+    if rows.len() < header_row {
+        return Err(FormatError::Header {
+            file: path.to_path_buf(),
+            message: match &sheet {
+                Some(sheet) => format!("sheet {} has no row {}", sheet, header_row),
+                None => format!("file has no row {}", header_row),
+            },
+        });
+    }
+
+    let data = rows.split_off(header_row);
+    let header = rows.pop().unwrap_or_default();
+
+    Ok(Table {
+        sheet,
+        header,
+        rows: data,
+        first_row: header_row + 1,
+    })
+}
+
+/// Read a CSV file (by extension `.csv`) or the selected worksheets of a
+/// spreadsheet as tables of strings. Whole-number cells are written without
+/// a decimal point.
+pub fn read_tables(path: &Path, options: &SheetOptions) -> Result<Vec<Table>, FormatError> {
+    let is_csv = path
+        .extension()
+        .is_some_and(|e| e.eq_ignore_ascii_case("csv"));
+
+    if is_csv {
+        let text = read_to_string(path).map_err(|source| FormatError::Io {
+            file: path.to_path_buf(),
+            source,
+        })?;
+        let table = split_header(path, None, parse_csv(&text, ','), options.header_row)?;
+        return Ok(vec![table]);
+    }
+
+    read_sheets(path, &options.sheets)?
+        .into_iter()
+        .map(|(name, sheet)| {
+            let rows = sheet
+                .rows()
+                .map(|row| row.iter().map(cell_to_string).collect())
+                .collect();
+            split_header(path, Some(name), rows, options.header_row)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_csv() {
+        let text = "a,b,c\r\n1,\"x, y\",\"say \"\"hi\"\"\"\n2,,\"multi\nline\"";
+
+        assert_eq!(
+            vec![
+                vec!["a", "b", "c"],
+                vec!["1", "x, y", "say \"hi\""],
+                vec!["2", "", "multi\nline"],
+            ],
+            parse_csv(text, ',')
+        );
+    }
+
+    #[test]
+    fn test_sheet_options() {
+        let mut params = BTreeMap::new();
+        let options = SheetOptions::from_params(&params).unwrap();
+        assert_eq!(SheetSelection::Index(0), options.sheets);
+        assert_eq!(1, options.header_row);
+
+        params.insert("sheet".to_string(), "2".to_string());
+        params.insert("headerRow".to_string(), "3".to_string());
+        let options = SheetOptions::from_params(&params).unwrap();
+        assert_eq!(SheetSelection::Index(1), options.sheets);
+        assert_eq!(3, options.header_row);
+
+        params.insert("sheet".to_string(), "All".to_string());
+        assert_eq!(
+            SheetSelection::All,
+            SheetOptions::from_params(&params).unwrap().sheets
+        );
+
+        params.insert("sheet".to_string(), "Ballots".to_string());
+        assert_eq!(
+            SheetSelection::Name("Ballots".to_string()),
+            SheetOptions::from_params(&params).unwrap().sheets
+        );
+
+        params.insert("headerRow".to_string(), "0".to_string());
+        assert!(SheetOptions::from_params(&params).is_err());
+    }
+
+    #[test]
+    fn test_split_header() {
+        let rows = parse_csv("Exported 2024-11-05\n\nID,1,2\n1,A,B\n", ',');
+        let table = split_header(Path::new("cvr.csv"), None, rows, 3).unwrap();
+        assert_eq!(vec!["ID", "1", "2"], table.header);
+        assert_eq!(vec![vec!["1", "A", "B"]], table.rows);
+        assert_eq!(4, table.first_row);
+
+        let rows = parse_csv("ID,1,2\n", ',');
+        assert!(split_header(Path::new("cvr.csv"), None, rows, 2).is_err());
+    }
+
+    #[test]
+    fn test_for_each_workbook_stops_at_error() {
+        let files = vec![
+            PathBuf::from("missing-1.xlsx"),
+            PathBuf::from("missing-2.xlsx"),
+        ];
+        let mut calls = 0;
+        let result = for_each_workbook(&files, &SheetSelection::Index(0), 2, |_, _| {
+            calls += 1;
+            Ok(())
+        });
+
+        assert!(matches!(result, Err(FormatError::Workbook { .. })));
+        assert_eq!(0, calls);
+    }
+
+    #[test]
+    fn test_parse_csv_trailing_newline() {
+        assert_eq!(vec![vec!["a", "b"]], parse_csv("a\tb\n", '\t'));
+    }
+}
@@ -0,0 +1,266 @@
+use crate::formats::common::{normalize_name, parse_csv, required_param, CandidateMap};
+use crate::formats::FormatError;
+use crate::model::election::{Ballot, Candidate, CandidateType, Choice, Election};
+use flate2::read::GzDecoder;
+use regex::Regex;
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io::Read;
+use std::path::Path;
+use tracing::info;
+
+/// Rank column headers recognized when `rankColumnPattern` isn't given, such
+/// as `Rank 1`, `Mayor Choice 2` or `3rd Choice`.
+const DEFAULT_RANK_COLUMN_PATTERN: &str =
+    r"(?i)^(?:.*\s)?(?:rank|choice)\s*#?\s*(\d+)$|^(\d+)(?:st|nd|rd|th)\s+(?:rank|choice)$";
+
+/// Ballot ID column headers recognized when `idColumn` isn't given, compared
+/// ignoring case, spaces and punctuation.
+const ID_COLUMN_NAMES: &[&str] = &[
+    "ballotid",
+    "cvrid",
+    "cvrnumber",
+    "castvoterecord",
+    "recordid",
+    "id",
+];
+
+fn is_id_column(header: &str) -> bool {
+    let key: String = header
+        .chars()
+        .filter(|c| c.is_alphanumeric())
+        .flat_map(|c| c.to_lowercase())
+        .collect();
+    ID_COLUMN_NAMES.contains(&key.as_str())
+}
+
+/// Read a CSV file as text, decompressing it first if its name ends in `.gz`.
+fn read_text(file: &Path) -> Result<String, FormatError> {
+    let io_error = |source| FormatError::Io {
+        file: file.to_path_buf(),
+        source,
+    };
+    let mut reader: Box<dyn Read> = Box::new(File::open(file).map_err(io_error)?);
+    if file
+        .extension()
+        .is_some_and(|e| e.eq_ignore_ascii_case("gz"))
+    {
+        reader = Box::new(GzDecoder::new(reader));
+    }
+    let mut text = String::new();
+    reader.read_to_string(&mut text).map_err(io_error)?;
+    Ok(text)
+}
+
+fn markers(params: &BTreeMap<String, String>, key: &str, default: &str) -> Vec<String> {
+    params
+        .get(key)
+        .map(|s| s.as_str())
+        .unwrap_or(default)
+        .split(';')
+        .map(|s| s.trim().to_lowercase())
+        .collect()
+}
+
+struct ReaderOptions {
+    files: Vec<String>,
+    delimiter: char,
+    rank_column: Regex,
+    id_column: Option<String>,
+    undervote: Vec<String>,
+    overvote: Vec<String>,
+    write_in: Vec<String>,
+}
+
+impl ReaderOptions {
+    pub fn from_params(params: BTreeMap<String, String>) -> Result<ReaderOptions, FormatError> {
+        let files: Vec<String> = required_param(&params, "files")?
+            .split(';')
+            .map(|x| x.to_string())
+            .collect();
+
+        let delimiter = match params.get("delimiter").map(|s| s.as_str()) {
+            None => ',',
+            Some("tab") | Some("\\t") => '\t',
+            Some(d) if d.chars().count() == 1 => d.chars().next().unwrap(),
+            Some(d) => {
+                return Err(FormatError::Param {
+                    name: "delimiter".to_string(),
+                    message: format!("should be a single character, got {:?}", d),
+                })
+            }
+        };
+
+        let rank_column = params
+            .get("rankColumnPattern")
+            .map_or(DEFAULT_RANK_COLUMN_PATTERN, |p| p.as_str());
+        let rank_column = Regex::new(rank_column).map_err(|err| FormatError::Param {
+            name: "rankColumnPattern".to_string(),
+            message: format!("is not a valid regex: {}", err),
+        })?;
+
+        Ok(ReaderOptions {
+            files,
+            delimiter,
+            rank_column,
+            id_column: params.get("idColumn").cloned(),
+            undervote: markers(&params, "undervoteMarkers", ";undervote"),
+            overvote: markers(&params, "overvoteMarkers", "overvote"),
+            write_in: markers(&params, "writeInMarkers", "write-in"),
+        })
+    }
+
+    pub fn parse_choice(&self, value: &str, candidate_map: &mut CandidateMap<String>) -> Choice {
+        let value = value.trim();
+        let lower = value.to_lowercase();
+
+        if self.undervote.contains(&lower) {
+            Choice::Undervote
+        } else if self.overvote.contains(&lower) {
+            Choice::Overvote
+        } else if self.write_in.contains(&lower) {
+            candidate_map.add_id_to_choice(
+                "Write-in".to_string(),
+                Candidate::new("Write-in".to_string(), CandidateType::WriteIn),
+            )
+        } else {
+            candidate_map.add_id_to_choice(
+                value.to_string(),
+                Candidate::new(normalize_name(value, false), CandidateType::Regular),
+            )
+        }
+    }
+}
+
+/// Reader for CSV cast vote records with one row per ballot and one column per
+/// rank. Rank columns are the headers matching `rankColumnPattern` (by default
+/// [`DEFAULT_RANK_COLUMN_PATTERN`]), whose first matching capture group (or the
+/// whole match, if it has none) is the rank number. Files ending in `.gz` are
+/// decompressed as they are read.
+pub fn csv_cvr_reader(
+    path: &Path,
+    params: BTreeMap<String, String>,
+) -> Result<Election, FormatError> {
+    let options = ReaderOptions::from_params(params)?;
+    let mut ballots: Vec<Ballot> = Vec::new();
+    let mut candidate_map: CandidateMap<String> = CandidateMap::new();
+
+    for file in &options.files {
+        info!("Reading: {}", file);
+        let file_path = path.join(file);
+        let text = read_text(&file_path)?;
+        let mut rows = parse_csv(&text, options.delimiter).into_iter();
+        let header = rows.next().ok_or_else(|| FormatError::Header {
+            file: file_path.clone(),
+            message: "file is empty".to_string(),
+        })?;
+
+        let mut rank_to_col: BTreeMap<u32, usize> = BTreeMap::new();
+        let mut id_col: Option<usize> = None;
+        for (i, colname) in header.iter().enumerate() {
+            let colname = colname.trim();
+            if let Some(caps) = options.rank_column.captures(colname) {
+                let rank = caps.iter().skip(1).flatten().next();
+                let rank = rank.or_else(|| caps.get(0)).unwrap().as_str();
+                let rank: u32 = rank.parse().map_err(|_| FormatError::Header {
+                    file: file_path.clone(),
+                    message: format!("rank column {} has no rank number", colname),
+                })?;
+                rank_to_col.insert(rank, i);
+            } else if match &options.id_column {
+                Some(id_column) => id_column == colname,
+                None => id_col.is_none() && is_id_column(colname),
+            } {
+                id_col = Some(i);
+            }
+        }
+        if rank_to_col.is_empty() {
+            return Err(FormatError::Header {
+                file: file_path,
+                message: "no rank columns".to_string(),
+            });
+        }
+
+        for (row_num, row) in rows.enumerate() {
+            if row.iter().all(|c| c.trim().is_empty()) {
+                continue;
+            }
+
+            let choices: Vec<Choice> = rank_to_col
+                .values()
+                .map(|col| {
+                    options.parse_choice(row.get(*col).map_or("", |s| s), &mut candidate_map)
+                })
+                .collect();
+            let id = match id_col {
+                Some(col) => row.get(col).cloned().unwrap_or_default(),
+                None => format!("{}:{}", file, row_num + 1),
+            };
+
+            ballots.push(Ballot::new(id, choices));
+        }
+    }
+
+    Ok(Election::new(candidate_map.into_vec(), ballots))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_choice() {
+        let mut params = BTreeMap::new();
+        params.insert("files".to_string(), "cvr.csv".to_string());
+        params.insert("rankColumnPattern".to_string(), "^Rank (\\d+)$".to_string());
+        params.insert("overvoteMarkers".to_string(), "overvote;OV".to_string());
+        let options = ReaderOptions::from_params(params).unwrap();
+        let mut candidate_map = CandidateMap::new();
+
+        assert_eq!(
+            Choice::Undervote,
+            options.parse_choice("", &mut candidate_map)
+        );
+        assert_eq!(
+            Choice::Overvote,
+            options.parse_choice("ov", &mut candidate_map)
+        );
+        assert_eq!(
+            Choice::Undervote,
+            options.parse_choice("UNDERVOTE", &mut candidate_map)
+        );
+        let a = options.parse_choice("Jane Doe", &mut candidate_map);
+        assert_eq!(a, options.parse_choice(" Jane Doe ", &mut candidate_map));
+        options.parse_choice("Write-in", &mut candidate_map);
+
+        let candidates = candidate_map.into_vec();
+        assert_eq!(2, candidates.len());
+        assert_eq!(CandidateType::WriteIn, candidates[1].candidate_type);
+    }
+
+    #[test]
+    fn test_default_rank_column_pattern() {
+        let pattern = Regex::new(DEFAULT_RANK_COLUMN_PATTERN).unwrap();
+        let rank = |header: &str| {
+            pattern
+                .captures(header)
+                .and_then(|caps| caps.iter().skip(1).flatten().next())
+                .map(|m| m.as_str().to_string())
+        };
+
+        assert_eq!(Some("1".to_string()), rank("Rank 1"));
+        assert_eq!(Some("2".to_string()), rank("City Council Choice 2"));
+        assert_eq!(Some("3".to_string()), rank("3rd Choice"));
+        assert_eq!(Some("10".to_string()), rank("RANK #10"));
+        assert_eq!(None, rank("Ranked"));
+        assert_eq!(None, rank("Precinct"));
+    }
+
+    #[test]
+    fn test_is_id_column() {
+        assert!(is_id_column("BallotID"));
+        assert!(is_id_column("Ballot ID"));
+        assert!(is_id_column("cvr_number"));
+        assert!(!is_id_column("Precinct"));
+    }
+}
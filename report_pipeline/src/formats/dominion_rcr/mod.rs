@@ -1,6 +1,7 @@
 mod parser;
 
 use crate::formats::dominion_rcr::parser::rcr_file;
+use crate::formats::FormatError;
 use crate::model::election::Election;
 use std::collections::BTreeMap;
 use std::fs::read_to_string;
@@ -18,10 +19,14 @@ impl ReaderOptions {
     }
 }
 
-pub fn dominion_rcr_ballot_reader(path: &Path, params: BTreeMap<String, String>) -> Election {
+pub fn dominion_rcr_ballot_reader(
+    path: &Path,
+    params: BTreeMap<String, String>,
+) -> Result<Election, FormatError> {
     let options = ReaderOptions::from_params(params);
 
-    let raw = read_to_string(path.join(options.rcr)).unwrap();
+    let file = path.join(options.rcr);
+    let raw = read_to_string(&file).map_err(|source| FormatError::Io { file, source })?;
 
-    rcr_file(&raw)
+    Ok(rcr_file(&raw))
 }
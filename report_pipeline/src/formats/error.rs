@@ -0,0 +1,139 @@
+use std::error::Error;
+use std::fmt;
+use std::io;
+use std::path::PathBuf;
+
+/// An error reading a contest's raw ballot data. Every variant carries enough
+/// location information (file, and where known sheet, row and column) to find
+/// the offending input.
+#[derive(Debug)]
+pub enum FormatError {
+    /// A `loaderParams` entry is missing or invalid.
+    Param { name: String, message: String },
+    /// A file or directory couldn't be read.
+    Io { file: PathBuf, source: io::Error },
+    /// A spreadsheet couldn't be opened, or one of its sheets couldn't be read.
+    Workbook {
+        file: PathBuf,
+        sheet: Option<String>,
+        message: String,
+    },
+    /// A ZIP archive couldn't be opened, or a file in it couldn't be found or
+    /// read.
+    Archive {
+        file: PathBuf,
+        entry: Option<String>,
+        message: String,
+    },
+    /// A file's header row is missing or lacks an expected column.
+    Header { file: PathBuf, message: String },
+    /// A cell couldn't be interpreted. Rows and columns are 1-based, and
+    /// rows count the header row.
+    Cell {
+        file: PathBuf,
+        sheet: Option<String>,
+        row: usize,
+        column: usize,
+        message: String,
+    },
+}
+
+impl FormatError {
+    pub fn missing_param(name: &str) -> FormatError {
+        FormatError::Param {
+            name: name.to_string(),
+            message: "is required".to_string(),
+        }
+    }
+}
+
+impl fmt::Display for FormatError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            FormatError::Param { name, message } => {
+                write!(f, "loader parameter {} {}", name, message)
+            }
+            FormatError::Io { file, source } => write!(f, "{}: {}", file.display(), source),
+            FormatError::Workbook {
+                file,
+                sheet: Some(sheet),
+                message,
+            } => write!(f, "{} (sheet {}): {}", file.display(), sheet, message),
+            FormatError::Workbook {
+                file,
+                sheet: None,
+                message,
+            } => write!(f, "{}: {}", file.display(), message),
+            FormatError::Archive {
+                file,
+                entry: Some(entry),
+                message,
+            } => write!(f, "{} ({}): {}", file.display(), entry, message),
+            FormatError::Archive {
+                file,
+                entry: None,
+                message,
+            } => write!(f, "{}: {}", file.display(), message),
+            FormatError::Header { file, message } => {
+                write!(f, "{}: header: {}", file.display(), message)
+            }
+            FormatError::Cell {
+                file,
+                sheet,
+                row,
+                column,
+                message,
+            } => {
+                write!(f, "{}", file.display())?;
+                if let Some(sheet) = sheet {
+                    write!(f, " (sheet {})", sheet)?;
+                }
+                write!(f, ": row {}, column {}: {}", row, column, message)
+            }
+        }
+    }
+}
+
+impl Error for FormatError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            FormatError::Io { source, .. } => Some(source),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_display() {
+        let err = FormatError::Cell {
+            file: PathBuf::from("raw/cvr.xlsx"),
+            sheet: Some("Sheet1".to_string()),
+            row: 12,
+            column: 4,
+            message: "unknown candidate id 99".to_string(),
+        };
+        assert_eq!(
+            "raw/cvr.xlsx (sheet Sheet1): row 12, column 4: unknown candidate id 99",
+            err.to_string()
+        );
+
+        let err = FormatError::Archive {
+            file: PathBuf::from("raw/cvr.zip"),
+            entry: Some("CandidateManifest.json".to_string()),
+            message: "file not found".to_string(),
+        };
+        assert_eq!(
+            "raw/cvr.zip (CandidateManifest.json): file not found",
+            err.to_string()
+        );
+
+        assert_eq!(
+            "loader parameter files is required",
+            FormatError::missing_param("files").to_string()
+        );
+    }
+}
@@ -1,18 +1,38 @@
+//! Readers for the raw cast vote record formats published by election
+//! offices. Each reader turns a directory of raw files plus the contest's
+//! `loaderParams` into an [`Election`].
+
 mod common;
+mod csv_cvr;
 mod dominion_rcr;
+mod error;
 mod nist_sp_1500;
+mod precinct_attributes;
 mod simple_json;
 mod us_ca_sfo;
+mod us_ma_cambridge;
 mod us_me;
+mod us_mn_minneapolis;
 mod us_ny_nyc;
 mod us_vt_btv;
 
+pub use crate::formats::error::FormatError;
+pub use crate::formats::precinct_attributes::{read_precinct_attributes, PrecinctAttributes};
 use crate::model::election::Election;
 use std::collections::BTreeMap;
 use std::path::Path;
 
-pub type BallotReader = dyn Fn(&Path, BTreeMap<String, String>) -> Election;
+pub type BallotReader = dyn Fn(&Path, BTreeMap<String, String>) -> Result<Election, FormatError>;
+
+/// A reader that reads several contests from the same raw files in one pass,
+/// given each contest's office and `loaderParams`, and returns the elections
+/// keyed by office.
+pub type MultiContestReader = dyn Fn(
+    &Path,
+    Vec<(String, BTreeMap<String, String>)>,
+) -> Result<BTreeMap<String, Election>, FormatError>;
 
+/// Return the reader for the given `dataFormat` name.
 pub fn get_reader_for_format(format: &str) -> &'static BallotReader {
     match format {
         "us_ca_sfo" => &us_ca_sfo::sfo_ballot_reader,
@@ -21,12 +41,69 @@ pub fn get_reader_for_format(format: &str) -> &'static BallotReader {
         "dominion_rcr" => &dominion_rcr::dominion_rcr_ballot_reader,
         "us_me" => &us_me::maine_ballot_reader,
         "simple_json" => &simple_json::json_reader,
+        "csv_cvr" => &csv_cvr::csv_cvr_reader,
         "us_ny_nyc" => &us_ny_nyc::nyc_ballot_reader,
+        "us_ma_cambridge" => &us_ma_cambridge::cambridge_ballot_reader,
+        "us_mn_minneapolis" => &us_mn_minneapolis::minneapolis_ballot_reader,
         _ => panic!("The format {} is not implemented.", format),
     }
 }
 
-pub fn read_election(format: &str, path: &Path, params: BTreeMap<String, String>) -> Election {
+/// Return the `loaderParams` each contest must set for the given `dataFormat`
+/// name, or `None` if the format is not implemented.
+pub fn required_params(format: &str) -> Option<&'static [&'static str]> {
+    let params: &[&str] = match format {
+        "us_ca_sfo" => &["contest", "masterLookup", "ballotImage"],
+        "nist_sp_1500" => &["cvr", "contest"],
+        "us_vt_btv" => &["ballots", "archive"],
+        "dominion_rcr" => &["rcr"],
+        "us_me" => &["files"],
+        "simple_json" => &["file"],
+        "csv_cvr" => &["files"],
+        "us_ny_nyc" => &[
+            "officeName",
+            "jurisdictionName",
+            "candidatesFile",
+            "cvrPattern",
+        ],
+        "us_ma_cambridge" => &["files"],
+        "us_mn_minneapolis" => &["files"],
+        _ => return None,
+    };
+    Some(params)
+}
+
+/// Return the multi-contest reader for the given `dataFormat` name, if it has
+/// one. Formats without one read each contest separately.
+pub fn get_multi_contest_reader_for_format(format: &str) -> Option<&'static MultiContestReader> {
+    match format {
+        "us_ny_nyc" => Some(&us_ny_nyc::nyc_multi_contest_reader),
+        _ => None,
+    }
+}
+
+/// Read an election from the raw data in `path` using the reader for `format`.
+pub fn read_election(
+    format: &str,
+    path: &Path,
+    params: BTreeMap<String, String>,
+) -> Result<Election, FormatError> {
     let reader = get_reader_for_format(format);
     reader(path, params)
 }
+
+/// Read several contests' elections from the raw data in `path`, keyed by
+/// office, in a single pass over the raw files if the format supports it.
+pub fn read_elections(
+    format: &str,
+    path: &Path,
+    contests: Vec<(String, BTreeMap<String, String>)>,
+) -> Result<BTreeMap<String, Election>, FormatError> {
+    match get_multi_contest_reader_for_format(format) {
+        Some(reader) => reader(path, contests),
+        None => contests
+            .into_iter()
+            .map(|(office, params)| Ok((office, read_election(format, path, params)?)))
+            .collect(),
+    }
+}
@@ -1,13 +1,18 @@
 pub mod model;
 
-use crate::formats::common::{normalize_name, CandidateMap};
+use crate::formats::common::{
+    archive_entry, archive_error, normalize_name, open_archive, parsed_param, required_param,
+    CandidateMap,
+};
 use crate::formats::nist_sp_1500::model::{CandidateManifest, CandidateType, CvrExport, Mark};
+use crate::formats::FormatError;
 use crate::model::election::{self, Ballot, Candidate, Choice, Election};
+use crate::util::progress_bar;
 use colored::*;
 use itertools::Itertools;
 use std::collections::BTreeMap;
-use std::fs::File;
 use std::io::BufReader;
+use tracing::info;
 
 use std::path::Path;
 
@@ -18,26 +23,18 @@ struct ReaderOptions {
 }
 
 impl ReaderOptions {
-    pub fn from_params(params: BTreeMap<String, String>) -> ReaderOptions {
-        let cvr = params
-            .get("cvr")
-            .expect("nist_sp_1500 elections should have cvr parameter.")
-            .clone();
-        let contest = params
-            .get("contest")
-            .expect("nist_sp_1500 elections should have contest parameter.")
-            .parse()
-            .expect("contest param should be a number.");
-        let drop_unqualified_write_in: bool = params
-            .get("dropUnqualifiedWriteIn")
-            .map(|d| d.parse().unwrap())
-            .unwrap_or(false);
-
-        ReaderOptions {
+    pub fn from_params(params: BTreeMap<String, String>) -> Result<ReaderOptions, FormatError> {
+        let cvr = required_param(&params, "cvr")?.clone();
+        let contest = parsed_param(&params, "contest")?
+            .ok_or_else(|| FormatError::missing_param("contest"))?;
+        let drop_unqualified_write_in: bool =
+            parsed_param(&params, "dropUnqualifiedWriteIn")?.unwrap_or(false);
+
+        Ok(ReaderOptions {
             contest,
             cvr,
             drop_unqualified_write_in,
-        }
+        })
     }
 }
 
@@ -81,7 +78,7 @@ fn get_ballots(
     map: &CandidateMap<u32>,
     filename: &str,
     dropped_write_in: Option<u32>,
-) -> Vec<Ballot> {
+) -> Result<Vec<Ballot>, String> {
     let mut ballots: Vec<Ballot> = Vec::new();
 
     for session in &cvr.sessions {
@@ -99,7 +96,12 @@ fn get_ballots(
                             // write-in candidates are dropped by treating them as undervotes.
                             Choice::Undervote
                         }
-                        [v] => map.id_to_choice(v.candidate_id),
+                        [v] => map.id_to_choice(v.candidate_id).ok_or_else(|| {
+                            format!(
+                                "record {}: unknown candidate id {}",
+                                session.record_id, v.candidate_id
+                            )
+                        })?,
                         [] => Choice::Undervote,
                         _ => Choice::Overvote,
                     };
@@ -115,19 +117,23 @@ fn get_ballots(
         }
     }
 
-    ballots
+    Ok(ballots)
 }
 
-pub fn nist_ballot_reader(path: &Path, params: BTreeMap<String, String>) -> Election {
-    let options = ReaderOptions::from_params(params);
+pub fn nist_ballot_reader(
+    path: &Path,
+    params: BTreeMap<String, String>,
+) -> Result<Election, FormatError> {
+    let options = ReaderOptions::from_params(params)?;
 
-    let file = File::open(path.join(&options.cvr)).unwrap();
-    let mut archive = zip::ZipArchive::new(file).unwrap();
+    let cvr_path = path.join(&options.cvr);
+    let mut archive = open_archive(&cvr_path)?;
 
     let candidate_manifest: CandidateManifest = {
-        let file = archive.by_name("CandidateManifest.json").unwrap();
+        let name = "CandidateManifest.json";
+        let file = archive_entry(&mut archive, &cvr_path, name)?;
         let reader = BufReader::new(file);
-        serde_json::from_reader(reader).unwrap()
+        serde_json::from_reader(reader).map_err(|err| archive_error(&cvr_path, Some(name), err))?
     };
 
     let (candidates, dropped_write_in) = get_candidates(
@@ -137,26 +143,33 @@ pub fn nist_ballot_reader(path: &Path, params: BTreeMap<String, String>) -> Elec
     );
 
     let mut ballots: Vec<Ballot> = Default::default();
-    let filenames: Vec<String> = archive.file_names().map(|d| d.to_string()).collect();
+    let filenames: Vec<String> = archive
+        .file_names()
+        .filter(|d| d.starts_with("CvrExport"))
+        .map(|d| d.to_string())
+        .collect();
 
+    let progress = progress_bar("CVR files", filenames.len() as u64);
     for filename in filenames {
-        if filename.starts_with("CvrExport") {
-            eprintln!("Reading CVR file: {}", filename.green());
-            let file = archive.by_name(&filename).unwrap();
-            let reader = BufReader::new(file);
-            let cvr = serde_json::from_reader(reader).unwrap();
-            let extra_ballots = get_ballots(
-                &cvr,
-                options.contest,
-                &candidates,
-                &filename,
-                dropped_write_in,
-            );
-            ballots.extend(extra_ballots);
-        }
+        info!("Reading CVR file: {}", filename.green());
+        let file = archive_entry(&mut archive, &cvr_path, &filename)?;
+        let reader = BufReader::new(file);
+        let cvr = serde_json::from_reader(reader)
+            .map_err(|err| archive_error(&cvr_path, Some(&filename), err))?;
+        let extra_ballots = get_ballots(
+            &cvr,
+            options.contest,
+            &candidates,
+            &filename,
+            dropped_write_in,
+        )
+        .map_err(|message| archive_error(&cvr_path, Some(&filename), message))?;
+        ballots.extend(extra_ballots);
+        progress.inc(1);
     }
+    progress.finish_and_clear();
 
-    eprintln!("Read {} ballots", ballots.len().to_string().blue());
+    info!("Read {} ballots", ballots.len().to_string().blue());
 
-    Election::new(candidates.into_vec(), ballots)
+    Ok(Election::new(candidates.into_vec(), ballots))
 }
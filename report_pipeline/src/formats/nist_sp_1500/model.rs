@@ -147,6 +147,7 @@ pub struct Candidate {
 
 // ContestManifest.json
 
+#[allow(unused)]
 #[derive(Serialize, Deserialize)]
 #[serde(rename_all = "PascalCase")]
 pub struct ContestManifest {
@@ -154,6 +155,7 @@ pub struct ContestManifest {
     list: Vec<Contest>,
 }
 
+#[allow(unused)]
 #[derive(Serialize, Deserialize)]
 #[serde(rename_all = "PascalCase")]
 pub struct Contest {
@@ -0,0 +1,83 @@
+use crate::formats::common::parse_csv;
+use crate::formats::FormatError;
+use std::collections::BTreeMap;
+use std::fs::read_to_string;
+use std::path::Path;
+
+/// Numeric attributes of each precinct, such as census-derived demographics,
+/// keyed by precinct and then attribute name.
+pub type PrecinctAttributes = BTreeMap<String, BTreeMap<String, f64>>;
+
+/// Read a CSV file whose first column is a precinct id and whose other
+/// columns are numeric attributes named by the header row. Blank cells are
+/// left out.
+pub fn read_precinct_attributes(file: &Path) -> Result<PrecinctAttributes, FormatError> {
+    let text = read_to_string(file).map_err(|source| FormatError::Io {
+        file: file.to_path_buf(),
+        source,
+    })?;
+    parse_precinct_attributes(&text, file)
+}
+
+fn parse_precinct_attributes(text: &str, file: &Path) -> Result<PrecinctAttributes, FormatError> {
+    let mut records = parse_csv(text, ',').into_iter();
+    let header = match records.next() {
+        Some(header) if header.len() > 1 => header,
+        _ => {
+            return Err(FormatError::Header {
+                file: file.to_path_buf(),
+                message: "expected a precinct column followed by attribute columns".to_string(),
+            })
+        }
+    };
+
+    let mut attributes = PrecinctAttributes::new();
+    for (i, record) in records.enumerate() {
+        let precinct = match record.first() {
+            Some(precinct) if !precinct.trim().is_empty() => precinct.trim().to_string(),
+            _ => continue,
+        };
+        let values = attributes.entry(precinct).or_default();
+        for (j, (name, cell)) in header.iter().zip(&record).enumerate().skip(1) {
+            let cell = cell.trim();
+            if cell.is_empty() {
+                continue;
+            }
+            let value: f64 = cell.parse().map_err(|_| FormatError::Cell {
+                file: file.to_path_buf(),
+                sheet: None,
+                row: i + 2,
+                column: j + 1,
+                message: format!("expected a number, found {:?}", cell),
+            })?;
+            values.insert(name.trim().to_string(), value);
+        }
+    }
+
+    Ok(attributes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_precinct_attributes() {
+        let file = Path::new("attributes.csv");
+        let attributes = parse_precinct_attributes(
+            "precinct,median_income,pct_renters\n1-1,52000,0.4\n1-2,,0.7\n",
+            file,
+        )
+        .unwrap();
+
+        assert_eq!(Some(&52000.0), attributes["1-1"].get("median_income"));
+        assert_eq!(None, attributes["1-2"].get("median_income"));
+        assert_eq!(Some(&0.7), attributes["1-2"].get("pct_renters"));
+
+        let err = parse_precinct_attributes("precinct,income\n1-1,n/a\n", file).unwrap_err();
+        assert_eq!(
+            "attributes.csv: row 2, column 2: expected a number, found \"n/a\"",
+            err.to_string()
+        );
+    }
+}
@@ -1,4 +1,5 @@
 use crate::formats::common::CandidateMap;
+use crate::formats::FormatError;
 use crate::model::election::{Ballot, Candidate, CandidateType, Choice, Election};
 use crate::util::read_serialized;
 use serde::Deserialize;
@@ -43,7 +44,7 @@ pub fn parse_choice(candidate: &str, candidate_map: &mut CandidateMap<String>) -
     }
 }
 
-pub fn json_reader(path: &Path, params: BTreeMap<String, String>) -> Election {
+pub fn json_reader(path: &Path, params: BTreeMap<String, String>) -> Result<Election, FormatError> {
     let options = ReaderOptions::from_params(params);
 
     let raw_ballots: RawBallots = read_serialized(&path.join(options.file));
@@ -63,5 +64,5 @@ pub fn json_reader(path: &Path, params: BTreeMap<String, String>) -> Election {
         })
         .collect();
 
-    Election::new(candidate_map.into_vec(), ballots)
+    Ok(Election::new(candidate_map.into_vec(), ballots))
 }
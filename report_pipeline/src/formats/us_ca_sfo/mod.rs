@@ -1,15 +1,55 @@
-use crate::formats::common::{normalize_name, CandidateMap};
+use crate::formats::common::{
+    archive_entry, normalize_name, open_archive, parsed_param, required_param, CandidateMap,
+    FieldError,
+};
+use crate::formats::FormatError;
 use crate::model::election::{Ballot, Candidate, CandidateType, Choice, Election};
 use crate::util::UnicodeString;
-use itertools::Itertools;
 use std::collections::BTreeMap;
 use std::fs::File;
 use std::io::{BufRead, BufReader};
+use std::ops::Range;
 use std::path::Path;
 
 const CANDIDATE: &str = "Candidate";
 const WRITE_IN_PREFIX: &str = "WRITE-IN ";
 
+/// A line of fixed-width fields.
+struct Fields {
+    input: UnicodeString,
+}
+
+impl Fields {
+    fn new(line: &str) -> Fields {
+        Fields {
+            input: UnicodeString::new(line),
+        }
+    }
+
+    fn text(&self, range: Range<usize>) -> Result<String, FieldError> {
+        self.input.get(range.clone()).ok_or_else(|| {
+            FieldError::new(
+                range.start + 1,
+                format!("line ends before column {}", range.end),
+            )
+        })
+    }
+
+    fn number(&self, range: Range<usize>) -> Result<u32, FieldError> {
+        let text = self.text(range.clone())?;
+        text.parse().map_err(|_| {
+            FieldError::new(
+                range.start + 1,
+                format!("expected a number, found {:?}", text),
+            )
+        })
+    }
+
+    fn flag(&self, range: Range<usize>) -> Result<bool, FieldError> {
+        Ok(self.text(range)? == "1")
+    }
+}
+
 #[derive(Debug)]
 struct MasterRecord {
     record_type: String,
@@ -22,17 +62,17 @@ struct MasterRecord {
 }
 
 impl MasterRecord {
-    fn parse(input: &str) -> MasterRecord {
-        let input = UnicodeString::new(input);
-        MasterRecord {
-            record_type: input.slice(0..10).trim().to_string(),
-            record_id: input.slice(10..17).parse().unwrap(),
-            description: input.slice(17..67).trim().to_string(),
-            _list_order: input.slice(67..74).parse().unwrap(),
-            contest_id: input.slice(74..81).parse().unwrap(),
-            is_writein: &input.slice(81..82) == "1",
-            _is_provisional: &(input.slice(82..83)) == "1",
-        }
+    fn parse(input: &str) -> Result<MasterRecord, FieldError> {
+        let input = Fields::new(input);
+        Ok(MasterRecord {
+            record_type: input.text(0..10)?.trim().to_string(),
+            record_id: input.number(10..17)?,
+            description: input.text(17..67)?.trim().to_string(),
+            _list_order: input.number(67..74)?,
+            contest_id: input.number(74..81)?,
+            is_writein: input.flag(81..82)?,
+            _is_provisional: input.flag(82..83)?,
+        })
     }
 }
 
@@ -49,29 +89,41 @@ struct BallotRecord {
     under_vote: bool,
 }
 
+/// Columns (1-based) of the ballot record fields that can be invalid after
+/// parsing.
+const VOTE_RANK_COLUMN: usize = 34;
+const CANDIDATE_ID_COLUMN: usize = 37;
+
 impl BallotRecord {
-    fn parse(input: &str) -> BallotRecord {
-        let input = UnicodeString::new(input);
-
-        BallotRecord {
-            contest_id: input.slice(0..7).parse().unwrap(),
-            pref_voter_id: input.slice(7..16).parse().unwrap(),
-            _serial_number: input.slice(16..23).parse().unwrap(),
-            _tally_type_id: input.slice(23..26).parse().unwrap(),
-            _precinct_id: input.slice(26..33).parse().unwrap(),
-            vote_rank: input.slice(33..36).parse().unwrap(),
-            candidate_id: input.slice(36..43).parse().unwrap(),
-            over_vote: &input.slice(43..44) == "1",
-            under_vote: &input.slice(44..45) == "1",
-        }
+    fn parse(input: &str) -> Result<BallotRecord, FieldError> {
+        let input = Fields::new(input);
+
+        Ok(BallotRecord {
+            contest_id: input.number(0..7)?,
+            pref_voter_id: input.number(7..16)?,
+            _serial_number: input.number(16..23)?,
+            _tally_type_id: input.number(23..26)?,
+            _precinct_id: input.number(26..33)?,
+            vote_rank: input.number(33..36)?,
+            candidate_id: input.number(36..43)?,
+            over_vote: input.flag(43..44)?,
+            under_vote: input.flag(44..45)?,
+        })
     }
 }
 
-fn read_candidates(reader: &mut dyn BufRead, contest_id: u32) -> CandidateMap<u32> {
+fn read_candidates(
+    reader: &mut dyn BufRead,
+    contest_id: u32,
+    file: &Path,
+) -> Result<CandidateMap<u32>, FormatError> {
     let mut candidates = CandidateMap::new();
-    for line in reader.lines() {
-        let line = line.unwrap();
-        let record = MasterRecord::parse(&line);
+    for (i, line) in reader.lines().enumerate() {
+        let line = line.map_err(|source| FormatError::Io {
+            file: file.to_path_buf(),
+            source,
+        })?;
+        let record = MasterRecord::parse(&line).map_err(|err| err.at(file, None, i + 1))?;
 
         if record.record_type == CANDIDATE {
             if record.contest_id != contest_id {
@@ -94,42 +146,70 @@ fn read_candidates(reader: &mut dyn BufRead, contest_id: u32) -> CandidateMap<u3
             candidates.add(record.record_id, candidate);
         }
     }
-    candidates
+    Ok(candidates)
 }
 
+/// Read the ballot records for `contest`. Each ballot's records must be on
+/// consecutive lines, in rank order.
 fn read_ballots(
     reader: &mut dyn BufRead,
     candidates: &CandidateMap<u32>,
     contest: u32,
-) -> Vec<Ballot> {
+    file: &Path,
+) -> Result<Vec<Ballot>, FormatError> {
     let mut ballots = Vec::new();
+    let mut current: Option<(u32, Vec<Choice>)> = None;
 
-    for (id, votes) in reader
-        .lines()
-        .into_iter()
-        .map(|v| BallotRecord::parse(&v.unwrap()))
-        .filter(|v| v.contest_id == contest)
-        .group_by(|v| v.pref_voter_id)
-        .into_iter()
-    {
-        let mut choices = Vec::new();
-
-        for (i, ballot_record) in votes.enumerate() {
-            if ballot_record.vote_rank != (i + 1) as u32 {
-                panic!("Got record out of order.")
-            }
-            if ballot_record.over_vote {
-                choices.push(Choice::Overvote)
-            } else if ballot_record.under_vote {
-                choices.push(Choice::Undervote)
-            } else {
-                choices.push(candidates.id_to_choice(ballot_record.candidate_id))
+    for (i, line) in reader.lines().enumerate() {
+        let line = line.map_err(|source| FormatError::Io {
+            file: file.to_path_buf(),
+            source,
+        })?;
+        let record = BallotRecord::parse(&line).map_err(|err| err.at(file, None, i + 1))?;
+        if record.contest_id != contest {
+            continue;
+        }
+
+        if current.as_ref().map(|(id, _)| *id) != Some(record.pref_voter_id) {
+            if let Some((id, choices)) = current.take() {
+                ballots.push(Ballot::new(id.to_string(), choices));
             }
         }
+        let (_, choices) = current.get_or_insert((record.pref_voter_id, Vec::new()));
+
+        if record.vote_rank != choices.len() as u32 + 1 {
+            return Err(FieldError::new(
+                VOTE_RANK_COLUMN,
+                format!(
+                    "expected rank {}, found {} (records out of order)",
+                    choices.len() + 1,
+                    record.vote_rank
+                ),
+            )
+            .at(file, None, i + 1));
+        }
+        if record.over_vote {
+            choices.push(Choice::Overvote)
+        } else if record.under_vote {
+            choices.push(Choice::Undervote)
+        } else {
+            let choice = candidates
+                .id_to_choice(record.candidate_id)
+                .ok_or_else(|| {
+                    FieldError::new(
+                        CANDIDATE_ID_COLUMN,
+                        format!("unknown candidate id {}", record.candidate_id),
+                    )
+                    .at(file, None, i + 1)
+                })?;
+            choices.push(choice)
+        }
+    }
 
-        ballots.push(Ballot::new(id.to_string(), choices))
+    if let Some((id, choices)) = current {
+        ballots.push(Ballot::new(id.to_string(), choices));
     }
-    ballots
+    Ok(ballots)
 }
 
 struct ReaderOptions {
@@ -140,58 +220,133 @@ struct ReaderOptions {
 }
 
 impl ReaderOptions {
-    pub fn from_params(params: BTreeMap<String, String>) -> ReaderOptions {
-        let contest: u32 = params
-            .get("contest")
-            .expect("SFO elections should have a contest param.")
-            .parse()
-            .expect("contest param should be a number.");
-        let master_file = params
-            .get("masterLookup")
-            .expect("SFO elections should have masterLookup parameter.")
-            .clone();
-        let ballot_file = params
-            .get("ballotImage")
-            .expect("SFO elections should have ballotImage parameter.")
-            .clone();
+    pub fn from_params(params: BTreeMap<String, String>) -> Result<ReaderOptions, FormatError> {
+        let contest: u32 = parsed_param(&params, "contest")?
+            .ok_or_else(|| FormatError::missing_param("contest"))?;
+        let master_file = required_param(&params, "masterLookup")?.clone();
+        let ballot_file = required_param(&params, "ballotImage")?.clone();
         let zip_file = params.get("zipFile").cloned();
 
-        ReaderOptions {
+        Ok(ReaderOptions {
             contest,
             master_file,
             ballot_file,
             zip_file,
-        }
+        })
     }
 }
 
-pub fn sfo_ballot_reader(path: &Path, params: BTreeMap<String, String>) -> Election {
-    let options = ReaderOptions::from_params(params);
+pub fn sfo_ballot_reader(
+    path: &Path,
+    params: BTreeMap<String, String>,
+) -> Result<Election, FormatError> {
+    let options = ReaderOptions::from_params(params)?;
 
     let (candidates, ballots) = if let Some(zip_file) = options.zip_file {
-        let file = File::open(path.join(&zip_file)).unwrap();
-        let mut archive = zip::ZipArchive::new(file).unwrap();
+        let zip_path = path.join(&zip_file);
+        let mut archive = open_archive(&zip_path)?;
         let candidates = {
-            let master = archive.by_name(&options.master_file).unwrap();
+            let master = archive_entry(&mut archive, &zip_path, &options.master_file)?;
             let mut master_reader = BufReader::new(master);
-            read_candidates(&mut master_reader, options.contest)
+            read_candidates(
+                &mut master_reader,
+                options.contest,
+                &zip_path.join(&options.master_file),
+            )?
         };
 
         let ballots = {
-            let ballots = archive.by_name(&options.ballot_file).unwrap();
+            let ballots = archive_entry(&mut archive, &zip_path, &options.ballot_file)?;
             let mut ballot_reader = BufReader::new(ballots);
-            read_ballots(&mut ballot_reader, &candidates, options.contest)
+            read_ballots(
+                &mut ballot_reader,
+                &candidates,
+                options.contest,
+                &zip_path.join(&options.ballot_file),
+            )?
         };
 
         (candidates, ballots)
     } else {
-        let mut master_reader = BufReader::new(File::open(path.join(options.master_file)).unwrap());
-        let candidates = read_candidates(&mut master_reader, options.contest);
+        let master_path = path.join(options.master_file);
+        let mut master_reader = BufReader::new(open_file(&master_path)?);
+        let candidates = read_candidates(&mut master_reader, options.contest, &master_path)?;
 
-        let mut ballot_reader = BufReader::new(File::open(path.join(options.ballot_file)).unwrap());
-        let ballots = read_ballots(&mut ballot_reader, &candidates, options.contest);
+        let ballot_path = path.join(options.ballot_file);
+        let mut ballot_reader = BufReader::new(open_file(&ballot_path)?);
+        let ballots = read_ballots(
+            &mut ballot_reader,
+            &candidates,
+            options.contest,
+            &ballot_path,
+        )?;
         (candidates, ballots)
     };
 
-    Election::new(candidates.into_vec(), ballots)
+    Ok(Election::new(candidates.into_vec(), ballots))
+}
+
+fn open_file(file: &Path) -> Result<File, FormatError> {
+    File::open(file).map_err(|source| FormatError::Io {
+        file: file.to_path_buf(),
+        source,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::election::CandidateId;
+
+    #[test]
+    fn test_read_ballots() {
+        let mut candidates = CandidateMap::new();
+        candidates.add(5, Candidate::new("A".into(), CandidateType::Regular));
+        let file = Path::new("BallotImage.txt");
+        let line = |voter: u32, rank: u32, candidate: u32| {
+            format!(
+                "{:07}{:09}{:07}{:03}{:07}{:03}{:07}{}",
+                1,
+                voter,
+                1,
+                1,
+                1,
+                rank,
+                candidate,
+                if candidate == 0 { "01" } else { "00" }
+            )
+        };
+
+        let text = [line(10, 1, 5), line(10, 2, 0), line(11, 1, 5)].join("\n");
+        let ballots = read_ballots(&mut text.as_bytes(), &candidates, 1, file).unwrap();
+        assert_eq!(2, ballots.len());
+        assert_eq!(
+            vec![Choice::Vote(CandidateId(0)), Choice::Undervote],
+            ballots[0].choices
+        );
+
+        let text = [line(10, 1, 5), line(10, 3, 5)].join("\n");
+        assert_eq!(
+            "BallotImage.txt: row 2, column 34: expected rank 2, found 3 (records out of order)",
+            read_ballots(&mut text.as_bytes(), &candidates, 1, file)
+                .unwrap_err()
+                .to_string()
+        );
+
+        let text = line(10, 1, 6);
+        assert_eq!(
+            "BallotImage.txt: row 1, column 37: unknown candidate id 6",
+            read_ballots(&mut text.as_bytes(), &candidates, 1, file)
+                .unwrap_err()
+                .to_string()
+        );
+
+        let text = "0000001000";
+        assert_eq!(
+            "BallotImage.txt: row 1, column 8: line ends before column 16",
+            read_ballots(&mut text.as_bytes(), &candidates, 1, file)
+                .unwrap_err()
+                .to_string()
+        );
+    }
 }
@@ -0,0 +1,150 @@
+use crate::formats::common::{
+    normalize_name, read_tables, required_param, CandidateMap, SheetOptions,
+};
+use crate::formats::FormatError;
+use crate::model::election::{Ballot, Candidate, CandidateType, Choice, Election};
+use lazy_static::lazy_static;
+use regex::Regex;
+use std::collections::BTreeMap;
+use std::path::Path;
+use tracing::info;
+
+struct ReaderOptions {
+    files: Vec<String>,
+    sheets: SheetOptions,
+}
+
+impl ReaderOptions {
+    pub fn from_params(params: BTreeMap<String, String>) -> Result<ReaderOptions, FormatError> {
+        let files: Vec<String> = required_param(&params, "files")?
+            .split(';')
+            .map(|x| x.to_string())
+            .collect();
+
+        Ok(ReaderOptions {
+            files,
+            sheets: SheetOptions::from_params(&params)?,
+        })
+    }
+}
+
+pub fn parse_choice(candidate: &str, candidate_map: &mut CandidateMap<String>) -> Choice {
+    let candidate = candidate.trim();
+    if candidate.is_empty() || candidate.eq_ignore_ascii_case("undervote") {
+        Choice::Undervote
+    } else if candidate.eq_ignore_ascii_case("overvote") {
+        Choice::Overvote
+    } else if candidate.to_lowercase().starts_with("write-in") {
+        candidate_map.add_id_to_choice(
+            candidate.to_string(),
+            Candidate::new(candidate.to_string(), CandidateType::WriteIn),
+        )
+    } else {
+        candidate_map.add_id_to_choice(
+            candidate.to_string(),
+            Candidate::new(normalize_name(candidate, true), CandidateType::Regular),
+        )
+    }
+}
+
+/// Reader for Cambridge, MA ballot data exported as one row per ballot (XLSX
+/// or CSV), with an `ID` column, an optional `Precinct` column, and rank
+/// columns headed `1`, `2`, ... (or `Rank 1`, `Rank 2`, ...). Ballots are
+/// kept in file order, which matters for Cincinnati-method surplus transfers.
+pub fn cambridge_ballot_reader(
+    path: &Path,
+    params: BTreeMap<String, String>,
+) -> Result<Election, FormatError> {
+    let options = ReaderOptions::from_params(params)?;
+    let mut ballots: Vec<Ballot> = Vec::new();
+    let mut candidate_map: CandidateMap<String> = CandidateMap::new();
+
+    lazy_static! {
+        static ref RANK_RX: Regex = Regex::new(r#"(?i)^(?:rank |choice )?(\d+)$"#).unwrap();
+    }
+
+    for file in options.files {
+        info!("Reading: {}", file);
+        let file_path = path.join(&file);
+        let tables = read_tables(&file_path, &options.sheets)?;
+        let multiple_sheets = tables.len() > 1;
+        for table in tables {
+            // Ballot ids name the sheet if more than one is read.
+            let source = match (&table.sheet, multiple_sheets) {
+                (Some(sheet), true) => format!("{}:{}", file, sheet),
+                _ => file.clone(),
+            };
+            let header = table.header;
+            let mut rank_to_col: BTreeMap<u32, usize> = BTreeMap::new();
+            let mut id_col: Option<usize> = None;
+            let mut precinct_col: Option<usize> = None;
+            for (i, colname) in header.iter().enumerate() {
+                let colname = colname.trim();
+                if let Some(caps) = RANK_RX.captures(colname) {
+                    rank_to_col.insert(caps.get(1).unwrap().as_str().parse().unwrap(), i);
+                } else if colname.eq_ignore_ascii_case("id")
+                    || colname.eq_ignore_ascii_case("ballot id")
+                {
+                    id_col = Some(i);
+                } else if colname.eq_ignore_ascii_case("precinct") {
+                    precinct_col = Some(i);
+                }
+            }
+            if rank_to_col.is_empty() {
+                return Err(FormatError::Header {
+                    file: file_path,
+                    message: match &table.sheet {
+                        Some(sheet) => format!("no rank columns in sheet {}", sheet),
+                        None => "no rank columns".to_string(),
+                    },
+                });
+            }
+
+            for (row_num, row) in table.rows.into_iter().enumerate() {
+                if row.iter().all(|c| c.trim().is_empty()) {
+                    continue;
+                }
+
+                let choices: Vec<Choice> = rank_to_col
+                    .values()
+                    .map(|col| parse_choice(row.get(*col).map_or("", |s| s), &mut candidate_map))
+                    .collect();
+                let id = match id_col {
+                    Some(col) => row.get(col).cloned().unwrap_or_default(),
+                    None => format!("{}:{}", source, row_num + 1),
+                };
+                let precinct = precinct_col
+                    .and_then(|col| row.get(col))
+                    .filter(|p| !p.trim().is_empty())
+                    .map(|p| format!("Precinct {}", p.trim()));
+
+                ballots.push(Ballot::new(id, choices).with_sub_jurisdiction(precinct));
+            }
+        }
+    }
+
+    Ok(Election::new(candidate_map.into_vec(), ballots))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_choice() {
+        let mut candidate_map = CandidateMap::new();
+
+        assert_eq!(Choice::Undervote, parse_choice("", &mut candidate_map));
+        assert_eq!(
+            Choice::Overvote,
+            parse_choice("OVERVOTE", &mut candidate_map)
+        );
+        let a = parse_choice("Siddiqui, Sumbul", &mut candidate_map);
+        assert_eq!(a, parse_choice("Siddiqui, Sumbul", &mut candidate_map));
+        parse_choice("Write-in 1", &mut candidate_map);
+
+        let candidates = candidate_map.into_vec();
+        assert_eq!("Sumbul Siddiqui", candidates[0].name);
+        assert_eq!(CandidateType::WriteIn, candidates[1].candidate_type);
+    }
+}
@@ -1,25 +1,31 @@
-use crate::formats::common::{normalize_name, CandidateMap};
+use crate::formats::common::{
+    normalize_name, read_sheets, required_param, CandidateMap, FieldError, SheetOptions,
+};
+use crate::formats::FormatError;
 use crate::model::election::{Ballot, Candidate, CandidateType, Choice, Election};
-use calamine::{open_workbook_auto, DataType, Reader};
+use calamine::DataType;
 use lazy_static::lazy_static;
 use regex::Regex;
 use std::collections::BTreeMap;
 use std::path::Path;
+use tracing::{info, warn};
 
 struct ReaderOptions {
     files: Vec<String>,
+    sheets: SheetOptions,
 }
 
 impl ReaderOptions {
-    pub fn from_params(params: BTreeMap<String, String>) -> ReaderOptions {
-        let files: Vec<String> = params
-            .get("files")
-            .unwrap()
+    pub fn from_params(params: BTreeMap<String, String>) -> Result<ReaderOptions, FormatError> {
+        let files: Vec<String> = required_param(&params, "files")?
             .split(';')
             .map(|x| x.to_string())
             .collect();
 
-        ReaderOptions { files }
+        Ok(ReaderOptions {
+            files,
+            sheets: SheetOptions::from_params(&params)?,
+        })
     }
 }
 
@@ -36,7 +42,7 @@ pub fn parse_choice(candidate: &str, candidate_map: &mut CandidateMap<String>) -
         let candidate = if let Some(c) = CANDIDATE_RX.captures(candidate) {
             c.get(1).unwrap().as_str()
         } else {
-            eprintln!("not matched: {}", candidate);
+            warn!("not matched: {}", candidate);
             candidate
         };
 
@@ -47,37 +53,73 @@ pub fn parse_choice(candidate: &str, candidate_map: &mut CandidateMap<String>) -
     }
 }
 
-pub fn read_ballot(row: &[DataType], candidate_map: &mut CandidateMap<String>) -> Ballot {
-    let id = row.get(0).unwrap().get_float().unwrap() as u32;
+/// Read a ballot row: an id in the first column, and choices from the fourth.
+pub fn read_ballot(
+    row: &[DataType],
+    candidate_map: &mut CandidateMap<String>,
+) -> Result<Ballot, FieldError> {
+    let id = row
+        .first()
+        .and_then(|cell| cell.get_float())
+        .ok_or_else(|| {
+            FieldError::new(1, format!("expected a ballot id, found {:?}", row.first()))
+        })? as u32;
 
     let mut choices = Vec::new();
-    for vote in &row[3..] {
-        let cand = vote.get_string().unwrap();
+    for (i, vote) in row.iter().enumerate().skip(3) {
+        let cand = vote.get_string().ok_or_else(|| {
+            FieldError::new(i + 1, format!("expected a candidate, found {:?}", vote))
+        })?;
         let choice = parse_choice(cand, candidate_map);
         choices.push(choice);
     }
 
-    Ballot::new(id.to_string(), choices)
+    Ok(Ballot::new(id.to_string(), choices))
 }
 
-pub fn maine_ballot_reader(path: &Path, params: BTreeMap<String, String>) -> Election {
-    let options = ReaderOptions::from_params(params);
+pub fn maine_ballot_reader(
+    path: &Path,
+    params: BTreeMap<String, String>,
+) -> Result<Election, FormatError> {
+    let options = ReaderOptions::from_params(params)?;
     let mut ballots: Vec<Ballot> = Vec::new();
     let mut candidate_map: CandidateMap<String> = CandidateMap::new();
 
+    let header_row = options.sheets.header_row;
     for file in options.files {
-        eprintln!("Reading: {}", file);
-        let mut workbook = open_workbook_auto(path.join(file)).unwrap();
-        let first_sheet = workbook.sheet_names().first().unwrap().clone();
-        let sheet = workbook.worksheet_range(&first_sheet).unwrap().unwrap();
-
-        let mut rows = sheet.rows();
-        rows.next();
-        for row in rows {
-            let ballot = read_ballot(row, &mut candidate_map);
-            ballots.push(ballot);
+        info!("Reading: {}", file);
+        let file_path = path.join(file);
+        for (name, sheet) in read_sheets(&file_path, &options.sheets.sheets)? {
+            for (i, row) in sheet.rows().skip(header_row).enumerate() {
+                let ballot = read_ballot(row, &mut candidate_map)
+                    .map_err(|err| err.at(&file_path, Some(&name), header_row + i + 1))?;
+                ballots.push(ballot);
+            }
         }
     }
 
-    Election::new(candidate_map.into_vec(), ballots)
+    Ok(Election::new(candidate_map.into_vec(), ballots))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_read_ballot() {
+        let mut candidate_map = CandidateMap::new();
+        let row = vec![
+            DataType::Float(7.),
+            DataType::Empty,
+            DataType::Empty,
+            DataType::String("overvote".into()),
+            DataType::String("undervote".into()),
+        ];
+        let ballot = read_ballot(&row, &mut candidate_map).unwrap();
+        assert_eq!("7", ballot.id);
+        assert_eq!(vec![Choice::Overvote, Choice::Undervote], ballot.choices);
+
+        let row = vec![DataType::String("id".into())];
+        assert_eq!(1, read_ballot(&row, &mut candidate_map).unwrap_err().column);
+    }
 }
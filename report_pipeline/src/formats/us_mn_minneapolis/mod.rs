@@ -0,0 +1,196 @@
+use crate::formats::common::{
+    normalize_name, read_tables, required_param, CandidateMap, RowIssues, SheetOptions,
+};
+use crate::formats::FormatError;
+use crate::model::election::{Ballot, Candidate, CandidateType, Choice, Election};
+use lazy_static::lazy_static;
+use regex::Regex;
+use std::collections::BTreeMap;
+use std::path::Path;
+use tracing::info;
+
+struct ReaderOptions {
+    files: Vec<String>,
+    sheets: SheetOptions,
+}
+
+impl ReaderOptions {
+    pub fn from_params(params: BTreeMap<String, String>) -> Result<ReaderOptions, FormatError> {
+        let files: Vec<String> = required_param(&params, "files")?
+            .split(';')
+            .map(|x| x.to_string())
+            .collect();
+
+        Ok(ReaderOptions {
+            files,
+            sheets: SheetOptions::from_params(&params)?,
+        })
+    }
+}
+
+pub fn parse_choice(candidate: &str, candidate_map: &mut CandidateMap<String>) -> Choice {
+    match candidate.trim() {
+        "" => Choice::Undervote,
+        c if c.eq_ignore_ascii_case("undervote") => Choice::Undervote,
+        c if c.eq_ignore_ascii_case("overvote") => Choice::Overvote,
+        // Undeclared write-in.
+        "UWI" => candidate_map.add_id_to_choice(
+            "UWI".to_string(),
+            Candidate::new("Write-in".to_string(), CandidateType::WriteIn),
+        ),
+        c => candidate_map.add_id_to_choice(
+            c.to_string(),
+            Candidate::new(normalize_name(c, false), CandidateType::Regular),
+        ),
+    }
+}
+
+/// Return the ward of a precinct name such as `MINNEAPOLIS W-3 P-07`.
+pub fn ward_for_precinct(precinct: &str) -> Option<String> {
+    lazy_static! {
+        static ref WARD_RX: Regex = Regex::new(r#"\bW-0*(\d+)\b"#).unwrap();
+    }
+
+    WARD_RX
+        .captures(precinct)
+        .map(|caps| format!("Ward {}", caps.get(1).unwrap().as_str()))
+}
+
+/// Reader for the Minneapolis cast vote record spreadsheets (XLSX or CSV),
+/// which have a `Precinct` column followed by `1st Choice`, `2nd Choice`, ...
+/// columns, and optionally a `Count` column giving the number of identical
+/// ballots a row represents.
+pub fn minneapolis_ballot_reader(
+    path: &Path,
+    params: BTreeMap<String, String>,
+) -> Result<Election, FormatError> {
+    let mut row_issues = RowIssues::from_params(&params);
+    let options = ReaderOptions::from_params(params)?;
+    let mut ballots: Vec<Ballot> = Vec::new();
+    let mut candidate_map: CandidateMap<String> = CandidateMap::new();
+
+    lazy_static! {
+        static ref RANK_RX: Regex = Regex::new(r#"(?i)^(\d+)(?:st|nd|rd|th) choice$"#).unwrap();
+    }
+
+    for file in options.files {
+        info!("Reading: {}", file);
+        let file_path = path.join(&file);
+        let tables = read_tables(&file_path, &options.sheets)?;
+        let multiple_sheets = tables.len() > 1;
+        for table in tables {
+            // Ballot ids name the sheet if more than one is read.
+            let source = match (&table.sheet, multiple_sheets) {
+                (Some(sheet), true) => format!("{}:{}", file, sheet),
+                _ => file.clone(),
+            };
+            let header = table.header;
+            let mut rank_to_col: BTreeMap<u32, usize> = BTreeMap::new();
+            let mut precinct_col: Option<usize> = None;
+            let mut count_col: Option<usize> = None;
+            for (i, colname) in header.iter().enumerate() {
+                let colname = colname.trim();
+                if let Some(caps) = RANK_RX.captures(colname) {
+                    rank_to_col.insert(caps.get(1).unwrap().as_str().parse().unwrap(), i);
+                } else if colname.eq_ignore_ascii_case("precinct") {
+                    precinct_col = Some(i);
+                } else if colname.eq_ignore_ascii_case("count") {
+                    count_col = Some(i);
+                }
+            }
+            if rank_to_col.is_empty() {
+                return Err(FormatError::Header {
+                    file: file_path,
+                    message: match &table.sheet {
+                        Some(sheet) => format!("no choice columns in sheet {}", sheet),
+                        None => "no choice columns".to_string(),
+                    },
+                });
+            }
+
+            for (row_num, row) in table.rows.into_iter().enumerate() {
+                if row.iter().all(|c| c.trim().is_empty()) {
+                    continue;
+                }
+
+                let count: u32 = match count_col {
+                    Some(col) => {
+                        let value = row.get(col).map_or("", |s| s.trim());
+                        match value.parse() {
+                            Ok(count) => count,
+                            Err(_) => {
+                                row_issues.skip_row(FormatError::Cell {
+                                    file: file_path.clone(),
+                                    sheet: table.sheet.clone(),
+                                    row: table.first_row + row_num,
+                                    column: col + 1,
+                                    message: format!("invalid ballot count {:?}", value),
+                                })?;
+                                continue;
+                            }
+                        }
+                    }
+                    None => 1,
+                };
+                let choices: Vec<Choice> = rank_to_col
+                    .values()
+                    .map(|col| parse_choice(row.get(*col).map_or("", |s| s), &mut candidate_map))
+                    .collect();
+                let ward = precinct_col
+                    .and_then(|col| row.get(col))
+                    .and_then(|p| ward_for_precinct(p));
+
+                for i in 0..count {
+                    let id = if count_col.is_some() {
+                        format!("{}:{}:{}", source, row_num + 1, i)
+                    } else {
+                        format!("{}:{}", source, row_num + 1)
+                    };
+                    ballots
+                        .push(Ballot::new(id, choices.clone()).with_sub_jurisdiction(ward.clone()));
+                }
+            }
+        }
+    }
+
+    Ok(Election::new(candidate_map.into_vec(), ballots).with_parse_issues(row_issues.into_vec()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ward_for_precinct() {
+        assert_eq!(
+            Some("Ward 3".to_string()),
+            ward_for_precinct("MINNEAPOLIS W-3 P-07")
+        );
+        assert_eq!(
+            Some("Ward 10".to_string()),
+            ward_for_precinct("MINNEAPOLIS W-10 P-01")
+        );
+        assert_eq!(None, ward_for_precinct("ABSENTEE"));
+    }
+
+    #[test]
+    fn test_parse_choice() {
+        let mut candidate_map = CandidateMap::new();
+
+        assert_eq!(
+            Choice::Undervote,
+            parse_choice("undervote", &mut candidate_map)
+        );
+        assert_eq!(
+            Choice::Overvote,
+            parse_choice("overvote", &mut candidate_map)
+        );
+        assert_eq!(Choice::Undervote, parse_choice(" ", &mut candidate_map));
+        let a = parse_choice("Jacob Frey", &mut candidate_map);
+        assert_eq!(a, parse_choice("Jacob Frey", &mut candidate_map));
+        parse_choice("UWI", &mut candidate_map);
+
+        let candidates = candidate_map.into_vec();
+        assert_eq!(CandidateType::WriteIn, candidates[1].candidate_type);
+    }
+}
@@ -1,138 +1,604 @@
-use crate::formats::common::CandidateMap;
+use crate::formats::common::{
+    for_each_workbook, read_first_sheet, required_param, CandidateMap, RowIssues, SheetOptions,
+};
+use crate::formats::FormatError;
 use crate::model::election::{Ballot, Candidate, CandidateType, Choice, Election};
-use calamine::{open_workbook_auto, Reader, Sheets};
+use crate::util::progress_bar;
+use calamine::{DataType, Range};
 use lazy_static::lazy_static;
 use regex::Regex;
 use std::collections::{BTreeMap, HashMap};
 use std::fs::read_dir;
 use std::path::Path;
+use tracing::info;
+
+/// Number of rankings NYC ballots have allowed so far.
+const DEFAULT_MAX_RANKS: u32 = 5;
+
+/// Number of CVR workbooks decoded at once by default. Each decoded
+/// workbook is held in memory until its rows are read.
+const DEFAULT_READER_THREADS: usize = 4;
 
 struct ReaderOptions {
     office_name: String,
+    /// Other office names in rank column headers that refer to this contest,
+    /// e.g. where supplemental drops spell the office differently.
+    office_name_rx: Option<Regex>,
     jurisdiction_name: String,
     candidates_file: String,
     cvr_pattern: String,
+    /// Rank columns beyond this rank are ignored.
+    max_ranks: u32,
+    sheets: SheetOptions,
+    /// Number of CVR workbooks to decode at once.
+    threads: usize,
 }
 
 impl ReaderOptions {
-    pub fn from_params(params: BTreeMap<String, String>) -> ReaderOptions {
-        let office_name: String = params.get("officeName").unwrap().clone();
-
-        let jurisdiction_name: String = params.get("jurisdictionName").unwrap().clone();
+    pub fn from_params(params: BTreeMap<String, String>) -> Result<ReaderOptions, FormatError> {
+        Ok(ReaderOptions {
+            office_name: required_param(&params, "officeName")?.clone(),
+            office_name_rx: match params.get("officeNamePattern") {
+                Some(pattern) => {
+                    Some(Regex::new(&format!("^(?:{})$", pattern)).map_err(|err| {
+                        FormatError::Param {
+                            name: "officeNamePattern".to_string(),
+                            message: format!("is not a valid regex: {}", err),
+                        }
+                    })?)
+                }
+                None => None,
+            },
+            jurisdiction_name: required_param(&params, "jurisdictionName")?.clone(),
+            candidates_file: required_param(&params, "candidatesFile")?.clone(),
+            cvr_pattern: required_param(&params, "cvrPattern")?.clone(),
+            max_ranks: match params.get("maxRanks") {
+                Some(max) => max.parse().map_err(|_| FormatError::Param {
+                    name: "maxRanks".to_string(),
+                    message: format!("should be a positive number, got {:?}", max),
+                })?,
+                None => DEFAULT_MAX_RANKS,
+            },
+            sheets: SheetOptions::from_params(&params)?,
+            threads: match params.get("readerThreads") {
+                Some(threads) => threads.parse().map_err(|_| FormatError::Param {
+                    name: "readerThreads".to_string(),
+                    message: format!("should be a positive number, got {:?}", threads),
+                })?,
+                None => DEFAULT_READER_THREADS,
+            },
+        })
+    }
 
-        let candidates_file: String = params.get("candidatesFile").unwrap().clone();
+    /// True if a rank column header's office name refers to this contest.
+    fn matches_office(&self, office: &str) -> bool {
+        office == self.office_name
+            || self
+                .office_name_rx
+                .as_ref()
+                .is_some_and(|rx| rx.is_match(office))
+    }
+}
 
-        let cvr_pattern: String = params.get("cvrPattern").unwrap().clone();
+/// A rank column header such as `DEM Mayor Choice 1 of 5 Citywide (026916)`.
+struct RankColumn<'a> {
+    office: &'a str,
+    rank: u32,
+    jurisdiction: &'a str,
+}
 
-        ReaderOptions {
-            office_name,
-            candidates_file,
-            jurisdiction_name,
-            cvr_pattern,
-        }
+fn parse_rank_column(colname: &str) -> Option<RankColumn<'_>> {
+    lazy_static! {
+        static ref COLUMN_RX: Regex =
+            Regex::new(r#"(.+) Choice (\d+) of (\d+) (.+) \((\d+)\)"#).unwrap();
     }
+
+    let caps = COLUMN_RX.captures(colname)?;
+    Some(RankColumn {
+        office: caps.get(1)?.as_str(),
+        rank: caps.get(2)?.as_str().parse().ok()?,
+        jurisdiction: caps.get(4)?.as_str(),
+    })
 }
 
-pub fn read_candidate_ids(workbook: &mut Sheets) -> HashMap<u32, String> {
+pub fn read_candidate_ids(file: &Path) -> Result<HashMap<u32, String>, FormatError> {
     let mut candidates = HashMap::new();
-    let first_sheet = workbook.sheet_names().first().unwrap().clone();
-    let sheet = workbook.worksheet_range(&first_sheet).unwrap().unwrap();
+    let (sheet_name, sheet) = read_first_sheet(file)?;
+    let cell_error = |row: usize, column: usize, message: &str| FormatError::Cell {
+        file: file.to_path_buf(),
+        sheet: Some(sheet_name.clone()),
+        row: row + 1,
+        column: column + 1,
+        message: message.to_string(),
+    };
 
-    let mut rows = sheet.rows();
-    rows.next();
-    for row in rows {
-        let id = row.get(0).unwrap().get_float().unwrap() as u32;
-        let name = row.get(1).unwrap().get_string().unwrap();
+    for (i, row) in sheet.rows().enumerate().skip(1) {
+        let id = row
+            .first()
+            .and_then(|c| c.get_float())
+            .ok_or_else(|| cell_error(i, 0, "expected a numeric candidate id"))?
+            as u32;
+        let name = row
+            .get(1)
+            .and_then(|c| c.get_string())
+            .ok_or_else(|| cell_error(i, 1, "expected a candidate name"))?;
 
         candidates.insert(id, name.to_string());
     }
 
-    candidates
+    Ok(candidates)
 }
 
-pub fn nyc_ballot_reader(path: &Path, params: BTreeMap<String, String>) -> Election {
-    let options = ReaderOptions::from_params(params);
-    let mut ballots: Vec<Ballot> = Vec::new();
-    let mut candidate_ids: CandidateMap<u32> = CandidateMap::new();
-    let mut candidates_workbook = open_workbook_auto(path.join(options.candidates_file)).unwrap();
+/// A cell of a ranking column, before write-ins and candidates are assigned
+/// ids.
+enum Ranking {
+    Choice(Choice),
+    Candidate(u32, Candidate),
+}
 
-    let candidates = read_candidate_ids(&mut candidates_workbook);
+fn parse_ranking(value: &str, candidates: &HashMap<u32, String>) -> Result<Ranking, String> {
+    match value {
+        "undervote" => Ok(Ranking::Choice(Choice::Undervote)),
+        "overvote" => Ok(Ranking::Choice(Choice::Overvote)),
+        "Write-in" => Ok(Ranking::Candidate(
+            0,
+            Candidate::new("Write-in".to_string(), CandidateType::WriteIn),
+        )),
+        _ => {
+            let ext_id: u32 = value
+                .parse()
+                .map_err(|_| format!("invalid candidate id {:?}", value))?;
+            let name = candidates
+                .get(&ext_id)
+                .ok_or_else(|| format!("unknown candidate id {}", ext_id))?;
+            Ok(Ranking::Candidate(
+                ext_id,
+                Candidate::new(name.clone(), CandidateType::Regular),
+            ))
+        }
+    }
+}
 
+/// Return the borough containing a precinct given as `AD: <assembly district> ED: <election district>`.
+pub fn borough_for_precinct(precinct: &str) -> Option<&'static str> {
     lazy_static! {
-        static ref COLUMN_RX: Regex =
-            Regex::new(r#"(.+) Choice ([1-5]) of ([1-5]) (.+) \((\d+)\)"#).unwrap();
+        static ref PRECINCT_RX: Regex = Regex::new(r#"^AD: *(\d+)"#).unwrap();
     }
 
-    let file_rx = Regex::new(&format!("^{}$", options.cvr_pattern)).unwrap();
+    let assembly_district: u32 = PRECINCT_RX
+        .captures(precinct)?
+        .get(1)?
+        .as_str()
+        .parse()
+        .ok()?;
 
-    for file in read_dir(path).unwrap() {
-        if !file_rx.is_match(file.as_ref().unwrap().file_name().to_str().unwrap()) {
-            eprintln!("Skipping: {:?}", file);
-            continue;
-        }
+    match assembly_district {
+        23..=40 => Some("Queens"),
+        41..=60 => Some("Brooklyn"),
+        61..=64 => Some("Staten Island"),
+        65..=76 => Some("Manhattan"),
+        77..=87 => Some("Bronx"),
+        _ => None,
+    }
+}
 
-        eprintln!("Reading: {:?}", file);
-        let mut workbook = open_workbook_auto(file.unwrap().path()).unwrap();
-        let first_sheet = workbook.sheet_names().first().unwrap().clone();
-        let sheet = workbook.worksheet_range(&first_sheet).unwrap().unwrap();
+/// The ballots read so far for one contest.
+struct ContestReader {
+    options: ReaderOptions,
+    file_rx: Regex,
+    candidates: HashMap<u32, String>,
+    row_issues: RowIssues,
+    ballots: Vec<Ballot>,
+    candidate_ids: CandidateMap<u32>,
+}
 
-        let mut rows = sheet.rows();
-        let first_row = rows.next().unwrap();
+impl ContestReader {
+    /// Set up a reader for one contest. Candidate files are read once and
+    /// shared between contests through `candidate_files`.
+    fn new(
+        path: &Path,
+        params: BTreeMap<String, String>,
+        candidate_files: &mut HashMap<String, HashMap<u32, String>>,
+    ) -> Result<ContestReader, FormatError> {
+        let row_issues = RowIssues::from_params(&params);
+        let options = ReaderOptions::from_params(params)?;
+
+        let candidates = match candidate_files.get(&options.candidates_file) {
+            Some(candidates) => candidates.clone(),
+            None => {
+                let candidates = read_candidate_ids(&path.join(&options.candidates_file))?;
+                candidate_files.insert(options.candidates_file.clone(), candidates.clone());
+                candidates
+            }
+        };
+
+        let file_rx = Regex::new(&format!("^{}$", options.cvr_pattern)).map_err(|err| {
+            FormatError::Param {
+                name: "cvrPattern".to_string(),
+                message: format!("is not a valid regex: {}", err),
+            }
+        })?;
+
+        Ok(ContestReader {
+            options,
+            file_rx,
+            candidates,
+            row_issues,
+            ballots: Vec::new(),
+            candidate_ids: CandidateMap::new(),
+        })
+    }
+
+    fn read_sheet(
+        &mut self,
+        file: &Path,
+        sheet_name: &str,
+        sheet: &Range<DataType>,
+    ) -> Result<(), FormatError> {
+        let cell_error = |row: usize, column: usize, message: String| FormatError::Cell {
+            file: file.to_path_buf(),
+            sheet: Some(sheet_name.to_string()),
+            row: row + 1,
+            column: column + 1,
+            message,
+        };
+
+        let header_row = self.options.sheets.header_row;
+        let mut rows = sheet.rows().enumerate().skip(header_row - 1);
+        let (_, first_row) = rows.next().ok_or_else(|| FormatError::Header {
+            file: file.to_path_buf(),
+            message: format!("sheet {} has no row {}", sheet_name, header_row),
+        })?;
 
         let mut rank_to_col: BTreeMap<u32, usize> = BTreeMap::new();
         let mut cvr_id_col: Option<usize> = None;
+        let mut precinct_col: Option<usize> = None;
 
         for (i, col) in first_row.iter().enumerate() {
-            let colname = col.get_string().unwrap();
+            let colname = col
+                .get_string()
+                .ok_or_else(|| cell_error(0, i, format!("expected a column name, got {}", col)))?;
             if colname == "Cast Vote Record" {
                 cvr_id_col = Some(i)
-            } else if let Some(caps) = COLUMN_RX.captures(colname) {
-                if caps.get(1).unwrap().as_str() != options.office_name {
+            } else if colname == "Precinct" {
+                precinct_col = Some(i)
+            } else if let Some(column) = parse_rank_column(colname) {
+                if !self.options.matches_office(column.office)
+                    || column.jurisdiction != self.options.jurisdiction_name
+                    || !(1..=self.options.max_ranks).contains(&column.rank)
+                {
                     continue;
                 }
-                if caps.get(4).unwrap().as_str() != options.jurisdiction_name {
+                if let Some(other) = rank_to_col.insert(column.rank, i) {
+                    return Err(FormatError::Header {
+                        file: file.to_path_buf(),
+                        message: format!(
+                            "columns {} and {} both hold choice {} of {}",
+                            other + 1,
+                            i + 1,
+                            column.rank,
+                            self.options.office_name
+                        ),
+                    });
+                }
+            }
+        }
+
+        let cvr_id_col = cvr_id_col.ok_or_else(|| FormatError::Header {
+            file: file.to_path_buf(),
+            message: "no Cast Vote Record column".to_string(),
+        })?;
+
+        for (row_num, row) in rows {
+            // Check every cell before adding candidates, so that skipped rows
+            // don't add candidates.
+            let parsed = row
+                .get(cvr_id_col)
+                .and_then(|c| c.get_string())
+                .ok_or_else(|| cell_error(row_num, cvr_id_col, "expected a ballot id".into()))
+                .and_then(|ballot_id| {
+                    let rankings = rank_to_col
+                        .values()
+                        .map(|col| {
+                            row.get(*col)
+                                .and_then(|c| c.get_string())
+                                .ok_or_else(|| "expected a choice".to_string())
+                                .and_then(|value| parse_ranking(value, &self.candidates))
+                                .map_err(|message| cell_error(row_num, *col, message))
+                        })
+                        .collect::<Result<Vec<Ranking>, FormatError>>()?;
+                    Ok((ballot_id, rankings))
+                });
+            let (ballot_id, rankings) = match parsed {
+                Ok(parsed) => parsed,
+                Err(err) => {
+                    self.row_issues.skip_row(err)?;
                     continue;
                 }
-                let rank: u32 = caps.get(2).unwrap().as_str().parse().unwrap();
-                assert!((1..=5).contains(&rank));
-                rank_to_col.insert(rank, i);
+            };
+
+            let votes: Vec<Choice> = rankings
+                .into_iter()
+                .map(|ranking| match ranking {
+                    Ranking::Choice(choice) => choice,
+                    Ranking::Candidate(ext_id, candidate) => {
+                        self.candidate_ids.add_id_to_choice(ext_id, candidate)
+                    }
+                })
+                .collect();
+
+            let borough = precinct_col
+                .and_then(|col| row.get(col))
+                .and_then(|cell| cell.get_string())
+                .and_then(borough_for_precinct)
+                .map(|b| b.to_string());
+
+            let ballot = Ballot::new(ballot_id.to_owned(), votes).with_sub_jurisdiction(borough);
+            self.ballots.push(ballot);
+        }
+
+        Ok(())
+    }
+
+    fn into_election(self) -> Election {
+        Election::new(self.candidate_ids.into_vec(), self.ballots)
+            .with_parse_issues(self.row_issues.into_vec())
+    }
+}
+
+/// The `sheet` and `headerRow` loader parameters of a contest.
+type SheetParams = (Option<String>, Option<String>);
+
+/// Read several contests from the same CVR workbooks, keyed by office. NYC
+/// workbooks hold the rankings for every contest on the ballot, so each
+/// workbook is decoded once and read for all of the contests whose
+/// `cvrPattern` matches it. Contests that select sheets differently (through
+/// `sheet` or `headerRow`) are read in separate passes.
+pub fn nyc_multi_contest_reader(
+    path: &Path,
+    contests: Vec<(String, BTreeMap<String, String>)>,
+) -> Result<BTreeMap<String, Election>, FormatError> {
+    let mut candidate_files = HashMap::new();
+    let mut passes: BTreeMap<SheetParams, Vec<(String, ContestReader)>> = BTreeMap::new();
+    for (office, params) in contests {
+        let pass = (
+            params.get("sheet").cloned(),
+            params.get("headerRow").cloned(),
+        );
+        let reader = ContestReader::new(path, params, &mut candidate_files)?;
+        passes.entry(pass).or_default().push((office, reader));
+    }
+
+    let io_error = |source| FormatError::Io {
+        file: path.to_path_buf(),
+        source,
+    };
+    let mut file_names = Vec::new();
+    for file in read_dir(path).map_err(io_error)? {
+        file_names.push(file.map_err(io_error)?.file_name());
+    }
+    file_names.sort();
+
+    let mut elections = BTreeMap::new();
+    for mut readers in passes.into_values() {
+        let mut files = Vec::new();
+        for file_name in &file_names {
+            let file_name = file_name.to_string_lossy();
+            if readers.iter().any(|(_, r)| r.file_rx.is_match(&file_name)) {
+                files.push(path.join(file_name.as_ref()));
+            } else {
+                info!("Skipping: {}", file_name);
             }
         }
 
-        for row in rows {
-            let mut votes: Vec<Choice> = Vec::new();
-            let ballot_id = row
-                .get(cvr_id_col.unwrap())
-                .expect("Getting column")
-                .get_string()
+        let threads = readers
+            .iter()
+            .map(|(_, r)| r.options.threads)
+            .max()
+            .ok_or_else(no_contests)?;
+        let selection = readers[0].1.options.sheets.sheets.clone();
+
+        let progress = progress_bar("CVR files", files.len() as u64);
+        for_each_workbook(&files, &selection, threads, |file, sheets| {
+            info!("Reading: {:?}", file);
+            let file_name = file.file_name().unwrap_or_default().to_string_lossy();
+            for (_, reader) in readers.iter_mut() {
+                if !reader.file_rx.is_match(&file_name) {
+                    continue;
+                }
+                for (sheet_name, sheet) in &sheets {
+                    reader.read_sheet(file, sheet_name, sheet)?;
+                }
+            }
+            progress.inc(1);
+            Ok(())
+        })?;
+        progress.finish_and_clear();
+
+        for (office, reader) in readers {
+            elections.insert(office, reader.into_election());
+        }
+    }
+
+    Ok(elections)
+}
+
+pub fn nyc_ballot_reader(
+    path: &Path,
+    params: BTreeMap<String, String>,
+) -> Result<Election, FormatError> {
+    let mut elections = nyc_multi_contest_reader(path, vec![(String::new(), params)])?;
+    elections.remove("").ok_or_else(no_contests)
+}
+
+fn no_contests() -> FormatError {
+    FormatError::Param {
+        name: "cvrPattern".to_string(),
+        message: "selected no contest to read".to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_rank_column() {
+        let column = parse_rank_column("DEM Mayor Choice 3 of 5 Citywide (026916)").unwrap();
+        assert_eq!("DEM Mayor", column.office);
+        assert_eq!(3, column.rank);
+        assert_eq!("Citywide", column.jurisdiction);
+
+        let column =
+            parse_rank_column("DEM Council Member Choice 10 of 10 1st Council District (1)")
                 .unwrap();
-            for col in rank_to_col.values() {
-                let value = row.get(*col).unwrap().get_string().unwrap();
-                let choice = if value == "undervote" {
-                    Choice::Undervote
-                } else if value == "overvote" {
-                    Choice::Overvote
-                } else if value == "Write-in" {
-                    candidate_ids.add_id_to_choice(
-                        0,
-                        Candidate::new("Write-in".to_string(), CandidateType::WriteIn),
-                    )
-                } else {
-                    let ext_id: u32 = value.parse().unwrap();
-                    let candidate_name = candidates.get(&ext_id).unwrap();
-                    candidate_ids.add_id_to_choice(
-                        ext_id,
-                        Candidate::new(candidate_name.clone(), CandidateType::Regular),
-                    )
-                };
-
-                votes.push(choice);
+        assert_eq!(10, column.rank);
+        assert_eq!("1st Council District", column.jurisdiction);
+
+        assert!(parse_rank_column("Cast Vote Record").is_none());
+    }
+
+    #[test]
+    fn test_contests_read_from_same_sheet() {
+        let rows = [
+            [
+                "Cast Vote Record",
+                "Precinct",
+                "DEM Mayor Choice 1 of 2 Citywide (1)",
+                "DEM Mayor Choice 2 of 2 Citywide (1)",
+                "DEM Comptroller Choice 1 of 1 Citywide (2)",
+            ],
+            ["1", "AD: 74 ED: 012", "11", "12", "21"],
+            ["2", "AD: 23 ED: 001", "12", "undervote", "Write-in"],
+        ];
+        let mut sheet = Range::new((0, 0), (2, 4));
+        for (r, row) in rows.iter().enumerate() {
+            for (c, value) in row.iter().enumerate() {
+                sheet.set_value((r as u32, c as u32), DataType::String(value.to_string()));
             }
+        }
 
-            let ballot = Ballot::new(ballot_id.to_owned(), votes);
-            ballots.push(ballot);
+        let mut candidate_files = HashMap::new();
+        let candidates = [(11, "Ann"), (12, "Bea"), (21, "Cal")];
+        candidate_files.insert(
+            "candidates.xlsx".to_string(),
+            candidates
+                .iter()
+                .map(|(id, n)| (*id, n.to_string()))
+                .collect(),
+        );
+        let mut reader = |office: &str| {
+            let mut params = BTreeMap::new();
+            params.insert("officeName".to_string(), office.to_string());
+            params.insert("jurisdictionName".to_string(), "Citywide".to_string());
+            params.insert("candidatesFile".to_string(), "candidates.xlsx".to_string());
+            params.insert("cvrPattern".to_string(), "cvr.*\\.xlsx".to_string());
+            ContestReader::new(Path::new("."), params, &mut candidate_files).unwrap()
+        };
+        let mut mayor = reader("DEM Mayor");
+        let mut comptroller = reader("DEM Comptroller");
+
+        for reader in [&mut mayor, &mut comptroller] {
+            reader
+                .read_sheet(Path::new("cvr1.xlsx"), "Sheet1", &sheet)
+                .unwrap();
         }
+
+        let mayor = mayor.into_election();
+        assert_eq!(2, mayor.ballots.len());
+        assert_eq!(2, mayor.ballots[0].choices.len());
+        assert_eq!(
+            Some("Manhattan".to_string()),
+            mayor.ballots[0].sub_jurisdiction
+        );
+        assert_eq!(2, mayor.candidates.len());
+
+        let comptroller = comptroller.into_election();
+        assert_eq!(1, comptroller.ballots[1].choices.len());
+        assert_eq!(2, comptroller.candidates.len());
+        assert_eq!(
+            CandidateType::WriteIn,
+            comptroller.candidates[1].candidate_type
+        );
+    }
+
+    #[test]
+    fn test_office_name_pattern() {
+        let sheet = |header: &str, ballot: &str| {
+            let rows = [["Cast Vote Record", header], [ballot, "11"]];
+            let mut sheet = Range::new((0, 0), (1, 1));
+            for (r, row) in rows.iter().enumerate() {
+                for (c, value) in row.iter().enumerate() {
+                    sheet.set_value((r as u32, c as u32), DataType::String(value.to_string()));
+                }
+            }
+            sheet
+        };
+
+        let mut candidate_files = HashMap::new();
+        candidate_files.insert(
+            "candidates.xlsx".to_string(),
+            vec![(11, "Ann".to_string())].into_iter().collect(),
+        );
+        let mut params = BTreeMap::new();
+        params.insert("officeName".to_string(), "DEM Council Member".to_string());
+        params.insert(
+            "officeNamePattern".to_string(),
+            "DEM (Council Member|Member of the City Council)".to_string(),
+        );
+        params.insert(
+            "jurisdictionName".to_string(),
+            "36th Council District".to_string(),
+        );
+        params.insert("candidatesFile".to_string(), "candidates.xlsx".to_string());
+        params.insert("cvrPattern".to_string(), "cvr.*\\.xlsx".to_string());
+        let mut reader = ContestReader::new(Path::new("."), params, &mut candidate_files).unwrap();
+
+        reader
+            .read_sheet(
+                Path::new("cvr1.xlsx"),
+                "Sheet1",
+                &sheet(
+                    "DEM Council Member Choice 1 of 5 36th Council District (1)",
+                    "1",
+                ),
+            )
+            .unwrap();
+        reader
+            .read_sheet(
+                Path::new("cvr2.xlsx"),
+                "Sheet1",
+                &sheet(
+                    "DEM Member of the City Council Choice 1 of 5 36th Council District (1)",
+                    "2",
+                ),
+            )
+            .unwrap();
+        reader
+            .read_sheet(
+                Path::new("cvr3.xlsx"),
+                "Sheet1",
+                &sheet(
+                    "DEM Council Member Choice 1 of 5 35th Council District (1)",
+                    "3",
+                ),
+            )
+            .unwrap();
+
+        let election = reader.into_election();
+        assert_eq!(3, election.ballots.len());
+        assert_eq!(1, election.ballots[0].choices.len());
+        assert_eq!(1, election.ballots[1].choices.len());
+        assert!(election.ballots[2].choices.is_empty());
+        assert_eq!(1, election.candidates.len());
     }
 
-    Election::new(candidate_ids.into_vec(), ballots)
+    #[test]
+    fn test_borough_for_precinct() {
+        assert_eq!(Some("Queens"), borough_for_precinct("AD: 23 ED: 001"));
+        assert_eq!(Some("Manhattan"), borough_for_precinct("AD: 74 ED: 012"));
+        assert_eq!(Some("Bronx"), borough_for_precinct("AD: 87 ED: 100"));
+        assert_eq!(None, borough_for_precinct("AD: 12 ED: 001"));
+        assert_eq!(None, borough_for_precinct("Absentee"));
+    }
 }
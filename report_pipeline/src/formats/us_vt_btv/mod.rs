@@ -1,7 +1,8 @@
+use crate::formats::common::{archive_entry, open_archive, required_param, FieldError};
+use crate::formats::FormatError;
 use crate::model::election::{Ballot, Candidate, CandidateId, CandidateType, Choice, Election};
-use regex::Regex;
+use regex::{Match, Regex};
 use std::collections::BTreeMap;
-use std::fs::File;
 use std::io::{BufRead, BufReader};
 use std::path::Path;
 
@@ -11,54 +12,59 @@ struct ReaderOptions {
 }
 
 impl ReaderOptions {
-    pub fn from_params(params: BTreeMap<String, String>) -> Self {
-        let ballots = params
-            .get("ballots")
-            .expect("BTV elections should have ballots parameter.")
-            .clone();
-        let archive = params
-            .get("archive")
-            .expect("BTV elections should have archive parameter.")
-            .clone();
-
-        ReaderOptions { ballots, archive }
+    pub fn from_params(params: BTreeMap<String, String>) -> Result<Self, FormatError> {
+        let ballots = required_param(&params, "ballots")?.clone();
+        let archive = required_param(&params, "archive")?.clone();
+
+        Ok(ReaderOptions { ballots, archive })
     }
 }
 
-pub fn parse_ballot(source: &str) -> Vec<Choice> {
+/// Parse a ballot's comma-separated ranks. `column` is the (1-based) column
+/// of `source` in its line, for errors.
+pub fn parse_ballot(source: &str, column: usize) -> Result<Vec<Choice>, FieldError> {
     if source.is_empty() {
-        return vec![];
+        return Ok(vec![]);
     }
 
-    let ranks = source.split(',');
     let mut choices = Vec::new();
+    let mut rank_column = column;
 
-    for rank in ranks {
+    for rank in source.split(',') {
+        let candidate_id = rank
+            .strip_prefix('C')
+            .and_then(|id| id.parse::<u32>().ok())
+            .filter(|id| *id > 0);
         let choice = if rank.contains('=') {
             Choice::Overvote
-        } else if let Some(candidate_id) = rank.strip_prefix('C') {
-            let candidate_id: u32 = candidate_id.parse().unwrap();
+        } else if let Some(candidate_id) = candidate_id {
             Choice::Vote(CandidateId(candidate_id - 1))
         } else {
-            panic!("Bad candidate list ({}).", rank)
+            return Err(FieldError::new(
+                rank_column,
+                format!("bad candidate {:?}", rank),
+            ));
         };
         choices.push(choice);
+        rank_column += rank.chars().count() + 1;
     }
 
-    choices
+    Ok(choices)
 }
 
-pub fn btv_ballot_reader(path: &Path, params: BTreeMap<String, String>) -> Election {
-    let options = ReaderOptions::from_params(params);
+pub fn btv_ballot_reader(
+    path: &Path,
+    params: BTreeMap<String, String>,
+) -> Result<Election, FormatError> {
+    let options = ReaderOptions::from_params(params)?;
 
-    let mut archive = {
-        let file = File::open(path.join(&options.archive)).unwrap();
-        zip::ZipArchive::new(file).unwrap()
-    };
+    let archive_path = path.join(&options.archive);
+    let mut archive = open_archive(&archive_path)?;
+    let file = archive_path.join(&options.ballots);
 
     let lines = {
-        let file = archive.by_name(&options.ballots).unwrap();
-        BufReader::new(file).lines()
+        let entry = archive_entry(&mut archive, &archive_path, &options.ballots)?;
+        BufReader::new(entry).lines()
     };
 
     let candidate_rx = Regex::new(r#".CANDIDATE C(\d+), "(.+)""#).unwrap();
@@ -67,29 +73,41 @@ pub fn btv_ballot_reader(path: &Path, params: BTreeMap<String, String>) -> Elect
     let mut candidates: Vec<Candidate> = Vec::new();
     let mut ballots: Vec<Ballot> = Vec::new();
 
-    for line in lines {
-        let line = line.unwrap();
+    for (i, line) in lines.enumerate() {
+        let line = line.map_err(|source| FormatError::Io {
+            file: file.clone(),
+            source,
+        })?;
+        let column = |m: Match| line[..m.start()].chars().count() + 1;
 
         if let Some(caps) = candidate_rx.captures(&line) {
-            let id: u32 = caps.get(1).unwrap().as_str().parse().unwrap();
+            let id = caps.get(1).unwrap();
             let name: String = caps.get(2).unwrap().as_str().into();
-            assert_eq!(id - 1, candidates.len() as u32);
+            if id.as_str().parse::<usize>().ok() != Some(candidates.len() + 1) {
+                return Err(FieldError::new(
+                    column(id),
+                    format!(
+                        "expected candidate C{:02}, found C{}",
+                        candidates.len() + 1,
+                        id.as_str()
+                    ),
+                )
+                .at(&file, None, i + 1));
+            }
 
             candidates.push(Candidate::new(name, CandidateType::Regular));
         } else if let Some(caps) = ballot_rx.captures(&line) {
             let id: &str = caps.get(1).unwrap().as_str();
-            let votes: &str = caps.get(2).unwrap().as_str();
+            let votes = caps.get(2).unwrap();
 
-            let choices = parse_ballot(votes);
+            let choices = parse_ballot(votes.as_str(), column(votes))
+                .map_err(|err| err.at(&file, None, i + 1))?;
             let ballot = Ballot::new(id.into(), choices);
             ballots.push(ballot);
         }
     }
 
-    Election {
-        candidates,
-        ballots,
-    }
+    Ok(Election::new(candidates, ballots))
 }
 
 #[cfg(test)]
@@ -98,18 +116,29 @@ mod tests {
 
     #[test]
     fn test_parse_ballot() {
-        assert_eq!(Vec::new() as Vec<Choice>, parse_ballot(""));
+        assert_eq!(Ok(Vec::new() as Vec<Choice>), parse_ballot("", 1));
+
+        assert_eq!(
+            Ok(vec![Choice::Vote(CandidateId(3))]),
+            parse_ballot("C04", 1)
+        );
 
-        assert_eq!(vec![Choice::Vote(CandidateId(3))], parse_ballot("C04"));
+        assert_eq!(
+            Ok(vec![
+                Choice::Vote(CandidateId(3)),
+                Choice::Vote(CandidateId(2))
+            ]),
+            parse_ballot("C04,C03", 1)
+        );
 
         assert_eq!(
-            vec![Choice::Vote(CandidateId(3)), Choice::Vote(CandidateId(2))],
-            parse_ballot("C04,C03")
+            Ok(vec![Choice::Overvote, Choice::Vote(CandidateId(2))]),
+            parse_ballot("C04=C06,C03", 1)
         );
 
         assert_eq!(
-            vec![Choice::Overvote, Choice::Vote(CandidateId(2))],
-            parse_ballot("C04=C06,C03")
+            Err(FieldError::new(14, "bad candidate \"C00\"")),
+            parse_ballot("C04,C00", 10)
         );
     }
 }
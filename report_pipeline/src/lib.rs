@@ -0,0 +1,38 @@
+//! Reading, normalizing, and tabulating ranked-choice ballot data, and
+//! generating the contest reports published on ranked.vote.
+//!
+//! The pipeline has three stages:
+//!
+//! 1. [`formats`] read raw cast vote records in a jurisdiction-specific
+//!    format into an [`model::election::Election`].
+//! 2. [`normalizers`] apply a jurisdiction's ballot rules (overvotes, skipped
+//!    rankings, duplicates) to produce a [`model::election::NormalizedElection`].
+//! 3. [`tabulator`] runs the instant-runoff count, and [`report`] builds a
+//!    [`model::report::ContestReport`] from the rounds and ballots.
+//!
+//! The `ranked-vote` binary is a thin command-line wrapper around [`commands`].
+
+pub mod aggregate;
+pub mod anonymize;
+pub mod commands;
+pub mod config;
+pub mod export;
+pub mod formats;
+pub mod metrics;
+pub mod model;
+pub mod normalizers;
+pub mod operations;
+pub mod read_metadata;
+pub mod report;
+#[cfg(feature = "research")]
+pub mod research;
+pub mod rules;
+#[cfg(feature = "schema")]
+pub mod schema;
+pub mod sensitivity;
+pub mod tabulator;
+#[cfg(test)]
+mod test_support;
+pub mod transfers;
+pub mod util;
+pub mod validate;
@@ -1,64 +1,470 @@
-mod commands;
-mod formats;
-mod model;
-mod normalizers;
-mod read_metadata;
-mod report;
-mod tabulator;
-mod util;
-
-use crate::commands::{info, report, sync};
-use clap::{Parser, Subcommand};
+use clap::{CommandFactory, FromArgMatches, Parser, Subcommand};
+use clap_complete::{generate, Shell};
+use ranked_vote::commands::{
+    benchmark, diff, export_ballot_images, export_ballots, export_rounds, generate_signing_key,
+    history, import_ballot_images, info, ingest_all, join_geojson, lookup_ballot, metrics,
+    rename_office, report, sample_ballots, sync, update_status, verify_bundle, what_if,
+    BallotExportFormat, ContestFilter, GeoJsonOptions, ReportOptions,
+};
+use ranked_vote::config::Config;
+use ranked_vote::metrics::{timestamp, MonitoringOptions};
+use ranked_vote::model::metadata::ElectionStatus;
+use ranked_vote::operations::{append_operation, OperationRecord};
+use ranked_vote::util::{
+    exit_code, finish_recording, init_logging, record_outcome, set_max_threads, set_output_format,
+    set_output_options, start_recording, LogFormat, Outcome, OutputFormat, OutputOptions,
+};
 use std::path::PathBuf;
+use tracing::error;
 
 #[derive(Parser)]
 struct Opts {
     #[clap(subcommand)]
     command: Command,
+
+    /// Read defaults for these options and the data directories from this
+    /// TOML file (default: rcvreport.toml, if it exists).
+    #[clap(long, global = true)]
+    config: Option<PathBuf>,
+
+    /// Only log warnings and errors, and don't draw progress bars.
+    #[clap(long, global = true)]
+    quiet: bool,
+
+    /// Also log debugging detail, such as each iteration of a Meek count.
+    #[clap(long, global = true)]
+    verbose: bool,
+
+    /// Write log records to stderr as JSON lines.
+    #[clap(long, global = true)]
+    json_logs: bool,
+
+    /// Write generated JSON files (reports, indexes) without indentation,
+    /// to save space; metadata files are always indented.
+    #[clap(long, global = true)]
+    compact: bool,
+
+    /// Compression level of gzipped files, from 0 (none) to 9 (best, the
+    /// default).
+    #[clap(long, global = true)]
+    gzip_level: Option<u32>,
+
+    /// Warn about any JSON file larger than this many bytes uncompressed.
+    #[clap(long, global = true)]
+    max_output_size: Option<u64>,
+
+    /// Append a record of the command, with the hashes of the files it read
+    /// and wrote, to this operations log (JSON lines).
+    #[clap(long, global = true)]
+    operations_log: Option<PathBuf>,
+
+    /// What to print to stdout when a command finishes: nothing (text), or
+    /// a JSON summary of the run (json). Info logs are suppressed with json.
+    #[clap(long, global = true, default_value = "text")]
+    output: OutputFormat,
+
+    /// Exit with an error (code 2) if any warnings were logged, e.g. hash
+    /// mismatches or skipped rows.
+    #[clap(long, global = true)]
+    strict: bool,
+
+    /// Hash at most this many raw data files at once (default: one per CPU).
+    #[clap(long, global = true)]
+    threads: Option<usize>,
 }
 
 #[derive(Subcommand)]
 enum Command {
-    /// Validate and dump info about election.
+    /// Validate and dump info about election. Exits with an error if any
+    /// metadata file has problems.
     Info {
         /// Input directory to validate and dump.
-        meta_dir: PathBuf,
+        meta_dir: Option<PathBuf>,
+        /// Print the problems found to stdout as a JSON list.
+        #[clap(long)]
+        json: bool,
     },
     /// Sync raw data files with metadata.
     Sync {
         /// Metadata directory
-        meta_dir: PathBuf,
+        meta_dir: Option<PathBuf>,
         /// Raw data directory
-        raw_data_dir: PathBuf,
+        raw_data_dir: Option<PathBuf>,
+        /// Don't re-hash files already recorded in metadata
+        #[clap(long)]
+        skip_verify: bool,
+        /// Don't download files listed in metadata sources
+        #[clap(long)]
+        offline: bool,
+        /// Update the metadata's file hashes to match the raw data, instead
+        /// of only listing the differences
+        #[clap(long)]
+        apply: bool,
+    },
+    /// Preprocess every election listed in a manifest file.
+    IngestAll {
+        /// JSON manifest listing the elections to preprocess
+        manifest: PathBuf,
+        /// Metadata directory
+        meta_dir: PathBuf,
+        /// Preprocessed file output directory
+        preprocessed_dir: PathBuf,
+        /// Write Prometheus metrics of the run to this file (e.g. for
+        /// node_exporter's textfile collector)
+        #[clap(long)]
+        prometheus_file: Option<PathBuf>,
+        /// Push Prometheus metrics of the run to the Pushgateway at this URL
+        #[clap(long)]
+        pushgateway: Option<String>,
+        /// POST a JSON summary of the run to this URL when it finishes
+        #[clap(long)]
+        webhook: Option<String>,
     },
     /// Generate reports
     Report {
         /// Metadata directory
-        meta_dir: PathBuf,
+        meta_dir: Option<PathBuf>,
         /// Raw data directory
-        raw_data_dir: PathBuf,
+        raw_data_dir: Option<PathBuf>,
         /// Preprocessed file output directory
-        preprocessed_dir: PathBuf,
+        preprocessed_dir: Option<PathBuf>,
         /// Report output directory
-        report_dir: PathBuf,
-        /// Whether to force preprocessing even if preprocessed files exist
+        report_dir: Option<PathBuf>,
+        /// Preprocess contests even if up-to-date preprocessed files exist
+        #[clap(long)]
         force_preprocess: bool,
+        /// Regenerate reports even if they are up to date
+        #[clap(long)]
         force_report: bool,
+        /// Preprocess even if raw data files don't match the hashes recorded
+        /// in metadata (mismatches are still logged as warnings)
+        #[clap(long)]
+        allow_hash_mismatch: bool,
+        /// Only process the given contest, as an office id or a full contest
+        /// path (e.g. us/ny/nyc/2025/07/mayor); may be repeated
+        #[clap(long = "contest")]
+        contests: Vec<String>,
+        /// Only process contests whose office id or name matches this regular
+        /// expression
+        #[clap(long)]
+        office_pattern: Option<String>,
+        /// Only process contests in the given jurisdiction (e.g. us/ny/nyc);
+        /// may be repeated. Defaults to the config file's jurisdictions
+        #[clap(long = "jurisdiction")]
+        jurisdictions: Vec<String>,
+        /// Only process contests in the given election, as an election path
+        /// (e.g. 2025/07) or in full (e.g. us/ny/nyc/2025/07); may be repeated
+        #[clap(long = "election")]
+        elections: Vec<String>,
+        /// List the contests that would be preprocessed and reported, without
+        /// writing anything
+        #[clap(long)]
+        dry_run: bool,
+        /// Sign a manifest of the report files' hashes with the Ed25519
+        /// private key in this file (see generate-signing-key), writing it to
+        /// manifest.json in the report directory
+        #[clap(long)]
+        sign_key: Option<PathBuf>,
+        /// Write trace.json next to each report, explaining why each round's
+        /// candidates were eliminated
+        #[clap(long)]
+        trace: bool,
+        /// File to which per-contest stage timings are appended
+        #[clap(long, default_value = "report_metrics.json")]
+        metrics_file: PathBuf,
+        /// Write Prometheus metrics of the run to this file (e.g. for
+        /// node_exporter's textfile collector)
+        #[clap(long)]
+        prometheus_file: Option<PathBuf>,
+        /// Push Prometheus metrics of the run to the Pushgateway at this URL
+        #[clap(long)]
+        pushgateway: Option<String>,
+        /// POST a JSON summary of the run to this URL when it finishes
+        #[clap(long)]
+        webhook: Option<String>,
+        /// Tabulate at most this many contests of an election at once
+        /// (default: one per CPU)
+        #[clap(long)]
+        jobs: Option<usize>,
+    },
+    /// Set whether an election's results are preliminary, updated or
+    /// certified.
+    UpdateStatus {
+        /// Metadata directory
+        meta_dir: PathBuf,
+        /// Election path, e.g. us/ny/nyc/2025/07
+        election: String,
+        /// New status: preliminary, updated or certified
+        status: ElectionStatus,
+        /// Number of the latest batch of cast vote records in the raw data,
+        /// for preliminary results released in drops
+        #[clap(long)]
+        drop: Option<u32>,
+    },
+    /// Rename an office id in metadata and move its contests' preprocessed
+    /// ballots and reports to match.
+    RenameOffice {
+        /// Metadata directory
+        meta_dir: PathBuf,
+        /// Jurisdiction path, e.g. us/ny/nyc
+        jurisdiction: String,
+        /// Current office id
+        old: String,
+        /// New office id
+        new: String,
+        /// Preprocessed file directory
+        preprocessed_dir: PathBuf,
+        /// Report directory
+        report_dir: PathBuf,
+    },
+    /// List the commands recorded in an operations log.
+    History {
+        /// Operations log written with --operations-log
+        log: PathBuf,
+        /// Only list runs of this subcommand, e.g. report
+        #[clap(long)]
+        command: Option<String>,
+        /// Only list runs that read or wrote a file whose path contains this,
+        /// with the file's hashes
+        #[clap(long)]
+        file: Option<String>,
+    },
+    /// Summarize the stage timings recorded while generating reports.
+    Metrics {
+        /// Metrics file written by the report command
+        #[clap(default_value = "report_metrics.json")]
+        metrics_file: PathBuf,
+    },
+    /// Time each processing stage for one contest and compare with the
+    /// previous run.
+    Benchmark {
+        /// Metadata directory
+        meta_dir: PathBuf,
+        /// Raw data directory
+        raw_data_dir: PathBuf,
+        /// Contest path, e.g. us/ca/sfo/2019/11/mayor
+        contest: String,
+        /// File in which benchmark runs are recorded
+        #[clap(long, default_value = "benchmarks.json")]
+        history: PathBuf,
+        /// Percentage slowdown of a stage, relative to the previous run,
+        /// that is reported as a regression
+        #[clap(long, default_value = "10")]
+        threshold: f64,
+    },
+    /// Print randomly chosen ballots of a contest with their raw and
+    /// normalized rankings side by side, for spot checks against the raw data.
+    SampleBallots {
+        /// Metadata directory
+        meta_dir: PathBuf,
+        /// Raw data directory
+        raw_data_dir: PathBuf,
+        /// Contest path, e.g. us/ca/sfo/2019/11/mayor
+        contest: String,
+        /// Number of ballots to print
+        #[clap(long, default_value = "10")]
+        count: usize,
+        /// Seed of the random choice, so a sample can be printed again
+        #[clap(long, default_value = "1")]
+        seed: u64,
+    },
+    /// Compare two report directories, or two reports of the same contest,
+    /// and list changed winners, ballot counts, round counts and tallies.
+    Diff {
+        /// Old report directory or report.json file
+        old: PathBuf,
+        /// New report directory or report.json file
+        new: PathBuf,
+    },
+    /// Re-run a contest's count as if some candidates had dropped out,
+    /// printing the rounds to stdout as JSON.
+    WhatIf {
+        /// Preprocessed file directory
+        preprocessed_dir: PathBuf,
+        /// Contest path, e.g. us/ny/nyc/2025/07/mayor
+        contest: String,
+        /// Name of a candidate to remove from every ballot; may be repeated
+        #[clap(long)]
+        eliminate: Vec<String>,
+    },
+    /// Print how one ballot of a contest was counted to stdout as JSON: its
+    /// ranking, the candidate it counted for in each round, and when it was
+    /// exhausted.
+    LookupBallot {
+        /// Preprocessed file directory
+        preprocessed_dir: PathBuf,
+        /// Contest path, e.g. us/ny/nyc/2025/07/mayor
+        contest: String,
+        /// Ballot (cast vote record) id
+        ballot_id: String,
+        /// Metadata directory, to include the ballot's raw rankings
+        #[clap(long, requires = "raw_data_dir")]
+        meta_dir: Option<PathBuf>,
+        /// Raw data directory, to include the ballot's raw rankings
+        #[clap(long, requires = "meta_dir")]
+        raw_data_dir: Option<PathBuf>,
+    },
+    /// Join each contest's precinct results to precinct boundaries in a
+    /// GeoJSON file, writing a merged GeoJSON file per contest for maps.
+    JoinGeojson {
+        /// Report directory
+        report_dir: PathBuf,
+        /// GeoJSON feature collection of precincts
+        geojson: PathBuf,
+        /// Merged GeoJSON output directory
+        output_dir: PathBuf,
+        /// Feature property holding the precinct id
+        #[clap(long, default_value = "precinct_id")]
+        id_property: String,
+        /// Feature property to write the precinct's ballot count to
+        #[clap(long, default_value = "ballots")]
+        ballots_property: String,
+        /// Feature property to write first-choice votes by candidate to
+        #[clap(long, default_value = "first_choice")]
+        first_choice_property: String,
+        /// Feature property to write final-round votes by candidate to
+        #[clap(long, default_value = "final_round")]
+        final_round_property: String,
+        /// Feature property to write the final-round leader's name to
+        #[clap(long, default_value = "leader")]
+        leader_property: String,
+    },
+    /// Generate an Ed25519 key pair for signing report directories.
+    GenerateSigningKey {
+        /// File to write the private key to; the public key is written to
+        /// the same path with .pub appended
+        key_file: PathBuf,
+    },
+    /// Check a signed report directory's files against its manifest.
+    VerifyBundle {
+        /// Report directory containing manifest.json
+        report_dir: PathBuf,
+        /// File containing the public key of the key the manifest was signed
+        /// with
+        public_key_file: PathBuf,
+    },
+    /// Export preprocessed ballots as rank-column CSV files that can be
+    /// re-tabulated with RCTab or the Universal RCV Tabulator, or as Parquet
+    /// tables for analysis.
+    ExportBallots {
+        /// Preprocessed file directory
+        preprocessed_dir: PathBuf,
+        /// Output directory
+        output_dir: PathBuf,
+        /// File format: csv (one rank-column file per contest) or parquet
+        /// (ballots.parquet and choices.parquet per contest)
+        #[clap(long, default_value = "csv")]
+        format: BallotExportFormat,
+    },
+    /// Write JSON Schemas of the published report files.
+    #[cfg(feature = "schema")]
+    Schema {
+        /// Schema output directory
+        output_dir: PathBuf,
+    },
+    /// Export each report's rounds as a round-by-round CSV.
+    ExportRounds {
+        /// Report directory
+        report_dir: PathBuf,
+        /// CSV output directory
+        output_dir: PathBuf,
+    },
+    /// Export preprocessed ballots as newline-delimited JSON ballot images.
+    ExportBallotImages {
+        /// Preprocessed file directory
+        preprocessed_dir: PathBuf,
+        /// Ballot image output directory
+        output_dir: PathBuf,
+    },
+    /// Import a ballot image file as the preprocessed ballots of a contest.
+    ImportBallotImages {
+        /// Metadata directory
+        meta_dir: PathBuf,
+        /// Ballot image file (.jsonl)
+        input: PathBuf,
+        /// Preprocessed file directory
+        preprocessed_dir: PathBuf,
+        /// Contest path, e.g. us/ca/sfo/2019/11/mayor
+        contest: String,
+        /// Raw data directory, which holds the salt of anonymized ballot ids
+        /// (default: from the config file)
+        #[clap(long)]
+        raw_data_dir: Option<PathBuf>,
+    },
+    /// Print a shell completion script to stdout.
+    Completions {
+        /// Shell to generate completions for
+        #[clap(value_parser)]
+        shell: Shell,
     },
 }
 
 fn main() {
-    let opts = Opts::parse();
+    let matches = Opts::command().get_matches();
+    let opts = Opts::from_arg_matches(&matches).unwrap_or_else(|err| err.exit());
+    let started_at = timestamp();
+
+    let config = match Config::load(opts.config.as_deref()) {
+        Ok(config) => config,
+        Err(err) => {
+            eprintln!("Could not read config file {}", err);
+            std::process::exit(Outcome::InfraError as i32);
+        }
+    };
+
+    init_logging(
+        opts.quiet || config.quiet || opts.output == OutputFormat::Json,
+        opts.verbose,
+        if opts.json_logs || config.json_logs {
+            LogFormat::Json
+        } else {
+            LogFormat::Text
+        },
+    );
+    set_output_options(OutputOptions {
+        compact: opts.compact || config.compact,
+        gzip_level: opts.gzip_level.or(config.gzip_level).unwrap_or(9),
+        max_size: opts.max_output_size.or(config.max_output_size),
+    });
+    set_output_format(opts.output);
+    set_max_threads(opts.threads.or(config.threads));
+    let operations_log = opts.operations_log.or(config.operations_log);
+    if operations_log.is_some() {
+        start_recording();
+    }
 
     match opts.command {
-        Command::Info { meta_dir } => {
-            info(&meta_dir);
+        Command::Info { meta_dir, json } => {
+            let meta_dir = meta_dir.unwrap_or(config.meta_dir);
+            if !info(&meta_dir, json) {
+                record_outcome(Outcome::DataError);
+            }
         }
         Command::Sync {
             meta_dir,
             raw_data_dir,
+            skip_verify,
+            offline,
+            apply,
         } => {
-            sync(&meta_dir, &raw_data_dir);
+            let meta_dir = meta_dir.unwrap_or(config.meta_dir);
+            let raw_data_dir = raw_data_dir.unwrap_or(config.raw_data_dir);
+            sync(&meta_dir, &raw_data_dir, skip_verify, offline, apply);
+        }
+        Command::IngestAll {
+            manifest,
+            meta_dir,
+            preprocessed_dir,
+            prometheus_file,
+            pushgateway,
+            webhook,
+        } => {
+            let monitoring = MonitoringOptions {
+                textfile: prometheus_file,
+                pushgateway,
+                webhook,
+            };
+            ingest_all(&manifest, &meta_dir, &preprocessed_dir, &monitoring);
         }
         Command::Report {
             meta_dir,
@@ -67,15 +473,224 @@ fn main() {
             report_dir,
             force_preprocess,
             force_report,
+            allow_hash_mismatch,
+            contests,
+            office_pattern,
+            jurisdictions,
+            elections,
+            dry_run,
+            sign_key,
+            trace,
+            metrics_file,
+            prometheus_file,
+            pushgateway,
+            webhook,
+            jobs,
         } => {
+            let meta_dir = meta_dir.unwrap_or(config.meta_dir);
+            let raw_data_dir = raw_data_dir.unwrap_or(config.raw_data_dir);
+            let preprocessed_dir = preprocessed_dir.unwrap_or(config.preprocessed_dir);
+            let report_dir = report_dir.unwrap_or(config.report_dir);
+            let jurisdictions = if jurisdictions.is_empty() {
+                config.jurisdictions
+            } else {
+                jurisdictions
+            };
+            let options = ReportOptions {
+                force_preprocess,
+                force_report,
+                allow_hash_mismatch,
+                filter: ContestFilter::new(contests, office_pattern.as_deref())
+                    .with_jurisdictions(jurisdictions)
+                    .with_elections(elections),
+                metrics_path: Some(metrics_file),
+                dry_run,
+                sign_key,
+                trace,
+                monitoring: MonitoringOptions {
+                    textfile: prometheus_file,
+                    pushgateway,
+                    webhook,
+                },
+                jobs: jobs.or(config.jobs),
+            };
             report(
                 &meta_dir,
                 &raw_data_dir,
                 &report_dir,
                 &preprocessed_dir,
-                force_preprocess,
-                force_report,
+                &options,
+            );
+        }
+        Command::UpdateStatus {
+            meta_dir,
+            election,
+            status,
+            drop,
+        } => {
+            update_status(&meta_dir, &election, status, drop);
+        }
+        Command::RenameOffice {
+            meta_dir,
+            jurisdiction,
+            old,
+            new,
+            preprocessed_dir,
+            report_dir,
+        } => {
+            rename_office(
+                &meta_dir,
+                &jurisdiction,
+                &old,
+                &new,
+                &preprocessed_dir,
+                &report_dir,
+            );
+        }
+        Command::History { log, command, file } => {
+            history(&log, command.as_deref(), file.as_deref());
+        }
+        Command::Metrics { metrics_file } => {
+            metrics(&metrics_file);
+        }
+        Command::Benchmark {
+            meta_dir,
+            raw_data_dir,
+            contest,
+            history,
+            threshold,
+        } => {
+            benchmark(&meta_dir, &raw_data_dir, &contest, &history, threshold);
+        }
+        Command::SampleBallots {
+            meta_dir,
+            raw_data_dir,
+            contest,
+            count,
+            seed,
+        } => {
+            sample_ballots(&meta_dir, &raw_data_dir, &contest, count, seed);
+        }
+        Command::Diff { old, new } => {
+            diff(&old, &new);
+        }
+        Command::WhatIf {
+            preprocessed_dir,
+            contest,
+            eliminate,
+        } => {
+            if !what_if(&preprocessed_dir, &contest, &eliminate) {
+                record_outcome(Outcome::DataError);
+            }
+        }
+        Command::LookupBallot {
+            preprocessed_dir,
+            contest,
+            ballot_id,
+            meta_dir,
+            raw_data_dir,
+        } => {
+            let raw = meta_dir.as_deref().zip(raw_data_dir.as_deref());
+            if !lookup_ballot(&preprocessed_dir, &contest, &ballot_id, raw) {
+                record_outcome(Outcome::DataError);
+            }
+        }
+        Command::JoinGeojson {
+            report_dir,
+            geojson,
+            output_dir,
+            id_property,
+            ballots_property,
+            first_choice_property,
+            final_round_property,
+            leader_property,
+        } => {
+            let options = GeoJsonOptions {
+                id_property,
+                ballots_property,
+                first_choice_property,
+                final_round_property,
+                leader_property,
+            };
+            join_geojson(&report_dir, &geojson, &output_dir, &options);
+        }
+        Command::GenerateSigningKey { key_file } => {
+            generate_signing_key(&key_file);
+        }
+        Command::VerifyBundle {
+            report_dir,
+            public_key_file,
+        } => {
+            if !verify_bundle(&report_dir, &public_key_file) {
+                record_outcome(Outcome::DataError);
+            }
+        }
+        Command::ExportBallots {
+            preprocessed_dir,
+            output_dir,
+            format,
+        } => {
+            export_ballots(&preprocessed_dir, &output_dir, format);
+        }
+        #[cfg(feature = "schema")]
+        Command::Schema { output_dir } => {
+            ranked_vote::commands::schema(&output_dir);
+        }
+        Command::ExportRounds {
+            report_dir,
+            output_dir,
+        } => {
+            export_rounds(&report_dir, &output_dir);
+        }
+        Command::ExportBallotImages {
+            preprocessed_dir,
+            output_dir,
+        } => {
+            export_ballot_images(&preprocessed_dir, &output_dir);
+        }
+        Command::ImportBallotImages {
+            meta_dir,
+            input,
+            preprocessed_dir,
+            contest,
+            raw_data_dir,
+        } => {
+            let raw_data_dir = raw_data_dir.unwrap_or(config.raw_data_dir);
+            import_ballot_images(
+                &meta_dir,
+                &raw_data_dir,
+                &input,
+                &preprocessed_dir,
+                &contest,
+            );
+        }
+        Command::Completions { shell } => {
+            generate(
+                shell,
+                &mut Opts::command(),
+                "ranked-vote",
+                &mut std::io::stdout(),
+            );
+        }
+    }
+
+    if let (Some(log_path), Some(files)) = (operations_log, finish_recording()) {
+        let record = OperationRecord::new(
+            matches.subcommand_name().unwrap_or_default(),
+            std::env::args().skip(1).collect(),
+            started_at,
+            timestamp(),
+            files,
+        );
+        if let Err(err) = append_operation(&log_path, &record) {
+            record_outcome(Outcome::InfraError);
+            error!(
+                "Could not write operations log {}: {}",
+                log_path.display(),
+                err
             );
         }
     }
+
+    std::process::exit(exit_code(opts.strict));
 }
@@ -0,0 +1,522 @@
+//! Timing of pipeline stages, the history of benchmark and report runs, and
+//! monitoring of batch runs with Prometheus metrics and webhook notifications.
+
+use crate::util::{
+    get_files_from_path, post_url, print_summary, read_serialized, record_outcome, write_output,
+    Outcome,
+};
+use colored::*;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::Write;
+use std::fs::write;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use tracing::{error, info};
+
+/// Wall-clock time spent in one stage of the pipeline.
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct StageTiming {
+    pub stage: String,
+    pub millis: f64,
+}
+
+/// Records how long each stage of processing a contest takes.
+#[derive(Default)]
+pub struct MetricsCollector {
+    stages: Vec<StageTiming>,
+}
+
+impl MetricsCollector {
+    pub fn new() -> MetricsCollector {
+        MetricsCollector::default()
+    }
+
+    /// Run `f`, recording its duration under `stage`.
+    pub fn time<T>(&mut self, stage: &str, f: impl FnOnce() -> T) -> T {
+        let start = Instant::now();
+        let result = f();
+        self.stages.push(StageTiming {
+            stage: stage.to_string(),
+            millis: start.elapsed().as_secs_f64() * 1000.,
+        });
+        result
+    }
+
+    /// Record that `stage` has been running since `start`.
+    pub fn record_since(&mut self, stage: &str, start: Instant) {
+        self.record_duration(stage, start.elapsed());
+    }
+
+    /// Record that `stage` took `duration`, e.g. when it ran on another
+    /// thread.
+    pub fn record_duration(&mut self, stage: &str, duration: Duration) {
+        self.stages.push(StageTiming {
+            stage: stage.to_string(),
+            millis: duration.as_secs_f64() * 1000.,
+        });
+    }
+
+    pub fn into_stages(self) -> Vec<StageTiming> {
+        self.stages
+    }
+}
+
+/// Seconds since the Unix epoch, used to timestamp metrics records.
+pub fn timestamp() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+/// Stage timings from processing one contest, either by the `benchmark`
+/// command or during report generation.
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ContestMetrics {
+    /// Contest path, e.g. `us/ca/sfo/2019/11/mayor`.
+    pub contest: String,
+    pub timestamp: u64,
+    pub ballots: u32,
+    pub stages: Vec<StageTiming>,
+}
+
+/// A stage that got slower than the previous run by more than the threshold.
+pub struct Regression {
+    pub stage: String,
+    pub previous_millis: f64,
+    pub current_millis: f64,
+    pub percent_change: f64,
+}
+
+/// All recorded runs, oldest first.
+#[derive(Serialize, Deserialize, Default)]
+pub struct MetricsHistory {
+    pub runs: Vec<ContestMetrics>,
+}
+
+impl MetricsHistory {
+    /// Load the history from `path`, or start an empty one if it doesn't exist.
+    pub fn load(path: &Path) -> MetricsHistory {
+        if path.exists() {
+            read_serialized(path)
+        } else {
+            MetricsHistory::default()
+        }
+    }
+
+    pub fn save(&self, path: &Path) {
+        write_output(path, self);
+    }
+
+    /// The most recent run for the given contest.
+    pub fn last_run(&self, contest: &str) -> Option<&ContestMetrics> {
+        self.runs.iter().rev().find(|r| r.contest == contest)
+    }
+
+    /// Summarize the most recent run of each contest, grouped by election.
+    pub fn election_summaries(&self) -> Vec<ElectionMetricsSummary> {
+        let mut latest: BTreeMap<&str, &ContestMetrics> = BTreeMap::new();
+        for run in &self.runs {
+            latest.insert(&run.contest, run);
+        }
+
+        let mut summaries: BTreeMap<String, ElectionMetricsSummary> = BTreeMap::new();
+        for (contest, run) in latest {
+            let election = match contest.rsplit_once('/') {
+                Some((election, _)) => election.to_string(),
+                None => contest.to_string(),
+            };
+            let summary =
+                summaries
+                    .entry(election.clone())
+                    .or_insert_with(|| ElectionMetricsSummary {
+                        election,
+                        contests: 0,
+                        ballots: 0,
+                        stage_millis: BTreeMap::new(),
+                    });
+            summary.contests += 1;
+            summary.ballots += run.ballots as u64;
+            for stage in &run.stages {
+                *summary.stage_millis.entry(stage.stage.clone()).or_default() += stage.millis;
+            }
+        }
+
+        summaries.into_values().collect()
+    }
+}
+
+/// Total stage timings across the contests of one election.
+pub struct ElectionMetricsSummary {
+    /// Election path, e.g. `us/ca/sfo/2019/11`.
+    pub election: String,
+    pub contests: u32,
+    pub ballots: u64,
+    pub stage_millis: BTreeMap<String, f64>,
+}
+
+/// Compare each stage of `current` with the same stage of `previous`,
+/// returning those that slowed down by more than `threshold_percent`.
+pub fn find_regressions(
+    previous: &ContestMetrics,
+    current: &ContestMetrics,
+    threshold_percent: f64,
+) -> Vec<Regression> {
+    current
+        .stages
+        .iter()
+        .filter_map(|stage| {
+            let prev = previous.stages.iter().find(|s| s.stage == stage.stage)?;
+            if prev.millis <= 0. {
+                return None;
+            }
+            let percent_change = (stage.millis - prev.millis) / prev.millis * 100.;
+            if percent_change > threshold_percent {
+                Some(Regression {
+                    stage: stage.stage.clone(),
+                    previous_millis: prev.millis,
+                    current_millis: stage.millis,
+                    percent_change,
+                })
+            } else {
+                None
+            }
+        })
+        .collect()
+}
+
+/// Where to send the metrics and summary of a batch run.
+#[derive(Default)]
+pub struct MonitoringOptions {
+    /// File to write Prometheus metrics to, e.g. for node_exporter's
+    /// textfile collector.
+    pub textfile: Option<PathBuf>,
+    /// Base URL of a Pushgateway to push Prometheus metrics to.
+    pub pushgateway: Option<String>,
+    /// URL to POST a `RunNotification` to when the run finishes.
+    pub webhook: Option<String>,
+}
+
+/// The JSON body posted to a webhook when a batch run finishes, e.g. to
+/// trigger a rebuild of the site when new data has landed. Also printed to
+/// stdout with `--output json`.
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct RunNotification {
+    pub command: String,
+    pub timestamp: u64,
+    /// Elections with newly processed contests.
+    pub elections: Vec<String>,
+    pub contests: u32,
+    pub ballots: u64,
+    pub failures: u32,
+    pub duration_seconds: f64,
+    /// Time spent in each stage, summed over contests.
+    #[serde(default)]
+    pub stage_seconds: BTreeMap<String, f64>,
+    /// Output directories, by name (e.g. `reports`).
+    #[serde(default)]
+    pub output_dirs: BTreeMap<String, String>,
+    pub warnings: Vec<String>,
+}
+
+/// Totals from one run of a batch command (`report` or `ingest-all`), for
+/// monitoring with Prometheus and webhook notifications.
+pub struct RunSummary {
+    command: String,
+    start: Instant,
+    elections: BTreeSet<String>,
+    contests: u32,
+    ballots: u64,
+    failures: u32,
+    warnings: Vec<String>,
+    stage_seconds: BTreeMap<String, f64>,
+    dirs: BTreeMap<String, String>,
+    dir_bytes: BTreeMap<String, u64>,
+}
+
+impl RunSummary {
+    pub fn new(command: &str) -> RunSummary {
+        RunSummary {
+            command: command.to_string(),
+            start: Instant::now(),
+            elections: BTreeSet::new(),
+            contests: 0,
+            ballots: 0,
+            failures: 0,
+            warnings: Vec::new(),
+            stage_seconds: BTreeMap::new(),
+            dirs: BTreeMap::new(),
+            dir_bytes: BTreeMap::new(),
+        }
+    }
+
+    /// Count a contest that was processed, with its stage timings.
+    pub fn add_contest(&mut self, metrics: &ContestMetrics) {
+        self.add_contests(1, metrics.ballots as u64);
+        for stage in &metrics.stages {
+            *self.stage_seconds.entry(stage.stage.clone()).or_default() += stage.millis / 1000.;
+        }
+    }
+
+    /// Count contests that were processed without stage timings.
+    pub fn add_contests(&mut self, contests: u32, ballots: u64) {
+        self.contests += contests;
+        self.ballots += ballots;
+    }
+
+    pub fn add_failures(&mut self, failures: u32) {
+        self.failures += failures;
+    }
+
+    /// Record that contests in the election at `path` (e.g.
+    /// `us/ny/nyc/2025/07`) were processed.
+    pub fn add_election(&mut self, path: &str) {
+        self.elections.insert(path.to_string());
+    }
+
+    pub fn add_warning(&mut self, warning: String) {
+        self.warnings.push(warning);
+    }
+
+    pub fn notification(&self) -> RunNotification {
+        RunNotification {
+            command: self.command.clone(),
+            timestamp: timestamp(),
+            elections: self.elections.iter().cloned().collect(),
+            contests: self.contests,
+            ballots: self.ballots,
+            failures: self.failures,
+            duration_seconds: self.start.elapsed().as_secs_f64(),
+            stage_seconds: self.stage_seconds.clone(),
+            output_dirs: self.dirs.clone(),
+            warnings: self.warnings.clone(),
+        }
+    }
+
+    /// Record an output directory and the total size of the files under it.
+    pub fn add_dir_size(&mut self, name: &str, dir: &Path) {
+        self.dirs
+            .insert(name.to_string(), dir.to_string_lossy().to_string());
+        if !dir.exists() {
+            return;
+        }
+        let bytes = get_files_from_path(dir)
+            .unwrap_or_default()
+            .iter()
+            .filter_map(|file| file.metadata().ok())
+            .map(|m| m.len())
+            .sum();
+        self.dir_bytes.insert(name.to_string(), bytes);
+    }
+
+    /// Render the totals in the Prometheus text exposition format. Every
+    /// metric is a gauge describing this run, labeled with the command.
+    pub fn to_prometheus(&self) -> String {
+        let seconds = self.start.elapsed().as_secs_f64();
+        let command = &self.command;
+        let mut out = String::new();
+        let mut gauge = |name: &str, help: &str, samples: Vec<(String, String)>| {
+            writeln!(out, "# HELP ranked_vote_{} {}", name, help).unwrap();
+            writeln!(out, "# TYPE ranked_vote_{} gauge", name).unwrap();
+            for (labels, value) in samples {
+                writeln!(out, "ranked_vote_{}{{{}}} {}", name, labels, value).unwrap();
+            }
+        };
+        let label = format!("command=\"{}\"", command);
+
+        gauge(
+            "run_timestamp_seconds",
+            "When the run finished.",
+            vec![(label.clone(), timestamp().to_string())],
+        );
+        gauge(
+            "run_duration_seconds",
+            "Wall-clock duration of the run.",
+            vec![(label.clone(), format!("{:.3}", seconds))],
+        );
+        gauge(
+            "run_contests",
+            "Contests processed in the run.",
+            vec![(label.clone(), self.contests.to_string())],
+        );
+        gauge(
+            "run_failures",
+            "Contests or elections that could not be processed.",
+            vec![(label.clone(), self.failures.to_string())],
+        );
+        gauge(
+            "run_ballots",
+            "Ballots processed in the run.",
+            vec![(label.clone(), self.ballots.to_string())],
+        );
+        gauge(
+            "run_ballots_per_second",
+            "Ballots processed per second of the run.",
+            vec![(
+                label.clone(),
+                format!(
+                    "{:.1}",
+                    if seconds > 0. {
+                        self.ballots as f64 / seconds
+                    } else {
+                        0.
+                    }
+                ),
+            )],
+        );
+        gauge(
+            "stage_duration_seconds",
+            "Time spent in each stage, summed over contests.",
+            self.stage_seconds
+                .iter()
+                .map(|(stage, s)| {
+                    (
+                        format!("{},stage=\"{}\"", label, stage),
+                        format!("{:.3}", s),
+                    )
+                })
+                .collect(),
+        );
+        gauge(
+            "output_bytes",
+            "Total size of the files in each output directory.",
+            self.dir_bytes
+                .iter()
+                .map(|(dir, bytes)| (format!("{},dir=\"{}\"", label, dir), bytes.to_string()))
+                .collect(),
+        );
+
+        out
+    }
+
+    /// Write or push the metrics and post the notification as set in
+    /// `options`, logging any failure, and print the notification with
+    /// `--output json`.
+    pub fn export(&self, options: &MonitoringOptions) {
+        print_summary(&self.notification());
+
+        if let Some(url) = &options.webhook {
+            info!("Notifying {}", url.bright_blue());
+            let body = serde_json::to_vec(&self.notification()).unwrap();
+            if let Err(err) = post_url(url, "application/json", &body) {
+                record_outcome(Outcome::InfraError);
+                error!("{}", err);
+            }
+        }
+
+        if options.textfile.is_none() && options.pushgateway.is_none() {
+            return;
+        }
+        let text = self.to_prometheus();
+
+        if let Some(path) = &options.textfile {
+            info!("Writing {}", path.to_string_lossy().bright_blue());
+            if let Err(err) = write(path, &text) {
+                record_outcome(Outcome::InfraError);
+                error!("Could not write {}: {}", path.display(), err);
+            }
+        }
+        if let Some(gateway) = &options.pushgateway {
+            let url = format!(
+                "{}/metrics/job/ranked_vote/command/{}",
+                gateway.trim_end_matches('/'),
+                self.command
+            );
+            info!("Pushing metrics to {}", url.bright_blue());
+            if let Err(err) = post_url(&url, "text/plain; version=0.0.4", text.as_bytes()) {
+                record_outcome(Outcome::InfraError);
+                error!("{}", err);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run(read: f64, tabulate: f64) -> ContestMetrics {
+        ContestMetrics {
+            contest: "us/tst/2024/11/mayor".into(),
+            timestamp: 0,
+            ballots: 100,
+            stages: vec![
+                StageTiming {
+                    stage: "read".into(),
+                    millis: read,
+                },
+                StageTiming {
+                    stage: "tabulate".into(),
+                    millis: tabulate,
+                },
+            ],
+        }
+    }
+
+    #[test]
+    fn test_run_summary_to_prometheus() {
+        let mut summary = RunSummary::new("report");
+        summary.add_contest(&run(1500., 500.));
+        summary.add_contests(1, 50);
+        summary.add_failures(1);
+
+        let text = summary.to_prometheus();
+        assert!(text.contains("# TYPE ranked_vote_run_contests gauge\n"));
+        assert!(text.contains("ranked_vote_run_contests{command=\"report\"} 2\n"));
+        assert!(text.contains("ranked_vote_run_ballots{command=\"report\"} 150\n"));
+        assert!(text.contains("ranked_vote_run_failures{command=\"report\"} 1\n"));
+        assert!(text.contains(
+            "ranked_vote_stage_duration_seconds{command=\"report\",stage=\"read\"} 1.500\n"
+        ));
+    }
+
+    #[test]
+    fn test_run_summary_notification() {
+        let mut summary = RunSummary::new("report");
+        summary.add_contest(&run(1500., 500.));
+        summary.add_election("us/tst/2024/11");
+        summary.add_election("us/tst/2024/11");
+        summary.add_warning("us/tst/2024/11/mayor: skipped 1 malformed row(s)".into());
+        summary.add_dir_size("reports", Path::new("missing-reports"));
+
+        let notification = summary.notification();
+        assert_eq!(vec!["us/tst/2024/11".to_string()], notification.elections);
+        assert_eq!(1, notification.contests);
+        assert_eq!(100, notification.ballots);
+        assert_eq!(1, notification.warnings.len());
+        assert!((notification.stage_seconds["read"] - 1.5).abs() < 1e-9);
+        assert_eq!("missing-reports", notification.output_dirs["reports"]);
+
+        let json = serde_json::to_value(&notification).unwrap();
+        assert_eq!(100, json["ballots"]);
+        assert!(json["durationSeconds"].is_number());
+    }
+
+    #[test]
+    fn test_find_regressions() {
+        let regressions = find_regressions(&run(100., 50.), &run(105., 80.), 10.);
+        assert_eq!(1, regressions.len());
+        assert_eq!("tabulate", regressions[0].stage);
+        assert!((regressions[0].percent_change - 60.).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_election_summaries_use_latest_run() {
+        let mut other = run(10., 10.);
+        other.contest = "us/tst/2024/11/council".into();
+        let history = MetricsHistory {
+            runs: vec![run(100., 50.), run(200., 60.), other],
+        };
+
+        let summaries = history.election_summaries();
+        assert_eq!(1, summaries.len());
+        assert_eq!("us/tst/2024/11", summaries[0].election);
+        assert_eq!(2, summaries[0].contests);
+        assert_eq!(200, summaries[0].ballots);
+        assert!((summaries[0].stage_millis["read"] - 210.).abs() < 1e-9);
+    }
+}
@@ -0,0 +1,91 @@
+use serde::{Deserialize, Serialize};
+
+/// Candidate profiles built from every contest report, written to
+/// `candidates.json` alongside the report index.
+#[derive(Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct CandidateIndex {
+    pub candidates: Vec<CandidateProfile>,
+}
+
+/// A candidate's appearances across contests and elections. Candidates are
+/// matched by name, ignoring case and extra whitespace.
+#[derive(Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct CandidateProfile {
+    /// Name as it appears in the candidate's most recent contest.
+    pub name: String,
+    pub races_entered: u32,
+    pub wins: u32,
+    pub appearances: Vec<CandidateAppearance>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct CandidateAppearance {
+    /// Path of the contest's report, e.g. `us/ca/sfo/2019/11/mayor`.
+    pub contest_path: String,
+    pub date: String,
+    pub jurisdiction_name: String,
+    pub office_name: String,
+    pub first_round_votes: u32,
+    pub final_votes: u32,
+    /// Number of rounds the candidate was still in the count.
+    pub rounds_survived: u32,
+    pub won: bool,
+}
+
+/// Summary statistics for the contests reported in one jurisdiction.
+#[derive(Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct JurisdictionSummary {
+    pub path: String,
+    pub name: String,
+    pub elections: u32,
+    pub contests: u32,
+    pub average_rounds: f64,
+    /// Mean fraction of ballots exhausted by the final round.
+    pub average_exhaustion_rate: f64,
+    /// Fraction of contests won by the candidate leading the first round.
+    pub first_round_leader_win_rate: f64,
+}
+
+/// Per-election trends for each jurisdiction, written to `trends.json`.
+#[derive(Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct TrendIndex {
+    pub jurisdictions: Vec<JurisdictionTrend>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct JurisdictionTrend {
+    pub path: String,
+    pub name: String,
+    /// One entry per election, oldest first.
+    pub elections: Vec<ElectionTrendPoint>,
+}
+
+/// Averages over the contests of one election.
+#[derive(Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct ElectionTrendPoint {
+    pub election_path: String,
+    pub date: String,
+    pub contests: u32,
+    /// Mean number of candidates ranked per ballot, if known for every contest.
+    pub average_ranks: Option<f64>,
+    /// Mean fraction of ballots exhausted by the final round.
+    pub exhaustion_rate: f64,
+    /// Mean number of declared candidates per contest.
+    pub candidates_per_contest: f64,
+    /// Mean fraction of first-round votes cast for write-in candidates.
+    pub write_in_share: f64,
+}
@@ -1,4 +1,4 @@
-use crate::model::metadata::TabulationOptions;
+use crate::model::metadata::{ElectionStatus, TabulationOptions};
 use serde::de::{self, Visitor};
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use std::collections::BTreeMap;
@@ -42,7 +42,21 @@ impl<'de> Deserialize<'de> for CandidateId {
     }
 }
 
+/// Candidate ids are serialized as bare indices into the report's candidate
+/// list.
+#[cfg(feature = "schema")]
+impl schemars::JsonSchema for CandidateId {
+    fn schema_name() -> String {
+        "CandidateId".to_string()
+    }
+
+    fn json_schema(gen: &mut schemars::gen::SchemaGenerator) -> schemars::schema::Schema {
+        <u32 as schemars::JsonSchema>::json_schema(gen)
+    }
+}
+
 #[derive(Ord, PartialOrd, Eq, PartialEq, Clone, Serialize, Deserialize, Debug)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub enum CandidateType {
     WriteIn,
     Regular,
@@ -50,9 +64,13 @@ pub enum CandidateType {
 }
 
 #[derive(Ord, PartialOrd, Eq, PartialEq, Clone, Serialize, Deserialize, Debug)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct Candidate {
     pub name: String,
     pub candidate_type: CandidateType,
+    /// Display names by language code (e.g. `es`, `zh`), from metadata.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub names: Option<BTreeMap<String, String>>,
 }
 
 impl Candidate {
@@ -60,6 +78,7 @@ impl Candidate {
         Candidate {
             name,
             candidate_type,
+            names: None,
         }
     }
 }
@@ -71,23 +90,38 @@ pub enum Choice {
     Overvote,
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, PartialEq, Clone)]
 pub struct Ballot {
     pub id: String,
     pub choices: Vec<Choice>,
+    /// Part of the jurisdiction the ballot was cast in (e.g. an NYC borough),
+    /// if the format records one.
+    pub sub_jurisdiction: Option<String>,
 }
 
 impl Ballot {
     pub fn new(id: String, choices: Vec<Choice>) -> Ballot {
-        Ballot { id, choices }
+        Ballot {
+            id,
+            choices,
+            sub_jurisdiction: None,
+        }
+    }
+
+    pub fn with_sub_jurisdiction(mut self, sub_jurisdiction: Option<String>) -> Ballot {
+        self.sub_jurisdiction = sub_jurisdiction;
+        self
     }
 }
 
 #[derive(Debug, PartialEq, Serialize, Deserialize, Clone)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct NormalizedBallot {
     pub id: String,
     choices: VecDeque<CandidateId>,
     pub overvoted: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sub_jurisdiction: Option<String>,
 }
 
 impl NormalizedBallot {
@@ -96,10 +130,15 @@ impl NormalizedBallot {
             id,
             choices: choices.into(),
             overvoted,
+            sub_jurisdiction: None,
         }
     }
 
-    #[allow(unused)]
+    pub fn with_sub_jurisdiction(mut self, sub_jurisdiction: Option<String>) -> NormalizedBallot {
+        self.sub_jurisdiction = sub_jurisdiction;
+        self
+    }
+
     pub fn choices(&self) -> Vec<CandidateId> {
         self.choices.clone().into()
     }
@@ -123,9 +162,25 @@ impl NormalizedBallot {
     }
 }
 
+/// A row of raw data that was skipped because it couldn't be parsed, when
+/// the contest is read with `skipMalformedRows`. Rows and columns are 1-based,
+/// and rows count the header row.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct ParseIssue {
+    pub file: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sheet: Option<String>,
+    pub row: usize,
+    pub column: usize,
+    pub reason: String,
+}
+
 pub struct Election {
     pub candidates: Vec<Candidate>,
     pub ballots: Vec<Ballot>,
+    pub parse_issues: Vec<ParseIssue>,
 }
 
 impl Election {
@@ -133,17 +188,29 @@ impl Election {
         Election {
             candidates,
             ballots,
+            parse_issues: Vec::new(),
         }
     }
+
+    pub fn with_parse_issues(mut self, parse_issues: Vec<ParseIssue>) -> Election {
+        self.parse_issues = parse_issues;
+        self
+    }
 }
 
 #[derive(Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(rename_all = "camelCase")]
 pub struct NormalizedElection {
     pub candidates: Vec<Candidate>,
     pub ballots: Vec<NormalizedBallot>,
+    /// Rows skipped while reading the raw data.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub parse_issues: Vec<ParseIssue>,
 }
 
-#[derive(Serialize, Deserialize, Clone)]
+#[derive(Serialize, Deserialize, Clone, PartialEq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 #[serde(rename_all = "camelCase")]
 pub struct ElectionInfo {
     /// Name of election.
@@ -164,6 +231,10 @@ pub struct ElectionInfo {
 
     pub office_name: String,
 
+    /// Display names of the office by language code, from metadata.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub office_names: Option<BTreeMap<String, String>>,
+
     pub jurisdiction_name: String,
 
     pub election_name: String,
@@ -171,9 +242,24 @@ pub struct ElectionInfo {
     pub loader_params: Option<BTreeMap<String, String>>,
 
     pub website: Option<String>,
+
+    /// Status of the election's results, if recorded in metadata.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub status: Option<ElectionStatus>,
+
+    /// Ballots cast in the contest according to the official results, if
+    /// recorded in metadata.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub official_ballots: Option<u32>,
+
+    /// Batch of cast vote records the results are as of, if the election's
+    /// results are being released in drops.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub drop: Option<u32>,
 }
 
 #[derive(Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 #[serde(rename_all = "camelCase")]
 pub struct ElectionPreprocessed {
     pub info: ElectionInfo,
@@ -1,13 +1,17 @@
+use crate::tabulator::TabulationError;
 use serde::{Deserialize, Serialize};
 use std::collections::BTreeMap;
+use std::fmt;
+use std::str::FromStr;
 
 #[derive(Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 #[serde(rename_all = "camelCase")]
 /// Represents a body that oversees elections for one or more constituancies.
 pub struct Jurisdiction {
     /// Name of the district.
     pub name: String,
-    /// Path to the district, e.g. <country>/<state>/<city>.
+    /// Path to the district, e.g. `<country>/<state>/<city>`.
     pub path: String,
     /// Kind of electoral commission, e.g. municipal, state.
     pub kind: String,
@@ -18,14 +22,24 @@ pub struct Jurisdiction {
 }
 
 #[derive(Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 #[serde(rename_all = "camelCase")]
 /// Represents an elected office in this constituancy.
 pub struct Office {
     /// Name of the office.
     pub name: String,
+    /// Display names of the office by language code (e.g. `es`, `zh`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub names: Option<BTreeMap<String, String>>,
+    /// Ids the office had before being renamed with `rename-office`, oldest
+    /// first. They can't be reused by other offices, so old report URLs
+    /// keep resolving to this office.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub previous_ids: Vec<String>,
 }
 
 #[derive(Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 #[serde(rename_all = "camelCase")]
 pub struct ElectionMetadata {
     /// Name of election.
@@ -40,28 +54,294 @@ pub struct ElectionMetadata {
 
     pub normalization: String,
 
+    /// Name of a built-in rules profile (e.g. `maine`), which sets defaults
+    /// for the tabulation options that carry the jurisdiction's rules. See
+    /// `rules::RULES_PROFILES`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rules_profile: Option<String>,
+
     pub contests: Vec<Contest>,
 
     pub files: BTreeMap<String, String>,
 
+    /// Where each raw data file can be downloaded from (`https://` or
+    /// `s3://`), keyed by the same file names as `files`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sources: Option<BTreeMap<String, String>>,
+
+    /// If true, `sync` extracts the contents of ZIP archives in the raw data
+    /// directory next to the archive, so readers can use the files directly.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub extract_archives: Option<bool>,
+
     pub website: Option<String>,
+
+    /// Display names of candidates by language code, keyed by the
+    /// candidate's name as it appears in reports.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub candidate_names: Option<BTreeMap<String, BTreeMap<String, String>>>,
+
+    /// CSV file in the raw data directory giving numeric attributes (such as
+    /// demographics) of each precinct, keyed by precinct in the first
+    /// column. If given, reports with precinct results include ecological
+    /// estimates correlating results with these attributes.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub precinct_attributes: Option<String>,
+
+    /// If true, ballot ids from the raw data are replaced with salted hashes
+    /// when the election is read, so published ballot-level data doesn't
+    /// expose the original cast vote record ids. The salt is read from
+    /// `.ballot-id-salt` in the election's raw data directory.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub anonymize_ballot_ids: Option<bool>,
+
+    /// Whether the results are preliminary, updated or certified. Elections
+    /// without a status are treated as final.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub status: Option<ElectionStatus>,
+
+    /// Number of the latest batch ("drop") of cast vote records in the raw
+    /// data, for preliminary results released in batches as ballots are
+    /// counted. Each report run then records the results as of this drop.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub drop: Option<u32>,
+}
+
+/// Where an election's results are in the canvass, so the site can label
+/// results that may still change.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(rename_all = "camelCase")]
+pub enum ElectionStatus {
+    /// Unofficial results released on or soon after election night.
+    Preliminary,
+    /// Unofficial results updated since the first release.
+    Updated,
+    /// Official results certified by the election office.
+    Certified,
+}
+
+impl fmt::Display for ElectionStatus {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            ElectionStatus::Preliminary => "preliminary",
+            ElectionStatus::Updated => "updated",
+            ElectionStatus::Certified => "certified",
+        };
+        write!(f, "{}", name)
+    }
 }
 
-#[derive(Serialize, Deserialize, Clone)]
+impl FromStr for ElectionStatus {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "preliminary" => Ok(ElectionStatus::Preliminary),
+            "updated" => Ok(ElectionStatus::Updated),
+            "certified" => Ok(ElectionStatus::Certified),
+            _ => Err(format!(
+                "unknown status {:?}; expected preliminary, updated or certified",
+                s
+            )),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 #[serde(rename_all = "camelCase")]
 pub struct TabulationOptions {
+    /// If true, the instant-runoff count stops at the first round in which a
+    /// candidate has a majority of continuing ballots. Otherwise (the
+    /// default), candidates are eliminated until two remain.
     pub eager: Option<bool>,
+
+    /// If true, candidates are eliminated until two remain even if `eager`
+    /// is set, as in official reports that show the full elimination path.
+    /// The report's `majorityRound` still records when a candidate first had
+    /// a majority.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub continue_until_two: Option<bool>,
+
+    /// Counting method for the official result: `irv` (the default), or for
+    /// multi-winner contests counted by single transferable vote,
+    /// `stv_cincinnati` (whole-ballot Cincinnati-method surplus transfers)
+    /// or `stv_meek` (Meek's method, with fractional transfers).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub method: Option<String>,
+
+    /// Number of seats to fill, for multi-winner methods.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub seats: Option<u32>,
+
+    /// For STV, candidates with fewer votes than this after surplus
+    /// transfers are eliminated together (50 in Cambridge).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub eliminate_below: Option<u32>,
+
+    /// For STV, the quota a candidate needs to be elected: `droop` (the
+    /// default) or `hare`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub quota: Option<QuotaRule>,
+
+    /// For STV, whether the quota is fixed from the first count (`static`,
+    /// the default) or recalculated each round from the continuing ballots
+    /// (`recalculated`). Meek's method always recalculates it.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub quota_mode: Option<QuotaMode>,
+
+    /// Final-round margin, in votes, at or below which a contest is flagged
+    /// as close.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub close_margin_votes: Option<u32>,
+
+    /// Final-round margin, as a percentage of continuing ballots, at or below
+    /// which a contest is flagged as close. Defaults to 0.5% if neither
+    /// threshold is given.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub close_margin_percent: Option<f64>,
+
+    /// If true, the report includes the outcome of re-running the count
+    /// with each candidate removed in turn.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub alternative_outcomes: Option<bool>,
+
+    /// If true, the report includes winners under other single-winner
+    /// methods for comparison.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub method_comparison: Option<bool>,
+
+    /// Difference between the ballots read and a contest's `officialBallots`,
+    /// as a percentage of the official count, above which the contest is
+    /// flagged. Defaults to 0.1%.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ballot_count_tolerance_percent: Option<f64>,
+
+    /// Number of top rankings counted as approvals in the method
+    /// comparison. Defaults to 3.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub approval_ranks: Option<u32>,
+
+    /// If given, the report estimates how often the winner changes over
+    /// this many bootstrap resamples of the ballots.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub bootstrap_samples: Option<u32>,
+
+    /// Seed for drawing the bootstrap resamples. Defaults to 1.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub bootstrap_seed: Option<u64>,
+
+    /// How a ranking with more than one candidate is treated, as set by the
+    /// jurisdiction's statute. Defaults to `exhaust`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub overvote_policy: Option<OvervotePolicy>,
+
+    /// When skipped rankings exhaust a ballot. Defaults to the rule of the
+    /// election's `normalization`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub skipped_rank_policy: Option<SkippedRankPolicy>,
+}
+
+/// How normalization treats an overvote: a ranking marked for more than one
+/// candidate.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(rename_all = "camelCase")]
+pub enum OvervotePolicy {
+    /// The ballot is exhausted when the overvote is reached, as in San
+    /// Francisco and Maine.
+    #[default]
+    Exhaust,
+    /// The overvoted ranking is skipped, and the ballot counts for its next
+    /// ranked candidate. With the `maine` normalization, it counts as a
+    /// skipped ranking.
+    Skip,
+}
+
+/// The number of votes an STV count requires for election.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(rename_all = "camelCase")]
+pub enum QuotaRule {
+    /// One more than the votes divided by one more than the seats, the
+    /// fewest votes that only as many candidates as there are seats can
+    /// reach.
+    #[default]
+    Droop,
+    /// The votes divided by the seats, rounded up.
+    Hare,
+}
+
+impl QuotaRule {
+    /// The quota for filling `seats` seats with `votes` votes. Returns an
+    /// error if there are no seats to fill.
+    pub fn quota(self, votes: u32, seats: u32) -> Result<u32, TabulationError> {
+        if seats == 0 {
+            return Err(TabulationError::NoSeats);
+        }
+        Ok(match self {
+            QuotaRule::Droop => votes / (seats + 1) + 1,
+            QuotaRule::Hare => votes / seats + u32::from(!votes.is_multiple_of(seats)),
+        })
+    }
+}
+
+/// When an STV count computes its quota.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(rename_all = "camelCase")]
+pub enum QuotaMode {
+    /// Once, from the ballots with at least one ranking.
+    #[default]
+    Static,
+    /// Before each round's elections, from the votes still held by
+    /// candidates in the count, so exhausted ballots lower the quota.
+    Recalculated,
+}
+
+/// When skipped rankings (undervotes between ranked candidates) exhaust a
+/// ballot. Written as `"never"`, `"any"` or `{"consecutive": 2}`.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(rename_all = "camelCase")]
+pub enum SkippedRankPolicy {
+    /// Skipped rankings are ignored (the `simple` normalization).
+    Never,
+    /// Any skipped ranking exhausts the ballot.
+    Any,
+    /// This many skipped rankings in a row exhaust the ballot (2 in the
+    /// `maine` normalization).
+    Consecutive(u32),
+}
+
+/// The rules for overvotes and skipped rankings an election's ballots are
+/// normalized with.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct BallotRules {
+    pub overvote_policy: OvervotePolicy,
+    /// If `None`, the rule of the named normalization applies.
+    pub skipped_rank_policy: Option<SkippedRankPolicy>,
 }
 
-impl Default for TabulationOptions {
-    fn default() -> Self {
-        TabulationOptions { eager: Some(true) }
+impl TabulationOptions {
+    /// The overvote and skipped ranking policies in these options.
+    pub fn ballot_rules(&self) -> BallotRules {
+        BallotRules {
+            overvote_policy: self.overvote_policy.unwrap_or_default(),
+            skipped_rank_policy: self.skipped_rank_policy,
+        }
     }
 }
 
 #[derive(Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 #[serde(rename_all = "camelCase")]
 pub struct Contest {
     pub office: String,
     pub loader_params: Option<BTreeMap<String, String>>,
+    /// Ballots cast in the contest according to the official results, to
+    /// reconcile against the ballots read from the raw data.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub official_ballots: Option<u32>,
 }
@@ -1,3 +1,8 @@
+//! Data types shared across the pipeline: election metadata, ballots, and
+//! the report structures that are serialized for the website.
+
+pub mod aggregate;
 pub mod election;
 pub mod metadata;
 pub mod report;
+pub mod transfers;
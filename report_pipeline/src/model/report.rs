@@ -1,35 +1,57 @@
-use crate::model::election::{Candidate, CandidateId, ElectionInfo};
-use crate::tabulator::{Allocatee, TabulatorRound};
+use crate::model::aggregate::JurisdictionSummary;
+use crate::model::election::{Candidate, CandidateId, ElectionInfo, ParseIssue};
+use crate::model::metadata::{ElectionStatus, TabulationOptions};
+use crate::tabulator::{Allocatee, MethodResult, StvResult, TabulatorAllocation, TabulatorRound};
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 
 #[derive(Serialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 #[serde(rename_all = "camelCase")]
 pub struct ReportIndex {
     pub elections: Vec<ElectionIndexEntry>,
+    pub jurisdiction_summaries: Vec<JurisdictionSummary>,
 }
 
 #[derive(Serialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 #[serde(rename_all = "camelCase")]
 pub struct ElectionIndexEntry {
     pub path: String,
     pub jurisdiction_name: String,
     pub election_name: String,
     pub date: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub status: Option<ElectionStatus>,
     pub contests: Vec<ContestIndexEntry>,
 }
 
 #[derive(Serialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 #[serde(rename_all = "camelCase")]
 pub struct ContestIndexEntry {
     pub office: String,
+    /// Ids the office had before it was renamed, so links to them can be
+    /// redirected.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub previous_offices: Vec<String>,
     pub office_name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub office_names: Option<BTreeMap<String, String>>,
     pub name: String,
     pub winner: String,
+    /// Names of the declared candidates, for searching the index.
+    pub candidates: Vec<String>,
     pub num_candidates: u32,
     pub num_rounds: u32,
+    pub close_contest: bool,
+    pub tie_break_used: bool,
+    pub uncontested: bool,
+    pub ballot_count_discrepancy: bool,
 }
 
 #[derive(Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 #[serde(rename_all = "camelCase")]
 pub struct CandidateVotes {
     pub candidate: CandidateId,
@@ -39,6 +61,7 @@ pub struct CandidateVotes {
 }
 
 #[derive(Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 #[serde(rename_all = "camelCase")]
 pub struct CandidatePairEntry {
     pub frac: f32,
@@ -57,6 +80,7 @@ impl CandidatePairEntry {
 }
 
 #[derive(Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 #[serde(rename_all = "camelCase")]
 pub struct CandidatePairTable {
     pub rows: Vec<Allocatee>,
@@ -65,13 +89,22 @@ pub struct CandidatePairTable {
 }
 
 #[derive(Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 #[serde(rename_all = "camelCase")]
 pub struct ContestReport {
     pub info: ElectionInfo,
     pub ballot_count: u32,
+    /// Mean number of candidates ranked per ballot, after normalization.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub average_ranks: Option<f64>,
     pub candidates: Vec<Candidate>,
     pub rounds: Vec<TabulatorRound>,
     pub winner: CandidateId,
+    /// Multi-winner result, for contests counted by single transferable vote.
+    /// The single-winner fields then describe an instant-runoff count of the
+    /// same ballots, for comparison.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stv: Option<StvResult>,
     pub condorcet: Option<CandidateId>,
     pub num_candidates: u32,
     pub total_votes: Vec<CandidateVotes>,
@@ -79,10 +112,338 @@ pub struct ContestReport {
     pub first_alternate: CandidatePairTable,
     pub first_final: CandidatePairTable,
     pub smith_set: Vec<CandidateId>,
+    /// Final-round margin between the winner and the runner-up.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub margin_of_victory: Option<MarginOfVictory>,
+    /// True if the final-round margin is within the close-contest threshold.
+    #[serde(default)]
+    pub close_contest: bool,
+    /// True if the winner or any elimination was decided between candidates
+    /// with equal votes.
+    #[serde(default)]
+    pub tie_break_used: bool,
+    /// First round (from 1) in which the leading candidate had a majority
+    /// of continuing ballots. Later rounds only continue the count until two
+    /// candidates remain.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub majority_round: Option<u32>,
+    /// True if at most one declared candidate ran. The report then has a
+    /// single round.
+    #[serde(default)]
+    pub uncontested: bool,
+    /// First-choice and final-round tallies for each part of the
+    /// jurisdiction, if the ballots record where they were cast.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sub_jurisdiction_results: Option<Vec<SubJurisdictionResult>>,
+    /// Ballots in `ballot_count` that never counted for a declared candidate.
+    #[serde(default)]
+    pub invalid_ballots: InvalidBallotCounts,
+    /// The winner of the contest when each candidate is removed from every
+    /// ballot, if requested in the tabulation options.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub alternative_outcomes: Option<Vec<AlternativeOutcome>>,
+    /// Winners under other single-winner methods, if requested in the
+    /// tabulation options.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub method_comparison: Option<MethodComparison>,
+    /// How often the winner changes when the ballots are resampled, if
+    /// requested in the tabulation options.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sensitivity: Option<Sensitivity>,
+    /// Raw data rows skipped because they couldn't be parsed.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub parse_issues: Vec<ParseIssue>,
+    /// The inputs and code the report was generated from.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub provenance: Option<Provenance>,
+    /// Comparison of the ballots read with the official ballot count, if
+    /// metadata records one.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ballot_count_check: Option<BallotCountCheck>,
+    /// Correlations between precinct results and precinct attributes, if
+    /// the election's metadata lists a precinct attributes file.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ecological_estimates: Option<EcologicalEstimates>,
+}
+
+/// Correlations across precincts between precinct attributes (such as
+/// demographics) and results. These describe precincts, not voters: a
+/// correlation between an attribute and a candidate's support does not show
+/// that voters with that attribute supported the candidate.
+#[derive(Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct EcologicalEstimates {
+    /// Caveat to display alongside the estimates.
+    pub note: String,
+    /// Precincts with both results and attributes.
+    pub precincts: u32,
+    pub attributes: Vec<AttributeCorrelations>,
+}
+
+/// Pearson correlations, across precincts, between one attribute and each
+/// candidate's first-choice share and the share of ballots exhausted by the
+/// final round. A correlation is `None` if fewer than three precincts have
+/// the attribute or either side doesn't vary.
+#[derive(Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct AttributeCorrelations {
+    pub attribute: String,
+    pub precincts: u32,
+    pub first_choice: Vec<CandidateCorrelation>,
+    pub exhaustion: Option<f64>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct CandidateCorrelation {
+    pub candidate: CandidateId,
+    pub correlation: Option<f64>,
+}
+
+/// Everything needed to trace a report's numbers back to their inputs.
+#[derive(Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct Provenance {
+    /// The election's raw data files, with their SHA-1 hashes as recorded in
+    /// metadata.
+    pub files: BTreeMap<String, String>,
+    /// Reader (`dataFormat`) and loader parameters used to read the files.
+    pub data_format: String,
+    pub loader_params: Option<BTreeMap<String, String>>,
+    pub normalization: String,
+    pub tabulation_options: TabulationOptions,
+    /// Version of the pipeline, which is also the version of its readers.
+    pub pipeline_version: String,
+    /// Git revision the pipeline was built from, if known.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pipeline_revision: Option<String>,
+}
+
+/// Ballots read from the raw data compared with the officially reported
+/// number of ballots cast in the contest.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct BallotCountCheck {
+    pub official: u32,
+    pub counted: u32,
+    /// `counted` minus `official`.
+    pub difference: i64,
+    /// `difference` as a percentage of `official`.
+    pub percent: f64,
+    /// True if the difference is above the tolerance in the tabulation
+    /// options.
+    pub discrepancy: bool,
+}
+
+#[derive(Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct MethodComparison {
+    pub condorcet: Option<CandidateId>,
+    pub smith_set: Vec<CandidateId>,
+    pub plurality: MethodResult,
+    pub borda: MethodResult,
+    /// Number of top rankings counted as approvals.
+    pub approval_ranks: u32,
+    pub approval: MethodResult,
+}
+
+/// Bootstrap estimate of the winner's robustness: the share of resamples of
+/// the ballots (drawn with replacement, as many as were cast) whose winner
+/// differs from the actual winner.
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct Sensitivity {
+    pub samples: u32,
+    /// Seed of the random number generator, so the resamples can be
+    /// reproduced.
+    pub seed: u64,
+    /// How often the winners by the contest's tabulation method change.
+    /// Named for instant runoff, the default method.
+    pub irv_winner_change_probability: f64,
+    /// Ranked pairs winner of the actual ballots, if there is a single one.
+    pub ranked_pairs_winner: Option<CandidateId>,
+    pub ranked_pairs_winner_change_probability: f64,
+}
+
+/// The count of a contest with some candidates removed from every ballot.
+#[derive(Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct WhatIfResult {
+    pub eliminated: Vec<CandidateId>,
+    /// Winner of the count, if any candidate received votes.
+    pub winner: Option<CandidateId>,
+    pub rounds: Vec<TabulatorRound>,
+}
+
+/// A ranking on a ballot as it was read from the raw data.
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(rename_all = "camelCase")]
+pub enum RawChoice {
+    Candidate(CandidateId),
+    /// The ranking was left blank.
+    Undervote,
+    /// More than one candidate was marked at the ranking.
+    Overvote,
+}
+
+/// How one ballot was counted in a contest's instant-runoff count.
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct BallotLookup {
+    pub ballot_id: String,
+    /// The ballot's rankings as read from the raw data, if it was available.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub raw_choices: Option<Vec<RawChoice>>,
+    /// The normalized ranking that was counted.
+    pub choices: Vec<CandidateId>,
+    /// True if the normalized ranking ends at an overvote.
+    pub overvoted: bool,
+    /// Candidate the ballot counted for in each round, from the first round
+    /// until it was exhausted or the count ended.
+    pub counted_for: Vec<CandidateId>,
+    /// Round (from 1) in which the ballot was first counted as exhausted.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub exhausted_round: Option<u32>,
+    /// Candidate the ballot counted for in the final round.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub final_candidate: Option<CandidateId>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct AlternativeOutcome {
+    /// Candidate removed from every ballot.
+    pub removed: CandidateId,
+    /// Winner of the count without that candidate, if any candidate received votes.
+    pub winner: Option<CandidateId>,
+    /// True if the winner differs from the actual winner.
+    pub winner_changed: bool,
+}
+
+/// Counts of ballots that did not count towards any declared candidate in
+/// the first round, by reason. These ballots are included in the contest's
+/// ballot count and are exhausted from the first round.
+#[derive(Serialize, Deserialize, Default, Debug, PartialEq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct InvalidBallotCounts {
+    /// Ballots with no candidate ranked.
+    pub blank: u32,
+    /// Ballots whose first valid ranking is an overvote.
+    pub overvote: u32,
+    /// Ballots ranking only unqualified write-in candidates. These are still
+    /// tabulated as votes for the write-in.
+    pub write_in_only: u32,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct MarginOfVictory {
+    /// Difference in votes between the winner and the runner-up.
+    pub votes: u32,
+    /// `votes` as a percentage of continuing ballots in the final round.
+    pub percent: f64,
+    /// Minimum number of ballots that would have to change from the winner
+    /// to the runner-up for the runner-up to win the final round. This is
+    /// exact for the final round only; changing the order of earlier
+    /// eliminations may alter the winner with fewer ballots.
+    pub ballots_to_flip: u32,
+}
+
+#[derive(Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct SubJurisdictionResult {
+    pub name: String,
+    pub ballot_count: u32,
+    pub first_choice: Vec<TabulatorAllocation>,
+    pub final_round: Vec<TabulatorAllocation>,
+}
+
+/// Earlier versions of a contest's report, kept in `history.json` next to
+/// `report.json` when regenerating the report changed its results.
+#[derive(Serialize, Deserialize, Default)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct ReportHistory {
+    /// Replaced reports, oldest first.
+    pub revisions: Vec<ReportRevision>,
+    /// Results as of each batch of cast vote records, for elections whose
+    /// preliminary results are released in drops. Oldest first.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub drops: Vec<DropResult>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct ReportRevision {
+    /// When the report was replaced, in seconds since the Unix epoch.
+    pub replaced_at: u64,
+    /// How the results changed, e.g. `ballots: 1200 -> 1250`.
+    pub changes: Vec<String>,
+    /// The report as it was before it was replaced.
+    pub report: ContestReport,
+}
+
+/// A contest's results as of one drop of cast vote records, and how they
+/// changed since the previous drop. Candidates are keyed by name.
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct DropResult {
+    pub drop: u32,
+    /// When the drop was reported, in seconds since the Unix epoch.
+    pub recorded_at: u64,
+    pub winner: String,
+    pub ballot_count: u32,
+    pub first_round_votes: BTreeMap<String, u32>,
+    pub final_votes: BTreeMap<String, u32>,
+    /// Ballots added since the previous drop (all ballots, for the first).
+    pub ballots_added: i64,
+    /// Change in each candidate's first-round votes since the previous drop.
+    pub first_round_changes: BTreeMap<String, i64>,
+    /// Change in each candidate's final votes since the previous drop.
+    pub final_changes: BTreeMap<String, i64>,
 }
 
 impl ContestReport {
     pub fn winner(&self) -> &Candidate {
         &self.candidates[self.winner.0 as usize]
     }
+
+    /// True if the candidate won: for multi-winner contests, if they were
+    /// elected by STV.
+    pub fn is_winner(&self, candidate: CandidateId) -> bool {
+        match &self.stv {
+            Some(stv) => stv.winners.contains(&candidate),
+            None => candidate == self.winner,
+        }
+    }
+
+    /// Names of the winning candidates: the STV winners for multi-winner
+    /// contests, otherwise the instant-runoff winner.
+    pub fn winner_names(&self) -> String {
+        match &self.stv {
+            Some(stv) => stv
+                .winners
+                .iter()
+                .map(|c| self.candidates[c.0 as usize].name.as_str())
+                .collect::<Vec<_>>()
+                .join(", "),
+            None => self.winner().name.clone(),
+        }
+    }
 }
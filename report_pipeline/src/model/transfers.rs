@@ -0,0 +1,43 @@
+use crate::model::election::CandidateId;
+use serde::{Deserialize, Serialize};
+
+/// The flow of votes between rounds of a contest's count, written to
+/// `transfers.json` next to its report in the node-link shape D3's sankey
+/// layout takes as input.
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct TransferGraph {
+    /// One node per candidate per round, plus one per round for exhausted
+    /// ballots, ordered by round.
+    pub nodes: Vec<TransferNode>,
+    pub links: Vec<TransferLink>,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct TransferNode {
+    /// Round number, starting at 1.
+    pub round: u32,
+    /// The candidate, or `None` for ballots exhausted by this round.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub candidate: Option<CandidateId>,
+    /// Candidate name, or `Exhausted`.
+    pub name: String,
+    pub votes: u32,
+}
+
+/// Votes moving from a node in one round to a node in the next. A
+/// candidate's votes kept from one round to the next are a link to their own
+/// node in the next round.
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct TransferLink {
+    /// Index of the source node in `nodes`.
+    pub source: usize,
+    /// Index of the target node in `nodes`.
+    pub target: usize,
+    pub value: u32,
+}
@@ -1,41 +1,16 @@
-use crate::model::election::{Ballot, Choice, NormalizedBallot};
-use std::collections::BTreeSet;
+use crate::model::election::{Ballot, NormalizedBallot};
+use crate::model::metadata::SkippedRankPolicy;
+use crate::normalizers::rules::normalize_ballot;
+
+/// The skipped ranking rule of the `maine` normalization.
+pub const MAINE_SKIPPED_RANK_POLICY: SkippedRankPolicy = SkippedRankPolicy::Consecutive(2);
 
 pub fn maine_normalizer(ballot: Ballot) -> NormalizedBallot {
     // "Exhausted ballot" means a ballot that does not rank any continuing candidate,
     // contains an overvote at the highest continuing ranking or contains 2 or more
     // sequential skipped rankings before its highest continuing ranking.
     // [IB 2015, c. 3, §5 (NEW).]
-
-    let mut seen = BTreeSet::new();
-    let Ballot { id, choices } = ballot;
-    let mut new_choices = Vec::new();
-    let mut last_skipped = false;
-    let mut overvoted = false;
-
-    for choice in choices {
-        match choice {
-            Choice::Vote(v) => {
-                if !seen.contains(&v) {
-                    seen.insert(v);
-                    new_choices.push(v);
-                }
-                last_skipped = false;
-            }
-            Choice::Undervote => {
-                if last_skipped {
-                    break;
-                }
-                last_skipped = true;
-            }
-            Choice::Overvote => {
-                overvoted = true;
-                break;
-            }
-        }
-    }
-
-    NormalizedBallot::new(id, new_choices, overvoted)
+    normalize_ballot(ballot, MAINE_SKIPPED_RANK_POLICY)
 }
 
 #[cfg(test)]
@@ -1,11 +1,25 @@
+//! Ballot normalizers, which apply a jurisdiction's rules for overvotes,
+//! skipped rankings, and duplicate rankings to raw ballots. Overvotes are
+//! first treated according to the election's `OvervotePolicy`. The named
+//! normalizers differ only in their `SkippedRankPolicy`, which metadata can
+//! override.
+
+// The normalizers' tests compare flags with `assert_eq!(false, ...)`.
+#[cfg_attr(test, allow(clippy::bool_assert_comparison))]
 mod maine;
+mod rules;
+#[cfg_attr(test, allow(clippy::bool_assert_comparison))]
 mod simple;
 
-use crate::model::election::{Ballot, Election, NormalizedBallot, NormalizedElection};
+use crate::model::election::{Ballot, Choice, Election, NormalizedBallot, NormalizedElection};
+use crate::model::metadata::{BallotRules, OvervotePolicy, SkippedRankPolicy};
+pub use rules::normalize_ballot;
 
-type BallotNormalizer = dyn Fn(Ballot) -> NormalizedBallot;
+pub type BallotNormalizer = dyn Fn(Ballot) -> NormalizedBallot;
 
-fn get_normalizer_for_format(format: &str) -> &'static BallotNormalizer {
+/// Return the normalizer with the given name (as used in the `normalization`
+/// field of election metadata).
+pub fn get_normalizer_for_format(format: &str) -> &'static BallotNormalizer {
     match format {
         "simple" => &simple::simple_normalizer,
         "maine" => &maine::maine_normalizer,
@@ -13,12 +27,141 @@ fn get_normalizer_for_format(format: &str) -> &'static BallotNormalizer {
     }
 }
 
-pub fn normalize_election(format: &str, election: Election) -> NormalizedElection {
-    let normalizer = get_normalizer_for_format(format);
-    let ballots = election.ballots.into_iter().map(normalizer).collect();
+/// Return the skipped ranking rule of the normalizer with the given name.
+pub fn default_skipped_rank_policy(format: &str) -> SkippedRankPolicy {
+    match format {
+        "simple" => simple::SIMPLE_SKIPPED_RANK_POLICY,
+        "maine" => maine::MAINE_SKIPPED_RANK_POLICY,
+        _ => panic!("The normalizer {} is not implemented.", format),
+    }
+}
+
+/// Return the skipped ranking rule to normalize with: the one in `rules`, or
+/// else that of the named normalizer.
+pub fn skipped_rank_policy(format: &str, rules: &BallotRules) -> SkippedRankPolicy {
+    rules
+        .skipped_rank_policy
+        .unwrap_or_else(|| default_skipped_rank_policy(format))
+}
+
+/// Return true if there is a normalizer with the given name.
+pub fn has_normalizer(format: &str) -> bool {
+    matches!(format, "simple" | "maine")
+}
+
+/// Apply an overvote policy to a raw ballot before it is normalized. Under
+/// `Skip`, overvoted rankings are treated as skipped rankings; under
+/// `Exhaust`, they are left for the normalizer, which exhausts the ballot.
+pub fn apply_overvote_policy(ballot: Ballot, policy: OvervotePolicy) -> Ballot {
+    match policy {
+        OvervotePolicy::Exhaust => ballot,
+        OvervotePolicy::Skip => Ballot {
+            choices: ballot
+                .choices
+                .into_iter()
+                .map(|choice| match choice {
+                    Choice::Overvote => Choice::Undervote,
+                    choice => choice,
+                })
+                .collect(),
+            ..ballot
+        },
+    }
+}
+
+/// Normalize every ballot in an election with the named normalizer, after
+/// applying the overvote policy. A skipped ranking policy in `rules`
+/// replaces the normalizer's own.
+pub fn normalize_election(
+    format: &str,
+    rules: &BallotRules,
+    election: Election,
+) -> NormalizedElection {
+    let skipped_rank_policy = skipped_rank_policy(format, rules);
+    let ballots = election
+        .ballots
+        .into_iter()
+        .map(|ballot| {
+            normalize_ballot(
+                apply_overvote_policy(ballot, rules.overvote_policy),
+                skipped_rank_policy,
+            )
+        })
+        .collect();
 
     NormalizedElection {
         candidates: election.candidates,
         ballots,
+        parse_issues: election.parse_issues,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::election::CandidateId;
+
+    /// The same ballots normalized under each policy: an overvote in the
+    /// first, second and last ranking, and after a skipped ranking.
+    fn fixture() -> Vec<Ballot> {
+        let (a, b, c) = (
+            Choice::Vote(CandidateId(0)),
+            Choice::Vote(CandidateId(1)),
+            Choice::Vote(CandidateId(2)),
+        );
+        vec![
+            Ballot::new("1".into(), vec![Choice::Overvote, a, b]),
+            Ballot::new("2".into(), vec![a, Choice::Overvote, b]),
+            Ballot::new("3".into(), vec![a, b, Choice::Overvote]),
+            Ballot::new("4".into(), vec![Choice::Undervote, Choice::Overvote, c]),
+        ]
+    }
+
+    fn normalize(format: &str, policy: OvervotePolicy) -> Vec<(Vec<u32>, bool)> {
+        let normalizer = get_normalizer_for_format(format);
+        fixture()
+            .into_iter()
+            .map(|ballot| {
+                let ballot = normalizer(apply_overvote_policy(ballot, policy));
+                let choices = ballot.choices().iter().map(|c| c.0).collect();
+                (choices, ballot.overvoted)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_exhaust_overvote_policy() {
+        let expected = vec![
+            (vec![], true),
+            (vec![0], true),
+            (vec![0, 1], true),
+            (vec![], true),
+        ];
+        assert_eq!(expected, normalize("simple", OvervotePolicy::Exhaust));
+        assert_eq!(expected, normalize("maine", OvervotePolicy::Exhaust));
+    }
+
+    #[test]
+    fn test_skip_overvote_policy() {
+        assert_eq!(
+            vec![
+                (vec![0, 1], false),
+                (vec![0, 1], false),
+                (vec![0, 1], false),
+                (vec![2], false),
+            ],
+            normalize("simple", OvervotePolicy::Skip)
+        );
+        // Under Maine's rule, the skipped overvote and the skipped ranking
+        // before it are two sequential skips, which exhaust the ballot.
+        assert_eq!(
+            vec![
+                (vec![0, 1], false),
+                (vec![0, 1], false),
+                (vec![0, 1], false),
+                (vec![], false),
+            ],
+            normalize("maine", OvervotePolicy::Skip)
+        );
     }
 }
@@ -0,0 +1,87 @@
+use crate::model::election::{Ballot, Choice, NormalizedBallot};
+use crate::model::metadata::SkippedRankPolicy;
+use std::collections::BTreeSet;
+
+/// Normalize a ballot: drop repeated rankings of a candidate, exhaust the
+/// ballot at an overvote, and exhaust it where skipped rankings do under
+/// `skipped_rank_policy`. Rankings of an already ranked candidate are neither
+/// skips nor votes, but end a run of skips.
+pub fn normalize_ballot(
+    ballot: Ballot,
+    skipped_rank_policy: SkippedRankPolicy,
+) -> NormalizedBallot {
+    let max_skips = match skipped_rank_policy {
+        SkippedRankPolicy::Never => None,
+        SkippedRankPolicy::Any => Some(1),
+        SkippedRankPolicy::Consecutive(n) => Some(n.max(1)),
+    };
+
+    let mut seen = BTreeSet::new();
+    let Ballot {
+        id,
+        choices,
+        sub_jurisdiction,
+    } = ballot;
+    let mut new_choices = Vec::new();
+    let mut skipped = 0;
+    let mut overvoted = false;
+
+    for choice in choices {
+        match choice {
+            Choice::Vote(v) => {
+                if seen.insert(v) {
+                    new_choices.push(v);
+                }
+                skipped = 0;
+            }
+            Choice::Undervote => {
+                skipped += 1;
+                if max_skips.is_some_and(|max| skipped >= max) {
+                    break;
+                }
+            }
+            Choice::Overvote => {
+                overvoted = true;
+                break;
+            }
+        }
+    }
+
+    NormalizedBallot::new(id, new_choices, overvoted).with_sub_jurisdiction(sub_jurisdiction)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::election::CandidateId;
+
+    fn choices(policy: SkippedRankPolicy) -> Vec<CandidateId> {
+        let (a, b, c) = (
+            Choice::Vote(CandidateId(0)),
+            Choice::Vote(CandidateId(1)),
+            Choice::Vote(CandidateId(2)),
+        );
+        let ballot = Ballot::new(
+            "1".into(),
+            vec![
+                a,
+                Choice::Undervote,
+                b,
+                Choice::Undervote,
+                Choice::Undervote,
+                c,
+            ],
+        );
+        normalize_ballot(ballot, policy).choices()
+    }
+
+    #[test]
+    fn test_skipped_rank_policies() {
+        let (a, b, c) = (CandidateId(0), CandidateId(1), CandidateId(2));
+        assert_eq!(vec![a, b, c], choices(SkippedRankPolicy::Never));
+        assert_eq!(vec![a], choices(SkippedRankPolicy::Any));
+        assert_eq!(vec![a], choices(SkippedRankPolicy::Consecutive(1)));
+        assert_eq!(vec![a, b], choices(SkippedRankPolicy::Consecutive(2)));
+        assert_eq!(vec![a, b, c], choices(SkippedRankPolicy::Consecutive(3)));
+    }
+}
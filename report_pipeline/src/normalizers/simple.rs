@@ -1,5 +1,9 @@
-use crate::model::election::{Ballot, Choice, NormalizedBallot};
-use std::collections::BTreeSet;
+use crate::model::election::{Ballot, NormalizedBallot};
+use crate::model::metadata::SkippedRankPolicy;
+use crate::normalizers::rules::normalize_ballot;
+
+/// The skipped ranking rule of the `simple` normalization.
+pub const SIMPLE_SKIPPED_RANK_POLICY: SkippedRankPolicy = SkippedRankPolicy::Never;
 
 pub fn simple_normalizer(ballot: Ballot) -> NormalizedBallot {
     // In most jurisdiction, ranks are considered as follows:
@@ -7,28 +11,7 @@ pub fn simple_normalizer(ballot: Ballot) -> NormalizedBallot {
     // has not yet been eliminated. If the top-ranked candidate
     // is ambiguous (i.e. an overvote), consider the ballot
     // exhausted.
-    let mut seen = BTreeSet::new();
-    let Ballot { id, choices } = ballot;
-    let mut new_choices = Vec::new();
-    let mut overvoted = false;
-
-    for choice in choices {
-        match choice {
-            Choice::Vote(v) => {
-                if !seen.contains(&v) {
-                    seen.insert(v);
-                    new_choices.push(v);
-                }
-            }
-            Choice::Overvote => {
-                overvoted = true;
-                break;
-            }
-            _ => (),
-        }
-    }
-
-    NormalizedBallot::new(id, new_choices, overvoted)
+    normalize_ballot(ballot, SIMPLE_SKIPPED_RANK_POLICY)
 }
 
 #[cfg(test)]
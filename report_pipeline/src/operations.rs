@@ -0,0 +1,111 @@
+//! The operations log: an append-only record of the commands run against
+//! the data, with the hashes of the files each one read and wrote, so the
+//! provenance of published results can be audited.
+
+use crate::util::RecordedFiles;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs::{read_to_string, OpenOptions};
+use std::io::{self, Write};
+use std::path::Path;
+
+/// One run of a command, as recorded in the operations log.
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct OperationRecord {
+    /// Subcommand name, e.g. `report`.
+    pub command: String,
+    /// Command-line arguments, after the program name.
+    pub args: Vec<String>,
+    /// When the command started and finished, in seconds since the Unix
+    /// epoch.
+    pub started_at: u64,
+    pub finished_at: u64,
+    /// Files read, by path, with their tagged hashes.
+    pub inputs: BTreeMap<String, String>,
+    /// Files written, by path, with their tagged hashes.
+    pub outputs: BTreeMap<String, String>,
+}
+
+impl OperationRecord {
+    pub fn new(
+        command: &str,
+        args: Vec<String>,
+        started_at: u64,
+        finished_at: u64,
+        files: RecordedFiles,
+    ) -> OperationRecord {
+        OperationRecord {
+            command: command.to_string(),
+            args,
+            started_at,
+            finished_at,
+            inputs: files.inputs,
+            outputs: files.outputs,
+        }
+    }
+
+    /// True if the operation read or wrote a file whose path contains
+    /// `file`.
+    pub fn touches(&self, file: &str) -> bool {
+        self.inputs
+            .keys()
+            .chain(self.outputs.keys())
+            .any(|path| path.contains(file))
+    }
+}
+
+/// Append a record to the log at `path` as a line of JSON, creating the log
+/// if it doesn't exist. Existing records are never rewritten.
+pub fn append_operation(path: &Path, record: &OperationRecord) -> io::Result<()> {
+    let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+    let mut line = serde_json::to_string(record)?;
+    line.push('\n');
+    file.write_all(line.as_bytes())
+}
+
+/// Read every record in the log at `path`, oldest first.
+pub fn read_operations(path: &Path) -> Result<Vec<OperationRecord>, String> {
+    let contents = read_to_string(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+    contents
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(i, line)| {
+            serde_json::from_str(line)
+                .map_err(|e| format!("{}, line {}: {}", path.display(), i + 1, e))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_append_and_read_operations() {
+        let path = std::env::temp_dir().join(format!("operations_{}.jsonl", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+
+        let mut files = RecordedFiles::default();
+        files.inputs.insert(
+            "election-metadata/us/ca/sfo.json".into(),
+            "sha256:aa".into(),
+        );
+        files.outputs.insert(
+            "reports/us/ca/sfo/2019/11/mayor/report.json".into(),
+            "sha256:bb".into(),
+        );
+        let first = OperationRecord::new("report", vec!["report".into()], 1, 2, files);
+        let second = OperationRecord::new("update-status", vec![], 3, 3, RecordedFiles::default());
+        append_operation(&path, &first).unwrap();
+        append_operation(&path, &second).unwrap();
+
+        let records = read_operations(&path).unwrap();
+        assert_eq!(vec![first, second], records);
+        assert!(records[0].touches("sfo/2019/11/mayor"));
+        assert!(!records[1].touches("sfo"));
+
+        std::fs::remove_file(&path).unwrap();
+    }
+}
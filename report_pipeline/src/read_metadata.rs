@@ -1,16 +1,26 @@
+//! Loading of jurisdiction metadata files.
+
 use crate::model::metadata::Jurisdiction;
-use crate::util::{get_files_from_path, read_serialized};
+use crate::util::{get_files_from_path, record_outcome, try_read_serialized, Outcome};
 use colored::*;
 use std::path::{Path, PathBuf};
+use tracing::{error, info};
 
 /// Read all metadata files under the given directory (recursively) and return
-/// an iterator over the results.
+/// an iterator over the results. Files that can't be read or parsed are
+/// logged and skipped, so one bad file doesn't hold up the others.
 pub fn read_meta(path: &Path) -> impl Iterator<Item = (PathBuf, Jurisdiction)> {
     let files = get_files_from_path(path).unwrap();
 
-    files.into_iter().map(|file| {
-        eprintln!("File: {}", file.to_string_lossy().blue());
-        let ec = read_serialized(&file);
-        (file, ec)
+    files.into_iter().filter_map(|file| {
+        info!("File: {}", file.to_string_lossy().blue());
+        match try_read_serialized(&file) {
+            Ok(ec) => Some((file, ec)),
+            Err(err) => {
+                record_outcome(Outcome::DataError);
+                error!("Skipping metadata file {}", err);
+                None
+            }
+        }
     })
 }
@@ -1,25 +1,38 @@
-use crate::formats::read_election;
+//! Preprocessing of raw election data and generation of contest reports.
+
+use crate::anonymize::{anonymize_ballot_ids, read_ballot_id_salt};
+use crate::formats::{read_election, read_elections, FormatError, PrecinctAttributes};
 use crate::model::election::{
-    CandidateId, CandidateType, ElectionInfo, ElectionPreprocessed, NormalizedBallot,
+    Candidate, CandidateId, CandidateType, Election, ElectionInfo, ElectionPreprocessed,
+    NormalizedBallot,
+};
+use crate::model::metadata::{
+    Contest, ElectionMetadata, Jurisdiction, OvervotePolicy, SkippedRankPolicy, TabulationOptions,
+};
+use crate::model::report::{
+    AlternativeOutcome, AttributeCorrelations, BallotCountCheck, BallotLookup,
+    CandidateCorrelation, CandidatePairEntry, CandidatePairTable, CandidateVotes, ContestReport,
+    EcologicalEstimates, InvalidBallotCounts, MarginOfVictory, MethodComparison, Provenance,
+    SubJurisdictionResult, WhatIfResult,
 };
-use crate::model::metadata::{Contest, ElectionMetadata, Jurisdiction};
-use crate::model::report::{CandidatePairEntry, CandidatePairTable, CandidateVotes, ContestReport};
 use crate::normalizers::normalize_election;
-use crate::tabulator::{tabulate, Allocatee, TabulatorRound};
+use crate::rules::tabulation_options;
+use crate::sensitivity::{bootstrap_sensitivity, DEFAULT_BOOTSTRAP_SEED};
+use crate::tabulator::{
+    approval, borda, count_rounds, first_round, plurality, selected_tabulator, tabulate, Allocatee,
+    TabulationError, TabulationOutcome, TabulatorAllocation, TabulatorRound, IRV_METHOD,
+};
+use crate::util::hash_serialized;
 use colored::*;
-use std::collections::{BTreeMap, HashMap, HashSet};
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, BTreeSet};
 use std::path::Path;
+use tracing::info;
 
-pub fn winner(rounds: &[TabulatorRound]) -> CandidateId {
-    rounds
-        .last()
-        .unwrap()
-        .allocations
-        .first()
-        .unwrap()
-        .allocatee
-        .candidate_id()
-        .unwrap()
+/// The leader of the last round, or `None` if no candidate received votes.
+pub fn winner(rounds: &[TabulatorRound]) -> Option<CandidateId> {
+    rounds.last()?.allocations.first()?.allocatee.candidate_id()
 }
 
 pub fn total_votes(rounds: &[TabulatorRound]) -> Vec<CandidateVotes> {
@@ -67,12 +80,12 @@ pub fn total_votes(rounds: &[TabulatorRound]) -> Vec<CandidateVotes> {
 pub fn generate_pairwise_counts(
     candidates: &[CandidateId],
     ballots: &[NormalizedBallot],
-) -> HashMap<(CandidateId, CandidateId), u32> {
-    let mut preference_map: HashMap<(CandidateId, CandidateId), u32> = HashMap::new();
-    let all_candidates: HashSet<CandidateId> = candidates.iter().copied().collect();
+) -> BTreeMap<(CandidateId, CandidateId), u32> {
+    let mut preference_map: BTreeMap<(CandidateId, CandidateId), u32> = BTreeMap::new();
+    let all_candidates: BTreeSet<CandidateId> = candidates.iter().copied().collect();
 
     for ballot in ballots {
-        let mut above_ranked: HashSet<CandidateId> = HashSet::new();
+        let mut above_ranked: BTreeSet<CandidateId> = BTreeSet::new();
 
         for vote in ballot.choices() {
             for arc in &above_ranked {
@@ -96,7 +109,7 @@ pub fn generate_pairwise_counts(
 
 pub fn generate_pairwise_preferences(
     candidates: &[CandidateId],
-    preference_map: &HashMap<(CandidateId, CandidateId), u32>,
+    preference_map: &BTreeMap<(CandidateId, CandidateId), u32>,
 ) -> CandidatePairTable {
     let axis: Vec<Allocatee> = candidates
         .iter()
@@ -134,8 +147,8 @@ pub fn generate_first_alternate(
     candidates: &[CandidateId],
     ballots: &[NormalizedBallot],
 ) -> CandidatePairTable {
-    let mut first_choice_count: HashMap<CandidateId, u32> = HashMap::new();
-    let mut alternate_map: HashMap<(CandidateId, Allocatee), u32> = HashMap::new();
+    let mut first_choice_count: BTreeMap<CandidateId, u32> = BTreeMap::new();
+    let mut alternate_map: BTreeMap<(CandidateId, Allocatee), u32> = BTreeMap::new();
 
     for ballot in ballots {
         let choices = ballot.choices();
@@ -184,10 +197,10 @@ pub fn generate_first_alternate(
 pub fn generate_first_final(
     candidates: &[CandidateId],
     ballots: &[NormalizedBallot],
-    final_round_candidates: &HashSet<CandidateId>,
+    final_round_candidates: &BTreeSet<CandidateId>,
 ) -> CandidatePairTable {
-    let mut first_final: HashMap<(CandidateId, Allocatee), u32> = HashMap::new();
-    let mut first_total: HashMap<CandidateId, u32> = HashMap::new();
+    let mut first_final: BTreeMap<(CandidateId, Allocatee), u32> = BTreeMap::new();
+    let mut first_total: BTreeMap<CandidateId, u32> = BTreeMap::new();
 
     for ballot in ballots {
         let choices = ballot.choices();
@@ -247,9 +260,9 @@ pub fn generate_first_final(
 
 pub fn graph(
     candidates: &[CandidateId],
-    preference_map: &HashMap<(CandidateId, CandidateId), u32>,
-) -> HashMap<CandidateId, Vec<CandidateId>> {
-    let mut graph = HashMap::new();
+    preference_map: &BTreeMap<(CandidateId, CandidateId), u32>,
+) -> BTreeMap<CandidateId, Vec<CandidateId>> {
+    let mut graph = BTreeMap::new();
 
     for c1 in candidates {
         for c2 in candidates {
@@ -267,12 +280,12 @@ pub fn graph(
 
 pub fn smith_set(
     candidates: &[CandidateId],
-    graph: &HashMap<CandidateId, Vec<CandidateId>>,
-) -> HashSet<CandidateId> {
-    let mut last_set: HashSet<CandidateId> = candidates.iter().cloned().collect();
+    graph: &BTreeMap<CandidateId, Vec<CandidateId>>,
+) -> BTreeSet<CandidateId> {
+    let mut last_set: BTreeSet<CandidateId> = candidates.iter().cloned().collect();
 
     loop {
-        let this_set: HashSet<CandidateId> = last_set
+        let this_set: BTreeSet<CandidateId> = last_set
             .iter()
             .flat_map(|d| graph.get(d).cloned().unwrap_or_default())
             .collect();
@@ -287,40 +300,556 @@ pub fn smith_set(
     last_set
 }
 
-/// Generate a `ContestReport` from preprocessed election data.
-pub fn generate_report(election: &ElectionPreprocessed) -> ContestReport {
-    let ballots = &election.ballots.ballots;
-    let rounds = tabulate(ballots);
+/// Ranked pairs (Tideman) winner. Pairwise majorities are locked in from
+/// strongest to weakest (by votes for the majority, then fewest votes
+/// against), skipping any that would create a cycle; the winner is the
+/// candidate no locked majority beats. Returns `None` if that isn't a single
+/// candidate, as when tied pairs leave several unbeaten.
+pub fn ranked_pairs(
+    candidates: &[CandidateId],
+    preference_map: &BTreeMap<(CandidateId, CandidateId), u32>,
+) -> Option<CandidateId> {
+    let mut majorities: Vec<(u32, u32, CandidateId, CandidateId)> = Vec::new();
+    for c1 in candidates {
+        for c2 in candidates {
+            let c1v = *preference_map.get(&(*c1, *c2)).unwrap_or(&0);
+            let c2v = *preference_map.get(&(*c2, *c1)).unwrap_or(&0);
+            if c1v > c2v {
+                majorities.push((c1v, c2v, *c1, *c2));
+            }
+        }
+    }
+    majorities.sort_by_key(|(wins, losses, c1, c2)| (std::cmp::Reverse(*wins), *losses, *c1, *c2));
+
+    let mut locked: BTreeMap<CandidateId, Vec<CandidateId>> = BTreeMap::new();
+    let reaches =
+        |locked: &BTreeMap<CandidateId, Vec<CandidateId>>, from: CandidateId, to: CandidateId| {
+            let mut stack = vec![from];
+            let mut seen = BTreeSet::new();
+            while let Some(c) = stack.pop() {
+                if c == to {
+                    return true;
+                }
+                if seen.insert(c) {
+                    stack.extend(locked.get(&c).into_iter().flatten().copied());
+                }
+            }
+            false
+        };
+    for (_, _, winner, loser) in majorities {
+        if !reaches(&locked, loser, winner) {
+            locked.entry(winner).or_default().push(loser);
+        }
+    }
+
+    let beaten: BTreeSet<CandidateId> = locked.values().flatten().copied().collect();
+    let mut unbeaten = candidates.iter().filter(|c| !beaten.contains(*c));
+    match (unbeaten.next(), unbeaten.next()) {
+        (Some(winner), None) => Some(*winner),
+        _ => None,
+    }
+}
+
+fn allocation_vec(counts: BTreeMap<Allocatee, u32>) -> Vec<TabulatorAllocation> {
+    let mut allocations: Vec<TabulatorAllocation> = counts
+        .into_iter()
+        .map(|(allocatee, votes)| TabulatorAllocation { allocatee, votes })
+        .collect();
+    // Candidates in descending order of votes, followed by exhausted ballots.
+    allocations.sort_by_key(|a| match a.allocatee {
+        Allocatee::Candidate(_) => -(a.votes as i64),
+        Allocatee::Exhausted => 1,
+    });
+    allocations
+}
+
+/// Break down first-choice and final-round tallies by the sub-jurisdiction
+/// each ballot was cast in. Returns `None` if no ballot records one.
+pub fn sub_jurisdiction_results(
+    ballots: &[NormalizedBallot],
+    final_round_candidates: &BTreeSet<CandidateId>,
+) -> Option<Vec<SubJurisdictionResult>> {
+    let mut first: BTreeMap<&str, BTreeMap<Allocatee, u32>> = BTreeMap::new();
+    let mut last: BTreeMap<&str, BTreeMap<Allocatee, u32>> = BTreeMap::new();
+    let mut counts: BTreeMap<&str, u32> = BTreeMap::new();
+
+    for ballot in ballots {
+        let name = match &ballot.sub_jurisdiction {
+            Some(name) => name.as_str(),
+            None => continue,
+        };
+        let choices = ballot.choices();
+
+        let first_choice = choices
+            .first()
+            .map(|c| Allocatee::Candidate(*c))
+            .unwrap_or(Allocatee::Exhausted);
+        let final_choice = choices
+            .iter()
+            .find(|c| final_round_candidates.contains(c))
+            .map(|c| Allocatee::Candidate(*c))
+            .unwrap_or(Allocatee::Exhausted);
+
+        *first
+            .entry(name)
+            .or_default()
+            .entry(first_choice)
+            .or_default() += 1;
+        *last
+            .entry(name)
+            .or_default()
+            .entry(final_choice)
+            .or_default() += 1;
+        *counts.entry(name).or_default() += 1;
+    }
+
+    if counts.is_empty() {
+        return None;
+    }
+
+    Some(
+        counts
+            .into_iter()
+            .map(|(name, ballot_count)| SubJurisdictionResult {
+                name: name.to_string(),
+                ballot_count,
+                first_choice: allocation_vec(first.remove(name).unwrap()),
+                final_round: allocation_vec(last.remove(name).unwrap()),
+            })
+            .collect(),
+    )
+}
+
+/// Default tolerance for the difference between the ballots read and the
+/// official ballot count, as a percentage of the official count.
+const DEFAULT_BALLOT_COUNT_TOLERANCE_PERCENT: f64 = 0.1;
+
+/// Compare the number of ballots read with the official count, if there is
+/// one, flagging a difference above the tolerance in `options`.
+pub fn ballot_count_check(
+    counted: u32,
+    official: Option<u32>,
+    options: &TabulationOptions,
+) -> Option<BallotCountCheck> {
+    let official = official?;
+    let difference = counted as i64 - official as i64;
+    let percent = if official == 0 {
+        if difference == 0 {
+            0.
+        } else {
+            100.
+        }
+    } else {
+        difference as f64 / official as f64 * 100.
+    };
+    let tolerance = options
+        .ballot_count_tolerance_percent
+        .unwrap_or(DEFAULT_BALLOT_COUNT_TOLERANCE_PERCENT);
+
+    Some(BallotCountCheck {
+        official,
+        counted,
+        difference,
+        percent,
+        discrepancy: percent.abs() > tolerance,
+    })
+}
+
+/// Caveat attached to ecological estimates in reports.
+const ECOLOGICAL_NOTE: &str = "Ecological estimates: correlations across precincts, \
+    not the behavior of individual voters.";
+
+/// Pearson correlation of paired values, or `None` if there are fewer than
+/// three pairs or either side is constant.
+fn correlation(pairs: &[(f64, f64)]) -> Option<f64> {
+    if pairs.len() < 3 {
+        return None;
+    }
+    // Checked directly, since the variance of a constant side may not come
+    // out as exactly zero.
+    let (x0, y0) = pairs[0];
+    if pairs.iter().all(|p| p.0 == x0) || pairs.iter().all(|p| p.1 == y0) {
+        return None;
+    }
+    let n = pairs.len() as f64;
+    let mean_x = pairs.iter().map(|p| p.0).sum::<f64>() / n;
+    let mean_y = pairs.iter().map(|p| p.1).sum::<f64>() / n;
+    let (mut cov, mut var_x, mut var_y) = (0., 0., 0.);
+    for (x, y) in pairs {
+        cov += (x - mean_x) * (y - mean_y);
+        var_x += (x - mean_x).powi(2);
+        var_y += (y - mean_y).powi(2);
+    }
+    Some(cov / (var_x * var_y).sqrt())
+}
+
+/// Correlate each precinct attribute with each candidate's first-choice
+/// share and the share of ballots exhausted by the final round, across the
+/// precincts in `results` that have attributes. Returns `None` if no
+/// precinct has attributes.
+pub fn ecological_estimates(
+    results: &[SubJurisdictionResult],
+    candidates: &[CandidateId],
+    attributes: &PrecinctAttributes,
+) -> Option<EcologicalEstimates> {
+    let share = |allocations: &[TabulatorAllocation], allocatee: Allocatee, ballots: u32| {
+        let votes = allocations
+            .iter()
+            .find(|a| a.allocatee == allocatee)
+            .map_or(0, |a| a.votes);
+        votes as f64 / ballots as f64
+    };
+    let precincts: Vec<(&SubJurisdictionResult, &BTreeMap<String, f64>)> = results
+        .iter()
+        .filter(|r| r.ballot_count > 0)
+        .filter_map(|r| attributes.get(&r.name).map(|a| (r, a)))
+        .collect();
+    if precincts.is_empty() {
+        return None;
+    }
+
+    let names: BTreeSet<&String> = precincts.iter().flat_map(|(_, a)| a.keys()).collect();
+    let attributes = names
+        .into_iter()
+        .map(|name| {
+            let with_value: Vec<(&SubJurisdictionResult, f64)> = precincts
+                .iter()
+                .filter_map(|(r, a)| a.get(name).map(|v| (*r, *v)))
+                .collect();
+            let pairs = |y: &dyn Fn(&SubJurisdictionResult) -> f64| -> Vec<(f64, f64)> {
+                with_value.iter().map(|(r, v)| (*v, y(r))).collect()
+            };
+
+            AttributeCorrelations {
+                attribute: name.clone(),
+                precincts: with_value.len() as u32,
+                first_choice: candidates
+                    .iter()
+                    .map(|c| CandidateCorrelation {
+                        candidate: *c,
+                        correlation: correlation(&pairs(&|r| {
+                            share(&r.first_choice, Allocatee::Candidate(*c), r.ballot_count)
+                        })),
+                    })
+                    .collect(),
+                exhaustion: correlation(&pairs(&|r| {
+                    share(&r.final_round, Allocatee::Exhausted, r.ballot_count)
+                })),
+            }
+        })
+        .collect();
+
+    Some(EcologicalEstimates {
+        note: ECOLOGICAL_NOTE.to_string(),
+        precincts: precincts.len() as u32,
+        attributes,
+    })
+}
+
+/// Mean number of candidates ranked per ballot, or `None` if there are no
+/// ballots.
+pub fn average_ranks(ballots: &[NormalizedBallot]) -> Option<f64> {
+    if ballots.is_empty() {
+        return None;
+    }
+    let ranks: usize = ballots.iter().map(|b| b.choices().len()).sum();
+    Some(ranks as f64 / ballots.len() as f64)
+}
+
+/// Count ballots that never counted for a declared candidate, by reason.
+pub fn invalid_ballots(
+    candidates: &[Candidate],
+    ballots: &[NormalizedBallot],
+) -> InvalidBallotCounts {
+    let mut counts = InvalidBallotCounts::default();
+
+    for ballot in ballots {
+        let choices = ballot.choices();
+        if choices.is_empty() {
+            if ballot.overvoted {
+                counts.overvote += 1;
+            } else {
+                counts.blank += 1;
+            }
+        } else if choices
+            .iter()
+            .all(|c| candidates[c.0 as usize].candidate_type == CandidateType::WriteIn)
+        {
+            counts.write_in_only += 1;
+        }
+    }
+
+    counts
+}
+
+/// Re-run the count once for each candidate with that candidate removed from
+/// every ballot, recording whether the winner changes.
+pub fn alternative_outcomes(
+    candidates: &[CandidateId],
+    ballots: &[NormalizedBallot],
+    winner: CandidateId,
+) -> Vec<AlternativeOutcome> {
+    candidates
+        .iter()
+        .map(|removed| {
+            let alternative_winner = what_if(ballots, &[*removed]).winner;
+
+            AlternativeOutcome {
+                removed: *removed,
+                winner: alternative_winner,
+                winner_changed: alternative_winner != Some(winner),
+            }
+        })
+        .collect()
+}
+
+/// Re-run the instant-runoff count as if the `eliminated` candidates had
+/// dropped out before the election: they are removed from every ballot, and
+/// their voters' next choices count instead.
+pub fn what_if(ballots: &[NormalizedBallot], eliminated: &[CandidateId]) -> WhatIfResult {
+    let remaining: Vec<NormalizedBallot> = ballots
+        .iter()
+        .map(|b| {
+            let choices = b
+                .choices()
+                .into_iter()
+                .filter(|c| !eliminated.contains(c))
+                .collect();
+            NormalizedBallot::new(b.id.clone(), choices, b.overvoted)
+        })
+        .collect();
+
+    let rounds = tabulate(&remaining);
     let winner = winner(&rounds);
+
+    WhatIfResult {
+        eliminated: eliminated.to_vec(),
+        winner,
+        rounds,
+    }
+}
+
+/// Check that `rules` select instant runoff. `what_if` and `lookup_ballot`
+/// follow instant-runoff rounds, so they don't describe a contest counted by
+/// another method.
+pub fn require_irv(rules: &TabulationOptions) -> Result<(), TabulationError> {
+    match selected_tabulator(rules)?.name() {
+        IRV_METHOD => Ok(()),
+        method => Err(TabulationError::NotIrv { method }),
+    }
+}
+
+/// Follow one ballot through the rounds of a contest's instant-runoff count:
+/// in each round it counts for its highest-ranked candidate who hasn't been
+/// eliminated, until it has none left. Candidates are eliminated when they
+/// drop out of a round's allocations.
+pub fn lookup_ballot(ballot: &NormalizedBallot, rounds: &[TabulatorRound]) -> BallotLookup {
+    let mut seen: BTreeSet<CandidateId> = BTreeSet::new();
+    let mut counted_for = Vec::new();
+    let mut exhausted_round = None;
+
+    for (i, round) in rounds.iter().enumerate() {
+        let continuing: BTreeSet<CandidateId> = round
+            .allocations
+            .iter()
+            .filter_map(|a| a.allocatee.candidate_id())
+            .collect();
+        seen.extend(continuing.iter().copied());
+        let choice = ballot
+            .choices()
+            .into_iter()
+            .find(|c| continuing.contains(c) || !seen.contains(c));
+        match choice {
+            Some(c) => counted_for.push(c),
+            None => {
+                exhausted_round = Some(i as u32 + 1);
+                break;
+            }
+        }
+    }
+
+    BallotLookup {
+        ballot_id: ballot.id.clone(),
+        raw_choices: None,
+        choices: ballot.choices(),
+        overvoted: ballot.overvoted,
+        final_candidate: if exhausted_round.is_none() {
+            counted_for.last().copied()
+        } else {
+            None
+        },
+        counted_for,
+        exhausted_round,
+    }
+}
+
+/// Default number of top rankings counted as approvals in the method comparison.
+const DEFAULT_APPROVAL_RANKS: u32 = 3;
+
+/// Default close-contest threshold, as a percentage of continuing ballots in
+/// the final round, used when the metadata does not specify one.
+const DEFAULT_CLOSE_MARGIN_PERCENT: f64 = 0.5;
+
+fn candidate_votes(round: &TabulatorRound) -> Vec<(CandidateId, u32)> {
+    round
+        .allocations
+        .iter()
+        .flat_map(|a| a.allocatee.candidate_id().map(|c| (c, a.votes)))
+        .collect()
+}
+
+/// Compute the margin between the top two candidates in the final round,
+/// or `None` if fewer than two candidates received votes.
+pub fn margin_of_victory(rounds: &[TabulatorRound]) -> Option<MarginOfVictory> {
+    let final_round = rounds.last().unwrap();
+    let mut votes: Vec<u32> = candidate_votes(final_round)
+        .into_iter()
+        .map(|(_, v)| v)
+        .collect();
+    votes.sort_unstable_by(|a, b| b.cmp(a));
+
+    let margin = match votes.as_slice() {
+        [first, second, ..] => first - second,
+        _ => return None,
+    };
+    let continuing = final_round.continuing_ballots;
+
+    Some(MarginOfVictory {
+        votes: margin,
+        percent: if continuing > 0 {
+            (margin as f64) * 100. / (continuing as f64)
+        } else {
+            0.
+        },
+        // Moving a ballot from the winner to the runner-up narrows the margin
+        // by two, so the runner-up overtakes the winner after this many.
+        ballots_to_flip: margin / 2 + 1,
+    })
+}
+
+/// Return true if the final-round margin is within the thresholds given in
+/// `options`.
+pub fn is_close_contest(margin: Option<&MarginOfVictory>, options: &TabulationOptions) -> bool {
+    let margin = match margin {
+        Some(margin) => margin,
+        None => return false,
+    };
+
+    let percent_threshold = match (options.close_margin_votes, options.close_margin_percent) {
+        (None, None) => Some(DEFAULT_CLOSE_MARGIN_PERCENT),
+        (_, p) => p,
+    };
+
+    options
+        .close_margin_votes
+        .is_some_and(|t| margin.votes <= t)
+        || percent_threshold.is_some_and(|t| margin.percent <= t)
+}
+
+/// Return true if any elimination, or the final result, separated candidates
+/// who had the same number of votes.
+pub fn tie_break_used(rounds: &[TabulatorRound]) -> bool {
+    for (prev, round) in rounds.iter().zip(rounds.iter().skip(1)) {
+        let eliminated: BTreeSet<CandidateId> = round.transfers.iter().map(|t| t.from).collect();
+        let (out, kept): (Vec<_>, Vec<_>) = candidate_votes(prev)
+            .into_iter()
+            .partition(|(c, _)| eliminated.contains(c));
+
+        let max_out = out.iter().map(|(_, v)| *v).max();
+        let min_kept = kept.iter().map(|(_, v)| *v).min();
+        if let (Some(a), Some(b)) = (max_out, min_kept) {
+            if a == b {
+                return true;
+            }
+        }
+    }
+
+    let mut last: Vec<u32> = candidate_votes(rounds.last().unwrap())
+        .into_iter()
+        .map(|(_, v)| v)
+        .collect();
+    last.sort_unstable_by(|a, b| b.cmp(a));
+    matches!(last.as_slice(), [first, second, ..] if first == second)
+}
+
+/// Return the first round (from 1) in which the leading candidate had a
+/// majority of continuing ballots, if any did.
+pub fn majority_round(rounds: &[TabulatorRound]) -> Option<u32> {
+    rounds
+        .iter()
+        .position(|round| {
+            round
+                .allocations
+                .iter()
+                .find(|a| a.allocatee.candidate_id().is_some())
+                .is_some_and(|a| a.votes >= round.threshold)
+        })
+        .map(|i| i as u32 + 1)
+}
+
+/// True if any ballot ranks a candidate, so that the contest has a winner.
+pub fn has_votes(ballots: &[NormalizedBallot]) -> bool {
+    ballots.iter().any(|b| !b.choices().is_empty())
+}
+
+/// Generate a `ContestReport` from preprocessed election data, counted by
+/// the method its tabulation options select. Returns an error if they don't
+/// describe a count that can be run, or if no ballot ranks a candidate (see
+/// `has_votes`).
+pub fn generate_report(election: &ElectionPreprocessed) -> Result<ContestReport, TabulationError> {
+    let ballots = &election.ballots.ballots;
+    let options = &election.info.tabulation_options;
+    if !has_votes(ballots) {
+        return Err(TabulationError::NoVotes);
+    }
     let num_candidates = election
         .ballots
         .candidates
         .iter()
         .filter(|d| d.candidate_type != CandidateType::WriteIn)
         .count() as u32;
+    // With at most one declared candidate, the first round decides the
+    // contest; eliminations would only move write-in votes around.
+    let uncontested = num_candidates <= 1;
+
+    // The single-winner sections always describe an instant-runoff count;
+    // another method's result is reported alongside it.
+    let first = first_round(ballots);
+    let counted: Vec<CandidateId> = first
+        .allocations
+        .iter()
+        .flat_map(|a| a.allocatee.candidate_id())
+        .collect();
+    let (rounds, stv) = match selected_tabulator(options)?.tabulate(ballots, &counted, options)? {
+        TabulationOutcome::Rounds(_) if uncontested => (vec![first], None),
+        TabulationOutcome::Rounds(rounds) => (rounds, None),
+        TabulationOutcome::Stv(result) if uncontested => (vec![first], Some(result)),
+        TabulationOutcome::Stv(result) => (count_rounds(ballots, options), Some(result)),
+    };
+    let winner = winner(&rounds).ok_or(TabulationError::NoVotes)?;
 
     let total_votes = total_votes(&rounds);
     let candidates: Vec<CandidateId> = total_votes.iter().map(|d| d.candidate).collect();
 
-    let pairwise_counts: HashMap<(CandidateId, CandidateId), u32> =
+    let pairwise_counts: BTreeMap<(CandidateId, CandidateId), u32> =
         generate_pairwise_counts(&candidates, ballots);
 
     let pairwise_preferences = generate_pairwise_preferences(&candidates, &pairwise_counts);
     let graph = graph(&candidates, &pairwise_counts);
-    let smith_set = smith_set(&candidates, &graph);
+    let smith_set: Vec<CandidateId> = smith_set(&candidates, &graph).into_iter().collect();
     let condorcet = if smith_set.len() == 1 {
-        smith_set.iter().next().copied()
+        smith_set.first().copied()
     } else {
         None
     };
 
     if Some(winner) != condorcet {
-        eprintln!("{}", "Non-condorcet!".purple());
+        info!("{}", "Non-condorcet!".purple());
     }
 
     let first_alternate = generate_first_alternate(&candidates, ballots);
 
-    let final_round_candidates: HashSet<CandidateId> = rounds
+    let final_round_candidates: BTreeSet<CandidateId> = rounds
         .last()
         .unwrap()
         .allocations
@@ -329,20 +858,258 @@ pub fn generate_report(election: &ElectionPreprocessed) -> ContestReport {
         .collect();
 
     let first_final = generate_first_final(&candidates, ballots, &final_round_candidates);
+    let sub_jurisdiction_results = sub_jurisdiction_results(ballots, &final_round_candidates);
+    let invalid_ballots = invalid_ballots(&election.ballots.candidates, ballots);
+    let alternative_outcomes =
+        if election.info.tabulation_options.alternative_outcomes == Some(true) {
+            Some(alternative_outcomes(&candidates, ballots, winner))
+        } else {
+            None
+        };
+
+    let margin_of_victory = margin_of_victory(&rounds);
+    let close_contest = is_close_contest(
+        margin_of_victory.as_ref(),
+        &election.info.tabulation_options,
+    );
+    let tie_break_used = tie_break_used(&rounds);
+    let majority_round = majority_round(&rounds);
+    if close_contest || tie_break_used {
+        info!("{}", "Close contest!".yellow());
+    }
+
+    let ballot_count_check = ballot_count_check(
+        ballots.len() as u32,
+        election.info.official_ballots,
+        options,
+    );
+    if ballot_count_check.as_ref().is_some_and(|c| c.discrepancy) {
+        info!("{}", "Ballot count differs from official count!".yellow());
+    }
+
+    let sensitivity = match options.bootstrap_samples {
+        Some(samples) if !uncontested => Some(bootstrap_sensitivity(
+            &candidates,
+            ballots,
+            options,
+            samples,
+            options.bootstrap_seed.unwrap_or(DEFAULT_BOOTSTRAP_SEED),
+        )?),
+        _ => None,
+    };
+
+    let method_comparison = if options.method_comparison == Some(true) {
+        let approval_ranks = options.approval_ranks.unwrap_or(DEFAULT_APPROVAL_RANKS);
+        Some(MethodComparison {
+            condorcet,
+            smith_set: smith_set.clone(),
+            plurality: plurality(&candidates, ballots),
+            borda: borda(&candidates, ballots),
+            approval_ranks,
+            approval: approval(&candidates, ballots, approval_ranks),
+        })
+    } else {
+        None
+    };
 
-    ContestReport {
+    Ok(ContestReport {
         info: election.info.clone(),
         ballot_count: election.ballots.ballots.len() as u32,
+        average_ranks: average_ranks(ballots),
         candidates: election.ballots.candidates.clone(),
         winner,
+        stv,
         num_candidates,
         rounds,
         total_votes,
         pairwise_preferences,
         first_alternate,
         first_final,
-        smith_set: smith_set.into_iter().collect(),
+        smith_set,
         condorcet,
+        margin_of_victory,
+        close_contest,
+        tie_break_used,
+        majority_round,
+        uncontested,
+        sub_jurisdiction_results,
+        invalid_ballots,
+        alternative_outcomes,
+        method_comparison,
+        sensitivity,
+        parse_issues: election.ballots.parse_issues.clone(),
+        provenance: None,
+        ballot_count_check,
+        ecological_estimates: None,
+    })
+}
+
+/// Build the `ElectionInfo` describing a contest from its metadata.
+pub fn election_info(
+    metadata: &ElectionMetadata,
+    election_path: &str,
+    ec: &Jurisdiction,
+    contest: &Contest,
+) -> ElectionInfo {
+    let office = ec.offices.get(&contest.office).unwrap();
+
+    ElectionInfo {
+        name: office.name.clone(),
+        office: contest.office.clone(),
+        date: metadata.date.clone(),
+        data_format: metadata.data_format.clone(),
+        tabulation_options: tabulation_options(metadata),
+        loader_params: contest.loader_params.clone(),
+        jurisdiction_path: ec.path.clone(),
+        election_path: election_path.to_string(),
+        jurisdiction_name: ec.name.clone(),
+        office_name: office.name.clone(),
+        office_names: office.names.clone(),
+        election_name: metadata.name.clone(),
+        website: metadata.website.clone(),
+        status: metadata.status,
+        official_ballots: contest.official_ballots,
+        drop: metadata.drop,
+    }
+}
+
+/// Everything preprocessing a contest depends on, hashed to key the
+/// preprocessed cache.
+#[derive(Serialize)]
+struct PreprocessInputs<'a> {
+    version: &'a str,
+    data_format: &'a str,
+    normalization: &'a str,
+    files: &'a BTreeMap<String, String>,
+    loader_params: &'a Option<BTreeMap<String, String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    anonymize_ballot_ids: Option<bool>,
+    /// SHA-256 of the ballot id salt, never the salt itself, since the key is
+    /// written next to the preprocessed ballots.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    ballot_id_salt: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    overvote_policy: Option<OvervotePolicy>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    skipped_rank_policy: Option<SkippedRankPolicy>,
+}
+
+/// Return a key identifying the inputs to preprocessing a contest: the
+/// content hashes of the raw files recorded in metadata, the format, loader
+/// parameters, normalization, overvote and skipped ranking policies
+/// (including those of the rules profile), the hash of the ballot id salt in
+/// the election's raw data directory `election_dir` if ids are anonymized,
+/// and the pipeline version. A preprocessed file is stale if it was
+/// generated under a different key.
+pub fn preprocess_cache_key(
+    metadata: &ElectionMetadata,
+    contest: &Contest,
+    election_dir: &Path,
+) -> String {
+    let options = tabulation_options(metadata);
+    // A salt that can't be read leaves the key without it; preprocessing
+    // then fails and reports why.
+    let salt = ballot_id_salt(metadata, election_dir).ok().flatten();
+    hash_serialized(&PreprocessInputs {
+        version: env!("CARGO_PKG_VERSION"),
+        data_format: &metadata.data_format,
+        normalization: &metadata.normalization,
+        files: &metadata.files,
+        loader_params: &contest.loader_params,
+        anonymize_ballot_ids: metadata.anonymize_ballot_ids,
+        ballot_id_salt: salt.map(|salt| format!("{:x}", Sha256::digest(salt))),
+        overvote_policy: options.overvote_policy,
+        skipped_rank_policy: options.skipped_rank_policy,
+    })
+}
+
+/// Describe the inputs a contest's report is generated from: the raw files
+/// and their hashes, how they are read, normalized and tabulated, and the
+/// pipeline version and revision.
+pub fn provenance(metadata: &ElectionMetadata, contest: &Contest) -> Provenance {
+    Provenance {
+        files: metadata.files.clone(),
+        data_format: metadata.data_format.clone(),
+        loader_params: contest.loader_params.clone(),
+        normalization: metadata.normalization.clone(),
+        tabulation_options: tabulation_options(metadata),
+        pipeline_version: env!("CARGO_PKG_VERSION").to_string(),
+        pipeline_revision: option_env!("GIT_REVISION").map(|r| r.to_string()),
+    }
+}
+
+/// Set each candidate's display names by language from the election's
+/// `candidateNames` metadata, clearing names no longer listed there.
+pub fn localize_candidates(candidates: &mut [Candidate], metadata: &ElectionMetadata) {
+    for candidate in candidates {
+        candidate.names = metadata
+            .candidate_names
+            .as_ref()
+            .and_then(|names| names.get(&candidate.name))
+            .cloned();
+    }
+}
+
+/// Read the raw ballot data for a contest, without normalizing it.
+pub fn read_contest(
+    raw_base: &Path,
+    metadata: &ElectionMetadata,
+    election_path: &str,
+    contest: &Contest,
+) -> Result<Election, FormatError> {
+    let election_dir = raw_base.join(election_path);
+    let mut election = read_election(
+        &metadata.data_format,
+        &election_dir,
+        contest.loader_params.clone().unwrap_or_default(),
+    )?;
+    if let Some(salt) = ballot_id_salt(metadata, &election_dir)? {
+        anonymize_ballot_ids(&mut election, &salt);
+    }
+    Ok(election)
+}
+
+/// Read the raw ballot data of several contests of an election, keyed by
+/// office. Formats that support it read all of the contests in one pass over
+/// the raw files.
+pub fn read_contests(
+    raw_base: &Path,
+    metadata: &ElectionMetadata,
+    election_path: &str,
+    contests: &[&Contest],
+) -> Result<BTreeMap<String, Election>, FormatError> {
+    let election_dir = raw_base.join(election_path);
+    let mut elections = read_elections(
+        &metadata.data_format,
+        &election_dir,
+        contests
+            .iter()
+            .map(|c| {
+                (
+                    c.office.clone(),
+                    c.loader_params.clone().unwrap_or_default(),
+                )
+            })
+            .collect(),
+    )?;
+    if let Some(salt) = ballot_id_salt(metadata, &election_dir)? {
+        for election in elections.values_mut() {
+            anonymize_ballot_ids(election, &salt);
+        }
+    }
+    Ok(elections)
+}
+
+/// The salt to hash the election's ballot ids with, if its metadata sets
+/// `anonymizeBallotIds`.
+pub fn ballot_id_salt(
+    metadata: &ElectionMetadata,
+    election_dir: &Path,
+) -> Result<Option<Vec<u8>>, FormatError> {
+    if metadata.anonymize_ballot_ids == Some(true) {
+        read_ballot_id_salt(election_dir).map(Some)
+    } else {
+        Ok(None)
     }
 }
 
@@ -354,31 +1121,439 @@ pub fn preprocess_election(
     election_path: &str,
     ec: &Jurisdiction,
     contest: &Contest,
+) -> Result<ElectionPreprocessed, FormatError> {
+    let election = read_contest(raw_base, metadata, election_path, contest)?;
+    Ok(normalize_contest(
+        metadata,
+        election_path,
+        ec,
+        contest,
+        election,
+    ))
+}
+
+/// Normalize a contest's ballots, already read from its raw data, according to
+/// the rules given in the metadata.
+pub fn normalize_contest(
+    metadata: &ElectionMetadata,
+    election_path: &str,
+    ec: &Jurisdiction,
+    contest: &Contest,
+    election: Election,
 ) -> ElectionPreprocessed {
-    let election = read_election(
-        &metadata.data_format,
-        &raw_base.join(&election_path),
-        contest.loader_params.clone().unwrap_or_default(),
-    );
-    let office = ec.offices.get(&contest.office).unwrap();
+    ElectionPreprocessed {
+        info: election_info(metadata, election_path, ec, contest),
+        ballots: normalize_election(
+            &metadata.normalization,
+            &tabulation_options(metadata).ballot_rules(),
+            election,
+        ),
+    }
+}
 
-    let normalized_election = normalize_election(&metadata.normalization, election);
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tabulator::{TabulatorAllocation, Transfer};
 
-    ElectionPreprocessed {
-        info: ElectionInfo {
-            name: office.name.clone(),
-            office: contest.office.clone(),
-            date: metadata.date.clone(),
-            data_format: metadata.data_format.clone(),
-            tabulation_options: metadata.tabulation_options.clone().unwrap_or_default(),
-            loader_params: contest.loader_params.clone(),
-            jurisdiction_path: ec.path.clone(),
-            election_path: election_path.to_string(),
-            jurisdiction_name: ec.name.clone(),
-            office_name: office.name.clone(),
-            election_name: metadata.name.clone(),
-            website: metadata.website.clone(),
-        },
-        ballots: normalized_election,
+    fn round(votes: &[(u32, u32)], transfers: Vec<Transfer>) -> TabulatorRound {
+        TabulatorRound {
+            allocations: votes
+                .iter()
+                .map(|(c, v)| TabulatorAllocation {
+                    allocatee: Allocatee::Candidate(CandidateId(*c)),
+                    votes: *v,
+                })
+                .collect(),
+            undervote: 0,
+            overvote: 0,
+            continuing_ballots: votes.iter().map(|(_, v)| v).sum(),
+            exhausted: 0,
+            threshold: 0,
+            transfers,
+        }
+    }
+
+    #[test]
+    fn test_margin_of_victory() {
+        let rounds = vec![round(&[(0, 1002), (1, 998)], vec![])];
+        let margin = margin_of_victory(&rounds).unwrap();
+        assert_eq!(4, margin.votes);
+        assert_eq!(3, margin.ballots_to_flip);
+        assert!((margin.percent - 0.2).abs() < 1e-9);
+
+        let rounds = vec![round(&[(0, 501), (1, 498)], vec![])];
+        assert_eq!(2, margin_of_victory(&rounds).unwrap().ballots_to_flip);
+
+        let rounds = vec![round(&[(0, 10)], vec![])];
+        assert!(margin_of_victory(&rounds).is_none());
+    }
+
+    #[test]
+    fn test_close_contest() {
+        let rounds = vec![round(&[(0, 1002), (1, 998)], vec![])];
+        let margin = margin_of_victory(&rounds);
+
+        assert!(is_close_contest(
+            margin.as_ref(),
+            &TabulationOptions::default()
+        ));
+
+        let options = TabulationOptions {
+            close_margin_votes: Some(3),
+            ..TabulationOptions::default()
+        };
+        assert!(!is_close_contest(margin.as_ref(), &options));
+
+        let rounds = vec![round(&[(0, 600), (1, 400)], vec![])];
+        assert!(!is_close_contest(
+            margin_of_victory(&rounds).as_ref(),
+            &TabulationOptions::default()
+        ));
+    }
+
+    #[test]
+    fn test_ballot_count_check() {
+        let options = TabulationOptions::default();
+        assert!(ballot_count_check(1000, None, &options).is_none());
+
+        let check = ballot_count_check(1000, Some(1001), &options).unwrap();
+        assert_eq!(-1, check.difference);
+        assert!(!check.discrepancy);
+
+        let check = ballot_count_check(990, Some(1000), &options).unwrap();
+        assert!((check.percent + 1.).abs() < 1e-9);
+        assert!(check.discrepancy);
+
+        let options = TabulationOptions {
+            ballot_count_tolerance_percent: Some(2.),
+            ..TabulationOptions::default()
+        };
+        assert!(
+            !ballot_count_check(990, Some(1000), &options)
+                .unwrap()
+                .discrepancy
+        );
+    }
+
+    #[test]
+    fn test_sub_jurisdiction_results() {
+        let (a, b, c) = (CandidateId(0), CandidateId(1), CandidateId(2));
+        let ballots = vec![
+            NormalizedBallot::new("1".into(), vec![c, a], false)
+                .with_sub_jurisdiction(Some("Queens".into())),
+            NormalizedBallot::new("2".into(), vec![b], false)
+                .with_sub_jurisdiction(Some("Queens".into())),
+            NormalizedBallot::new("3".into(), vec![c], false)
+                .with_sub_jurisdiction(Some("Bronx".into())),
+        ];
+        let final_round: BTreeSet<CandidateId> = vec![a, b].into_iter().collect();
+
+        let results = sub_jurisdiction_results(&ballots, &final_round).unwrap();
+        assert_eq!(2, results.len());
+        assert_eq!("Bronx", results[0].name);
+        assert_eq!(Allocatee::Exhausted, results[0].final_round[0].allocatee);
+        assert_eq!(2, results[1].ballot_count);
+        assert_eq!(2, results[1].final_round.len());
+
+        let unassigned = vec![NormalizedBallot::new("1".into(), vec![a], false)];
+        assert!(sub_jurisdiction_results(&unassigned, &final_round).is_none());
+    }
+
+    #[test]
+    fn test_ecological_estimates() {
+        let (a, b) = (CandidateId(0), CandidateId(1));
+        let result = |name: &str, a_votes: u32, exhausted: u32| SubJurisdictionResult {
+            name: name.into(),
+            ballot_count: 10,
+            first_choice: vec![
+                TabulatorAllocation {
+                    allocatee: Allocatee::Candidate(a),
+                    votes: a_votes,
+                },
+                TabulatorAllocation {
+                    allocatee: Allocatee::Candidate(b),
+                    votes: 10 - a_votes,
+                },
+            ],
+            final_round: vec![TabulatorAllocation {
+                allocatee: Allocatee::Exhausted,
+                votes: exhausted,
+            }],
+        };
+        let results = vec![
+            result("1", 2, 1),
+            result("2", 5, 1),
+            result("3", 8, 1),
+            result("4", 5, 0),
+        ];
+        let mut attributes = PrecinctAttributes::new();
+        for (precinct, income) in &[("1", 10.), ("2", 20.), ("3", 30.)] {
+            attributes
+                .entry(precinct.to_string())
+                .or_default()
+                .insert("income".into(), *income);
+        }
+
+        let estimates = ecological_estimates(&results, &[a, b], &attributes).unwrap();
+        assert_eq!(3, estimates.precincts);
+        let income = &estimates.attributes[0];
+        assert_eq!("income", income.attribute);
+        assert!((income.first_choice[0].correlation.unwrap() - 1.).abs() < 1e-9);
+        assert!((income.first_choice[1].correlation.unwrap() + 1.).abs() < 1e-9);
+        // Exhaustion doesn't vary across the precincts with attributes.
+        assert_eq!(None, income.exhaustion);
+
+        assert!(ecological_estimates(&results, &[a, b], &PrecinctAttributes::new()).is_none());
+    }
+
+    #[test]
+    fn test_invalid_ballots() {
+        let candidates = vec![
+            Candidate::new("A".into(), CandidateType::Regular),
+            Candidate::new("Write-in".into(), CandidateType::WriteIn),
+        ];
+        let (a, w) = (CandidateId(0), CandidateId(1));
+        let ballots = vec![
+            NormalizedBallot::new("1".into(), vec![a], false),
+            NormalizedBallot::new("2".into(), vec![], false),
+            NormalizedBallot::new("3".into(), vec![], true),
+            NormalizedBallot::new("4".into(), vec![w], false),
+            NormalizedBallot::new("5".into(), vec![w, a], false),
+        ];
+
+        assert_eq!(
+            InvalidBallotCounts {
+                blank: 1,
+                overvote: 1,
+                write_in_only: 1,
+            },
+            invalid_ballots(&candidates, &ballots)
+        );
+    }
+
+    #[test]
+    fn test_alternative_outcomes() {
+        let (a, b, c) = (CandidateId(0), CandidateId(1), CandidateId(2));
+        let mut ballots = Vec::new();
+        for i in 0..4 {
+            ballots.push(NormalizedBallot::new(i.to_string(), vec![a], false));
+        }
+        for i in 4..7 {
+            ballots.push(NormalizedBallot::new(i.to_string(), vec![b, c], false));
+        }
+        for i in 7..9 {
+            ballots.push(NormalizedBallot::new(i.to_string(), vec![c, b], false));
+        }
+
+        let outcomes = alternative_outcomes(&[a, b, c], &ballots, b);
+
+        assert_eq!(Some(b), outcomes[0].winner);
+        assert!(!outcomes[0].winner_changed);
+        assert_eq!(Some(c), outcomes[1].winner);
+        assert!(outcomes[1].winner_changed);
+        assert_eq!(Some(b), outcomes[2].winner);
+    }
+
+    #[test]
+    fn test_what_if() {
+        let (a, b, c) = (CandidateId(0), CandidateId(1), CandidateId(2));
+        let ballots = vec![
+            NormalizedBallot::new("1".into(), vec![a], false),
+            NormalizedBallot::new("2".into(), vec![b, a], false),
+            NormalizedBallot::new("3".into(), vec![b, c], false),
+            NormalizedBallot::new("4".into(), vec![c, b], false),
+        ];
+
+        let result = what_if(&ballots, &[b, c]);
+        assert_eq!(vec![b, c], result.eliminated);
+        assert_eq!(Some(a), result.winner);
+        assert_eq!(1, result.rounds.len());
+        assert_eq!(2, result.rounds[0].allocations[0].votes);
+        assert_eq!(2, result.rounds[0].exhausted);
+    }
+
+    #[test]
+    fn test_lookup_ballot() {
+        let (a, b, c) = (CandidateId(0), CandidateId(1), CandidateId(2));
+        let ballots = vec![
+            NormalizedBallot::new("1".into(), vec![a], false),
+            NormalizedBallot::new("2".into(), vec![a], false),
+            NormalizedBallot::new("3".into(), vec![b], false),
+            NormalizedBallot::new("4".into(), vec![b], false),
+            NormalizedBallot::new("5".into(), vec![c, b], false),
+            NormalizedBallot::new("6".into(), vec![c], true),
+        ];
+        let rounds = tabulate(&ballots);
+
+        let transferred = lookup_ballot(&ballots[4], &rounds);
+        assert_eq!(vec![c, b], transferred.counted_for);
+        assert_eq!(None, transferred.exhausted_round);
+        assert_eq!(Some(b), transferred.final_candidate);
+
+        let exhausted = lookup_ballot(&ballots[5], &rounds);
+        assert_eq!(vec![c], exhausted.counted_for);
+        assert_eq!(Some(2), exhausted.exhausted_round);
+        assert_eq!(None, exhausted.final_candidate);
+        assert!(exhausted.overvoted);
+    }
+
+    #[test]
+    fn test_require_irv() {
+        let mut rules = TabulationOptions::default();
+        assert_eq!(Ok(()), require_irv(&rules));
+
+        rules.method = Some("stv_meek".into());
+        assert_eq!(
+            Err(TabulationError::NotIrv { method: "stv_meek" }),
+            require_irv(&rules)
+        );
+        assert_eq!(
+            "the contest is counted by stv_meek, not instant runoff (irv)",
+            require_irv(&rules).unwrap_err().to_string()
+        );
+    }
+
+    #[test]
+    fn test_majority_round() {
+        let (a, b, c, d) = (
+            CandidateId(0),
+            CandidateId(1),
+            CandidateId(2),
+            CandidateId(3),
+        );
+        let ballots = vec![
+            NormalizedBallot::new("1".into(), vec![a], false),
+            NormalizedBallot::new("2".into(), vec![a], false),
+            NormalizedBallot::new("3".into(), vec![a], false),
+            NormalizedBallot::new("4".into(), vec![a], false),
+            NormalizedBallot::new("5".into(), vec![b], false),
+            NormalizedBallot::new("6".into(), vec![b], false),
+            NormalizedBallot::new("7".into(), vec![c], false),
+            NormalizedBallot::new("8".into(), vec![d, a], false),
+        ];
+
+        let eager = TabulationOptions {
+            eager: Some(true),
+            ..TabulationOptions::default()
+        };
+        assert_eq!(2, count_rounds(&ballots, &eager).len());
+
+        let until_two = TabulationOptions {
+            continue_until_two: Some(true),
+            ..eager
+        };
+        let rounds = count_rounds(&ballots, &until_two);
+        assert_eq!(3, rounds.len());
+        assert_eq!(Some(2), majority_round(&rounds));
+    }
+
+    #[test]
+    fn test_tie_break_used() {
+        let transfer = Transfer {
+            from: CandidateId(2),
+            to: Allocatee::Candidate(CandidateId(0)),
+            count: 10,
+        };
+        let rounds = vec![
+            round(&[(0, 50), (1, 10), (2, 10)], vec![]),
+            round(&[(0, 60), (1, 10)], vec![transfer]),
+        ];
+        assert!(tie_break_used(&rounds));
+
+        let rounds = vec![round(&[(0, 50), (1, 50)], vec![])];
+        assert!(tie_break_used(&rounds));
+
+        let rounds = vec![round(&[(0, 51), (1, 49)], vec![])];
+        assert!(!tie_break_used(&rounds));
+    }
+
+    #[test]
+    fn test_ranked_pairs() {
+        let (a, b, c) = (CandidateId(0), CandidateId(1), CandidateId(2));
+        let candidates = [a, b, c];
+        let counts = |pairs: &[((CandidateId, CandidateId), u32)]| -> BTreeMap<_, _> {
+            pairs.iter().copied().collect()
+        };
+
+        // A cycle a > b > c > a; the weakest majority, c > a, isn't locked.
+        let cycle = counts(&[
+            ((a, b), 8),
+            ((b, a), 2),
+            ((b, c), 7),
+            ((c, b), 3),
+            ((c, a), 6),
+            ((a, c), 4),
+        ]);
+        assert_eq!(Some(a), ranked_pairs(&candidates, &cycle));
+
+        let cycle = counts(&[
+            ((a, b), 7),
+            ((b, a), 3),
+            ((b, c), 6),
+            ((c, b), 4),
+            ((c, a), 8),
+            ((a, c), 2),
+        ]);
+        assert_eq!(Some(c), ranked_pairs(&candidates, &cycle));
+
+        let tied = counts(&[((a, b), 5), ((b, a), 5)]);
+        assert_eq!(None, ranked_pairs(&[a, b], &tied));
+    }
+
+    #[test]
+    fn test_has_votes() {
+        let blank = NormalizedBallot::new("1".into(), vec![], false);
+        let overvoted = NormalizedBallot::new("2".into(), vec![], true);
+        assert!(!has_votes(&[]));
+        assert!(!has_votes(&[blank.clone(), overvoted]));
+
+        let vote = NormalizedBallot::new("3".into(), vec![CandidateId(0)], false);
+        assert!(has_votes(&[blank, vote]));
+    }
+
+    #[test]
+    fn test_preprocess_cache_key() {
+        let metadata: ElectionMetadata = serde_json::from_str(
+            r#"{"name": "General", "date": "2024-11-05", "dataFormat": "simple_json",
+                "tabulationOptions": null, "normalization": "simple",
+                "contests": [{"office": "mayor", "loaderParams": {"file": "a.json"}}],
+                "files": {"a.json": "abc"}, "website": null}"#,
+        )
+        .unwrap();
+        let dir = std::env::temp_dir().join(format!("cache_key_test_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let key = preprocess_cache_key(&metadata, &metadata.contests[0], &dir);
+        assert_eq!(
+            key,
+            preprocess_cache_key(&metadata, &metadata.contests[0], &dir)
+        );
+
+        let mut changed_file: ElectionMetadata =
+            serde_json::from_str(&serde_json::to_string(&metadata).unwrap()).unwrap();
+        changed_file.files.insert("a.json".into(), "def".into());
+        assert_ne!(
+            key,
+            preprocess_cache_key(&changed_file, &metadata.contests[0], &dir)
+        );
+
+        let contest: Contest =
+            serde_json::from_str(r#"{"office": "mayor", "loaderParams": {"file": "b.json"}}"#)
+                .unwrap();
+        assert_ne!(key, preprocess_cache_key(&metadata, &contest, &dir));
+
+        let mut anonymized: ElectionMetadata =
+            serde_json::from_str(&serde_json::to_string(&metadata).unwrap()).unwrap();
+        anonymized.anonymize_ballot_ids = Some(true);
+        std::fs::write(dir.join(".ballot-id-salt"), "first salt").unwrap();
+        let salted = preprocess_cache_key(&anonymized, &metadata.contests[0], &dir);
+        assert!(!salted.contains("first salt"));
+        std::fs::write(dir.join(".ballot-id-salt"), "second salt").unwrap();
+        assert_ne!(
+            salted,
+            preprocess_cache_key(&anonymized, &metadata.contests[0], &dir)
+        );
+
+        std::fs::remove_dir_all(&dir).unwrap();
     }
 }
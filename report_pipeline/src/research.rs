@@ -0,0 +1,172 @@
+//! Experimental single-winner methods for research comparisons, built with
+//! the `research` feature. They count the same normalized ballots as the
+//! official count, but their results are written only to `methods.json`
+//! next to a contest's report, never to `report.json`.
+
+use crate::model::election::{CandidateId, NormalizedBallot};
+use crate::model::report::WhatIfResult;
+use crate::report::{generate_pairwise_counts, graph, ranked_pairs, smith_set, what_if};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Winners of a contest under the research methods.
+#[derive(Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct ResearchMethods {
+    /// Winner of the official count, for reference.
+    pub official_winner: CandidateId,
+    /// Tideman ranked pairs winner, if there is a single one.
+    pub ranked_pairs: Option<CandidateId>,
+    /// Schulze winner, if there is a single one.
+    pub schulze: Option<CandidateId>,
+    /// Ranked Robin winner: the candidate with the most pairwise wins, ties
+    /// broken by the sum of pairwise margins. `None` if that still ties.
+    pub ranked_robin: Option<CandidateId>,
+    /// Condorcet-IRV hybrid (Smith-IRV): an instant-runoff count with every
+    /// candidate outside the Smith set removed from the ballots, so a
+    /// Condorcet winner always wins.
+    pub smith_irv: WhatIfResult,
+}
+
+type PairwiseCounts = BTreeMap<(CandidateId, CandidateId), u32>;
+
+fn votes(counts: &PairwiseCounts, c1: CandidateId, c2: CandidateId) -> u32 {
+    counts.get(&(c1, c2)).copied().unwrap_or(0)
+}
+
+/// Schulze winner. The strength of a path between two candidates is its
+/// weakest pairwise majority, in votes for the majority; a candidate wins
+/// if their strongest path to every other candidate is at least as strong
+/// as the reverse. Returns `None` unless exactly one candidate wins.
+pub fn schulze(candidates: &[CandidateId], counts: &PairwiseCounts) -> Option<CandidateId> {
+    let mut strength: BTreeMap<(CandidateId, CandidateId), u32> = BTreeMap::new();
+    for c1 in candidates {
+        for c2 in candidates {
+            let (for_c1, for_c2) = (votes(counts, *c1, *c2), votes(counts, *c2, *c1));
+            strength.insert((*c1, *c2), if for_c1 > for_c2 { for_c1 } else { 0 });
+        }
+    }
+
+    // Widest paths, by Floyd-Warshall.
+    for via in candidates {
+        for c1 in candidates {
+            if c1 == via {
+                continue;
+            }
+            for c2 in candidates {
+                if c2 == via || c2 == c1 {
+                    continue;
+                }
+                let through = strength[&(*c1, *via)].min(strength[&(*via, *c2)]);
+                if through > strength[&(*c1, *c2)] {
+                    strength.insert((*c1, *c2), through);
+                }
+            }
+        }
+    }
+
+    let winners: Vec<CandidateId> = candidates
+        .iter()
+        .filter(|c1| {
+            candidates
+                .iter()
+                .all(|c2| strength[&(**c1, *c2)] >= strength[&(*c2, **c1)])
+        })
+        .copied()
+        .collect();
+    match winners.as_slice() {
+        [winner] => Some(*winner),
+        _ => None,
+    }
+}
+
+/// Ranked Robin winner: the candidate who wins the most head-to-head
+/// matchups, with ties broken by the sum of their pairwise margins. Returns
+/// `None` if candidates are still tied.
+pub fn ranked_robin(candidates: &[CandidateId], counts: &PairwiseCounts) -> Option<CandidateId> {
+    let mut scores: Vec<(u32, i64, CandidateId)> = candidates
+        .iter()
+        .map(|c1| {
+            let mut wins = 0;
+            let mut margin = 0;
+            for c2 in candidates {
+                let (for_c1, for_c2) = (votes(counts, *c1, *c2), votes(counts, *c2, *c1));
+                if for_c1 > for_c2 {
+                    wins += 1;
+                }
+                margin += for_c1 as i64 - for_c2 as i64;
+            }
+            (wins, margin, *c1)
+        })
+        .collect();
+    scores.sort_by_key(|(wins, margin, _)| std::cmp::Reverse((*wins, *margin)));
+
+    match scores.as_slice() {
+        [] => None,
+        [(wins, margin, winner), rest @ ..] => match rest.first() {
+            Some((w, m, _)) if (w, m) == (wins, margin) => None,
+            _ => Some(*winner),
+        },
+    }
+}
+
+/// Count the contest's ballots under each research method.
+pub fn research_methods(
+    candidates: &[CandidateId],
+    ballots: &[NormalizedBallot],
+    official_winner: CandidateId,
+) -> ResearchMethods {
+    let counts = generate_pairwise_counts(candidates, ballots);
+    let smith = smith_set(candidates, &graph(candidates, &counts));
+    let outside_smith: Vec<CandidateId> = candidates
+        .iter()
+        .filter(|c| !smith.contains(*c))
+        .copied()
+        .collect();
+
+    ResearchMethods {
+        official_winner,
+        ranked_pairs: ranked_pairs(candidates, &counts),
+        schulze: schulze(candidates, &counts),
+        ranked_robin: ranked_robin(candidates, &counts),
+        smith_irv: what_if(ballots, &outside_smith),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::ballots;
+
+    #[test]
+    fn test_center_squeeze() {
+        // Candidate 1 is the Condorcet winner but has the fewest first
+        // choices, so instant runoff elects candidate 0.
+        let candidates: Vec<CandidateId> = (0..3).map(CandidateId).collect();
+        let ballots = ballots(&[(&[0, 1], 8), (&[1, 0], 3), (&[1, 2], 3), (&[2, 1], 7)]);
+
+        let result = research_methods(&candidates, &ballots, CandidateId(0));
+
+        assert_eq!(Some(CandidateId(1)), result.ranked_pairs);
+        assert_eq!(Some(CandidateId(1)), result.schulze);
+        assert_eq!(Some(CandidateId(1)), result.ranked_robin);
+        assert_eq!(Some(CandidateId(1)), result.smith_irv.winner);
+        assert_eq!(
+            vec![CandidateId(0), CandidateId(2)],
+            result.smith_irv.eliminated
+        );
+    }
+
+    #[test]
+    fn test_schulze_cycle() {
+        // A beats B 6-3, B beats C 7-2, C beats A 5-4. The weakest majority
+        // in the cycle, C over A, is overruled.
+        let candidates: Vec<CandidateId> = (0..3).map(CandidateId).collect();
+        let ballots = ballots(&[(&[0, 1, 2], 4), (&[1, 2, 0], 3), (&[2, 0, 1], 2)]);
+        let counts = generate_pairwise_counts(&candidates, &ballots);
+
+        assert_eq!(Some(CandidateId(0)), schulze(&candidates, &counts));
+        assert_eq!(None, ranked_robin(&candidates, &counts));
+    }
+}
@@ -0,0 +1,144 @@
+//! Named rules profiles: the counting rules of a jurisdiction, declared once
+//! in election metadata with `rulesProfile` instead of repeating each option.
+//!
+//! A profile sets defaults for the tabulation options that carry a
+//! jurisdiction's statutory rules. Options set explicitly in an election's
+//! `tabulationOptions` take precedence. Tie-breaks and the threshold are not
+//! part of a profile: the tabulator breaks ties between last-place
+//! candidates by candidate order, and the threshold follows from the method
+//! (a majority of continuing ballots for IRV, and for STV the quota set by
+//! the `quota` and `quotaMode` options, a static Droop quota by default).
+
+use crate::model::metadata::{
+    ElectionMetadata, OvervotePolicy, SkippedRankPolicy, TabulationOptions,
+};
+
+/// A jurisdiction's rules for normalizing and counting ballots.
+pub struct RulesProfile {
+    /// Name used in metadata, e.g. `maine`.
+    pub name: &'static str,
+    pub description: &'static str,
+    pub overvote_policy: OvervotePolicy,
+    pub skipped_rank_policy: SkippedRankPolicy,
+    /// Counting method, as in `TabulationOptions::method`.
+    pub method: &'static str,
+    /// For STV, as in `TabulationOptions::eliminate_below`.
+    pub eliminate_below: Option<u32>,
+}
+
+/// The built-in profiles.
+pub const RULES_PROFILES: &[RulesProfile] = &[
+    RulesProfile {
+        name: "maine",
+        description: "Maine: overvotes exhaust; two skipped rankings in a row exhaust",
+        overvote_policy: OvervotePolicy::Exhaust,
+        skipped_rank_policy: SkippedRankPolicy::Consecutive(2),
+        method: "irv",
+        eliminate_below: None,
+    },
+    RulesProfile {
+        name: "nyc",
+        description: "New York City: overvotes exhaust; skipped rankings are ignored",
+        overvote_policy: OvervotePolicy::Exhaust,
+        skipped_rank_policy: SkippedRankPolicy::Never,
+        method: "irv",
+        eliminate_below: None,
+    },
+    RulesProfile {
+        name: "sf",
+        description: "San Francisco: overvotes exhaust; skipped rankings are ignored",
+        overvote_policy: OvervotePolicy::Exhaust,
+        skipped_rank_policy: SkippedRankPolicy::Never,
+        method: "irv",
+        eliminate_below: None,
+    },
+    RulesProfile {
+        name: "alaska",
+        description: "Alaska: overvotes exhaust; two skipped rankings in a row exhaust",
+        overvote_policy: OvervotePolicy::Exhaust,
+        skipped_rank_policy: SkippedRankPolicy::Consecutive(2),
+        method: "irv",
+        eliminate_below: None,
+    },
+    RulesProfile {
+        name: "minneapolis",
+        description: "Minneapolis: overvoted rankings are skipped; skipped rankings are ignored",
+        overvote_policy: OvervotePolicy::Skip,
+        skipped_rank_policy: SkippedRankPolicy::Never,
+        method: "irv",
+        eliminate_below: None,
+    },
+    RulesProfile {
+        name: "cambridge",
+        description: "Cambridge, MA: STV with Cincinnati surplus transfers; candidates \
+            under 50 votes are eliminated together",
+        overvote_policy: OvervotePolicy::Skip,
+        skipped_rank_policy: SkippedRankPolicy::Never,
+        method: "stv_cincinnati",
+        eliminate_below: Some(50),
+    },
+];
+
+/// Return the built-in profile with the given name.
+pub fn get_rules_profile(name: &str) -> Option<&'static RulesProfile> {
+    RULES_PROFILES.iter().find(|profile| profile.name == name)
+}
+
+impl RulesProfile {
+    /// Fill in the options the profile covers that `options` leaves unset.
+    pub fn apply(&self, options: &mut TabulationOptions) {
+        options.overvote_policy.get_or_insert(self.overvote_policy);
+        options
+            .skipped_rank_policy
+            .get_or_insert(self.skipped_rank_policy);
+        options
+            .method
+            .get_or_insert_with(|| self.method.to_string());
+        if options.eliminate_below.is_none() {
+            options.eliminate_below = self.eliminate_below;
+        }
+    }
+}
+
+/// Return an election's tabulation options with the defaults of its rules
+/// profile, if it names one, filled in.
+pub fn tabulation_options(metadata: &ElectionMetadata) -> TabulationOptions {
+    let mut options = metadata.tabulation_options.clone().unwrap_or_default();
+    if let Some(profile) = metadata
+        .rules_profile
+        .as_deref()
+        .and_then(get_rules_profile)
+    {
+        profile.apply(&mut options);
+    }
+    options
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_apply_rules_profile() {
+        let mut options = TabulationOptions {
+            overvote_policy: Some(OvervotePolicy::Exhaust),
+            seats: Some(9),
+            ..TabulationOptions::default()
+        };
+        get_rules_profile("cambridge").unwrap().apply(&mut options);
+
+        assert_eq!(Some(OvervotePolicy::Exhaust), options.overvote_policy);
+        assert_eq!(Some(SkippedRankPolicy::Never), options.skipped_rank_policy);
+        assert_eq!(Some("stv_cincinnati"), options.method.as_deref());
+        assert_eq!(Some(50), options.eliminate_below);
+        assert_eq!(Some(9), options.seats);
+    }
+
+    #[test]
+    fn test_get_rules_profile() {
+        for name in ["maine", "nyc", "sf", "alaska", "minneapolis", "cambridge"] {
+            assert_eq!(name, get_rules_profile(name).unwrap().name);
+        }
+        assert!(get_rules_profile("atlantis").is_none());
+    }
+}
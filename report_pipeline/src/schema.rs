@@ -0,0 +1,41 @@
+//! JSON Schemas of the files published in the report directory, derived
+//! from the Rust types that write them, so that the site's TypeScript types
+//! can be generated from (or checked against) the pipeline's output.
+
+use crate::model::aggregate::{CandidateIndex, TrendIndex};
+use crate::model::report::{ContestReport, ReportHistory, ReportIndex};
+use crate::model::transfers::TransferGraph;
+use crate::tabulator::TraceEvent;
+use schemars::schema::RootSchema;
+use schemars::schema_for;
+
+/// Return the schema of each published file, keyed by file name.
+pub fn report_schemas() -> Vec<(&'static str, RootSchema)> {
+    vec![
+        ("index.json", schema_for!(ReportIndex)),
+        ("candidates.json", schema_for!(CandidateIndex)),
+        ("trends.json", schema_for!(TrendIndex)),
+        ("report.json", schema_for!(ContestReport)),
+        ("transfers.json", schema_for!(TransferGraph)),
+        ("history.json", schema_for!(ReportHistory)),
+        ("trace.json", schema_for!(Vec<TraceEvent>)),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_report_schemas() {
+        let schemas = report_schemas();
+        let (_, report) = schemas
+            .iter()
+            .find(|(file, _)| *file == "report.json")
+            .unwrap();
+        let properties = &report.schema.object.as_ref().unwrap().properties;
+        assert!(properties.contains_key("winner"));
+        assert!(properties.contains_key("ballotCount"));
+        assert!(report.definitions.contains_key("Allocatee"));
+    }
+}
@@ -0,0 +1,146 @@
+//! Bootstrap estimates of how robust a contest's winner is to small changes
+//! in which ballots were cast.
+
+use crate::model::election::{CandidateId, NormalizedBallot};
+use crate::model::metadata::TabulationOptions;
+use crate::model::report::Sensitivity;
+use crate::report::{generate_pairwise_counts, ranked_pairs};
+use crate::tabulator::{selected_tabulator, TabulationError, Tabulator};
+
+/// Seed used if the tabulation options don't give one.
+pub const DEFAULT_BOOTSTRAP_SEED: u64 = 1;
+
+/// SplitMix64 generator. It is dependency-free and fully specified, so a
+/// seed gives the same resamples on every platform and toolchain.
+pub(crate) struct Rng(pub(crate) u64);
+
+impl Rng {
+    pub(crate) fn next(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// Return a value in `0..n`.
+    pub(crate) fn below(&mut self, n: usize) -> usize {
+        (self.next() % n as u64) as usize
+    }
+}
+
+/// The winners of counting `ballots` by `tabulator`, in candidate order.
+fn winners(
+    tabulator: &dyn Tabulator,
+    candidates: &[CandidateId],
+    ballots: &[NormalizedBallot],
+    rules: &TabulationOptions,
+) -> Result<Vec<CandidateId>, TabulationError> {
+    let mut winners = tabulator.tabulate(ballots, candidates, rules)?.winners();
+    winners.sort();
+    Ok(winners)
+}
+
+/// Draw `samples` bootstrap resamples of the ballots (as many ballots as
+/// were cast, drawn with replacement) and count how often the winners by
+/// the method `rules` select, and the ranked pairs winner, of a resample
+/// differ from those of the actual ballots. Each resample is a full count,
+/// so this takes about `samples` times as long as tabulating the contest.
+pub fn bootstrap_sensitivity(
+    candidates: &[CandidateId],
+    ballots: &[NormalizedBallot],
+    rules: &TabulationOptions,
+    samples: u32,
+    seed: u64,
+) -> Result<Sensitivity, TabulationError> {
+    let tabulator = selected_tabulator(rules)?;
+    let actual = winners(tabulator, candidates, ballots, rules)?;
+    let ranked_pairs_winner =
+        ranked_pairs(candidates, &generate_pairwise_counts(candidates, ballots));
+
+    let mut rng = Rng(seed);
+    let mut irv_changed = 0;
+    let mut ranked_pairs_changed = 0;
+    for _ in 0..samples {
+        let sample: Vec<NormalizedBallot> = (0..ballots.len())
+            .map(|_| ballots[rng.below(ballots.len())].clone())
+            .collect();
+
+        if winners(tabulator, candidates, &sample, rules)? != actual {
+            irv_changed += 1;
+        }
+        let pairwise_counts = generate_pairwise_counts(candidates, &sample);
+        if ranked_pairs(candidates, &pairwise_counts) != ranked_pairs_winner {
+            ranked_pairs_changed += 1;
+        }
+    }
+
+    let probability = |changed: u32| {
+        if samples > 0 {
+            changed as f64 / samples as f64
+        } else {
+            0.
+        }
+    };
+    Ok(Sensitivity {
+        samples,
+        seed,
+        irv_winner_change_probability: probability(irv_changed),
+        ranked_pairs_winner,
+        ranked_pairs_winner_change_probability: probability(ranked_pairs_changed),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::ballots;
+
+    #[test]
+    fn test_bootstrap_sensitivity() {
+        let candidates = [CandidateId(0), CandidateId(1)];
+        let rules = TabulationOptions::default();
+
+        let landslide = ballots(&[(&[0], 90), (&[1], 10)]);
+        let result = bootstrap_sensitivity(&candidates, &landslide, &rules, 50, 7).unwrap();
+        assert_eq!(Some(CandidateId(0)), result.ranked_pairs_winner);
+        assert_eq!(0., result.irv_winner_change_probability);
+        assert_eq!(0., result.ranked_pairs_winner_change_probability);
+
+        let close = ballots(&[(&[0], 51), (&[1], 49)]);
+        let result = bootstrap_sensitivity(&candidates, &close, &rules, 50, 7).unwrap();
+        assert!(result.irv_winner_change_probability > 0.);
+        assert_eq!(
+            result,
+            bootstrap_sensitivity(&candidates, &close, &rules, 50, 7).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_bootstrap_sensitivity_uses_method() {
+        let candidates = [CandidateId(0), CandidateId(1), CandidateId(2)];
+        // The instant-runoff winner of a close contest often changes, but
+        // with two seats both leading candidates are always elected.
+        let close = ballots(&[(&[0, 1], 51), (&[2, 1], 49)]);
+        let irv = TabulationOptions::default();
+        let result = bootstrap_sensitivity(&candidates, &close, &irv, 50, 7).unwrap();
+        assert!(result.irv_winner_change_probability > 0.);
+
+        let stv = TabulationOptions {
+            method: Some("stv_meek".into()),
+            seats: Some(2),
+            ..Default::default()
+        };
+        let result = bootstrap_sensitivity(&candidates, &close, &stv, 50, 7).unwrap();
+        assert_eq!(0., result.irv_winner_change_probability);
+
+        let unknown = TabulationOptions {
+            method: Some("borda".into()),
+            ..Default::default()
+        };
+        assert_eq!(
+            Err(TabulationError::UnknownMethod("borda".into())),
+            bootstrap_sensitivity(&candidates, &close, &unknown, 50, 7)
+        );
+    }
+}
@@ -0,0 +1,316 @@
+use crate::model::election::{CandidateId, NormalizedBallot};
+use crate::model::metadata::QuotaRule;
+use crate::tabulator::{Allocatee, StvResult, StvRound, TabulationError, TabulatorAllocation};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use tracing::{debug, warn};
+
+/// Keep factors have converged when the elected candidates' combined surplus
+/// over the quota is below this many votes.
+const CONVERGENCE_TOLERANCE: f64 = 1e-6;
+
+/// Most keep factor updates made in one round before the count moves on
+/// without converging.
+const MAX_ITERATIONS: u32 = 1000;
+
+/// Added to the Droop quota so that no more candidates than there are seats
+/// can reach it, as in the New Zealand rules.
+const DROOP_MARGIN: f64 = 1e-9;
+
+/// Exact tallies of a round of a Meek count, whose transfers are fractional.
+#[derive(Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct MeekRound {
+    /// Each candidate still in the count, by votes held.
+    pub tallies: Vec<MeekTally>,
+    pub exhausted: f64,
+    pub quota: f64,
+    /// Keep factor updates made before the tallies converged.
+    pub iterations: u32,
+}
+
+#[derive(Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct MeekTally {
+    pub candidate: CandidateId,
+    pub votes: f64,
+    /// Share of each ballot reaching the candidate that they keep: 1 for a
+    /// hopeful candidate, and less for an elected one so that they keep only
+    /// the quota.
+    pub keep_factor: f64,
+}
+
+struct MeekState<'a> {
+    ballots: &'a [NormalizedBallot],
+    seats: u32,
+    quota_rule: QuotaRule,
+    keep_factors: BTreeMap<CandidateId, f64>,
+    elected: Vec<CandidateId>,
+    defeated: BTreeSet<CandidateId>,
+}
+
+impl<'a> MeekState<'a> {
+    fn hopeful(&self) -> Vec<CandidateId> {
+        self.keep_factors
+            .keys()
+            .filter(|c| !self.defeated.contains(c) && !self.elected.contains(c))
+            .copied()
+            .collect()
+    }
+
+    /// Pass each ballot down its rankings, leaving each candidate their keep
+    /// factor of what reaches them. Returns the votes held by each candidate
+    /// and the weight left over when ballots run out of rankings.
+    fn distribute(&self) -> (BTreeMap<CandidateId, f64>, f64) {
+        let mut votes: BTreeMap<CandidateId, f64> =
+            self.keep_factors.keys().map(|c| (*c, 0.0)).collect();
+        let mut exhausted = 0.0;
+
+        for ballot in self.ballots {
+            let mut weight = 1.0;
+            for candidate in ballot.choices() {
+                let keep = self.keep_factors.get(&candidate).copied().unwrap_or(0.0);
+                if keep > 0.0 {
+                    *votes.entry(candidate).or_default() += weight * keep;
+                    weight *= 1.0 - keep;
+                }
+                if weight <= 0.0 {
+                    break;
+                }
+            }
+            exhausted += weight;
+        }
+
+        (votes, exhausted)
+    }
+
+    fn quota(&self, exhausted: f64) -> Result<f64, TabulationError> {
+        if self.seats == 0 {
+            return Err(TabulationError::NoSeats);
+        }
+        let votes = self.ballots.len() as f64 - exhausted;
+        Ok(match self.quota_rule {
+            QuotaRule::Droop => votes / (self.seats + 1) as f64 + DROOP_MARGIN,
+            QuotaRule::Hare => votes / self.seats as f64,
+        })
+    }
+
+    /// Lower the elected candidates' keep factors until each keeps only the
+    /// quota, redistributing the ballots after every update. Returns the
+    /// converged tallies.
+    fn converge(
+        &mut self,
+        round: usize,
+    ) -> Result<(BTreeMap<CandidateId, f64>, MeekRound), TabulationError> {
+        let mut iterations = 0;
+        loop {
+            let (votes, exhausted) = self.distribute();
+            let quota = self.quota(exhausted)?;
+            let surplus: f64 = self
+                .elected
+                .iter()
+                .map(|c| (votes[c] - quota).max(0.0))
+                .sum();
+            debug!(
+                "Meek round {} iteration {}: quota {:.9}, surplus {:.9}",
+                round, iterations, quota, surplus
+            );
+
+            if surplus < CONVERGENCE_TOLERANCE || iterations == MAX_ITERATIONS {
+                if surplus >= CONVERGENCE_TOLERANCE {
+                    warn!(
+                        "Meek round {} did not converge after {} iterations; surplus is {}.",
+                        round, iterations, surplus
+                    );
+                }
+                let mut tallies: Vec<MeekTally> = self
+                    .keep_factors
+                    .iter()
+                    .filter(|(c, _)| !self.defeated.contains(c))
+                    .map(|(c, k)| MeekTally {
+                        candidate: *c,
+                        votes: votes[c],
+                        keep_factor: *k,
+                    })
+                    .collect();
+                tallies.sort_by(|a, b| b.votes.total_cmp(&a.votes));
+                return Ok((
+                    votes,
+                    MeekRound {
+                        tallies,
+                        exhausted,
+                        quota,
+                        iterations,
+                    },
+                ));
+            }
+
+            for candidate in &self.elected {
+                let keep = self.keep_factors.get_mut(candidate).unwrap();
+                if votes[candidate] > 0.0 {
+                    *keep = (*keep * quota / votes[candidate]).min(1.0);
+                }
+            }
+            iterations += 1;
+        }
+    }
+}
+
+fn as_round(meek: MeekRound, elected: Vec<CandidateId>, eliminated: Vec<CandidateId>) -> StvRound {
+    let mut allocations: Vec<TabulatorAllocation> = meek
+        .tallies
+        .iter()
+        .map(|t| TabulatorAllocation {
+            allocatee: Allocatee::Candidate(t.candidate),
+            votes: t.votes as u32,
+        })
+        .collect();
+    allocations.push(TabulatorAllocation {
+        allocatee: Allocatee::Exhausted,
+        votes: meek.exhausted as u32,
+    });
+
+    StvRound {
+        allocations,
+        elected,
+        eliminated,
+        quota: meek.quota.ceil() as u32,
+        meek: Some(meek),
+    }
+}
+
+/// Count a multi-winner contest by single transferable vote with Meek's
+/// method, as in New Zealand.
+///
+/// Every ballot is passed down its rankings in every round: each elected
+/// candidate keeps a fraction of what reaches them (their keep factor) and
+/// passes the rest on, and a defeated candidate passes on everything. The
+/// quota is recalculated from the votes not exhausted each time the ballots
+/// are distributed. Each round, the keep factors of the elected candidates
+/// are lowered until none holds more than the quota, then every hopeful
+/// candidate at the quota is elected; if none is, the hopeful candidate with
+/// the fewest votes is defeated before the next round. Ties go to the
+/// candidate listed last.
+///
+/// The `allocations` of each round are whole votes, rounded down; the exact
+/// tallies are in its `meek` section. Returns an error if `seats` is zero.
+pub fn tabulate_stv_meek(
+    candidates: &[CandidateId],
+    ballots: &[NormalizedBallot],
+    seats: u32,
+    quota_rule: QuotaRule,
+) -> Result<StvResult, TabulationError> {
+    let mut state = MeekState {
+        ballots,
+        seats,
+        quota_rule,
+        keep_factors: candidates.iter().map(|c| (*c, 1.0)).collect(),
+        elected: Vec::new(),
+        defeated: BTreeSet::new(),
+    };
+
+    let mut rounds: Vec<StvRound> = Vec::new();
+    let mut to_eliminate: Vec<CandidateId> = Vec::new();
+    loop {
+        for candidate in &to_eliminate {
+            state.defeated.insert(*candidate);
+            state.keep_factors.insert(*candidate, 0.0);
+        }
+
+        let (votes, meek) = state.converge(rounds.len() + 1)?;
+        let mut newly_elected: Vec<CandidateId> = state
+            .hopeful()
+            .into_iter()
+            .filter(|c| votes[c] >= meek.quota)
+            .collect();
+        newly_elected.sort_by(|a, b| votes[b].total_cmp(&votes[a]));
+        state.elected.extend(newly_elected.iter().copied());
+
+        let eliminated = std::mem::take(&mut to_eliminate);
+        let hopeful = state.hopeful();
+        let open_seats = (seats as usize).saturating_sub(state.elected.len());
+        if hopeful.len() <= open_seats {
+            // Every remaining hopeful candidate fills a seat.
+            state.elected.extend(hopeful.iter().copied());
+            newly_elected.extend(hopeful);
+            rounds.push(as_round(meek, newly_elected, eliminated));
+            break;
+        }
+        rounds.push(as_round(meek, newly_elected.clone(), eliminated));
+        if open_seats == 0 {
+            break;
+        }
+
+        if newly_elected.is_empty() {
+            let last = hopeful
+                .iter()
+                .min_by(|a, b| votes[*a].total_cmp(&votes[*b]).then(b.cmp(a)))
+                .copied()
+                .unwrap();
+            to_eliminate = vec![last];
+        }
+    }
+
+    Ok(StvResult {
+        seats,
+        quota: rounds[0].quota,
+        winners: state.elected,
+        rounds,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::ballots;
+
+    #[test]
+    fn test_meek_surplus_transfer() {
+        let candidates: Vec<CandidateId> = (0..4).map(CandidateId).collect();
+        // 20 ballots, 2 seats: the quota is just over 6.67. Candidate 0's
+        // surplus goes to candidate 1, who is elected once candidate 3's
+        // ballots exhaust and the quota falls.
+        let ballots = ballots(&[(&[0, 1], 10), (&[2], 4), (&[3], 3), (&[1], 3)]);
+
+        let result = tabulate_stv_meek(&candidates, &ballots, 2, QuotaRule::Droop).unwrap();
+
+        assert_eq!(7, result.quota);
+        assert_eq!(vec![CandidateId(0), CandidateId(1)], result.winners);
+        assert_eq!(vec![CandidateId(0)], result.rounds[0].elected);
+
+        let second = result.rounds[1].meek.as_ref().unwrap();
+        assert!(second.iterations > 0);
+        let tally = |c: u32| {
+            second
+                .tallies
+                .iter()
+                .find(|t| t.candidate == CandidateId(c))
+                .unwrap()
+        };
+        assert!((tally(0).votes - second.quota).abs() < 1e-5);
+        assert!((tally(0).keep_factor - 2.0 / 3.0).abs() < 1e-5);
+        assert!((tally(1).votes - (3.0 + 10.0 / 3.0)).abs() < 1e-5);
+
+        let third = &result.rounds[2];
+        assert_eq!(vec![CandidateId(3)], third.eliminated);
+        assert_eq!(vec![CandidateId(1)], third.elected);
+        assert!((third.meek.as_ref().unwrap().quota - 17.0 / 3.0).abs() < 1e-5);
+    }
+
+    #[test]
+    fn test_meek_elimination() {
+        let candidates: Vec<CandidateId> = (0..3).map(CandidateId).collect();
+        let ballots = ballots(&[(&[0], 4), (&[1], 3), (&[2, 1], 2)]);
+
+        let result = tabulate_stv_meek(&candidates, &ballots, 1, QuotaRule::Droop).unwrap();
+
+        assert_eq!(vec![CandidateId(2)], result.rounds[1].eliminated);
+        assert_eq!(vec![CandidateId(1)], result.winners);
+        assert_eq!(
+            Some(TabulationError::NoSeats),
+            tabulate_stv_meek(&candidates, &ballots, 0, QuotaRule::Hare).err()
+        );
+    }
+}
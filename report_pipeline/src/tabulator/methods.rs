@@ -0,0 +1,114 @@
+use crate::model::election::{CandidateId, NormalizedBallot};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Scores for each candidate under a single-winner method, in descending
+/// order of score, and the candidates with the highest score.
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct MethodResult {
+    pub winners: Vec<CandidateId>,
+    pub scores: Vec<CandidateScore>,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct CandidateScore {
+    pub candidate: CandidateId,
+    pub score: u32,
+}
+
+impl MethodResult {
+    fn from_scores(candidates: &[CandidateId], scores: BTreeMap<CandidateId, u32>) -> MethodResult {
+        let mut scores: Vec<CandidateScore> = candidates
+            .iter()
+            .map(|c| CandidateScore {
+                candidate: *c,
+                score: *scores.get(c).unwrap_or(&0),
+            })
+            .collect();
+        scores.sort_by_key(|s| (std::cmp::Reverse(s.score), s.candidate));
+
+        let winners = match scores.first() {
+            Some(top) => scores
+                .iter()
+                .take_while(|s| s.score == top.score)
+                .map(|s| s.candidate)
+                .collect(),
+            None => Vec::new(),
+        };
+
+        MethodResult { winners, scores }
+    }
+}
+
+/// Count the first choice of each ballot.
+pub fn plurality(candidates: &[CandidateId], ballots: &[NormalizedBallot]) -> MethodResult {
+    approval(candidates, ballots, 1)
+}
+
+/// Give each candidate one point for every ballot that ranks them in its
+/// top `k` choices.
+pub fn approval(candidates: &[CandidateId], ballots: &[NormalizedBallot], k: u32) -> MethodResult {
+    let mut scores: BTreeMap<CandidateId, u32> = BTreeMap::new();
+    for ballot in ballots {
+        for choice in ballot.choices().into_iter().take(k as usize) {
+            *scores.entry(choice).or_default() += 1;
+        }
+    }
+
+    MethodResult::from_scores(candidates, scores)
+}
+
+/// Borda count: with `n` candidates, a ballot gives `n - 1` points to its
+/// first choice, `n - 2` to its second, and so on. Unranked candidates
+/// receive no points.
+pub fn borda(candidates: &[CandidateId], ballots: &[NormalizedBallot]) -> MethodResult {
+    let n = candidates.len() as u32;
+    let mut scores: BTreeMap<CandidateId, u32> = BTreeMap::new();
+    for ballot in ballots {
+        for (i, choice) in ballot.choices().into_iter().enumerate() {
+            *scores.entry(choice).or_default() += n.saturating_sub(i as u32 + 1);
+        }
+    }
+
+    MethodResult::from_scores(candidates, scores)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::ballots;
+
+    #[test]
+    fn test_methods() {
+        let candidates = [CandidateId(0), CandidateId(1), CandidateId(2)];
+        let ballots = ballots(&[(&[0, 2], 2), (&[1, 2], 3), (&[2, 0], 1)]);
+
+        assert_eq!(
+            vec![CandidateId(1)],
+            plurality(&candidates, &ballots).winners
+        );
+        assert_eq!(
+            vec![CandidateId(2)],
+            approval(&candidates, &ballots, 2).winners
+        );
+
+        let borda = borda(&candidates, &ballots);
+        assert_eq!(vec![CandidateId(2)], borda.winners);
+        assert_eq!(7, borda.scores[0].score);
+    }
+
+    #[test]
+    fn test_tied_winners() {
+        let candidates = [CandidateId(0), CandidateId(1)];
+        let ballots = ballots(&[(&[0], 1), (&[1], 1)]);
+
+        assert_eq!(
+            vec![CandidateId(0), CandidateId(1)],
+            plurality(&candidates, &ballots).winners
+        );
+    }
+}
@@ -1,7 +1,25 @@
+//! Instant-runoff tabulation of normalized ballots, plus single
+//! transferable vote counts for multi-winner contests. Each counting method
+//! an election can select is registered as a `Tabulator`.
+
+mod meek;
+mod methods;
+mod registry;
 mod schema;
+mod stv;
+mod trace;
 
 use crate::model::election::{CandidateId, Choice, NormalizedBallot};
+use crate::model::metadata::TabulationOptions;
+pub use crate::tabulator::meek::{tabulate_stv_meek, MeekRound, MeekTally};
+pub use crate::tabulator::methods::{approval, borda, plurality, CandidateScore, MethodResult};
+pub use crate::tabulator::registry::{
+    get_tabulator, selected_tabulator, Irv, StvCincinnati, StvMeek, TabulationError,
+    TabulationOutcome, Tabulator, IRV_METHOD, TABULATORS,
+};
 pub use crate::tabulator::schema::{Allocatee, TabulatorAllocation, TabulatorRound, Transfer};
+pub use crate::tabulator::stv::{tabulate_stv_cincinnati, StvResult, StvRound};
+pub use crate::tabulator::trace::{trace, TraceEvent};
 use std::collections::{BTreeMap, BTreeSet, HashSet};
 
 /// Represents the number of ballots considered to be allocated to
@@ -14,13 +32,12 @@ struct Allocations {
 impl Allocations {
     pub fn new(mut votes: Vec<(CandidateId, u32)>, exhausted: u32) -> Allocations {
         // Sort descending by number of votes.
-        votes.sort_by(|a, b| (b.1).cmp(&a.1));
+        votes.sort_by_key(|b| std::cmp::Reverse(b.1));
 
         Allocations { votes, exhausted }
     }
 
     /// Returns true if a winner can be declared from this allocation.
-    #[allow(unused)]
     pub fn is_final(&self) -> bool {
         match self.votes.first() {
             Some((_, first_votes)) => {
@@ -53,6 +70,32 @@ impl Allocations {
     }
 }
 
+/// Choose the candidates to eliminate after a round, given each continuing
+/// candidate's votes in descending order. Every candidate below the highest
+/// one whose votes exceed the combined votes of all candidates below it is
+/// eliminated at once, since none of them could overtake it. Also returns
+/// the index in `votes` of that candidate, or `None` if the trailing
+/// candidates are tied, in which case no batch of them can be safely
+/// eliminated and the last candidate in sort order is.
+pub(crate) fn select_eliminated(
+    votes: &[(CandidateId, u32)],
+) -> (BTreeSet<CandidateId>, Option<usize>) {
+    let mut remaining_votes: u32 = votes.iter().map(|(_, v)| v).sum();
+
+    for (i, (_, v)) in votes.iter().enumerate() {
+        remaining_votes -= v;
+        if *v > remaining_votes && i > 0 {
+            let batch: BTreeSet<CandidateId> = votes[i + 1..].iter().map(|d| d.0).collect();
+            if !batch.is_empty() {
+                return (batch, Some(i));
+            }
+            break;
+        }
+    }
+
+    (votes.last().map(|d| d.0).into_iter().collect(), None)
+}
+
 struct TabulatorState {
     /// Map from candidate to ballots attributed to that candidate at this round.
     /// Eliminated candidates ranking above the top non-eliminated candidate have
@@ -89,6 +132,8 @@ impl TabulatorState {
             undervote,
             overvote,
             continuing_ballots,
+            exhausted: undervote + overvote,
+            threshold: continuing_ballots / 2 + 1,
             transfers: self.transfers.clone(),
         }
     }
@@ -97,10 +142,7 @@ impl TabulatorState {
         let mut allocations: BTreeMap<Choice, Vec<NormalizedBallot>> = BTreeMap::new();
         for ballot in ballots {
             let choice = ballot.top_vote();
-            allocations
-                .entry(choice)
-                .or_insert_with(Vec::new)
-                .push(ballot.clone());
+            allocations.entry(choice).or_default().push(ballot.clone());
         }
         TabulatorState {
             candidate_ballots: allocations,
@@ -133,20 +175,7 @@ impl TabulatorState {
     pub fn do_elimination(self) -> TabulatorState {
         let allocations = self.allocations();
 
-        // Determine which candidates to eliminate.
-        let candidates_to_eliminate: BTreeSet<CandidateId> = {
-            let mut ai = allocations.votes.iter();
-            let mut remaining_votes = allocations.continuing();
-
-            for (i, (_, votes)) in (&mut ai).enumerate() {
-                remaining_votes -= votes;
-                if votes > &remaining_votes && i > 0 {
-                    break;
-                }
-            }
-
-            ai.map(|d| d.0).collect()
-        };
+        let (candidates_to_eliminate, _) = select_eliminated(&allocations.votes);
 
         let mut transfers: BTreeSet<Transfer> = BTreeSet::new();
         let mut eliminated = self.eliminated;
@@ -182,7 +211,7 @@ impl TabulatorState {
 
                 candidate_ballots
                     .entry(new_choice)
-                    .or_insert_with(Vec::new)
+                    .or_default()
                     .push(ballot.clone());
 
                 *transfer_map
@@ -222,7 +251,41 @@ impl TabulatorState {
     }
 }
 
+/// Count the ballots' first choices, as the only round of a contest that
+/// needs no eliminations.
+pub fn first_round(ballots: &[NormalizedBallot]) -> TabulatorRound {
+    TabulatorState::new(ballots).as_round()
+}
+
+/// Run an instant-runoff count over the given ballots, returning one
+/// `TabulatorRound` per round until two or fewer candidates remain.
 pub fn tabulate(ballots: &[NormalizedBallot]) -> Vec<TabulatorRound> {
+    tabulate_rounds(ballots, false)
+}
+
+/// Run an instant-runoff count that stops at the first round in which a
+/// candidate has a majority of continuing ballots (or two or fewer
+/// candidates remain), as some jurisdictions report their counts. The winner
+/// is the same as with `tabulate`; only later rounds are left out.
+pub fn tabulate_eager(ballots: &[NormalizedBallot]) -> Vec<TabulatorRound> {
+    tabulate_rounds(ballots, true)
+}
+
+/// Run a contest's instant-runoff count. With the `eager` tabulation
+/// option (and without `continueUntilTwo`), the count stops once a candidate
+/// has a majority; otherwise candidates are eliminated until two remain.
+pub fn count_rounds(
+    ballots: &[NormalizedBallot],
+    options: &TabulationOptions,
+) -> Vec<TabulatorRound> {
+    if options.eager == Some(true) && options.continue_until_two != Some(true) {
+        tabulate_eager(ballots)
+    } else {
+        tabulate(ballots)
+    }
+}
+
+fn tabulate_rounds(ballots: &[NormalizedBallot], eager: bool) -> Vec<TabulatorRound> {
     let mut state = TabulatorState::new(ballots);
     let mut rounds = Vec::new();
 
@@ -230,7 +293,7 @@ pub fn tabulate(ballots: &[NormalizedBallot]) -> Vec<TabulatorRound> {
         let allocations = state.allocations();
         rounds.push(state.as_round());
 
-        if allocations.votes.len() <= 2 {
+        if allocations.votes.len() <= 2 || (eager && allocations.is_final()) {
             break;
         }
 
@@ -239,3 +302,95 @@ pub fn tabulate(ballots: &[NormalizedBallot]) -> Vec<TabulatorRound> {
 
     rounds
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tied_last_place_terminates() {
+        let (a, b, c) = (CandidateId(0), CandidateId(1), CandidateId(2));
+        let ballots = vec![
+            NormalizedBallot::new("1".into(), vec![a], false),
+            NormalizedBallot::new("2".into(), vec![a], false),
+            NormalizedBallot::new("3".into(), vec![a], false),
+            NormalizedBallot::new("4".into(), vec![b, a], false),
+            NormalizedBallot::new("5".into(), vec![b], false),
+            NormalizedBallot::new("6".into(), vec![c], false),
+            NormalizedBallot::new("7".into(), vec![c], false),
+        ];
+
+        let rounds = tabulate(&ballots);
+        assert_eq!(2, rounds.len());
+        assert_eq!(CandidateId(2), rounds[1].transfers[0].from);
+    }
+
+    #[test]
+    fn test_first_round() {
+        let (a, w) = (CandidateId(0), CandidateId(1));
+        let ballots = vec![
+            NormalizedBallot::new("1".into(), vec![a], false),
+            NormalizedBallot::new("2".into(), vec![a], false),
+            NormalizedBallot::new("3".into(), vec![w, a], false),
+            NormalizedBallot::new("4".into(), vec![], false),
+        ];
+
+        let round = first_round(&ballots);
+        assert_eq!(Allocatee::Candidate(a), round.allocations[0].allocatee);
+        assert_eq!(2, round.allocations[0].votes);
+        assert_eq!(3, round.continuing_ballots);
+        assert_eq!(1, round.exhausted);
+        assert!(round.transfers.is_empty());
+    }
+
+    #[test]
+    fn test_tabulate_eager() {
+        let (a, b, c, d) = (
+            CandidateId(0),
+            CandidateId(1),
+            CandidateId(2),
+            CandidateId(3),
+        );
+        let ballots = vec![
+            NormalizedBallot::new("1".into(), vec![a], false),
+            NormalizedBallot::new("2".into(), vec![a], false),
+            NormalizedBallot::new("3".into(), vec![a], false),
+            NormalizedBallot::new("4".into(), vec![a], false),
+            NormalizedBallot::new("5".into(), vec![b], false),
+            NormalizedBallot::new("6".into(), vec![b], false),
+            NormalizedBallot::new("7".into(), vec![c], false),
+            NormalizedBallot::new("8".into(), vec![d, a], false),
+        ];
+
+        // After d is eliminated, a has 5 of 8 votes with three candidates left.
+        let eager = tabulate_eager(&ballots);
+        assert_eq!(2, eager.len());
+        assert_eq!(Allocatee::Candidate(a), eager[1].allocations[0].allocatee);
+        assert_eq!(5, eager[1].allocations[0].votes);
+        assert_eq!(3, tabulate(&ballots).len());
+    }
+
+    #[test]
+    fn test_round_totals() {
+        let (a, b, c) = (CandidateId(0), CandidateId(1), CandidateId(2));
+        let ballots = vec![
+            NormalizedBallot::new("1".into(), vec![a], false),
+            NormalizedBallot::new("2".into(), vec![a], false),
+            NormalizedBallot::new("3".into(), vec![b], false),
+            NormalizedBallot::new("4".into(), vec![b, a], false),
+            NormalizedBallot::new("5".into(), vec![c], false),
+            NormalizedBallot::new("6".into(), vec![], true),
+        ];
+
+        let rounds = tabulate(&ballots);
+        assert_eq!(2, rounds.len());
+
+        assert_eq!(5, rounds[0].continuing_ballots);
+        assert_eq!(1, rounds[0].exhausted);
+        assert_eq!(3, rounds[0].threshold);
+
+        assert_eq!(4, rounds[1].continuing_ballots);
+        assert_eq!(2, rounds[1].exhausted);
+        assert_eq!(3, rounds[1].threshold);
+    }
+}
@@ -0,0 +1,261 @@
+//! The counting methods an election can name in `tabulationOptions.method`,
+//! each behind the `Tabulator` trait so it can be selected by name and
+//! tested on its own.
+
+use crate::model::election::{CandidateId, NormalizedBallot};
+use crate::model::metadata::TabulationOptions;
+use crate::tabulator::{
+    count_rounds, tabulate_stv_cincinnati, tabulate_stv_meek, StvResult, TabulatorRound,
+};
+use std::error::Error;
+use std::fmt;
+
+/// Name of the default method, instant runoff.
+pub const IRV_METHOD: &str = "irv";
+
+/// The result of counting a contest by one method.
+pub enum TabulationOutcome {
+    /// Rounds of a single-winner instant-runoff count.
+    Rounds(Vec<TabulatorRound>),
+    /// A multi-winner single transferable vote count.
+    Stv(StvResult),
+}
+
+impl TabulationOutcome {
+    /// The elected candidates: for a single-winner count, the leader of the
+    /// last round, if any candidate received votes.
+    pub fn winners(&self) -> Vec<CandidateId> {
+        match self {
+            TabulationOutcome::Rounds(rounds) => rounds
+                .last()
+                .and_then(|r| r.allocations.first())
+                .and_then(|a| a.allocatee.candidate_id())
+                .into_iter()
+                .collect(),
+            TabulationOutcome::Stv(result) => result.winners.clone(),
+        }
+    }
+}
+
+/// An error counting a contest because its tabulation options don't
+/// describe a count that can be run.
+#[derive(Debug, PartialEq)]
+pub enum TabulationError {
+    /// `tabulationOptions.method` names no registered method.
+    UnknownMethod(String),
+    /// A multi-winner method was selected without `tabulationOptions.seats`.
+    MissingSeats { method: &'static str },
+    /// `tabulationOptions.seats` is zero, so no quota can be computed.
+    NoSeats,
+    /// No ballot ranks a candidate (or there are no candidates), so the
+    /// contest has no winner.
+    NoVotes,
+    /// The contest is counted by a method other than instant runoff, so its
+    /// count can't be followed round by round.
+    NotIrv { method: &'static str },
+}
+
+impl fmt::Display for TabulationError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            TabulationError::UnknownMethod(method) => {
+                write!(f, "unknown tabulation method {:?}", method)
+            }
+            TabulationError::MissingSeats { method } => {
+                write!(f, "tabulation method {} requires seats", method)
+            }
+            TabulationError::NoSeats => write!(f, "seats must be at least 1"),
+            TabulationError::NoVotes => write!(f, "no ballot ranks a candidate"),
+            TabulationError::NotIrv { method } => write!(
+                f,
+                "the contest is counted by {}, not instant runoff ({})",
+                method, IRV_METHOD
+            ),
+        }
+    }
+}
+
+impl Error for TabulationError {}
+
+/// A method of counting a contest's normalized ballots.
+pub trait Tabulator: Sync {
+    /// Name of the method in `tabulationOptions.method`.
+    fn name(&self) -> &'static str;
+
+    /// True if the method fills `tabulationOptions.seats` seats, which must
+    /// then be set.
+    fn multi_winner(&self) -> bool {
+        false
+    }
+
+    /// Count `ballots` for `candidates` under the election's tabulation
+    /// options.
+    fn tabulate(
+        &self,
+        ballots: &[NormalizedBallot],
+        candidates: &[CandidateId],
+        rules: &TabulationOptions,
+    ) -> Result<TabulationOutcome, TabulationError>;
+}
+
+/// Instant runoff, honoring `eager` and `continueUntilTwo`.
+pub struct Irv;
+
+impl Tabulator for Irv {
+    fn name(&self) -> &'static str {
+        IRV_METHOD
+    }
+
+    fn tabulate(
+        &self,
+        ballots: &[NormalizedBallot],
+        _candidates: &[CandidateId],
+        rules: &TabulationOptions,
+    ) -> Result<TabulationOutcome, TabulationError> {
+        Ok(TabulationOutcome::Rounds(count_rounds(ballots, rules)))
+    }
+}
+
+fn seats(method: &'static str, rules: &TabulationOptions) -> Result<u32, TabulationError> {
+    rules.seats.ok_or(TabulationError::MissingSeats { method })
+}
+
+/// STV with whole-ballot Cincinnati-method surplus transfers.
+pub struct StvCincinnati;
+
+impl Tabulator for StvCincinnati {
+    fn name(&self) -> &'static str {
+        "stv_cincinnati"
+    }
+
+    fn multi_winner(&self) -> bool {
+        true
+    }
+
+    fn tabulate(
+        &self,
+        ballots: &[NormalizedBallot],
+        candidates: &[CandidateId],
+        rules: &TabulationOptions,
+    ) -> Result<TabulationOutcome, TabulationError> {
+        tabulate_stv_cincinnati(
+            candidates,
+            ballots,
+            seats(self.name(), rules)?,
+            rules.eliminate_below,
+            rules.quota.unwrap_or_default(),
+            rules.quota_mode.unwrap_or_default(),
+        )
+        .map(TabulationOutcome::Stv)
+    }
+}
+
+/// STV by Meek's method.
+pub struct StvMeek;
+
+impl Tabulator for StvMeek {
+    fn name(&self) -> &'static str {
+        "stv_meek"
+    }
+
+    fn multi_winner(&self) -> bool {
+        true
+    }
+
+    fn tabulate(
+        &self,
+        ballots: &[NormalizedBallot],
+        candidates: &[CandidateId],
+        rules: &TabulationOptions,
+    ) -> Result<TabulationOutcome, TabulationError> {
+        tabulate_stv_meek(
+            candidates,
+            ballots,
+            seats(self.name(), rules)?,
+            rules.quota.unwrap_or_default(),
+        )
+        .map(TabulationOutcome::Stv)
+    }
+}
+
+/// The registered methods. A new method implements `Tabulator` and is added
+/// here.
+pub const TABULATORS: &[&dyn Tabulator] = &[&Irv, &StvCincinnati, &StvMeek];
+
+/// Return the registered method with the given name.
+pub fn get_tabulator(name: &str) -> Option<&'static dyn Tabulator> {
+    TABULATORS.iter().find(|t| t.name() == name).copied()
+}
+
+/// Return the method selected by `tabulationOptions.method`, instant runoff
+/// if none is.
+pub fn selected_tabulator(
+    rules: &TabulationOptions,
+) -> Result<&'static dyn Tabulator, TabulationError> {
+    let method = rules.method.as_deref().unwrap_or(IRV_METHOD);
+    get_tabulator(method).ok_or_else(|| TabulationError::UnknownMethod(method.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_get_tabulator() {
+        for name in ["irv", "stv_cincinnati", "stv_meek"] {
+            assert_eq!(name, get_tabulator(name).unwrap().name());
+        }
+        assert!(get_tabulator("borda").is_none());
+
+        let borda = TabulationOptions {
+            method: Some("borda".to_string()),
+            ..TabulationOptions::default()
+        };
+        assert_eq!(
+            Some(TabulationError::UnknownMethod("borda".to_string())),
+            selected_tabulator(&borda).err()
+        );
+        assert_eq!(
+            IRV_METHOD,
+            selected_tabulator(&TabulationOptions::default())
+                .unwrap()
+                .name()
+        );
+    }
+
+    #[test]
+    fn test_tabulation_outcome_winners() {
+        let candidates: Vec<CandidateId> = (0..3).map(CandidateId).collect();
+        let ballots: Vec<NormalizedBallot> = [0, 0, 0, 1, 1, 2]
+            .iter()
+            .enumerate()
+            .map(|(i, c)| NormalizedBallot::new(i.to_string(), vec![CandidateId(*c)], false))
+            .collect();
+        let rules = TabulationOptions {
+            seats: Some(2),
+            ..TabulationOptions::default()
+        };
+
+        let irv = get_tabulator(IRV_METHOD).unwrap();
+        assert_eq!(
+            vec![CandidateId(0)],
+            irv.tabulate(&ballots, &candidates, &rules)
+                .unwrap()
+                .winners()
+        );
+        for name in ["stv_cincinnati", "stv_meek"] {
+            let stv = get_tabulator(name).unwrap();
+            assert_eq!(
+                vec![CandidateId(0), CandidateId(1)],
+                stv.tabulate(&ballots, &candidates, &rules)
+                    .unwrap()
+                    .winners()
+            );
+            assert_eq!(
+                Some(TabulationError::MissingSeats { method: name }),
+                stv.tabulate(&ballots, &candidates, &TabulationOptions::default())
+                    .err()
+            );
+        }
+    }
+}
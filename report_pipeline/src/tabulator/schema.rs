@@ -3,17 +3,26 @@ use serde::de::{self, Visitor};
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
 
 #[derive(Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 #[serde(rename_all = "camelCase")]
 pub struct TabulatorRound {
     pub allocations: Vec<TabulatorAllocation>,
     pub undervote: u32,
     pub overvote: u32,
     pub continuing_ballots: u32,
+    /// Ballots exhausted in this or any earlier round (undervotes plus
+    /// overvotes).
+    #[serde(default)]
+    pub exhausted: u32,
+    /// Votes needed to win a majority of continuing ballots in this round.
+    #[serde(default)]
+    pub threshold: u32,
     pub transfers: Vec<Transfer>,
     //eliminated: Vec<u32>,
 }
 
 #[derive(Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 #[serde(rename_all = "camelCase")]
 pub struct TabulatorAllocation {
     pub allocatee: Allocatee,
@@ -90,7 +99,34 @@ impl<'de> Deserialize<'de> for Allocatee {
     }
 }
 
+/// An allocatee is a candidate id, or `"X"` for exhausted ballots.
+#[cfg(feature = "schema")]
+impl schemars::JsonSchema for Allocatee {
+    fn schema_name() -> String {
+        "Allocatee".to_string()
+    }
+
+    fn json_schema(gen: &mut schemars::gen::SchemaGenerator) -> schemars::schema::Schema {
+        use schemars::schema::{InstanceType, SchemaObject, SubschemaValidation};
+
+        let exhausted = SchemaObject {
+            instance_type: Some(InstanceType::String.into()),
+            enum_values: Some(vec!["X".into()]),
+            ..Default::default()
+        };
+        SchemaObject {
+            subschemas: Some(Box::new(SubschemaValidation {
+                any_of: Some(vec![gen.subschema_for::<CandidateId>(), exhausted.into()]),
+                ..Default::default()
+            })),
+            ..Default::default()
+        }
+        .into()
+    }
+}
+
 #[derive(Serialize, Deserialize, Clone, PartialEq, Ord, PartialOrd, Eq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 #[serde(rename_all = "camelCase")]
 pub struct Transfer {
     pub from: CandidateId,
@@ -0,0 +1,424 @@
+use crate::model::election::{CandidateId, NormalizedBallot};
+use crate::model::metadata::{QuotaMode, QuotaRule};
+use crate::tabulator::meek::MeekRound;
+use crate::tabulator::{Allocatee, TabulationError, TabulatorAllocation};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+
+/// Result of a multi-winner single transferable vote count.
+#[derive(Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct StvResult {
+    pub seats: u32,
+    /// Votes needed for election in the first round. With a recalculated
+    /// quota, each round records its own.
+    pub quota: u32,
+    /// Elected candidates, in order of election.
+    pub winners: Vec<CandidateId>,
+    pub rounds: Vec<StvRound>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct StvRound {
+    /// Votes held by each candidate still in the count, followed by exhausted
+    /// ballots, at the end of the round.
+    pub allocations: Vec<TabulatorAllocation>,
+    /// Candidates elected in this round.
+    pub elected: Vec<CandidateId>,
+    /// Candidates eliminated in this round.
+    pub eliminated: Vec<CandidateId>,
+    /// Votes needed for election in this round, rounded up for a Meek count.
+    pub quota: u32,
+    /// For a Meek count, the exact tallies and keep factors behind
+    /// `allocations`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub meek: Option<MeekRound>,
+}
+
+struct StvState<'a> {
+    ballots: &'a [NormalizedBallot],
+    quota: u32,
+    /// Ballot indices held by each candidate, in the order they were received.
+    piles: BTreeMap<CandidateId, Vec<usize>>,
+    exhausted: u32,
+    elected: Vec<CandidateId>,
+    defeated: BTreeSet<CandidateId>,
+}
+
+impl<'a> StvState<'a> {
+    fn is_hopeful(&self, candidate: &CandidateId) -> bool {
+        !self.defeated.contains(candidate) && !self.elected.contains(candidate)
+    }
+
+    fn votes(&self, candidate: &CandidateId) -> u32 {
+        self.piles.get(candidate).map_or(0, |p| p.len() as u32)
+    }
+
+    /// The next candidate on the ballot who is still in the count and has not
+    /// already reached the quota.
+    fn next_choice(&self, ballot: usize) -> Option<CandidateId> {
+        self.ballots[ballot]
+            .choices()
+            .into_iter()
+            .find(|c| self.is_hopeful(c) && self.votes(c) < self.quota)
+    }
+
+    /// Transfer a whole ballot to its next choice, or exhaust it. Returns
+    /// false if the ballot had no next choice.
+    fn transfer(&mut self, ballot: usize) -> bool {
+        match self.next_choice(ballot) {
+            Some(c) => {
+                self.piles.entry(c).or_default().push(ballot);
+                true
+            }
+            None => {
+                self.exhausted += 1;
+                false
+            }
+        }
+    }
+
+    /// Votes held by candidates still in the count, including those elected.
+    fn continuing_votes(&self) -> u32 {
+        self.piles
+            .iter()
+            .filter(|(c, _)| !self.defeated.contains(c))
+            .map(|(_, p)| p.len() as u32)
+            .sum()
+    }
+
+    fn hopeful(&self) -> Vec<CandidateId> {
+        self.piles
+            .keys()
+            .filter(|c| self.is_hopeful(c))
+            .copied()
+            .collect()
+    }
+
+    /// Elect every hopeful candidate at or above the quota, in descending
+    /// order of votes, transferring each one's surplus. Candidates who reach
+    /// the quota through those transfers are elected in the same round.
+    fn elect_and_transfer_surplus(&mut self) -> Vec<CandidateId> {
+        let mut elected = Vec::new();
+        loop {
+            let mut newly_elected: Vec<CandidateId> = self
+                .hopeful()
+                .into_iter()
+                .filter(|c| self.votes(c) >= self.quota)
+                .collect();
+            if newly_elected.is_empty() {
+                return elected;
+            }
+            newly_elected.sort_by_key(|c| std::cmp::Reverse(self.votes(c)));
+
+            for candidate in &newly_elected {
+                self.elected.push(*candidate);
+            }
+            for candidate in &newly_elected {
+                self.transfer_surplus(*candidate);
+            }
+            elected.extend(newly_elected);
+        }
+    }
+
+    /// Cincinnati method: the surplus is transferred as whole ballots taken at
+    /// regular intervals through the elected candidate's pile. If a selected
+    /// ballot has no next choice, the next ballot in the pile is used instead.
+    fn transfer_surplus(&mut self, candidate: CandidateId) {
+        let pile = self.piles.remove(&candidate).unwrap_or_default();
+        let surplus = pile.len().saturating_sub(self.quota as usize);
+        if surplus == 0 {
+            self.piles.insert(candidate, pile);
+            return;
+        }
+
+        let interval = pile.len() as f64 / surplus as f64;
+        let mut selected: BTreeSet<usize> = BTreeSet::new();
+        let mut k = 1;
+        while selected.len() < surplus && k <= surplus {
+            let mut position = ((k as f64 * interval).floor() as usize).clamp(1, pile.len()) - 1;
+            while position < pile.len()
+                && (selected.contains(&position) || self.next_choice(pile[position]).is_none())
+            {
+                position += 1;
+            }
+            if position < pile.len() {
+                selected.insert(position);
+                self.transfer(pile[position]);
+            }
+            k += 1;
+        }
+
+        let kept: Vec<usize> = pile
+            .into_iter()
+            .enumerate()
+            .filter(|(i, _)| !selected.contains(i))
+            .map(|(_, b)| b)
+            .collect();
+        self.piles.insert(candidate, kept);
+    }
+
+    fn eliminate(&mut self, candidates: &[CandidateId]) {
+        for candidate in candidates {
+            self.defeated.insert(*candidate);
+        }
+        for candidate in candidates {
+            for ballot in self.piles.remove(candidate).unwrap_or_default() {
+                self.transfer(ballot);
+            }
+        }
+    }
+
+    fn as_round(&self, elected: Vec<CandidateId>, eliminated: Vec<CandidateId>) -> StvRound {
+        let mut allocations: Vec<TabulatorAllocation> = self
+            .piles
+            .iter()
+            .filter(|(c, _)| !self.defeated.contains(c))
+            .map(|(c, p)| TabulatorAllocation {
+                allocatee: Allocatee::Candidate(*c),
+                votes: p.len() as u32,
+            })
+            .collect();
+        allocations.sort_by_key(|a| std::cmp::Reverse(a.votes));
+        allocations.push(TabulatorAllocation {
+            allocatee: Allocatee::Exhausted,
+            votes: self.exhausted,
+        });
+
+        StvRound {
+            allocations,
+            elected,
+            eliminated,
+            quota: self.quota,
+            meek: None,
+        }
+    }
+}
+
+/// Count a multi-winner contest by single transferable vote with whole-ballot
+/// surplus transfers by the Cincinnati method, as used in Cambridge, MA.
+///
+/// Ballots are counted in the order given. After first choices are counted,
+/// candidates at the quota are elected and their surpluses transferred;
+/// candidates who have reached the quota receive no further ballots. Then,
+/// if `minimum_votes` is given, every candidate with fewer votes than that is
+/// eliminated at once; after that, candidates are eliminated one at a time,
+/// fewest votes first, until every seat is filled.
+///
+/// The quota is computed by `quota_rule` from the ballots with at least one
+/// ranking. With `QuotaMode::Recalculated`, it is computed again before each
+/// later round's elections from the votes still held by candidates in the
+/// count; candidates already elected keep the ballots they hold. Returns an
+/// error if `seats` is zero.
+pub fn tabulate_stv_cincinnati(
+    candidates: &[CandidateId],
+    ballots: &[NormalizedBallot],
+    seats: u32,
+    minimum_votes: Option<u32>,
+    quota_rule: QuotaRule,
+    quota_mode: QuotaMode,
+) -> Result<StvResult, TabulationError> {
+    let votes = ballots.iter().filter(|b| !b.choices().is_empty()).count() as u32;
+    let quota = quota_rule.quota(votes, seats)?;
+
+    let mut state = StvState {
+        ballots,
+        quota,
+        piles: candidates.iter().map(|c| (*c, Vec::new())).collect(),
+        exhausted: 0,
+        elected: Vec::new(),
+        defeated: BTreeSet::new(),
+    };
+
+    for ballot in 0..ballots.len() {
+        state.transfer(ballot);
+    }
+
+    let mut rounds = Vec::new();
+    let elected = state.elect_and_transfer_surplus();
+    rounds.push(state.as_round(elected, Vec::new()));
+
+    let mut minimum_votes = minimum_votes;
+    while (state.elected.len() as u32) < seats {
+        let hopeful = state.hopeful();
+        let open_seats = seats as usize - state.elected.len();
+
+        if hopeful.len() <= open_seats {
+            state.elected.extend(hopeful.iter().copied());
+            rounds.push(state.as_round(hopeful, Vec::new()));
+            break;
+        }
+
+        let mut to_eliminate: Vec<CandidateId> = match minimum_votes.take() {
+            Some(minimum) => hopeful
+                .iter()
+                .filter(|c| state.votes(c) < minimum)
+                .copied()
+                .collect(),
+            None => Vec::new(),
+        };
+        if to_eliminate.is_empty() || hopeful.len() - to_eliminate.len() < open_seats {
+            // Eliminate the candidate with the fewest votes; ties go to the
+            // candidate listed last.
+            let last = hopeful
+                .iter()
+                .min_by_key(|c| (state.votes(c), std::cmp::Reverse(**c)))
+                .copied()
+                .unwrap();
+            to_eliminate = vec![last];
+        }
+
+        state.eliminate(&to_eliminate);
+        if quota_mode == QuotaMode::Recalculated {
+            state.quota = quota_rule.quota(state.continuing_votes(), seats)?;
+        }
+        let elected = state.elect_and_transfer_surplus();
+        rounds.push(state.as_round(elected, to_eliminate));
+    }
+
+    Ok(StvResult {
+        seats,
+        quota,
+        winners: state.elected,
+        rounds,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::ballots;
+
+    #[test]
+    fn test_surplus_transfer() {
+        let candidates: Vec<CandidateId> = (0..4).map(CandidateId).collect();
+        // 20 ballots, 2 seats: quota is 7.
+        let ballots = ballots(&[(&[0, 1], 10), (&[2], 5), (&[3, 2], 4), (&[1], 1)]);
+
+        let result = tabulate_stv_cincinnati(
+            &candidates,
+            &ballots,
+            2,
+            None,
+            QuotaRule::Droop,
+            QuotaMode::Static,
+        )
+        .unwrap();
+
+        assert_eq!(7, result.quota);
+        assert_eq!(vec![CandidateId(0), CandidateId(2)], result.winners);
+        // Candidate 0's surplus of 3 goes to candidate 1.
+        let first = &result.rounds[0];
+        assert_eq!(vec![CandidateId(0)], first.elected);
+        assert_eq!(
+            Allocatee::Candidate(CandidateId(0)),
+            first.allocations[0].allocatee
+        );
+        assert_eq!(7, first.allocations[0].votes);
+        assert_eq!(
+            4,
+            first
+                .allocations
+                .iter()
+                .find(|a| a.allocatee == Allocatee::Candidate(CandidateId(1)))
+                .unwrap()
+                .votes
+        );
+    }
+
+    #[test]
+    fn test_elected_by_surplus_transfer() {
+        let candidates: Vec<CandidateId> = (0..4).map(CandidateId).collect();
+        // 21 ballots, 2 seats: quota is 8. Candidate 0's surplus of 3 brings
+        // candidate 1 to the quota in the first round.
+        let ballots = ballots(&[(&[0, 1], 11), (&[1], 5), (&[2], 3), (&[3], 2)]);
+
+        let result = tabulate_stv_cincinnati(
+            &candidates,
+            &ballots,
+            2,
+            None,
+            QuotaRule::Droop,
+            QuotaMode::Static,
+        )
+        .unwrap();
+
+        assert_eq!(1, result.rounds.len());
+        assert_eq!(
+            vec![CandidateId(0), CandidateId(1)],
+            result.rounds[0].elected
+        );
+        assert_eq!(vec![CandidateId(0), CandidateId(1)], result.winners);
+    }
+
+    #[test]
+    fn test_minimum_votes_batch_elimination() {
+        let candidates: Vec<CandidateId> = (0..5).map(CandidateId).collect();
+        let ballots = ballots(&[(&[0], 6), (&[1], 5), (&[2], 4), (&[3, 2], 1), (&[4, 2], 1)]);
+
+        let result = tabulate_stv_cincinnati(
+            &candidates,
+            &ballots,
+            2,
+            Some(2),
+            QuotaRule::Droop,
+            QuotaMode::Static,
+        )
+        .unwrap();
+
+        assert_eq!(
+            vec![CandidateId(3), CandidateId(4)],
+            result.rounds[1].eliminated
+        );
+        assert_eq!(2, result.winners.len());
+        assert!(result.winners.contains(&CandidateId(0)));
+    }
+
+    #[test]
+    fn test_quota_rules() {
+        assert_eq!(Ok(7), QuotaRule::Droop.quota(20, 2));
+        assert_eq!(Ok(10), QuotaRule::Hare.quota(20, 2));
+        assert_eq!(Ok(7), QuotaRule::Hare.quota(20, 3));
+        assert_eq!(Err(TabulationError::NoSeats), QuotaRule::Hare.quota(20, 0));
+    }
+
+    #[test]
+    fn test_recalculated_quota() {
+        let candidates: Vec<CandidateId> = (0..4).map(CandidateId).collect();
+        // 20 ballots, 2 seats. Candidate 3's four ballots exhaust when they
+        // are eliminated, so the Droop quota falls from 7 to 6.
+        let ballots = ballots(&[(&[0], 6), (&[1], 6), (&[2], 4), (&[3], 4)]);
+
+        let fixed = tabulate_stv_cincinnati(
+            &candidates,
+            &ballots,
+            2,
+            None,
+            QuotaRule::Droop,
+            QuotaMode::Static,
+        )
+        .unwrap();
+        assert!(fixed.rounds.iter().all(|r| r.quota == 7));
+
+        let result = tabulate_stv_cincinnati(
+            &candidates,
+            &ballots,
+            2,
+            None,
+            QuotaRule::Droop,
+            QuotaMode::Recalculated,
+        )
+        .unwrap();
+        assert_eq!(7, result.quota);
+        assert_eq!(vec![CandidateId(3)], result.rounds[1].eliminated);
+        assert_eq!(6, result.rounds[1].quota);
+        assert_eq!(
+            vec![CandidateId(0), CandidateId(1)],
+            result.rounds[1].elected
+        );
+        assert_eq!(vec![CandidateId(0), CandidateId(1)], result.winners);
+    }
+}
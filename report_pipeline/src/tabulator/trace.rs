@@ -0,0 +1,187 @@
+use crate::model::election::CandidateId;
+use crate::tabulator::{select_eliminated, TabulatorRound};
+use serde::{Deserialize, Serialize};
+
+/// A step of an instant-runoff count, with the numbers that decided it.
+/// Rounds are numbered from 1.
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(tag = "event", rename_all = "camelCase")]
+pub enum TraceEvent {
+    /// The totals of a round.
+    #[serde(rename_all = "camelCase")]
+    Tally {
+        round: u32,
+        continuing_ballots: u32,
+        exhausted: u32,
+        /// Votes needed for a majority of continuing ballots.
+        threshold: u32,
+    },
+    /// Candidates eliminated after a round because together they have fewer
+    /// votes than the next candidate above them, so none of them could
+    /// overtake that candidate. A single candidate is the one in last place.
+    #[serde(rename_all = "camelCase")]
+    Elimination {
+        round: u32,
+        candidates: Vec<CandidateId>,
+        combined_votes: u32,
+        next_candidate: CandidateId,
+        next_votes: u32,
+    },
+    /// Candidates tied for last place after a round. The one listed last in
+    /// the round's allocations (the highest candidate id) is eliminated.
+    #[serde(rename_all = "camelCase")]
+    TieBreak {
+        round: u32,
+        tied: Vec<CandidateId>,
+        votes: u32,
+        eliminated: CandidateId,
+    },
+    /// The count stops when two or fewer candidates remain; the leader of
+    /// the last round wins.
+    #[serde(rename_all = "camelCase")]
+    Winner {
+        round: u32,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        candidate: Option<CandidateId>,
+        votes: u32,
+        /// True if the winner's votes reach the last round's threshold.
+        majority: bool,
+    },
+}
+
+/// Explain the given instant-runoff rounds as a list of events: each round's
+/// totals, why the candidates eliminated after it were chosen, and the
+/// winner. Eliminations are re-derived from each round's tallies by the same
+/// rule the tabulator applies.
+pub fn trace(rounds: &[TabulatorRound]) -> Vec<TraceEvent> {
+    let mut events = Vec::new();
+
+    for (i, round) in rounds.iter().enumerate() {
+        let number = i as u32 + 1;
+        events.push(TraceEvent::Tally {
+            round: number,
+            continuing_ballots: round.continuing_ballots,
+            exhausted: round.exhausted,
+            threshold: round.threshold,
+        });
+
+        let votes: Vec<(CandidateId, u32)> = round
+            .allocations
+            .iter()
+            .filter_map(|a| a.allocatee.candidate_id().map(|c| (c, a.votes)))
+            .collect();
+
+        if i + 1 == rounds.len() {
+            let leader = votes.first();
+            events.push(TraceEvent::Winner {
+                round: number,
+                candidate: leader.map(|(c, _)| *c),
+                votes: leader.map_or(0, |(_, v)| *v),
+                majority: leader.is_some_and(|(_, v)| *v >= round.threshold),
+            });
+            break;
+        }
+
+        let (eliminated, above) = select_eliminated(&votes);
+        match above {
+            Some(above) => {
+                let (next_candidate, next_votes) = votes[above];
+                events.push(TraceEvent::Elimination {
+                    round: number,
+                    candidates: eliminated.into_iter().collect(),
+                    combined_votes: votes[above + 1..].iter().map(|(_, v)| v).sum(),
+                    next_candidate,
+                    next_votes,
+                });
+            }
+            None => {
+                let (last, last_votes) = *votes.last().unwrap();
+                events.push(TraceEvent::TieBreak {
+                    round: number,
+                    tied: votes
+                        .iter()
+                        .filter(|(_, v)| *v == last_votes)
+                        .map(|(c, _)| *c)
+                        .collect(),
+                    votes: last_votes,
+                    eliminated: last,
+                });
+            }
+        }
+    }
+
+    events
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::election::NormalizedBallot;
+    use crate::tabulator::tabulate;
+
+    #[test]
+    fn test_trace() {
+        let (a, b, c, d) = (
+            CandidateId(0),
+            CandidateId(1),
+            CandidateId(2),
+            CandidateId(3),
+        );
+        let mut ballots = Vec::new();
+        let mut add = |choices: Vec<CandidateId>, count: usize| {
+            for _ in 0..count {
+                let id = ballots.len().to_string();
+                ballots.push(NormalizedBallot::new(id, choices.clone(), false));
+            }
+        };
+        add(vec![a], 5);
+        add(vec![b, a], 4);
+        add(vec![c, b], 2);
+        add(vec![d, b], 2);
+
+        let events = trace(&tabulate(&ballots));
+        assert_eq!(
+            vec![
+                TraceEvent::Tally {
+                    round: 1,
+                    continuing_ballots: 13,
+                    exhausted: 0,
+                    threshold: 7
+                },
+                TraceEvent::TieBreak {
+                    round: 1,
+                    tied: vec![c, d],
+                    votes: 2,
+                    eliminated: d
+                },
+                TraceEvent::Tally {
+                    round: 2,
+                    continuing_ballots: 13,
+                    exhausted: 0,
+                    threshold: 7
+                },
+                TraceEvent::Elimination {
+                    round: 2,
+                    candidates: vec![c],
+                    combined_votes: 2,
+                    next_candidate: a,
+                    next_votes: 5
+                },
+                TraceEvent::Tally {
+                    round: 3,
+                    continuing_ballots: 13,
+                    exhausted: 0,
+                    threshold: 7
+                },
+                TraceEvent::Winner {
+                    round: 3,
+                    candidate: Some(b),
+                    votes: 8,
+                    majority: true
+                },
+            ],
+            events
+        );
+    }
+}
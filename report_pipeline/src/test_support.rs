@@ -0,0 +1,20 @@
+//! Helpers shared by unit tests.
+
+use crate::model::election::{CandidateId, NormalizedBallot};
+
+/// Build ballots from `(ranking, count)` pairs: `count` identical ballots
+/// ranking the candidates with the given ids in order, numbered from 0.
+pub fn ballots(rankings: &[(&[u32], usize)]) -> Vec<NormalizedBallot> {
+    rankings
+        .iter()
+        .flat_map(|(ranking, count)| std::iter::repeat_n(*ranking, *count))
+        .enumerate()
+        .map(|(i, ranking)| {
+            NormalizedBallot::new(
+                i.to_string(),
+                ranking.iter().map(|c| CandidateId(*c)).collect(),
+                false,
+            )
+        })
+        .collect()
+}
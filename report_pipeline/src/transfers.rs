@@ -0,0 +1,163 @@
+//! Vote transfers between rounds, shaped for Sankey diagrams.
+
+use crate::model::election::Candidate;
+use crate::model::transfers::{TransferGraph, TransferLink, TransferNode};
+use crate::tabulator::{Allocatee, TabulatorRound};
+use std::collections::{BTreeMap, BTreeSet};
+
+/// Build the graph of votes flowing between the given instant-runoff rounds.
+/// Each candidate still in the count keeps their votes into the next round,
+/// eliminated candidates' votes follow the round's transfers, and exhausted
+/// ballots stay exhausted. Links carrying no votes are left out.
+pub fn transfer_graph(candidates: &[Candidate], rounds: &[TabulatorRound]) -> TransferGraph {
+    let mut nodes = Vec::new();
+    // Index in `nodes` of each allocatee's node in each round.
+    let mut node_index: Vec<BTreeMap<Allocatee, usize>> = Vec::with_capacity(rounds.len());
+
+    for (i, round) in rounds.iter().enumerate() {
+        let mut index = BTreeMap::new();
+        for allocation in &round.allocations {
+            let (candidate, name) = match allocation.allocatee {
+                Allocatee::Candidate(c) => (Some(c), candidates[c.0 as usize].name.clone()),
+                Allocatee::Exhausted => (None, "Exhausted".to_string()),
+            };
+            index.insert(allocation.allocatee, nodes.len());
+            nodes.push(TransferNode {
+                round: i as u32 + 1,
+                candidate,
+                name,
+                votes: allocation.votes,
+            });
+        }
+        node_index.push(index);
+    }
+
+    let mut links = Vec::new();
+    for (i, (round, next)) in rounds.iter().zip(rounds.iter().skip(1)).enumerate() {
+        let eliminated: BTreeSet<Allocatee> = next
+            .transfers
+            .iter()
+            .map(|t| Allocatee::Candidate(t.from))
+            .collect();
+
+        for allocation in &round.allocations {
+            if eliminated.contains(&allocation.allocatee) || allocation.votes == 0 {
+                continue;
+            }
+            if let Some(target) = node_index[i + 1].get(&allocation.allocatee) {
+                links.push(TransferLink {
+                    source: node_index[i][&allocation.allocatee],
+                    target: *target,
+                    value: allocation.votes,
+                });
+            }
+        }
+
+        for transfer in &next.transfers {
+            if transfer.count == 0 {
+                continue;
+            }
+            let from = Allocatee::Candidate(transfer.from);
+            if let (Some(source), Some(target)) = (
+                node_index[i].get(&from),
+                node_index[i + 1].get(&transfer.to),
+            ) {
+                links.push(TransferLink {
+                    source: *source,
+                    target: *target,
+                    value: transfer.count,
+                });
+            }
+        }
+    }
+
+    TransferGraph { nodes, links }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::election::{CandidateId, CandidateType};
+    use crate::tabulator::{TabulatorAllocation, Transfer};
+
+    fn round(votes: &[(Allocatee, u32)], transfers: Vec<Transfer>) -> TabulatorRound {
+        TabulatorRound {
+            allocations: votes
+                .iter()
+                .map(|(a, v)| TabulatorAllocation {
+                    allocatee: *a,
+                    votes: *v,
+                })
+                .collect(),
+            undervote: 0,
+            overvote: 0,
+            continuing_ballots: 0,
+            exhausted: 0,
+            threshold: 0,
+            transfers,
+        }
+    }
+
+    #[test]
+    fn test_transfer_graph() {
+        let candidates: Vec<Candidate> = ["A", "B", "C"]
+            .iter()
+            .map(|n| Candidate::new(n.to_string(), CandidateType::Regular))
+            .collect();
+        let a = Allocatee::Candidate(CandidateId(0));
+        let b = Allocatee::Candidate(CandidateId(1));
+        let c = Allocatee::Candidate(CandidateId(2));
+        let x = Allocatee::Exhausted;
+        let rounds = vec![
+            round(&[(a, 5), (b, 4), (c, 3), (x, 0)], vec![]),
+            round(
+                &[(a, 6), (b, 5), (x, 1)],
+                vec![
+                    Transfer {
+                        from: CandidateId(2),
+                        to: a,
+                        count: 1,
+                    },
+                    Transfer {
+                        from: CandidateId(2),
+                        to: b,
+                        count: 1,
+                    },
+                    Transfer {
+                        from: CandidateId(2),
+                        to: x,
+                        count: 1,
+                    },
+                ],
+            ),
+        ];
+
+        let graph = transfer_graph(&candidates, &rounds);
+
+        assert_eq!(7, graph.nodes.len());
+        assert_eq!("Exhausted", graph.nodes[6].name);
+        assert_eq!(None, graph.nodes[6].candidate);
+        assert_eq!(2, graph.nodes[6].round);
+
+        let links: Vec<(usize, usize, u32)> = graph
+            .links
+            .iter()
+            .map(|l| (l.source, l.target, l.value))
+            .collect();
+        assert_eq!(
+            vec![(0, 4, 5), (1, 5, 4), (2, 4, 1), (2, 5, 1), (2, 6, 1)],
+            links
+        );
+
+        // Every node's outgoing votes add up to its total.
+        for (i, node) in graph.nodes.iter().enumerate().filter(|(_, n)| n.round == 1) {
+            let out: u32 = graph
+                .links
+                .iter()
+                .filter(|l| l.source == i)
+                .map(|l| l.value)
+                .sum();
+            assert_eq!(node.votes, out);
+        }
+    }
+}
@@ -0,0 +1,43 @@
+use std::fs::File;
+use std::io;
+use std::path::Path;
+
+/// Extract the files in the ZIP archive at `archive` into `dest_dir`,
+/// flattening any directory structure inside the archive. `should_extract`
+/// is called with each entry's file name and decides whether it is written;
+/// this lets callers skip entries that were already extracted. Returns the
+/// names of the files written.
+pub fn extract_archive(
+    archive: &Path,
+    dest_dir: &Path,
+    should_extract: impl Fn(&str) -> bool,
+) -> io::Result<Vec<String>> {
+    let mut zip = zip::ZipArchive::new(File::open(archive)?)?;
+    let mut extracted = Vec::new();
+
+    for i in 0..zip.len() {
+        let mut entry = zip.by_index(i)?;
+        if entry.is_dir() {
+            continue;
+        }
+
+        let name = match entry
+            .enclosed_name()
+            .and_then(|p| p.file_name())
+            .and_then(|f| f.to_str())
+        {
+            Some(name) if !name.starts_with('.') => name.to_string(),
+            _ => continue,
+        };
+
+        if !should_extract(&name) {
+            continue;
+        }
+
+        let mut out = File::create(dest_dir.join(&name))?;
+        io::copy(&mut entry, &mut out)?;
+        extracted.push(name);
+    }
+
+    Ok(extracted)
+}
@@ -0,0 +1,110 @@
+use crate::util::hash_file;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+/// Files read and written since `start_recording`, or `None` if the
+/// process isn't recording.
+static RECORDING: Mutex<Option<Recording>> = Mutex::new(None);
+
+#[derive(Default)]
+struct Recording {
+    inputs: BTreeMap<PathBuf, String>,
+    outputs: Vec<PathBuf>,
+}
+
+/// The files a command read and wrote, each with its tagged hash.
+#[derive(Default, Debug)]
+pub struct RecordedFiles {
+    /// Files read, hashed when they were first read.
+    pub inputs: BTreeMap<String, String>,
+    /// Files written, hashed when recording finished.
+    pub outputs: BTreeMap<String, String>,
+}
+
+/// Start recording the files that are read and written through the helpers
+/// in this module (serialized files, and raw files as they're verified), for
+/// the rest of the process or until `finish_recording`.
+pub fn start_recording() {
+    *RECORDING.lock().unwrap() = Some(Recording::default());
+}
+
+/// Record that the file at `path` was read, if recording.
+pub fn record_input(path: &Path) {
+    let needed = RECORDING
+        .lock()
+        .unwrap()
+        .as_ref()
+        .is_some_and(|recording| !recording.inputs.contains_key(path));
+    if needed {
+        // Hash without holding the lock, since input files can be large.
+        record_hashed_input(path, &hash_file(path.to_path_buf()));
+    }
+}
+
+/// Record that the file at `path` was read and has the given tagged hash,
+/// if recording. Used for files that were just hashed anyway.
+pub fn record_hashed_input(path: &Path, hash: &str) {
+    if let Some(recording) = RECORDING.lock().unwrap().as_mut() {
+        recording
+            .inputs
+            .entry(path.to_path_buf())
+            .or_insert_with(|| hash.to_string());
+    }
+}
+
+/// Record that the file at `path` was written, if recording.
+pub fn record_output(path: &Path) {
+    if let Some(recording) = RECORDING.lock().unwrap().as_mut() {
+        recording.outputs.push(path.to_path_buf());
+    }
+}
+
+/// Stop recording and return the files recorded, hashing the outputs as
+/// they are now. Returns `None` if the process wasn't recording.
+pub fn finish_recording() -> Option<RecordedFiles> {
+    let recording = RECORDING.lock().unwrap().take()?;
+    let name = |path: &Path| path.to_string_lossy().to_string();
+
+    Some(RecordedFiles {
+        inputs: recording
+            .inputs
+            .iter()
+            .map(|(path, hash)| (name(path), hash.clone()))
+            .collect(),
+        outputs: recording
+            .outputs
+            .iter()
+            .filter(|path| path.exists())
+            .map(|path| (name(path), hash_file(path.clone())))
+            .collect(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_recording() {
+        let path = std::env::temp_dir().join(format!("audit_test_{}", std::process::id()));
+        std::fs::write(&path, "abc").unwrap();
+
+        record_output(&path);
+        start_recording();
+        record_input(&path);
+        record_output(&path);
+        record_output(&path.with_extension("missing"));
+        let files = finish_recording().unwrap();
+        assert!(finish_recording().is_none());
+
+        let name = path.to_string_lossy().to_string();
+        let hash = "sha256:ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad";
+        assert_eq!(Some(hash), files.inputs.get(&name).map(|h| h.as_str()));
+        assert_eq!(Some(hash), files.outputs.get(&name).map(|h| h.as_str()));
+        let missing = path.with_extension("missing");
+        assert!(!files.outputs.contains_key(&*missing.to_string_lossy()));
+
+        std::fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,35 @@
+use std::io::{self, Write};
+
+/// Quote a CSV field if it contains a delimiter, quote, or line break.
+fn escape_field(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+/// Write one comma-separated row, quoting fields as needed.
+pub fn write_csv_row<W: Write, S: AsRef<str>>(writer: &mut W, fields: &[S]) -> io::Result<()> {
+    let row: Vec<String> = fields.iter().map(|f| escape_field(f.as_ref())).collect();
+    writeln!(writer, "{}", row.join(","))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_write_csv_row() {
+        let mut out: Vec<u8> = Vec::new();
+        write_csv_row(
+            &mut out,
+            &["1", "Jane Doe", "Smith, John", "Joe \"Bud\" Lee"],
+        )
+        .unwrap();
+        assert_eq!(
+            "1,Jane Doe,\"Smith, John\",\"Joe \"\"Bud\"\" Lee\"\n",
+            String::from_utf8(out).unwrap()
+        );
+    }
+}
@@ -0,0 +1,70 @@
+use std::sync::atomic::{AtomicI32, Ordering};
+
+/// How a command went, from best to worst. The discriminant is the process
+/// exit code.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Outcome {
+    Success = 0,
+    /// Something was logged as a warning, e.g. a raw file whose hash doesn't
+    /// match metadata, or a malformed row that was skipped.
+    Warning = 2,
+    /// Input data or metadata is invalid, so some output couldn't be
+    /// produced.
+    DataError = 3,
+    /// The environment failed: a lock was held, a file couldn't be written,
+    /// or a download or notification failed.
+    InfraError = 4,
+}
+
+/// The worst outcome recorded so far, as its exit code.
+struct OutcomeRecord(AtomicI32);
+
+impl OutcomeRecord {
+    const fn new() -> OutcomeRecord {
+        OutcomeRecord(AtomicI32::new(Outcome::Success as i32))
+    }
+
+    fn record(&self, outcome: Outcome) {
+        self.0.fetch_max(outcome as i32, Ordering::Relaxed);
+    }
+
+    fn exit_code(&self, strict: bool) -> i32 {
+        match self.0.load(Ordering::Relaxed) {
+            code if code == Outcome::Warning as i32 && !strict => Outcome::Success as i32,
+            code => code,
+        }
+    }
+}
+
+/// The outcome of the running command.
+static OUTCOME: OutcomeRecord = OutcomeRecord::new();
+
+/// Record that the command has (at least) this outcome. Warnings logged
+/// through `tracing` are recorded as `Warning`; call this with `DataError` or
+/// `InfraError` before logging an error.
+pub fn record_outcome(outcome: Outcome) {
+    OUTCOME.record(outcome);
+}
+
+/// The code the process should exit with. Warnings only fail the command
+/// if `strict` is set.
+pub fn exit_code(strict: bool) -> i32 {
+    OUTCOME.exit_code(strict)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_exit_code() {
+        let outcome = OutcomeRecord::new();
+        assert_eq!(0, outcome.exit_code(true));
+        outcome.record(Outcome::Warning);
+        assert_eq!(0, outcome.exit_code(false));
+        assert_eq!(2, outcome.exit_code(true));
+        outcome.record(Outcome::InfraError);
+        outcome.record(Outcome::DataError);
+        assert_eq!(4, outcome.exit_code(false));
+    }
+}
@@ -0,0 +1,96 @@
+use std::fs::{remove_file, rename, File};
+use std::io::copy;
+use std::path::{Path, PathBuf};
+
+/// The temporary file a download to `dest` is written to: `dest` with
+/// `.part` appended, so that files differing only in extension don't share
+/// one.
+fn partial_path(dest: &Path) -> PathBuf {
+    let file_name = dest.file_name().unwrap_or_default().to_string_lossy();
+    dest.with_file_name(format!("{}.part", file_name))
+}
+
+/// The HTTPS URL of an object in a public S3 bucket, given its
+/// `s3://bucket/key` URI.
+fn s3_https_url(uri: &str) -> Option<String> {
+    let (bucket, key) = uri.strip_prefix("s3://")?.split_once('/')?;
+    Some(format!("https://{}.s3.amazonaws.com/{}", bucket, key))
+}
+
+fn request_error(url: &str, err: ureq::Error) -> String {
+    match err {
+        ureq::Error::Status(code, response) => format!(
+            "Request to {} failed with status {} {}",
+            url,
+            code,
+            response.status_text()
+        ),
+        ureq::Error::Transport(transport) => {
+            format!("Request to {} failed: {}", url, transport)
+        }
+    }
+}
+
+/// Download `url` to `dest`. `https://` and `http://` URLs are fetched
+/// directly, and `s3://` URIs from the bucket's public HTTPS endpoint
+/// (unsigned, as in `mount.sh`). The download is written to a temporary
+/// file next to `dest` and moved into place only once it completes.
+pub fn fetch_url(url: &str, dest: &Path) -> Result<(), String> {
+    let http_url = if url.starts_with("s3://") {
+        s3_https_url(url).ok_or_else(|| format!("Invalid S3 URI: {}", url))?
+    } else if url.starts_with("https://") || url.starts_with("http://") {
+        url.to_string()
+    } else {
+        return Err(format!("Unsupported URL scheme: {}", url));
+    };
+
+    let response = ureq::get(&http_url)
+        .call()
+        .map_err(|e| request_error(url, e))?;
+
+    let partial = partial_path(dest);
+    let written =
+        File::create(&partial).and_then(|mut file| copy(&mut response.into_reader(), &mut file));
+    if let Err(e) = written {
+        let _ = remove_file(&partial);
+        return Err(format!("Downloading {} failed: {}", url, e));
+    }
+
+    rename(&partial, dest).map_err(|e| format!("Could not move download into place: {}", e))
+}
+
+/// POST `body`, of the given content type, to an `https://` or `http://`
+/// URL.
+pub fn post_url(url: &str, content_type: &str, body: &[u8]) -> Result<(), String> {
+    ureq::post(url)
+        .set("Content-Type", content_type)
+        .send_bytes(body)
+        .map(|_| ())
+        .map_err(|e| request_error(url, e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_s3_https_url() {
+        assert_eq!(
+            Some("https://bucket.s3.amazonaws.com/us/ca/x.zip".to_string()),
+            s3_https_url("s3://bucket/us/ca/x.zip")
+        );
+        assert_eq!(None, s3_https_url("s3://bucket"));
+    }
+
+    #[test]
+    fn test_partial_path() {
+        assert_eq!(
+            Path::new("raw/x.csv.gz.part"),
+            partial_path(Path::new("raw/x.csv.gz"))
+        );
+        assert_ne!(
+            partial_path(Path::new("raw/a.csv")),
+            partial_path(Path::new("raw/a.xlsx"))
+        );
+    }
+}
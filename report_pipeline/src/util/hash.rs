@@ -1,13 +1,200 @@
+use crate::util::{map_parallel, record_hashed_input};
+use serde::Serialize;
 use sha1::{Digest, Sha1};
+use sha2::Sha256;
+use std::collections::BTreeMap;
+use std::fmt;
 use std::fs::File;
 use std::io;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
-/// Return the SHA-1 hash of the file at the given location.
-pub fn hash_file(path: PathBuf) -> String {
+/// An algorithm used to hash raw data files. Hashes are written with the
+/// algorithm's tag as a prefix (e.g. `sha256:…`); untagged hashes are
+/// legacy SHA-1 hashes.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum HashAlgorithm {
+    Sha1,
+    Sha256,
+    Blake3,
+}
+
+impl HashAlgorithm {
+    /// Algorithm used for newly recorded hashes.
+    pub const DEFAULT: HashAlgorithm = HashAlgorithm::Sha256;
+
+    pub fn tag(&self) -> &'static str {
+        match self {
+            HashAlgorithm::Sha1 => "sha1",
+            HashAlgorithm::Sha256 => "sha256",
+            HashAlgorithm::Blake3 => "blake3",
+        }
+    }
+
+    /// Return the algorithm a recorded hash was made with, or `None` if its
+    /// tag isn't known.
+    pub fn of(hash: &str) -> Option<HashAlgorithm> {
+        match hash.split_once(':') {
+            None => Some(HashAlgorithm::Sha1),
+            Some(("sha1", _)) => Some(HashAlgorithm::Sha1),
+            Some(("sha256", _)) => Some(HashAlgorithm::Sha256),
+            Some(("blake3", _)) => Some(HashAlgorithm::Blake3),
+            Some(_) => None,
+        }
+    }
+}
+
+/// Return the hash of the file at the given location, tagged with the
+/// algorithm.
+pub fn hash_file_with(path: &Path, algorithm: HashAlgorithm) -> String {
     let mut file = File::open(path).unwrap();
+    let hash = match algorithm {
+        HashAlgorithm::Sha1 => {
+            let mut hasher = Sha1::new();
+            io::copy(&mut file, &mut hasher).unwrap();
+            format!("{:x}", hasher.finalize())
+        }
+        HashAlgorithm::Sha256 => {
+            let mut hasher = Sha256::new();
+            io::copy(&mut file, &mut hasher).unwrap();
+            format!("{:x}", hasher.finalize())
+        }
+        HashAlgorithm::Blake3 => {
+            let mut hasher = blake3::Hasher::new();
+            io::copy(&mut file, &mut hasher).unwrap();
+            hasher.finalize().to_hex().to_string()
+        }
+    };
+    format!("{}:{}", algorithm.tag(), hash)
+}
+
+/// Return the hash of the file at the given location with the default
+/// algorithm, tagged.
+pub fn hash_file(path: PathBuf) -> String {
+    hash_file_with(&path, HashAlgorithm::DEFAULT)
+}
+
+/// Hash the file at the given location with the algorithm `expected` was
+/// made with, returning the tagged hash and whether it matches.
+pub fn hash_to_match(path: &Path, expected: &str) -> (String, bool) {
+    let algorithm = HashAlgorithm::of(expected);
+    let actual = hash_file_with(path, algorithm.unwrap_or(HashAlgorithm::DEFAULT));
+    let matches = match algorithm {
+        Some(HashAlgorithm::Sha1) if !expected.contains(':') => {
+            actual == format!("{}:{}", HashAlgorithm::Sha1.tag(), expected)
+        }
+        Some(_) => actual == expected,
+        None => false,
+    };
+    (actual, matches)
+}
+
+/// Return true if the file at the given location has the recorded hash,
+/// hashing it with the algorithm the hash was made with.
+pub fn file_matches(path: &Path, expected: &str) -> bool {
+    hash_to_match(path, expected).1
+}
+
+/// Return true if the recorded hash is a legacy, untagged SHA-1 hash, so
+/// should be replaced with a new hash. Tagged hashes, whatever their
+/// algorithm, are kept as recorded.
+pub fn is_legacy_hash(hash: &str) -> bool {
+    !hash.contains(':')
+}
+
+/// Return the SHA-1 hash of the JSON serialization of `value`.
+pub fn hash_serialized<T: Serialize>(value: &T) -> String {
     let mut hasher = Sha1::new();
-    io::copy(&mut file, &mut hasher).unwrap();
-    let hash = hasher.finalize();
-    format!("{:x}", hash)
+    serde_json::to_writer(&mut hasher, value).unwrap();
+    format!("{:x}", hasher.finalize())
+}
+
+/// A raw data file whose contents don't match the hash recorded in metadata.
+pub enum FileHashProblem {
+    Missing(String),
+    Mismatch {
+        file: String,
+        expected: String,
+        actual: String,
+    },
+}
+
+impl fmt::Display for FileHashProblem {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            FileHashProblem::Missing(file) => write!(f, "{} is missing", file),
+            FileHashProblem::Mismatch {
+                file,
+                expected,
+                actual,
+            } => write!(
+                f,
+                "{} has hash {} but metadata records {}",
+                file, actual, expected
+            ),
+        }
+    }
+}
+
+/// Hash each file in `files` (a map from file name to expected hash) under
+/// `dir`, returning the files that are missing or whose hash differs. Files
+/// are hashed in parallel, each with the algorithm of its recorded hash.
+pub fn verify_files(dir: &Path, files: &BTreeMap<String, String>) -> Vec<FileHashProblem> {
+    let files: Vec<(&String, &String)> = files.iter().collect();
+    map_parallel(&files, |(file, expected)| {
+        let path = dir.join(file);
+        if !path.exists() {
+            return Some(FileHashProblem::Missing(file.to_string()));
+        }
+        match hash_to_match(&path, expected) {
+            (actual, true) => {
+                record_hashed_input(&path, &actual);
+                None
+            }
+            (actual, false) => Some(FileHashProblem::Mismatch {
+                file: file.to_string(),
+                expected: expected.to_string(),
+                actual,
+            }),
+        }
+    })
+    .into_iter()
+    .flatten()
+    .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_file_matches() {
+        let path = std::env::temp_dir().join(format!("hash_test_{}", std::process::id()));
+        std::fs::write(&path, "abc").unwrap();
+
+        let sha1 = "a9993e364706816aba3e25717850c26c9cd0d89d";
+        let sha256 = "sha256:ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad";
+        assert_eq!(sha256, hash_file(path.clone()));
+        assert_eq!(
+            format!("sha1:{}", sha1),
+            hash_file_with(&path, HashAlgorithm::Sha1)
+        );
+
+        assert!(file_matches(&path, sha1));
+        assert!(file_matches(&path, &format!("sha1:{}", sha1)));
+        assert!(file_matches(&path, sha256));
+        assert!(file_matches(
+            &path,
+            &hash_file_with(&path, HashAlgorithm::Blake3)
+        ));
+        assert!(!file_matches(&path, "sha256:00"));
+        assert!(!file_matches(&path, "md5:900150983cd24fb0d6963f7d28e17f72"));
+
+        assert!(is_legacy_hash(sha1));
+        assert!(!is_legacy_hash(sha256));
+        let blake3 = hash_file_with(&path, HashAlgorithm::Blake3);
+        assert!(!is_legacy_hash(&blake3));
+        assert!(!is_legacy_hash("md5:900150983cd24fb0d6963f7d28e17f72"));
+
+        std::fs::remove_file(&path).unwrap();
+    }
 }
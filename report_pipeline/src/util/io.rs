@@ -1,19 +1,137 @@
+use crate::util::{record_input, record_output};
 use colored::*;
 use flate2::{read::GzDecoder, write::GzEncoder, Compression};
 use serde::de::DeserializeOwned;
 use serde::Serialize;
+use std::error::Error;
 use std::ffi::OsString;
-use std::fs::File;
-use std::fs::OpenOptions;
-use std::io::Read;
+use std::fmt;
+use std::fs::{remove_file, rename, File, OpenOptions};
+use std::io::{self, Read, Write};
 use std::io::{BufReader, BufWriter};
-use std::path::Path;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicU32, AtomicU64, Ordering};
+use tracing::{info, warn};
+
+/// How generated output files (preprocessed ballots, reports, indexes) are
+/// written. Metadata files are always pretty-printed, so they diff cleanly
+/// in review; so are reports unless `compact` is set, since they are
+/// committed too.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct OutputOptions {
+    /// Don't indent JSON that isn't gzipped.
+    pub compact: bool,
+    /// Gzip compression level, from 0 (none) to 9 (best).
+    pub gzip_level: u32,
+    /// Warn about any file (of either kind) whose uncompressed JSON is
+    /// larger than this many bytes.
+    pub max_size: Option<u64>,
+}
+
+impl Default for OutputOptions {
+    fn default() -> Self {
+        OutputOptions {
+            compact: false,
+            gzip_level: 9,
+            max_size: None,
+        }
+    }
+}
+
+static OUTPUT_COMPACT: AtomicBool = AtomicBool::new(false);
+static OUTPUT_GZIP_LEVEL: AtomicU32 = AtomicU32::new(9);
+/// Zero means no limit.
+static OUTPUT_MAX_SIZE: AtomicU64 = AtomicU64::new(0);
+
+/// Set how `write_output` writes files for the rest of the process.
+pub fn set_output_options(options: OutputOptions) {
+    OUTPUT_COMPACT.store(options.compact, Ordering::Relaxed);
+    OUTPUT_GZIP_LEVEL.store(options.gzip_level.min(9), Ordering::Relaxed);
+    OUTPUT_MAX_SIZE.store(options.max_size.unwrap_or(0), Ordering::Relaxed);
+}
+
+fn output_options() -> OutputOptions {
+    let max_size = OUTPUT_MAX_SIZE.load(Ordering::Relaxed);
+    OutputOptions {
+        compact: OUTPUT_COMPACT.load(Ordering::Relaxed),
+        gzip_level: OUTPUT_GZIP_LEVEL.load(Ordering::Relaxed),
+        max_size: if max_size > 0 { Some(max_size) } else { None },
+    }
+}
+
+/// Counts the bytes written through it, to measure uncompressed size.
+struct CountingWriter<W: Write> {
+    inner: W,
+    count: u64,
+}
+
+impl<W: Write> Write for CountingWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let n = self.inner.write(buf)?;
+        self.count += n as u64;
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+/// An error reading or writing a JSON-serialized file.
+#[derive(Debug)]
+pub enum IoError {
+    /// The file couldn't be opened or read.
+    Read { path: PathBuf, source: io::Error },
+    /// The file's contents aren't valid JSON of the expected shape.
+    Parse {
+        path: PathBuf,
+        source: serde_json::Error,
+    },
+    /// The file couldn't be created, written or moved into place.
+    Write { path: PathBuf, source: io::Error },
+    /// The value couldn't be serialized as JSON.
+    Serialize {
+        path: PathBuf,
+        source: serde_json::Error,
+    },
+}
+
+impl fmt::Display for IoError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            IoError::Read { path, source } => write!(f, "{}: {}", path.display(), source),
+            IoError::Parse { path, source } => write!(f, "{}: {}", path.display(), source),
+            IoError::Write { path, source } => write!(f, "{}: {}", path.display(), source),
+            IoError::Serialize { path, source } => write!(f, "{}: {}", path.display(), source),
+        }
+    }
+}
+
+impl Error for IoError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            IoError::Read { source, .. } => Some(source),
+            IoError::Parse { source, .. } => Some(source),
+            IoError::Write { source, .. } => Some(source),
+            IoError::Serialize { source, .. } => Some(source),
+        }
+    }
+}
 
 /// Read a JSON-serialized file into an object. Applies GZ decompression
 /// if the file path ends in `.gz`.
-pub fn read_serialized<T: DeserializeOwned>(path: &Path) -> T {
-    eprintln!("Reading {}", path.to_str().unwrap().bright_blue());
-    let file = File::open(path).unwrap();
+pub fn try_read_serialized<T: DeserializeOwned>(path: &Path) -> Result<T, IoError> {
+    info!("Reading {}", path.to_string_lossy().bright_blue());
+    let read_error = |source| IoError::Read {
+        path: path.to_path_buf(),
+        source,
+    };
+    let parse_error = |source| IoError::Parse {
+        path: path.to_path_buf(),
+        source,
+    };
+    let file = File::open(path).map_err(read_error)?;
+    record_input(path);
 
     if path.extension() == Some(&OsString::from("gz")) {
         // For some reason, reading from a BufReader fails so we instead
@@ -22,33 +140,158 @@ pub fn read_serialized<T: DeserializeOwned>(path: &Path) -> T {
         // https://github.com/serde-rs/json/issues/160
         let mut gzfile = GzDecoder::new(file);
         let mut contents = String::new();
-        gzfile.read_to_string(&mut contents).unwrap();
-        serde_json::from_str(&contents).unwrap()
+        gzfile.read_to_string(&mut contents).map_err(read_error)?;
+        serde_json::from_str(&contents).map_err(parse_error)
     } else {
         let reader = BufReader::new(file);
-        serde_json::from_reader(reader).unwrap()
+        serde_json::from_reader(reader).map_err(parse_error)
     }
 }
 
-/// Write the given object as JSON. Applies GZ compression if the file
-/// path ends in `.gz`. Creates the file if it doesn't exist, otherwise
-/// overwrites it.
+/// Like [`try_read_serialized`], but panics with the file name and cause if
+/// the file can't be read.
+pub fn read_serialized<T: DeserializeOwned>(path: &Path) -> T {
+    try_read_serialized(path).unwrap_or_else(|err| panic!("Could not read {}", err))
+}
+
+/// Write the given metadata object as pretty-printed JSON. Applies GZ
+/// compression if the file path ends in `.gz`. Creates the file if it
+/// doesn't exist, otherwise replaces it. The object is written to a
+/// temporary file next to `path` that is then renamed over it, so readers
+/// never see a partly written file.
+pub fn try_write_serialized<T: Serialize>(path: &Path, value: &T) -> Result<(), IoError> {
+    let options = OutputOptions {
+        compact: false,
+        ..output_options()
+    };
+    write_serialized_with(path, value, &options)
+}
+
+/// Like [`try_write_serialized`], but panics with the file name and cause if
+/// the file can't be written.
 pub fn write_serialized<T: Serialize>(path: &Path, value: &T) {
-    eprintln!("Writing {}", path.to_str().unwrap().bright_blue());
+    try_write_serialized(path, value).unwrap_or_else(|err| panic!("Could not write {}", err))
+}
+
+/// Like [`try_write_serialized`], but for generated output: written as set
+/// by [`set_output_options`], pretty-printed unless `compact` is set.
+pub fn try_write_output<T: Serialize>(path: &Path, value: &T) -> Result<(), IoError> {
+    write_serialized_with(path, value, &output_options())
+}
+
+/// Like [`try_write_output`], but panics with the file name and cause if the
+/// file can't be written.
+pub fn write_output<T: Serialize>(path: &Path, value: &T) {
+    try_write_output(path, value).unwrap_or_else(|err| panic!("Could not write {}", err))
+}
+
+fn write_serialized_with<T: Serialize>(
+    path: &Path,
+    value: &T,
+    options: &OutputOptions,
+) -> Result<(), IoError> {
+    info!("Writing {}", path.to_string_lossy().bright_blue());
+    let write_error = |source| IoError::Write {
+        path: path.to_path_buf(),
+        source,
+    };
+    let serialize_error = |source| IoError::Serialize {
+        path: path.to_path_buf(),
+        source,
+    };
+
+    let mut tmp_name = path
+        .file_name()
+        .ok_or_else(|| write_error(io::Error::new(io::ErrorKind::InvalidInput, "not a file")))?
+        .to_os_string();
+    tmp_name.push(".tmp");
+    let tmp_path = path.with_file_name(tmp_name);
 
     let file = OpenOptions::new()
         .write(true)
         .create(true)
         .truncate(true)
-        .open(path)
-        .unwrap();
+        .open(&tmp_path)
+        .map_err(write_error)?;
 
-    if path.extension() == Some(&OsString::from("gz")) {
-        let gzfile = GzEncoder::new(file, Compression::best());
-        let writer = BufWriter::new(gzfile);
-        serde_json::to_writer(writer, &value).unwrap();
-    } else {
-        let writer = BufWriter::new(file);
-        serde_json::to_writer_pretty(writer, &value).unwrap();
+    let write_tmp = || -> Result<u64, IoError> {
+        if path.extension() == Some(&OsString::from("gz")) {
+            let gzfile = GzEncoder::new(file, Compression::new(options.gzip_level));
+            let mut writer = CountingWriter {
+                inner: BufWriter::new(gzfile),
+                count: 0,
+            };
+            serde_json::to_writer(&mut writer, &value).map_err(serialize_error)?;
+            writer
+                .inner
+                .into_inner()
+                .map_err(|err| write_error(err.into_error()))?
+                .finish()
+                .map_err(write_error)?;
+            Ok(writer.count)
+        } else {
+            let mut writer = CountingWriter {
+                inner: BufWriter::new(file),
+                count: 0,
+            };
+            if options.compact {
+                serde_json::to_writer(&mut writer, &value).map_err(serialize_error)?;
+            } else {
+                serde_json::to_writer_pretty(&mut writer, &value).map_err(serialize_error)?;
+            }
+            writer.flush().map_err(write_error)?;
+            Ok(writer.count)
+        }
+    };
+    let size = write_tmp()
+        .and_then(|size| rename(&tmp_path, path).map_err(write_error).map(|_| size))
+        .inspect_err(|_| {
+            // Don't leave a partly written temporary file behind.
+            let _ = remove_file(&tmp_path);
+        })?;
+    record_output(path);
+
+    if let Some(max_size) = options.max_size {
+        if size > max_size {
+            warn!(
+                "{} is {} bytes uncompressed, over the limit of {}.",
+                path.display(),
+                size,
+                max_size
+            );
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_write_serialized_with() {
+        let path =
+            std::env::temp_dir().join(format!("write_serialized_{}.json", std::process::id()));
+        let value = vec![1, 2];
+
+        let pretty = OutputOptions::default();
+        write_serialized_with(&path, &value, &pretty).unwrap();
+        assert_eq!("[\n  1,\n  2\n]", std::fs::read_to_string(&path).unwrap());
+        assert_eq!(value, read_serialized::<Vec<i32>>(&path));
+
+        let compact = OutputOptions {
+            compact: true,
+            ..pretty
+        };
+        write_serialized_with(&path, &value, &compact).unwrap();
+        assert_eq!("[1,2]", std::fs::read_to_string(&path).unwrap());
+
+        std::fs::remove_file(&path).unwrap();
+
+        let missing_dir = std::env::temp_dir()
+            .join(format!("write_serialized_{}", std::process::id()))
+            .join("missing/out.json");
+        let err = try_write_output(&missing_dir, &value).unwrap_err();
+        assert!(matches!(err, IoError::Write { path, .. } if path == missing_dir));
     }
 }
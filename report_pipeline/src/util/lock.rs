@@ -0,0 +1,123 @@
+use std::error::Error;
+use std::fmt;
+use std::fs::{create_dir_all, read_to_string, remove_file, OpenOptions};
+use std::io::{self, ErrorKind, Write};
+use std::path::{Path, PathBuf};
+
+/// Name of the lock file. It is hidden, so directory walks and report
+/// bundles skip it.
+const LOCK_FILENAME: &str = ".lock";
+
+/// An error taking a directory lock.
+#[derive(Debug)]
+pub enum LockError {
+    /// Another command holds the lock. `owner` is the contents of the lock
+    /// file, e.g. `report (pid 1234)`.
+    Held { path: PathBuf, owner: String },
+    /// The lock file couldn't be created.
+    Io { path: PathBuf, source: io::Error },
+}
+
+impl fmt::Display for LockError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            LockError::Held { path, owner } => write!(
+                f,
+                "{} is being written by {}; if that command is no longer running, delete {}",
+                path.parent().unwrap_or(path).display(),
+                owner,
+                path.display()
+            ),
+            LockError::Io { path, source } => write!(f, "{}: {}", path.display(), source),
+        }
+    }
+}
+
+impl Error for LockError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            LockError::Held { .. } => None,
+            LockError::Io { source, .. } => Some(source),
+        }
+    }
+}
+
+/// Exclusive claim on an output directory by a command that writes to it,
+/// so that two commands (such as `report` and `ingest-all`) can't write the
+/// same preprocessed or report files at once. Readers don't take the lock:
+/// files are replaced atomically, so they always see a complete file.
+///
+/// The lock is released when dropped. If a command is killed, the lock file
+/// is left behind and must be deleted by hand.
+pub struct DirLock {
+    path: PathBuf,
+}
+
+impl DirLock {
+    /// Lock `dir` for `command`, creating the directory if needed.
+    pub fn acquire(dir: &Path, command: &str) -> Result<DirLock, LockError> {
+        let path = dir.join(LOCK_FILENAME);
+        let io_error = |source| LockError::Io {
+            path: path.clone(),
+            source,
+        };
+        create_dir_all(dir).map_err(io_error)?;
+
+        match OpenOptions::new().write(true).create_new(true).open(&path) {
+            Ok(mut file) => {
+                write!(file, "{} (pid {})", command, std::process::id()).map_err(io_error)?;
+                Ok(DirLock { path })
+            }
+            Err(err) if err.kind() == ErrorKind::AlreadyExists => {
+                let owner = read_to_string(&path)
+                    .map(|owner| owner.trim().to_string())
+                    .unwrap_or_default();
+                Err(LockError::Held {
+                    owner: if owner.is_empty() {
+                        "another command".to_string()
+                    } else {
+                        owner
+                    },
+                    path,
+                })
+            }
+            Err(err) => Err(io_error(err)),
+        }
+    }
+
+    /// Lock each of `dirs` for `command`. If any is already locked, the
+    /// others are released again.
+    pub fn acquire_all(dirs: &[&Path], command: &str) -> Result<Vec<DirLock>, LockError> {
+        dirs.iter()
+            .map(|dir| DirLock::acquire(dir, command))
+            .collect()
+    }
+}
+
+impl Drop for DirLock {
+    fn drop(&mut self) {
+        let _ = remove_file(&self.path);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_dir_lock() {
+        let dir = std::env::temp_dir().join(format!("dir_lock_test_{}", std::process::id()));
+
+        let lock = DirLock::acquire(&dir, "report").unwrap();
+        match DirLock::acquire(&dir, "ingest-all") {
+            Err(LockError::Held { owner, .. }) => {
+                assert_eq!(format!("report (pid {})", std::process::id()), owner)
+            }
+            _ => panic!("expected the directory to be locked"),
+        }
+
+        drop(lock);
+        assert!(DirLock::acquire(&dir, "ingest-all").is_ok());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,139 @@
+use crate::util::{record_outcome, Outcome};
+use indicatif::{ProgressBar, ProgressStyle};
+use serde::Serialize;
+use std::io::IsTerminal;
+use std::str::FromStr;
+use std::sync::atomic::{AtomicBool, Ordering};
+use tracing::{Event, Level, Subscriber};
+use tracing_subscriber::filter::LevelFilter;
+use tracing_subscriber::fmt;
+use tracing_subscriber::layer::{Context, Layer, SubscriberExt};
+use tracing_subscriber::util::SubscriberInitExt;
+
+/// Whether interactive output (progress bars) should be drawn. Disabled
+/// when logs are quiet or machine-readable.
+static INTERACTIVE: AtomicBool = AtomicBool::new(true);
+
+/// Whether commands print a JSON summary of what they did to stdout.
+static JSON_OUTPUT: AtomicBool = AtomicBool::new(false);
+
+/// Selects how log events are written to stderr.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum LogFormat {
+    /// Human-readable lines, colored when stderr is a terminal.
+    Text,
+    /// One JSON object per line, suitable for log aggregation.
+    Json,
+}
+
+/// Selects what a command prints to stdout when it finishes.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum OutputFormat {
+    /// Nothing beyond the logs on stderr.
+    Text,
+    /// A JSON summary of the run (counts, warnings, durations, paths).
+    Json,
+}
+
+impl FromStr for OutputFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "text" => Ok(OutputFormat::Text),
+            "json" => Ok(OutputFormat::Json),
+            _ => Err(format!(
+                "unknown output format {:?}; expected text or json",
+                s
+            )),
+        }
+    }
+}
+
+/// Records warnings as the command's outcome, which sets its exit code.
+/// Errors aren't recorded here: the code that logs one records whether it
+/// is a `DataError` or an `InfraError`.
+struct OutcomeLayer;
+
+impl<S: Subscriber> Layer<S> for OutcomeLayer {
+    fn on_event(&self, event: &Event<'_>, _ctx: Context<'_, S>) {
+        if *event.metadata().level() == Level::WARN {
+            record_outcome(Outcome::Warning);
+        }
+    }
+}
+
+/// Install the global `tracing` subscriber. With `quiet`, only warnings and
+/// errors are emitted; with `verbose` (and not `quiet`), debug events are
+/// emitted too. With `LogFormat::Json`, color codes are disabled and every
+/// event is written as a JSON line. Warnings are recorded as the command's
+/// outcome, which sets its exit code.
+pub fn init_logging(quiet: bool, verbose: bool, format: LogFormat) {
+    if format == LogFormat::Json {
+        colored::control::set_override(false);
+    }
+
+    INTERACTIVE.store(!quiet && format == LogFormat::Text, Ordering::Relaxed);
+
+    let level = if quiet {
+        LevelFilter::WARN
+    } else if verbose {
+        LevelFilter::DEBUG
+    } else {
+        LevelFilter::INFO
+    };
+    let text = (format == LogFormat::Text).then(|| {
+        fmt::layer()
+            .without_time()
+            .with_target(false)
+            .with_ansi(std::io::stderr().is_terminal())
+            .with_writer(std::io::stderr)
+    });
+    let json = (format == LogFormat::Json).then(|| {
+        fmt::layer()
+            .json()
+            .flatten_event(true)
+            .with_writer(std::io::stderr)
+    });
+
+    tracing_subscriber::registry()
+        .with(level)
+        .with(OutcomeLayer)
+        .with(text)
+        .with(json)
+        .try_init()
+        .expect("Logging should only be initialized once.");
+}
+
+/// Return true if progress bars and other terminal-only output should be drawn.
+pub fn is_interactive() -> bool {
+    INTERACTIVE.load(Ordering::Relaxed)
+}
+
+/// A progress bar of `total` steps drawn on stderr, labeled with `label`.
+/// It is hidden unless output is interactive.
+pub fn progress_bar(label: &str, total: u64) -> ProgressBar {
+    if !is_interactive() {
+        return ProgressBar::hidden();
+    }
+    let style = ProgressStyle::with_template("{msg} [{bar:30}] {pos}/{len}")
+        .expect("Progress bar template should be valid.")
+        .progress_chars("= ");
+    ProgressBar::new(total)
+        .with_style(style)
+        .with_message(label.to_string())
+}
+
+/// Set whether commands print a JSON summary to stdout for the rest of the
+/// process.
+pub fn set_output_format(format: OutputFormat) {
+    JSON_OUTPUT.store(format == OutputFormat::Json, Ordering::Relaxed);
+}
+
+/// Print `summary` to stdout as JSON if the output format is JSON, so that
+/// scripts can read the result of a command without parsing its logs.
+pub fn print_summary<T: Serialize>(summary: &T) {
+    if JSON_OUTPUT.load(Ordering::Relaxed) {
+        println!("{}", serde_json::to_string_pretty(summary).unwrap());
+    }
+}
@@ -1,9 +1,38 @@
+//! Filesystem, serialization, hashing, parallelism, and logging helpers.
+
+mod archive;
+mod audit;
+mod csv;
+mod exit;
+mod fetch;
 mod hash;
 mod io;
+mod lock;
+mod logging;
+mod parallel;
 mod path;
 mod string;
 
-pub use hash::hash_file;
-pub use io::{read_serialized, write_serialized};
+pub use archive::extract_archive;
+pub use audit::{
+    finish_recording, record_hashed_input, record_input, record_output, start_recording,
+    RecordedFiles,
+};
+pub use csv::write_csv_row;
+pub use exit::{exit_code, record_outcome, Outcome};
+pub use fetch::{fetch_url, post_url};
+pub use hash::{
+    file_matches, hash_file, hash_file_with, hash_serialized, hash_to_match, is_legacy_hash,
+    verify_files, FileHashProblem, HashAlgorithm,
+};
+pub use io::{
+    read_serialized, set_output_options, try_read_serialized, try_write_output,
+    try_write_serialized, write_output, write_serialized, IoError, OutputOptions,
+};
+pub use lock::{DirLock, LockError};
+pub use logging::{
+    init_logging, print_summary, progress_bar, set_output_format, LogFormat, OutputFormat,
+};
+pub use parallel::{available_threads, map_parallel, map_parallel_with, set_max_threads};
 pub use path::get_files_from_path;
 pub use string::UnicodeString;
@@ -0,0 +1,111 @@
+//! Running work on several threads at once.
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread;
+
+/// Most threads `map_parallel` uses at once; zero means one per CPU.
+static MAX_THREADS: AtomicUsize = AtomicUsize::new(0);
+
+/// Limit the threads `map_parallel` uses at once for the rest of the
+/// process. `None` uses one per available CPU.
+pub fn set_max_threads(threads: Option<usize>) {
+    MAX_THREADS.store(threads.unwrap_or(0), Ordering::Relaxed);
+}
+
+/// Number of CPUs available to the process, or 1 if that can't be told.
+pub fn available_threads() -> usize {
+    thread::available_parallelism().map_or(1, |n| n.get())
+}
+
+/// Apply `f` to each item, on up to one thread per available CPU (or the
+/// limit set by `set_max_threads`) at a time, returning the results in
+/// order. Used to hash many large raw files.
+pub fn map_parallel<T, R, F>(items: &[T], f: F) -> Vec<R>
+where
+    T: Sync,
+    R: Send,
+    F: Fn(&T) -> R + Sync,
+{
+    let threads = match MAX_THREADS.load(Ordering::Relaxed) {
+        0 => available_threads(),
+        max => max,
+    };
+    map_parallel_with(items, threads, f)
+}
+
+/// Apply `f` to each item on up to `threads` worker threads, returning the
+/// results in order. The workers share a queue of the items: each takes the
+/// next one as soon as it finishes the last, so one slow item holds up only
+/// its own worker.
+pub fn map_parallel_with<T, R, F>(items: &[T], threads: usize, f: F) -> Vec<R>
+where
+    T: Sync,
+    R: Send,
+    F: Fn(&T) -> R + Sync,
+{
+    let next = AtomicUsize::new(0);
+    let (next, f) = (&next, &f);
+    let mut results: Vec<Option<R>> = items.iter().map(|_| None).collect();
+    thread::scope(|scope| {
+        let workers: Vec<_> = (0..threads.clamp(1, items.len().max(1)))
+            .map(|_| {
+                scope.spawn(move || {
+                    let mut done = Vec::new();
+                    loop {
+                        let i = next.fetch_add(1, Ordering::Relaxed);
+                        match items.get(i) {
+                            Some(item) => done.push((i, f(item))),
+                            None => return done,
+                        }
+                    }
+                })
+            })
+            .collect();
+        for worker in workers {
+            for (i, result) in worker.join().expect("Worker thread panicked.") {
+                results[i] = Some(result);
+            }
+        }
+    });
+    results
+        .into_iter()
+        .map(|result| result.expect("Every item is taken by a worker."))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_map_parallel_with() {
+        let items: Vec<u32> = (0..10).collect();
+        let doubled: Vec<u32> = items.iter().map(|i| i * 2).collect();
+        assert_eq!(doubled, map_parallel_with(&items, 3, |i| i * 2));
+        assert_eq!(doubled, map_parallel_with(&items, 0, |i| i * 2));
+        assert_eq!(doubled, map_parallel_with(&items, 20, |i| i * 2));
+        assert!(map_parallel_with(&[] as &[u32], 3, |i| i * 2).is_empty());
+    }
+
+    #[test]
+    fn test_map_parallel_with_shares_work() {
+        // The first item waits for all the others, which finish only if the
+        // other worker keeps taking items meanwhile.
+        let items: Vec<usize> = (0..6).collect();
+        let done = AtomicUsize::new(0);
+        let results = map_parallel_with(&items, 2, |i| {
+            if *i > 0 {
+                done.fetch_add(1, Ordering::SeqCst);
+                return true;
+            }
+            for _ in 0..10_000 {
+                if done.load(Ordering::SeqCst) == items.len() - 1 {
+                    return true;
+                }
+                thread::sleep(std::time::Duration::from_millis(1));
+            }
+            false
+        });
+        assert!(results.iter().all(|r| *r));
+    }
+}
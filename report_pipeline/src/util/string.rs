@@ -16,4 +16,10 @@ impl UnicodeString {
     pub fn slice(&self, range: std::ops::Range<usize>) -> String {
         self.chars[range].iter().collect()
     }
+
+    /// Like [`UnicodeString::slice`], but returns `None` if the range extends
+    /// past the end of the string.
+    pub fn get(&self, range: std::ops::Range<usize>) -> Option<String> {
+        self.chars.get(range).map(|chars| chars.iter().collect())
+    }
 }
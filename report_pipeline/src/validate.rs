@@ -0,0 +1,379 @@
+//! Validation of jurisdiction metadata files, reporting every problem found
+//! with its location rather than stopping at the first.
+
+use crate::formats::required_params;
+use crate::model::metadata::Jurisdiction;
+use crate::normalizers::has_normalizer;
+use crate::rules::{get_rules_profile, tabulation_options};
+use crate::tabulator::get_tabulator;
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::BTreeSet;
+use std::fmt;
+use std::fs::read_to_string;
+use std::path::Path;
+
+const JURISDICTION_FIELDS: &[&str] = &["name", "path", "kind", "offices", "elections"];
+const OFFICE_FIELDS: &[&str] = &["name", "names", "previousIds"];
+const ELECTION_FIELDS: &[&str] = &[
+    "name",
+    "date",
+    "dataFormat",
+    "tabulationOptions",
+    "normalization",
+    "rulesProfile",
+    "contests",
+    "files",
+    "sources",
+    "extractArchives",
+    "website",
+    "status",
+    "drop",
+    "candidateNames",
+    "precinctAttributes",
+    "anonymizeBallotIds",
+];
+const TABULATION_OPTIONS_FIELDS: &[&str] = &[
+    "eager",
+    "continueUntilTwo",
+    "method",
+    "seats",
+    "eliminateBelow",
+    "quota",
+    "quotaMode",
+    "closeMarginVotes",
+    "closeMarginPercent",
+    "alternativeOutcomes",
+    "methodComparison",
+    "approvalRanks",
+    "ballotCountTolerancePercent",
+    "bootstrapSamples",
+    "bootstrapSeed",
+    "overvotePolicy",
+    "skippedRankPolicy",
+];
+const CONTEST_FIELDS: &[&str] = &["office", "loaderParams", "officialBallots"];
+
+/// A problem with a metadata file, located by a JSON pointer (RFC 6901)
+/// into the file, e.g. `/elections/2025~107/contests/0/office`.
+#[derive(Serialize, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct MetadataError {
+    pub file: String,
+    pub pointer: String,
+    pub message: String,
+}
+
+impl fmt::Display for MetadataError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.pointer.is_empty() {
+            write!(f, "{}: {}", self.file, self.message)
+        } else {
+            write!(f, "{}#{}: {}", self.file, self.pointer, self.message)
+        }
+    }
+}
+
+/// Append `key` to a JSON pointer, escaping it.
+fn pointer(base: &str, key: &str) -> String {
+    format!("{}/{}", base, key.replace('~', "~0").replace('/', "~1"))
+}
+
+/// Return true if `date` is a valid `YYYY-MM-DD` date.
+fn is_valid_date(date: &str) -> bool {
+    let parts: Vec<&str> = date.split('-').collect();
+    if parts.len() != 3 || parts[0].len() != 4 || parts[1].len() != 2 || parts[2].len() != 2 {
+        return false;
+    }
+    let (year, month, day): (u32, u32, u32) =
+        match (parts[0].parse(), parts[1].parse(), parts[2].parse()) {
+            (Ok(y), Ok(m), Ok(d)) => (y, m, d),
+            _ => return false,
+        };
+    let leap = year % 4 == 0 && (year % 100 != 0 || year % 400 == 0);
+    let days = match month {
+        1 | 3 | 5 | 7 | 8 | 10 | 12 => 31,
+        4 | 6 | 9 | 11 => 30,
+        2 if leap => 29,
+        2 => 28,
+        _ => return false,
+    };
+    (1..=days).contains(&day)
+}
+
+struct Validator<'a> {
+    file: &'a str,
+    errors: Vec<MetadataError>,
+}
+
+impl Validator<'_> {
+    fn error(&mut self, pointer: String, message: String) {
+        self.errors.push(MetadataError {
+            file: self.file.to_string(),
+            pointer,
+            message,
+        });
+    }
+
+    /// Report fields of the object at `at` that aren't in `known`.
+    fn unknown_fields(&mut self, value: &Value, at: &str, known: &[&str]) {
+        if let Some(object) = value.as_object() {
+            for key in object.keys() {
+                if !known.contains(&key.as_str()) {
+                    self.error(pointer(at, key), format!("unknown field {:?}", key));
+                }
+            }
+        }
+    }
+
+    /// Check field names throughout the file.
+    fn check_fields(&mut self, value: &Value) {
+        self.unknown_fields(value, "", JURISDICTION_FIELDS);
+        for (id, office) in value["offices"].as_object().into_iter().flatten() {
+            self.unknown_fields(office, &pointer("/offices", id), OFFICE_FIELDS);
+        }
+        for (key, election) in value["elections"].as_object().into_iter().flatten() {
+            let at = pointer("/elections", key);
+            self.unknown_fields(election, &at, ELECTION_FIELDS);
+            self.unknown_fields(
+                &election["tabulationOptions"],
+                &pointer(&at, "tabulationOptions"),
+                TABULATION_OPTIONS_FIELDS,
+            );
+            for (i, contest) in election["contests"]
+                .as_array()
+                .into_iter()
+                .flatten()
+                .enumerate()
+            {
+                self.unknown_fields(
+                    contest,
+                    &pointer(&pointer(&at, "contests"), &i.to_string()),
+                    CONTEST_FIELDS,
+                );
+            }
+        }
+    }
+
+    /// Check the contents of a file that parsed as a `Jurisdiction`.
+    fn check_jurisdiction(&mut self, jurisdiction: &Jurisdiction) {
+        for (key, election) in &jurisdiction.elections {
+            let at = pointer("/elections", key);
+
+            if !is_valid_date(&election.date) {
+                self.error(
+                    pointer(&at, "date"),
+                    format!("{:?} is not a YYYY-MM-DD date", election.date),
+                );
+            }
+            if !has_normalizer(&election.normalization) {
+                self.error(
+                    pointer(&at, "normalization"),
+                    format!("unknown normalization {:?}", election.normalization),
+                );
+            }
+            if let Some(profile) = &election.rules_profile {
+                if get_rules_profile(profile).is_none() {
+                    self.error(
+                        pointer(&at, "rulesProfile"),
+                        format!("unknown rules profile {:?}", profile),
+                    );
+                }
+            }
+            let options = tabulation_options(election);
+            let options_at = pointer(&at, "tabulationOptions");
+            if options.seats == Some(0) {
+                self.error(
+                    pointer(&options_at, "seats"),
+                    "must be at least 1".to_string(),
+                );
+            }
+            if let Some(method) = &options.method {
+                match get_tabulator(method) {
+                    None => self.error(
+                        pointer(&options_at, "method"),
+                        format!("unknown tabulation method {:?}", method),
+                    ),
+                    Some(tabulator) if tabulator.multi_winner() && options.seats.is_none() => {
+                        let message = format!(
+                            "missing \"seats\", required by tabulation method {}",
+                            method
+                        );
+                        self.error(options_at, message);
+                    }
+                    Some(_) => {}
+                }
+            }
+            let required = required_params(&election.data_format);
+            if required.is_none() {
+                self.error(
+                    pointer(&at, "dataFormat"),
+                    format!("unknown data format {:?}", election.data_format),
+                );
+            }
+
+            let mut seen = BTreeSet::new();
+            for (i, contest) in election.contests.iter().enumerate() {
+                let at = pointer(&pointer(&at, "contests"), &i.to_string());
+
+                if !jurisdiction.offices.contains_key(&contest.office) {
+                    self.error(
+                        pointer(&at, "office"),
+                        format!("office {:?} is not in offices", contest.office),
+                    );
+                }
+                if !seen.insert(&contest.office) {
+                    self.error(
+                        pointer(&at, "office"),
+                        format!("duplicate contest for office {:?}", contest.office),
+                    );
+                }
+                for param in required.into_iter().flatten() {
+                    let present = contest
+                        .loader_params
+                        .as_ref()
+                        .is_some_and(|p| p.contains_key(*param));
+                    if !present {
+                        self.error(
+                            pointer(&at, "loaderParams"),
+                            format!(
+                                "missing {:?}, required by data format {}",
+                                param, election.data_format
+                            ),
+                        );
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Validate the metadata file at `path`: its JSON syntax and shape, unknown
+/// fields, dates, data formats and their required loader parameters,
+/// normalizations, tabulation methods and their seats, and that each
+/// contest names a declared office once per election. Returns every problem
+/// found.
+pub fn validate_metadata(path: &Path) -> Vec<MetadataError> {
+    let file = path.to_string_lossy();
+    let mut validator = Validator {
+        file: &file,
+        errors: Vec::new(),
+    };
+
+    let text = match read_to_string(path) {
+        Ok(text) => text,
+        Err(err) => {
+            validator.error(String::new(), err.to_string());
+            return validator.errors;
+        }
+    };
+    let value: Value = match serde_json::from_str(&text) {
+        Ok(value) => value,
+        Err(err) => {
+            validator.error(String::new(), err.to_string());
+            return validator.errors;
+        }
+    };
+
+    validator.check_fields(&value);
+    match serde_json::from_value::<Jurisdiction>(value) {
+        Ok(jurisdiction) => validator.check_jurisdiction(&jurisdiction),
+        Err(err) => validator.error(String::new(), err.to_string()),
+    }
+
+    validator.errors
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn validate(json: &str) -> Vec<String> {
+        let mut validator = Validator {
+            file: "meta.json",
+            errors: Vec::new(),
+        };
+        let value: Value = serde_json::from_str(json).unwrap();
+        validator.check_fields(&value);
+        let jurisdiction: Jurisdiction = serde_json::from_value(value).unwrap();
+        validator.check_jurisdiction(&jurisdiction);
+        validator.errors.iter().map(|e| e.to_string()).collect()
+    }
+
+    #[test]
+    fn test_is_valid_date() {
+        assert!(is_valid_date("2024-02-29"));
+        assert!(!is_valid_date("2023-02-29"));
+        assert!(!is_valid_date("2023-13-01"));
+        assert!(!is_valid_date("11/05/2024"));
+    }
+
+    #[test]
+    fn test_validate() {
+        let errors = validate(
+            r#"{
+                "name": "Town", "path": "us/xx/town", "kind": "city", "mayor": true,
+                "offices": {"mayor": {"name": "Mayor"}},
+                "elections": {"2024/11": {
+                    "name": "General", "date": "2024-11-31", "dataFormat": "simple_json",
+                    "normalization": "simple", "rulesProfile": "atlantis", "files": {},
+                    "contests": [
+                        {"office": "mayor", "loaderParams": {"file": "a.json"}},
+                        {"office": "mayor"},
+                        {"office": "council", "loaderParams": {"file": "b.json"}, "seats": 2}
+                    ]
+                }}
+            }"#,
+        );
+
+        assert_eq!(
+            vec![
+                "meta.json#/mayor: unknown field \"mayor\"",
+                "meta.json#/elections/2024~111/contests/2/seats: unknown field \"seats\"",
+                "meta.json#/elections/2024~111/date: \"2024-11-31\" is not a YYYY-MM-DD date",
+                "meta.json#/elections/2024~111/rulesProfile: unknown rules profile \"atlantis\"",
+                "meta.json#/elections/2024~111/contests/1/office: duplicate contest for office \"mayor\"",
+                "meta.json#/elections/2024~111/contests/1/loaderParams: missing \"file\", required by data format simple_json",
+                "meta.json#/elections/2024~111/contests/2/office: office \"council\" is not in offices",
+            ],
+            errors
+        );
+    }
+
+    #[test]
+    fn test_validate_tabulation_method() {
+        let errors = validate(
+            r#"{
+                "name": "Town", "path": "us/xx/town", "kind": "city",
+                "offices": {"council": {"name": "Council"}},
+                "elections": {
+                    "2023/11": {
+                        "name": "General", "date": "2023-11-07", "dataFormat": "simple_json",
+                        "normalization": "simple", "files": {},
+                        "tabulationOptions": {"method": "stv_meek"}, "contests": []
+                    },
+                    "2024/11": {
+                        "name": "General", "date": "2024-11-05", "dataFormat": "simple_json",
+                        "normalization": "simple", "files": {},
+                        "tabulationOptions": {"method": "borda"}, "contests": []
+                    },
+                    "2025/11": {
+                        "name": "General", "date": "2025-11-04", "dataFormat": "simple_json",
+                        "normalization": "simple", "files": {},
+                        "tabulationOptions": {"method": "stv_cincinnati", "seats": 0},
+                        "contests": []
+                    }
+                }
+            }"#,
+        );
+
+        assert_eq!(
+            vec![
+                "meta.json#/elections/2023~111/tabulationOptions: missing \"seats\", required by tabulation method stv_meek",
+                "meta.json#/elections/2024~111/tabulationOptions/method: unknown tabulation method \"borda\"",
+                "meta.json#/elections/2025~111/tabulationOptions/seats: must be at least 1",
+            ],
+            errors
+        );
+    }
+}
@@ -1,4 +1,5 @@
 #!/bin/sh
 
-cargo run --release -- sync election-metadata raw-data
+cargo run --release -- sync election-metadata raw-data --apply \
+    --operations-log operations.jsonl
 
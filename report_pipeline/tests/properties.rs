@@ -0,0 +1,158 @@
+//! Property tests of invariants that should hold for any ballots, using
+//! proptest to generate contests and shrink any failing one to a minimal
+//! case. Generated contests deliberately include edge cases such as
+//! single-candidate contests, blank ballots, overvotes, and repeated or
+//! skipped rankings.
+//!
+//! Each property runs proptest's default number of cases; set
+//! `PROPTEST_CASES` to run more.
+
+use proptest::collection::vec;
+use proptest::prelude::*;
+use ranked_vote::model::election::{
+    Ballot, Candidate, CandidateId, CandidateType, Choice, Election, NormalizedBallot,
+};
+use ranked_vote::model::metadata::BallotRules;
+use ranked_vote::normalizers::{get_normalizer_for_format, normalize_election};
+use ranked_vote::tabulator::{tabulate, Allocatee};
+use std::collections::HashSet;
+
+/// A generated contest: its number of candidates and each ballot's raw
+/// choices.
+#[derive(Clone, Debug)]
+struct Contest {
+    num_candidates: u32,
+    ballots: Vec<Vec<Choice>>,
+}
+
+impl Contest {
+    fn election(&self) -> Election {
+        let candidates = (0..self.num_candidates)
+            .map(|i| Candidate::new(format!("Candidate {}", i), CandidateType::Regular))
+            .collect();
+        let ballots = self
+            .ballots
+            .iter()
+            .enumerate()
+            .map(|(i, choices)| Ballot::new(i.to_string(), choices.clone()))
+            .collect();
+        Election::new(candidates, ballots)
+    }
+
+    fn normalized(&self) -> Vec<NormalizedBallot> {
+        normalize_election("simple", &BallotRules::default(), self.election()).ballots
+    }
+}
+
+/// A ranking: usually a vote for one of the candidates, sometimes a skipped
+/// ranking or an overvote.
+fn choice(num_candidates: u32) -> impl Strategy<Value = Choice> {
+    prop_oneof![
+        1 => Just(Choice::Undervote),
+        1 => Just(Choice::Overvote),
+        8 => (0..num_candidates).prop_map(|c| Choice::Vote(CandidateId(c))),
+    ]
+}
+
+/// A contest of 1 to 6 candidates and 0 to 80 ballots of up to 6 rankings.
+/// Some contests have every ranking overvoted, so no ballot is valid.
+fn contest() -> impl Strategy<Value = Contest> {
+    (1..=6u32).prop_flat_map(|num_candidates| {
+        let ballots = prop_oneof![
+            19 => vec(vec(choice(num_candidates), 0..=6), 0..=80),
+            1 => vec(vec(Just(Choice::Overvote), 0..=6), 0..=80),
+        ];
+        ballots.prop_map(move |ballots| Contest {
+            num_candidates,
+            ballots,
+        })
+    })
+}
+
+/// Re-express a normalized ballot as a raw ballot.
+fn denormalize(ballot: &NormalizedBallot) -> Ballot {
+    let mut choices: Vec<Choice> = ballot.choices().into_iter().map(Choice::Vote).collect();
+    if ballot.overvoted {
+        choices.push(Choice::Overvote);
+    }
+    Ballot::new(ballot.id.clone(), choices)
+}
+
+proptest! {
+    #[test]
+    fn test_normalizers_are_idempotent(contest in contest()) {
+        for normalization in ["simple", "maine"] {
+            let normalizer = get_normalizer_for_format(normalization);
+            for ballot in contest.election().ballots {
+                let once = normalizer(ballot);
+                let twice = normalizer(denormalize(&once));
+                prop_assert_eq!(once, twice);
+            }
+        }
+    }
+
+    #[test]
+    fn test_normalized_ballots_have_no_repeated_candidates(contest in contest()) {
+        for ballot in contest.normalized() {
+            let choices = ballot.choices();
+            let unique: HashSet<_> = choices.iter().collect();
+            prop_assert_eq!(choices.len(), unique.len());
+        }
+    }
+
+    #[test]
+    fn test_every_round_accounts_for_every_ballot(contest in contest()) {
+        let ballots = contest.normalized();
+        let num_ballots = ballots.len() as u32;
+
+        for round in tabulate(&ballots) {
+            let allocated: u32 = round.allocations.iter().map(|a| a.votes).sum();
+            prop_assert_eq!(num_ballots, allocated);
+            prop_assert_eq!(num_ballots, round.continuing_ballots + round.exhausted);
+            prop_assert_eq!(round.exhausted, round.undervote + round.overvote);
+        }
+    }
+
+    #[test]
+    fn test_eliminated_candidates_stay_eliminated(contest in contest()) {
+        let rounds = tabulate(&contest.normalized());
+
+        let mut eliminated: HashSet<CandidateId> = HashSet::new();
+        for round in &rounds {
+            for transfer in &round.transfers {
+                eliminated.insert(transfer.from);
+            }
+            for allocation in &round.allocations {
+                if let Allocatee::Candidate(c) = allocation.allocatee {
+                    prop_assert!(!eliminated.contains(&c));
+                }
+            }
+        }
+
+        // Exhausted ballots never become continuing again.
+        for pair in rounds.windows(2) {
+            prop_assert!(pair[1].exhausted >= pair[0].exhausted);
+        }
+    }
+
+    #[test]
+    fn test_winner_leads_final_round(contest in contest()) {
+        let rounds = tabulate(&contest.normalized());
+        let last = rounds.last().unwrap();
+
+        let candidates: Vec<_> = last
+            .allocations
+            .iter()
+            .filter(|a| a.allocatee != Allocatee::Exhausted)
+            .collect();
+        prop_assert!(candidates.len() <= 2);
+
+        // The first allocation is the winner whenever any ballot is continuing.
+        if let Some(winner) = candidates.first() {
+            prop_assert!(candidates.iter().all(|c| c.votes <= winner.votes));
+            prop_assert_eq!(last.allocations[0].allocatee, winner.allocatee);
+        } else {
+            prop_assert_eq!(0, last.continuing_ballots);
+        }
+    }
+}
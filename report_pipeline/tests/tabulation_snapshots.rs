@@ -0,0 +1,170 @@
+//! Regression tests that run the full pipeline (reader, normalizer,
+//! tabulator) over the small fixture elections in `tests/fixtures` and
+//! compare the round-by-round results with the expected JSON checked in
+//! under `tests/fixtures/expected`.
+//!
+//! When a change to the results is intended, regenerate the expected files
+//! with `BLESS=1 cargo test --test tabulation_snapshots` and review the diff.
+
+use ranked_vote::model::election::ElectionPreprocessed;
+use ranked_vote::model::report::ContestReport;
+use ranked_vote::read_metadata::read_meta;
+use ranked_vote::report::{generate_report, preprocess_election};
+use ranked_vote::tabulator::{StvResult, TabulationError, TabulatorRound};
+use serde::Serialize;
+use std::fs::{create_dir_all, read_to_string, write};
+use std::path::{Path, PathBuf};
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Snapshot<'a> {
+    winner: String,
+    rounds: &'a [TabulatorRound],
+    #[serde(skip_serializing_if = "Option::is_none")]
+    stv: &'a Option<StvResult>,
+}
+
+impl<'a> Snapshot<'a> {
+    fn new(report: &'a ContestReport) -> Snapshot<'a> {
+        Snapshot {
+            winner: report.winner_names(),
+            rounds: &report.rounds,
+            stv: &report.stv,
+        }
+    }
+}
+
+fn fixtures_dir() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures")
+}
+
+/// Preprocess each fixture contest, passing its contest path and ballots to
+/// `f`.
+fn for_each_contest(mut f: impl FnMut(String, ElectionPreprocessed)) {
+    let fixtures = fixtures_dir();
+    for (_, jurisdiction) in read_meta(&fixtures.join("meta")) {
+        let raw_base = fixtures.join("raw").join(&jurisdiction.path);
+
+        for (election_path, election) in &jurisdiction.elections {
+            for contest in &election.contests {
+                let contest_path =
+                    format!("{}/{}/{}", jurisdiction.path, election_path, contest.office);
+                let preprocessed =
+                    preprocess_election(&raw_base, election, election_path, &jurisdiction, contest)
+                        .unwrap();
+                f(contest_path, preprocessed);
+            }
+        }
+    }
+}
+
+#[test]
+fn test_tabulation_snapshots() {
+    let fixtures = fixtures_dir();
+    let bless = std::env::var_os("BLESS").is_some();
+    let mut failures = Vec::new();
+    let mut num_contests = 0;
+
+    for_each_contest(|contest_path, preprocessed| {
+        num_contests += 1;
+
+        let report = generate_report(&preprocessed).unwrap();
+        let actual = serde_json::to_string_pretty(&Snapshot::new(&report)).unwrap() + "\n";
+
+        let expected_path = fixtures
+            .join("expected")
+            .join(&contest_path)
+            .with_extension("json");
+
+        if bless {
+            create_dir_all(expected_path.parent().unwrap()).unwrap();
+            write(&expected_path, &actual).unwrap();
+            return;
+        }
+
+        match read_to_string(&expected_path) {
+            Ok(expected) if expected == actual => {}
+            Ok(_) => failures.push(format!("{}: results changed", contest_path)),
+            Err(_) => failures.push(format!(
+                "{}: missing {}",
+                contest_path,
+                expected_path.display()
+            )),
+        }
+    });
+
+    assert!(num_contests > 0, "No fixture contests found.");
+    assert!(
+        failures.is_empty(),
+        "Tabulation snapshots differ (run with BLESS=1 to update):\n{}",
+        failures.join("\n")
+    );
+}
+
+/// Reading and reporting a contest twice must give byte-identical JSON, so
+/// that regenerated reports only differ where the results do.
+#[test]
+fn test_reports_are_reproducible() {
+    let mut first_run = Vec::new();
+    for_each_contest(|contest_path, preprocessed| {
+        let report = serde_json::to_string(&generate_report(&preprocessed).unwrap()).unwrap();
+        let ballots = serde_json::to_string(&preprocessed).unwrap();
+        first_run.push((contest_path, ballots, report));
+    });
+
+    let mut first_run = first_run.into_iter();
+    for_each_contest(|contest_path, preprocessed| {
+        let (expected_path, ballots, report) = first_run.next().unwrap();
+        assert_eq!(expected_path, contest_path);
+        assert!(
+            ballots == serde_json::to_string(&preprocessed).unwrap(),
+            "{}: preprocessed ballots differ between runs",
+            contest_path
+        );
+        assert!(
+            report == serde_json::to_string(&generate_report(&preprocessed).unwrap()).unwrap(),
+            "{}: report differs between runs",
+            contest_path
+        );
+    });
+}
+
+/// Every field of a report must be read back as it was written, so that
+/// tools reading report.json with these types (like `diff`) see exactly what
+/// was generated.
+#[test]
+fn test_reports_round_trip() {
+    for_each_contest(|contest_path, preprocessed| {
+        let written = serde_json::to_string(&generate_report(&preprocessed).unwrap()).unwrap();
+        let read: ContestReport = serde_json::from_str(&written)
+            .unwrap_or_else(|err| panic!("{}: report can't be read: {}", contest_path, err));
+        assert!(
+            written == serde_json::to_string(&read).unwrap(),
+            "{}: report changes when read and written again",
+            contest_path
+        );
+    });
+}
+
+/// A contest with no votes, or no candidates, can't be reported, but must
+/// fail with an error rather than a panic.
+#[test]
+fn test_empty_contest_is_an_error() {
+    for_each_contest(|contest_path, mut preprocessed| {
+        preprocessed.ballots.ballots.clear();
+        assert_eq!(
+            Some(TabulationError::NoVotes),
+            generate_report(&preprocessed).err(),
+            "{}: no ballots",
+            contest_path
+        );
+
+        preprocessed.ballots.candidates.clear();
+        assert_eq!(
+            Some(TabulationError::NoVotes),
+            generate_report(&preprocessed).err(),
+            "{}: no candidates",
+            contest_path
+        );
+    });
+}
@@ -0,0 +1,35 @@
+import { createHash } from "crypto"
+
+// Cache-Control header of API responses. Override with
+// RANKED_VOTE_CACHE_CONTROL, e.g. to let a CDN in front of the server keep
+// responses longer.
+const CACHE_CONTROL = process.env.RANKED_VOTE_CACHE_CONTROL ?? "public, max-age=300, must-revalidate"
+
+// Whether an If-None-Match header lists the given ETag.
+function matchesETag(ifNoneMatch: string | undefined, etag: string): boolean {
+    if (!ifNoneMatch) {
+        return false
+    }
+
+    return ifNoneMatch.split(",").map((t) => t.trim()).some((t) => t === etag || t === "*")
+}
+
+// Send `value` as a JSON response with a strong ETag (the SHA-256 hash of the
+// JSON) and Cache-Control. If the request's If-None-Match has the same ETag,
+// reply 304 Not Modified without a body, so clients and CDNs that already
+// have the response don't download it again.
+export function sendJson(req, res, value: any) {
+    let json = JSON.stringify(value)
+    let etag = `"${createHash("sha256").update(json).digest("hex")}"`
+
+    res.setHeader("ETag", etag)
+    res.setHeader("Cache-Control", CACHE_CONTROL)
+    if (matchesETag(req.headers["if-none-match"], etag)) {
+        res.statusCode = 304
+        res.end()
+        return
+    }
+
+    res.setHeader("Content-Type", "application/json")
+    res.end(json)
+}
@@ -5,6 +5,17 @@ export type Allocatee = CandidateId | 'X'
 
 export interface IReportIndex {
     elections: IElectionIndexEntry[]
+    jurisdictionSummaries: IJurisdictionSummary[]
+}
+
+export interface IJurisdictionSummary {
+    path: string
+    name: string
+    elections: number
+    contests: number
+    averageRounds: number
+    averageExhaustionRate: number
+    firstRoundLeaderWinRate: number
 }
 
 export interface IElectionIndexEntry {
@@ -12,16 +23,75 @@ export interface IElectionIndexEntry {
     jurisdictionName: string
     electionName: string
     date: string
+    status?: ElectionStatus
     contests: IContestIndexEntry[]
 }
 
+export type ElectionStatus = "preliminary" | "updated" | "certified"
+
+// Display names by language code, e.g. {"es": "...", "zh": "..."}.
+export type LocalizedNames = {[language: string]: string}
+
 export interface IContestIndexEntry {
     office: string
+    previousOffices?: string[]
     officeName: string
+    officeNames?: LocalizedNames
     name: string
     winner: string
+    candidates: string[]
     numCandidates: number
     numRounds: number
+    closeContest: boolean
+    tieBreakUsed: boolean
+    uncontested: boolean
+    ballotCountDiscrepancy: boolean
+}
+
+// candidates.json
+
+export interface ICandidateIndex {
+    candidates: ICandidateProfile[]
+}
+
+export interface ICandidateProfile {
+    name: string
+    racesEntered: number
+    wins: number
+    appearances: ICandidateAppearance[]
+}
+
+export interface ICandidateAppearance {
+    contestPath: string
+    date: string
+    jurisdictionName: string
+    officeName: string
+    firstRoundVotes: number
+    finalVotes: number
+    roundsSurvived: number
+    won: boolean
+}
+
+// trends.json
+
+export interface ITrendIndex {
+    jurisdictions: IJurisdictionTrend[]
+}
+
+export interface IJurisdictionTrend {
+    path: string
+    name: string
+    elections: IElectionTrendPoint[]
+}
+
+export interface IElectionTrendPoint {
+    electionPath: string
+    date: string
+    contests: number
+    averageRanks?: number
+    exhaustionRate: number
+    candidatesPerContest: number
+    writeInShare: number
 }
 
 // report.json
@@ -29,9 +99,11 @@ export interface IContestIndexEntry {
 export interface IContestReport {
     info: IElectionInfo
     ballotCount: number
+    averageRanks?: number
     candidates: ICandidate[]
     rounds: ITabulatorRound[]
     winner: CandidateId
+    stv?: IStvResult
     condorcet?: CandidateId
     smithSet: CandidateId[]
     numCandidates: number
@@ -39,6 +111,253 @@ export interface IContestReport {
     pairwisePreferences: ICandidatePairTable
     firstAlternate: ICandidatePairTable
     firstFinal: ICandidatePairTable
+    marginOfVictory?: IMarginOfVictory
+    closeContest: boolean
+    tieBreakUsed: boolean
+    majorityRound?: number
+    uncontested: boolean
+    subJurisdictionResults?: ISubJurisdictionResult[]
+    invalidBallots: IInvalidBallotCounts
+    alternativeOutcomes?: IAlternativeOutcome[]
+    methodComparison?: IMethodComparison
+    sensitivity?: ISensitivity
+    parseIssues?: IParseIssue[]
+    provenance?: IProvenance
+    ballotCountCheck?: IBallotCountCheck
+    ecologicalEstimates?: IEcologicalEstimates
+}
+
+export interface IBallotCountCheck {
+    official: number
+    counted: number
+    difference: number
+    percent: number
+    discrepancy: boolean
+}
+
+// Correlations across precincts, not individual voters' behavior; display
+// with `note`.
+export interface IEcologicalEstimates {
+    note: string
+    precincts: number
+    attributes: IAttributeCorrelations[]
+}
+
+export interface IAttributeCorrelations {
+    attribute: string
+    precincts: number
+    firstChoice: ICandidateCorrelation[]
+    exhaustion?: number
+}
+
+export interface ICandidateCorrelation {
+    candidate: CandidateId
+    correlation?: number
+}
+
+// index-manifest.json
+
+export interface IIndexManifest {
+    contests: IIndexManifestEntry[]
+}
+
+export interface IIndexManifestEntry {
+    path: string
+    sha256: string
+    bytes: number
+}
+
+// transfers.json
+
+export interface ITransferGraph {
+    nodes: ITransferNode[]
+    links: ITransferLink[]
+}
+
+export interface ITransferNode {
+    round: number
+    candidate?: CandidateId
+    name: string
+    votes: number
+}
+
+export interface ITransferLink {
+    source: number
+    target: number
+    value: number
+}
+
+// trace.json
+
+export type ITraceEvent = ITallyEvent | IEliminationEvent | ITieBreakEvent | IWinnerEvent
+
+export interface ITallyEvent {
+    event: "tally"
+    round: number
+    continuingBallots: number
+    exhausted: number
+    threshold: number
+}
+
+export interface IEliminationEvent {
+    event: "elimination"
+    round: number
+    candidates: CandidateId[]
+    combinedVotes: number
+    nextCandidate: CandidateId
+    nextVotes: number
+}
+
+export interface ITieBreakEvent {
+    event: "tieBreak"
+    round: number
+    tied: CandidateId[]
+    votes: number
+    eliminated: CandidateId
+}
+
+export interface IWinnerEvent {
+    event: "winner"
+    round: number
+    candidate?: CandidateId
+    votes: number
+    majority: boolean
+}
+
+// history.json
+
+export interface IReportHistory {
+    revisions: IReportRevision[]
+    drops?: IDropResult[]
+}
+
+export interface IDropResult {
+    drop: number
+    recordedAt: number
+    winner: string
+    ballotCount: number
+    firstRoundVotes: {[candidate: string]: number}
+    finalVotes: {[candidate: string]: number}
+    ballotsAdded: number
+    firstRoundChanges: {[candidate: string]: number}
+    finalChanges: {[candidate: string]: number}
+}
+
+export interface IReportRevision {
+    replacedAt: number
+    changes: string[]
+    report: IContestReport
+}
+
+export interface IProvenance {
+    files: {[file: string]: string}
+    dataFormat: string
+    loaderParams?: {[param: string]: string}
+    normalization: string
+    tabulationOptions: {[option: string]: unknown}
+    pipelineVersion: string
+    pipelineRevision?: string
+}
+
+export interface IParseIssue {
+    file: string
+    sheet?: string
+    row: number
+    column: number
+    reason: string
+}
+
+export interface IMethodComparison {
+    condorcet?: CandidateId
+    smithSet: CandidateId[]
+    plurality: IMethodResult
+    borda: IMethodResult
+    approvalRanks: number
+    approval: IMethodResult
+}
+
+export interface IMethodResult {
+    winners: CandidateId[]
+    scores: {candidate: CandidateId, score: number}[]
+}
+
+export interface ISensitivity {
+    samples: number
+    seed: number
+    irvWinnerChangeProbability: number
+    rankedPairsWinner?: CandidateId
+    rankedPairsWinnerChangeProbability: number
+}
+
+export interface IWhatIfResult {
+    eliminated: CandidateId[]
+    winner?: CandidateId
+    rounds: ITabulatorRound[]
+}
+
+export type IRawChoice = { candidate: CandidateId } | "undervote" | "overvote"
+
+export interface IBallotLookup {
+    ballotId: string
+    rawChoices?: IRawChoice[]
+    choices: CandidateId[]
+    overvoted: boolean
+    countedFor: CandidateId[]
+    exhaustedRound?: number
+    finalCandidate?: CandidateId
+}
+
+export interface IAlternativeOutcome {
+    removed: CandidateId
+    winner?: CandidateId
+    winnerChanged: boolean
+}
+
+export interface IStvResult {
+    seats: number
+    quota: number
+    winners: CandidateId[]
+    rounds: IStvRound[]
+}
+
+export interface IStvRound {
+    allocations: ITabulatorAllocation[]
+    elected: CandidateId[]
+    eliminated: CandidateId[]
+    quota: number
+    meek?: IMeekRound
+}
+
+export interface IMeekRound {
+    tallies: IMeekTally[]
+    exhausted: number
+    quota: number
+    iterations: number
+}
+
+export interface IMeekTally {
+    candidate: CandidateId
+    votes: number
+    keepFactor: number
+}
+
+export interface IMarginOfVictory {
+    votes: number
+    percent: number
+    ballotsToFlip: number
+}
+
+export interface IInvalidBallotCounts {
+    blank: number
+    overvote: number
+    writeInOnly: number
+}
+
+export interface ISubJurisdictionResult {
+    name: string
+    ballotCount: number
+    firstChoice: ITabulatorAllocation[]
+    finalRound: ITabulatorAllocation[]
 }
 
 export interface ICandidatePairTable {
@@ -71,12 +390,17 @@ export interface IElectionInfo {
     loaderParams?: {[param: string]: string}
     jurisdictionName: string
     officeName: string
+    officeNames?: LocalizedNames
     electionName: string
     website?: string
+    status?: ElectionStatus
+    officialBallots?: number
+    drop?: number
 }
 
 export interface ICandidate {
     name: string
+    names?: LocalizedNames
     writeIn?: boolean
     candidate_type?: string
 }
@@ -86,6 +410,8 @@ export interface ITabulatorRound {
     undervote: number
     overvote: number
     continuingBallots: number
+    exhausted: number
+    threshold: number
     transfers: Transfer[]
 }
 
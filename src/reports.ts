@@ -1,18 +1,233 @@
-import { readFileSync } from "fs"
-import type { IReportIndex, IContestReport } from "./report_types"
+import { execFileSync } from "child_process"
+import { createHash } from "crypto"
+import { existsSync, readFileSync, statSync } from "fs"
+import type { IIndexManifest, IReportIndex, IContestReport, IContestIndexEntry, IElectionIndexEntry, ICandidateIndex, ICandidateProfile, IReportHistory, ITransferGraph, ITraceEvent, IWhatIfResult, IBallotLookup } from "./report_types"
 
 const RANKED_VOTE_REPORTS = process.env.RANKED_VOTE_REPORTS ?? "./report_pipeline/reports"
+const RANKED_VOTE_PREPROCESSED = process.env.RANKED_VOTE_PREPROCESSED ?? "./report_pipeline/preprocessed"
+const RANKED_VOTE_PIPELINE = process.env.RANKED_VOTE_PIPELINE ?? "./report_pipeline/target/release/ranked-vote"
+// Raw data isn't published, so ballot lookups only include raw rankings if
+// these are set.
+const RANKED_VOTE_METADATA = process.env.RANKED_VOTE_METADATA
+const RANKED_VOTE_RAW_DATA = process.env.RANKED_VOTE_RAW_DATA
+
+const SITE_URL = "https://rcv.report"
+
+// The parsed index, kept until index.json is rewritten, so that API requests
+// don't each re-read and parse the whole file.
+let cachedIndex: { mtimeMs: number, index: IReportIndex } | undefined
 
 export function getIndex(): IReportIndex {
-    let indexRaw = readFileSync(`${RANKED_VOTE_REPORTS}/index.json`)
-    let indexParsed = JSON.parse(indexRaw.toString()) as IReportIndex
+    let indexPath = `${RANKED_VOTE_REPORTS}/index.json`
+    let mtimeMs = statSync(indexPath).mtimeMs
+    if (cachedIndex?.mtimeMs !== mtimeMs) {
+        let indexRaw = readFileSync(indexPath)
+        cachedIndex = { mtimeMs, index: JSON.parse(indexRaw.toString()) as IReportIndex }
+    }
+
+    return cachedIndex.index
+}
+
+// Map the path of a contest whose office has since been renamed to its
+// current path, so old links keep working. Other paths are returned as is.
+export function resolveContestPath(path: string): string {
+    if (existsSync(`${RANKED_VOTE_REPORTS}/${path}/report.json`)) {
+        return path
+    }
+
+    let split = path.lastIndexOf("/")
+    let electionPath = path.substr(0, split)
+    let office = path.substr(split + 1)
+    let election = getIndex().elections.find((e) => e.path === electionPath)
+    let contest = election?.contests.find((c) => (c.previousOffices ?? []).includes(office))
 
-    return indexParsed
+    return contest ? `${electionPath}/${contest.office}` : path
 }
 
 export function getReport(path: string): IContestReport {
+    path = resolveContestPath(path)
     let reportRaw = readFileSync(`${RANKED_VOTE_REPORTS}/${path}/report.json`)
     let reportParsed = JSON.parse(reportRaw.toString()) as IContestReport
 
     return reportParsed
-}
\ No newline at end of file
+}
+
+export function getTransfers(path: string): ITransferGraph {
+    let transfersRaw = readFileSync(`${RANKED_VOTE_REPORTS}/${path}/transfers.json`)
+
+    return JSON.parse(transfersRaw.toString()) as ITransferGraph
+}
+
+// Why each round's candidates were eliminated, or null if the pipeline wasn't
+// run with `--trace`.
+export function getTrace(path: string): ITraceEvent[] | null {
+    let tracePath = `${RANKED_VOTE_REPORTS}/${path}/trace.json`
+    if (!existsSync(tracePath)) {
+        return null
+    }
+
+    return JSON.parse(readFileSync(tracePath).toString()) as ITraceEvent[]
+}
+
+// Earlier reports of a contest whose results have changed, oldest first. Empty
+// if the results have never been updated.
+export function getReportHistory(path: string): IReportHistory {
+    let historyPath = `${RANKED_VOTE_REPORTS}/${path}/history.json`
+    if (!existsSync(historyPath)) {
+        return { revisions: [] }
+    }
+
+    return JSON.parse(readFileSync(historyPath).toString()) as IReportHistory
+}
+
+// Re-run a contest's count without the named candidates, using the pipeline's
+// `what-if` command on the preprocessed ballots. Throws if the contest or a
+// candidate isn't found.
+export function getWhatIf(path: string, eliminate: string[]): IWhatIfResult {
+    let args = ["--quiet", "what-if", RANKED_VOTE_PREPROCESSED, resolveContestPath(path)]
+    for (let name of eliminate) {
+        args.push("--eliminate", name)
+    }
+    let output = execFileSync(RANKED_VOTE_PIPELINE, args, { stdio: ["ignore", "pipe", "pipe"] })
+
+    return JSON.parse(output.toString()) as IWhatIfResult
+}
+
+// How one ballot was counted, using the pipeline's `lookup-ballot` command on
+// the preprocessed ballots. Throws if the contest or ballot isn't found.
+export function getBallotLookup(path: string, ballotId: string): IBallotLookup {
+    let args = ["--quiet", "lookup-ballot", RANKED_VOTE_PREPROCESSED, resolveContestPath(path), ballotId]
+    if (RANKED_VOTE_METADATA && RANKED_VOTE_RAW_DATA) {
+        args.push("--meta-dir", RANKED_VOTE_METADATA, "--raw-data-dir", RANKED_VOTE_RAW_DATA)
+    }
+    let output = execFileSync(RANKED_VOTE_PIPELINE, args, { stdio: ["ignore", "pipe", "pipe"] })
+
+    return JSON.parse(output.toString()) as IBallotLookup
+}
+
+// Paths of every contest in the index, e.g. us/ny/nyc/2025/07/mayor.
+function contestPaths(): string[] {
+    let paths: string[] = []
+    for (let election of getIndex().elections) {
+        for (let contest of election.contests) {
+            paths.push(`${election.path}/${contest.office}`)
+        }
+    }
+
+    return paths
+}
+
+// Every contest in the index with the SHA-256 hash and size of its
+// report.json, so clients can find reports and tell which have changed
+// without calling the API.
+export function getIndexManifest(): IIndexManifest {
+    let contests = contestPaths().map((path) => {
+        let report = readFileSync(`${RANKED_VOTE_REPORTS}/${path}/report.json`)
+        return {
+            path,
+            sha256: createHash("sha256").update(report).digest("hex"),
+            bytes: report.length,
+        }
+    })
+
+    return { contests }
+}
+
+// An XML sitemap of the home page and every contest's report page.
+export function getSitemap(): string {
+    let urls = ["/", ...contestPaths().map((path) => `/report/${path}`)]
+    let entries = urls.map((url) => `  <url><loc>${SITE_URL}${url}</loc></url>`)
+
+    return [
+        `<?xml version="1.0" encoding="UTF-8"?>`,
+        `<urlset xmlns="http://www.sitemaps.org/schemas/sitemap/0.9">`,
+        ...entries,
+        `</urlset>`,
+        ``,
+    ].join("\n")
+}
+
+export function getCandidateProfile(name: string): ICandidateProfile | undefined {
+    let indexRaw = readFileSync(`${RANKED_VOTE_REPORTS}/candidates.json`)
+    let index = JSON.parse(indexRaw.toString()) as ICandidateIndex
+    let key = (n: string) => n.trim().split(/\s+/).join(" ").toLowerCase()
+
+    return index.candidates.find((c) => key(c.name) === key(name))
+}
+
+export interface IIndexQuery {
+    q?: string
+    jurisdiction?: string
+    fromYear?: number
+    toYear?: number
+    office?: string
+    winner?: string
+    page?: number
+    pageSize?: number
+}
+
+export interface IIndexPage extends IReportIndex {
+    total: number
+    page: number
+    pageSize: number
+}
+
+export function parseIndexQuery(query: {[key: string]: string}): IIndexQuery {
+    let number = (v?: string) => (v === undefined || v === "" ? undefined : parseInt(v, 10))
+
+    return {
+        q: query.q,
+        jurisdiction: query.jurisdiction,
+        fromYear: number(query.fromYear),
+        toYear: number(query.toYear),
+        office: query.office,
+        winner: query.winner,
+        page: number(query.page),
+        pageSize: number(query.pageSize),
+    }
+}
+
+// Filter elections and contests in the index. Elections are kept if their path
+// starts with `jurisdiction` and their year is in range; contests are kept if
+// their office name contains `office`, their winner contains `winner`, and
+// each word of `q` appears in the jurisdiction, election, office or a
+// candidate's name (case-insensitive). Elections with no matching contests are
+// dropped. Without `pageSize`, all matching elections are returned.
+export function searchIndex(index: IReportIndex, query: IIndexQuery): IIndexPage {
+    let contains = (haystack: string, needle?: string) =>
+        !needle || haystack.toLowerCase().includes(needle.toLowerCase())
+    let terms = (query.q ?? "").toLowerCase().split(/\s+/).filter((t) => t.length > 0)
+    let matchesTerms = (e: IElectionIndexEntry, c: IContestIndexEntry) => {
+        let text = [e.jurisdictionName, e.electionName, c.office, c.officeName, c.name,
+            ...(c.candidates ?? [])].join("\n").toLowerCase()
+        return terms.every((t) => text.includes(t))
+    }
+
+    let elections: IElectionIndexEntry[] = index.elections
+        .filter((e) => !query.jurisdiction || e.path.startsWith(query.jurisdiction))
+        .filter((e) => {
+            let year = parseInt(e.date.substr(0, 4), 10)
+            return (query.fromYear === undefined || year >= query.fromYear)
+                && (query.toYear === undefined || year <= query.toYear)
+        })
+        .map((e) => ({
+            ...e,
+            contests: e.contests.filter((c) =>
+                (contains(c.officeName, query.office) || contains(c.office, query.office))
+                && contains(c.winner, query.winner)
+                && matchesTerms(e, c)),
+        }))
+        .filter((e) => e.contests.length > 0)
+
+    let total = elections.length
+    let pageSize = query.pageSize ?? total
+    let page = query.page ?? 1
+    if (pageSize > 0) {
+        elections = elections.slice((page - 1) * pageSize, page * pageSize)
+    }
+
+    let jurisdictionSummaries = index.jurisdictionSummaries
+        .filter((j) => !query.jurisdiction || j.path.startsWith(query.jurisdiction))
+
+    return { elections, jurisdictionSummaries, total, page, pageSize }
+}
@@ -0,0 +1,24 @@
+import {getBallotLookup} from '../../../reports'
+import {sendJson} from '../../../http'
+
+export async function get(req, res, next) {
+    const {path} = req.params;
+    const id = req.query?.id;
+    if (typeof id !== 'string' || id === '') {
+        res.setHeader('Content-Type', 'application/json');
+        res.statusCode = 400;
+        res.end(JSON.stringify({error: 'Missing ballot id'}));
+        return;
+    }
+
+    let result;
+    try {
+        result = await getBallotLookup(path.join('/'), id);
+    } catch (err) {
+        res.setHeader('Content-Type', 'application/json');
+        res.statusCode = 404;
+        res.end(JSON.stringify({error: 'Contest or ballot not found'}));
+        return;
+    }
+    sendJson(req, res, result);
+}
@@ -0,0 +1,10 @@
+import {getReportHistory} from '../../../reports'
+import {sendJson} from '../../../http'
+
+export async function get(req, res, next) {
+    const {path} = req.params;
+
+    let history = await getReportHistory(path.join('/'));
+
+    sendJson(req, res, history);
+}
@@ -1,10 +1,10 @@
 import {getReport} from '../../../reports'
+import {sendJson} from '../../../http'
 
 export async function get(req, res, next) {
     const {path} = req.params;
 
     let report = await getReport(path.join('/'));
 
-    res.setHeader('Content-Type', 'application/json');
-    res.end(JSON.stringify(report));
+    sendJson(req, res, report);
 }
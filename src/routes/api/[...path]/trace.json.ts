@@ -0,0 +1,16 @@
+import {getTrace} from '../../../reports'
+import {sendJson} from '../../../http'
+
+export async function get(req, res, next) {
+    const {path} = req.params;
+
+    let trace = await getTrace(path.join('/'));
+
+    if (trace === null) {
+        res.setHeader('Content-Type', 'application/json');
+        res.statusCode = 404;
+        res.end(JSON.stringify({error: 'No trace for this contest'}));
+        return;
+    }
+    sendJson(req, res, trace);
+}
@@ -0,0 +1,10 @@
+import {getTransfers} from '../../../reports'
+import {sendJson} from '../../../http'
+
+export async function get(req, res, next) {
+    const {path} = req.params;
+
+    let transfers = await getTransfers(path.join('/'));
+
+    sendJson(req, res, transfers);
+}
@@ -0,0 +1,21 @@
+import {getWhatIf} from '../../../reports'
+import {sendJson} from '../../../http'
+
+export async function get(req, res, next) {
+    const {path} = req.params;
+    let eliminate = req.query?.eliminate ?? [];
+    if (!Array.isArray(eliminate)) {
+        eliminate = [eliminate];
+    }
+
+    let result;
+    try {
+        result = await getWhatIf(path.join('/'), eliminate);
+    } catch (err) {
+        res.setHeader('Content-Type', 'application/json');
+        res.statusCode = 404;
+        res.end(JSON.stringify({error: 'Contest or candidate not found'}));
+        return;
+    }
+    sendJson(req, res, result);
+}
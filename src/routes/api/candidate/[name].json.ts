@@ -0,0 +1,16 @@
+import {getCandidateProfile} from '../../../reports'
+import {sendJson} from '../../../http'
+
+export async function get(req, res, next) {
+    const {name} = req.params;
+
+    let profile = await getCandidateProfile(decodeURIComponent(name));
+
+    if (profile === undefined) {
+        res.setHeader('Content-Type', 'application/json');
+        res.statusCode = 404;
+        res.end(JSON.stringify({error: 'Candidate not found'}));
+        return;
+    }
+    sendJson(req, res, profile);
+}
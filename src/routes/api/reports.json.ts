@@ -1,8 +1,9 @@
-import {getIndex} from '../../reports'
+import {getIndex, parseIndexQuery, searchIndex} from '../../reports'
+import {sendJson} from '../../http'
 
 export async function get(req, res, next) {
     let index = await getIndex();
+    let result = searchIndex(index, parseIndexQuery(req.query ?? {}));
 
-    res.setHeader('Content-Type', 'application/json');
-    res.end(JSON.stringify(index));
+    sendJson(req, res, result);
 }
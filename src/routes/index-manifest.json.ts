@@ -0,0 +1,8 @@
+import {getIndexManifest} from '../reports'
+import {sendJson} from '../http'
+
+export async function get(req, res, next) {
+    let manifest = await getIndexManifest();
+
+    sendJson(req, res, manifest);
+}
@@ -0,0 +1,8 @@
+import {getSitemap} from '../reports'
+
+export async function get(req, res, next) {
+    let sitemap = await getSitemap();
+
+    res.setHeader('Content-Type', 'application/xml');
+    res.end(sitemap);
+}